@@ -1,6 +1,7 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{ItemFn, ReturnType, Signature, Stmt};
+use syn::meta::ParseNestedMeta;
+use syn::{ItemFn, LitBool, ReturnType, Signature, Stmt};
 
 pub enum TokioMode {
     Main,
@@ -11,8 +12,23 @@ pub enum TokioMode {
 ///
 /// This method uses proc_macro2 TokenStream in order to allow easier testing and tarpaulin code coverage.
 /// This is the only benefice to have it as a sub-method here (and have it bound to sub-crate hermes-five-macros-internals)
-pub fn runtime_macro(item: TokenStream, tokio: TokioMode) -> TokenStream {
+pub fn runtime_macro(args: TokenStream, item: TokenStream, tokio: TokioMode) -> TokenStream {
     let hermes_five = quote!(::hermes_five);
+
+    // Parse the macro arguments.
+    let mut graceful_shutdown = true;
+    let parser = syn::meta::parser(|meta: ParseNestedMeta| {
+        if meta.path.is_ident("graceful_shutdown") {
+            graceful_shutdown = meta.value()?.parse::<LitBool>()?.value;
+            Ok(())
+        } else {
+            Err(meta.error("unsupported runtime argument"))
+        }
+    });
+    if let Err(error) = syn::parse::Parser::parse2(parser, args) {
+        return error.to_compile_error();
+    }
+
     // Parse the input tokens into a syntax tree
     let input: ItemFn = syn::parse2(item).unwrap();
 
@@ -71,9 +87,16 @@ pub fn runtime_macro(item: TokenStream, tokio: TokioMode) -> TokenStream {
         },
     };
 
+    // Define the graceful shutdown hook (not for tests).
+    let shutdown_hook = match (tokio, graceful_shutdown) {
+        (TokioMode::Main, true) => quote! { #hermes_five::utils::shutdown::install(); },
+        _ => quote! {},
+    };
+
     // Generate the function body
     let mut body = vec![quote! {
         #hermes_five::utils::task::init_task_channel().await;
+        #shutdown_hook
 
         // // Original code
     }];
@@ -191,6 +214,7 @@ mod tests {
                 .unwrap();
                 rt.block_on(async {
                     #before
+                    ::hermes_five::utils::shutdown::install();
 
                     // Original code
                     let x = 3;
@@ -203,7 +227,7 @@ mod tests {
             }
         };
 
-        let output = runtime_macro(input, TokioMode::Main);
+        let output = runtime_macro(quote!(), input, TokioMode::Main);
         assert_eq!(
             format!("{}", output),
             format!("{}", control),
@@ -232,6 +256,7 @@ mod tests {
                 .unwrap();
                 rt.block_on(async {
                     #before
+                    ::hermes_five::utils::shutdown::install();
 
                     // Original code
                     let x = 3;
@@ -243,7 +268,7 @@ mod tests {
             }
         };
 
-        let output = runtime_macro(input, TokioMode::Main);
+        let output = runtime_macro(quote!(), input, TokioMode::Main);
         assert_eq!(
             format!("{}", output),
             format!("{}", control),
@@ -272,6 +297,7 @@ mod tests {
                 .unwrap();
                 rt.block_on(async {
                     #before
+                    ::hermes_five::utils::shutdown::install();
 
                     // Original code
                     let x = 3;
@@ -282,7 +308,7 @@ mod tests {
             }
         };
 
-        let output = runtime_macro(input, TokioMode::Main);
+        let output = runtime_macro(quote!(), input, TokioMode::Main);
         assert_eq!(
             format!("{}", output),
             format!("{}", control),
@@ -314,11 +340,59 @@ mod tests {
             }
         };
 
-        let output = runtime_macro(input, TokioMode::Test);
+        let output = runtime_macro(quote!(), input, TokioMode::Test);
         assert_eq!(
             format!("{}", output),
             format!("{}", control),
             "Macro expansion for test mode should be correct."
         );
     }
+
+    #[test]
+    fn test_runtime_macro_without_graceful_shutdown() {
+        let before = before();
+        let after = after();
+
+        let input = quote! {
+            async fn main() { }
+        };
+
+        let control = quote! {
+            fn main() {
+                let rt = ::hermes_five::utils::tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(4)
+                .enable_all()
+                .build()
+                .unwrap();
+                rt.block_on(async {
+                    #before
+
+                    // Original code
+                    // ---
+
+                    #after
+                })
+            }
+        };
+
+        let output = runtime_macro(quote!(graceful_shutdown = false), input, TokioMode::Main);
+        assert_eq!(
+            format!("{}", output),
+            format!("{}", control),
+            "Macro expansion for runtime should be correct without graceful shutdown."
+        );
+    }
+
+    #[test]
+    fn test_runtime_macro_invalid_arguments() {
+        let input = quote! {
+            async fn main() { }
+        };
+
+        let output = runtime_macro(quote!(unknown = true), input.clone(), TokioMode::Main);
+        assert!(format!("{}", output).contains("unsupported runtime argument"));
+
+        let output = runtime_macro(quote!(graceful_shutdown = 3), input, TokioMode::Main);
+        assert!(format!("{}", output).contains("compile_error"));
+    }
 }
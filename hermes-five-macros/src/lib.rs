@@ -15,13 +15,17 @@ mod internals;
 ///
 /// _Executes the entire function in a blocking thread and provides synchronization for waiting on all
 /// subsequently and dynamically created tasks (using `task::run`)._
+///
+/// The runtime also installs a graceful shutdown hook (see `hermes_five::utils::shutdown`) that resets the
+/// outputs and closes the boards on SIGINT (Ctrl-C), SIGTERM or panic: it can be opted out using
+/// `#[hermes_five::runtime(graceful_shutdown = false)]`.
 #[proc_macro_attribute]
-pub fn runtime(_: TokenStream, item: TokenStream) -> TokenStream {
-    runtime_macro(item.into(), TokioMode::Main).into()
+pub fn runtime(args: TokenStream, item: TokenStream) -> TokenStream {
+    runtime_macro(args.into(), item.into(), TokioMode::Main).into()
 }
 
 /// Defines `#[hermes_five_macros::runtime]` test macro.
 #[proc_macro_attribute]
-pub fn test(_: TokenStream, item: TokenStream) -> TokenStream {
-    runtime_macro(item.into(), TokioMode::Test).into()
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    runtime_macro(args.into(), item.into(), TokioMode::Test).into()
 }
@@ -7,6 +7,7 @@
 use hermes_five::hardware::{Board, BoardEvent};
 use hermes_five::io::RemoteIo;
 use hermes_five::io::Serial;
+use hermes_five::io::Tcp;
 
 #[hermes_five::runtime]
 async fn main() {
@@ -22,6 +23,7 @@ async fn main() {
     Board::new(RemoteIo::new("COM3")); // custom port
     Board::new(RemoteIo::from(Serial::new("COM3"))); // custom transport
     let _ = Board::from(Serial::default()); // RemoteIo + serial with default port.
+    let _ = Board::from(Tcp::new("192.168.1.100:3030")); // RemoteIo + tcp (StandardFirmataWiFi / StandardFirmataEthernet).
 
     // Beware: the program will stop here since no work as been registered through the `BoardEvent::OnReady` event.
     // Find more about this in the 'examples/board/creation.rs' example.
@@ -0,0 +1,32 @@
+//! Demonstrates how to use the pins of a GPIO expander (MCP23017) exactly as the ones of a board:
+//! a led on pin GPA0 is toggled by a pull-up button on pin GPB0.
+//! <https://learn.adafruit.com/using-mcp23008-mcp23017-with-circuitpython>
+
+use hermes_five::devices::{Button, InputEvent, Led};
+use hermes_five::hardware::{Board, BoardEvent, MCP23017};
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // The MCP23017 expander at the default address 0x20.
+        let expander = MCP23017::default(&board)?;
+
+        // Register a led on pin GPA0 and a pull-up button on pin GPB0 of the expander.
+        let led = Led::new(&expander, 0, false)?;
+        let button = Button::new_pullup(&expander, "GPB0")?;
+
+        // Triggered function when the button is pressed: the button value is read from the expander.
+        button.on(InputEvent::OnPress, move |_: ()| {
+            let mut led = led.clone();
+            async move {
+                led.toggle()?;
+                println!("Led is now {}", led.is_on());
+                Ok(())
+            }
+        });
+
+        Ok(())
+    });
+}
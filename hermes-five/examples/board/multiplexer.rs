@@ -0,0 +1,30 @@
+//! Demonstrates how to use two devices with the same I2C address behind a TCA9548A multiplexer:
+//! a servo is plugged on each of two PCA9685 (both at address 0x40) connected to the channels 0 and 1.
+//! <https://learn.adafruit.com/adafruit-tca9548a-1-to-8-i2c-multiplexer-breakout>
+
+use hermes_five::devices::Servo;
+use hermes_five::hardware::{Board, BoardEvent, PCA9685, TCA9548A};
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // The TCA9548A multiplexer at the default address 0x70.
+        let mux = TCA9548A::default(&board)?;
+
+        // Two PCA9685 at the same address, each on its own channel.
+        let left = PCA9685::new(&mux.channel(0)?, 0x40)?;
+        let right = PCA9685::new(&mux.channel(1)?, 0x40)?;
+
+        // Register a servo on pin 0 of each PCA9685.
+        let mut left_servo = Servo::new(&left, 0, 90)?;
+        let mut right_servo = Servo::new(&right, 0, 90)?;
+
+        // The channel is selected before each write: both servos can be moved independently.
+        left_servo.to(0)?;
+        right_servo.to(180)?;
+
+        Ok(())
+    });
+}
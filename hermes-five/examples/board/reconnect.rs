@@ -0,0 +1,51 @@
+//! This example shows how to make a board resilient to a connection loss (USB cable glitch, board reset, etc.).
+//!
+//! Wrapping any transport layer within an [`AutoReconnect`] makes the board able to detect the connection loss and
+//! to recover from it: the connexion is re-opened (with an exponential backoff), the handshake is made again and
+//! the pin modes and reporting are restored.
+//!
+//! In this example, we register three event handlers:
+//! - `OnDisconnect`: This event is triggered when the connection is lost.
+//! - `OnReconnect`: This event is triggered when the connection has been recovered.
+//! - `OnReconnectFailed`: This event is triggered when the connection could not be recovered.
+
+use hermes_five::devices::{Button, InputEvent};
+use hermes_five::hardware::{Board, BoardEvent};
+use hermes_five::io::{AutoReconnect, Serial};
+
+#[hermes_five::runtime]
+async fn main() {
+    // Try to reconnect every 500ms, 1s, 2s, 4s... up to 5 times before giving up.
+    let transport = AutoReconnect::new(Serial::default())
+        .set_retry_delay(500)
+        .set_max_attempts(5);
+    let board = Board::from(transport).open();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        println!("Connection done on board: unplug and replug the USB cable to test.");
+
+        // The button reporting will be restored on reconnection.
+        let button = Button::new(&board, 2)?;
+        button.on(InputEvent::OnPress, |_: ()| async move {
+            println!("Push button pressed");
+            Ok(())
+        });
+        Ok(())
+    });
+
+    board.on(BoardEvent::OnDisconnect, |_: Board| async move {
+        println!("Connection lost: trying to reconnect...");
+        Ok(())
+    });
+
+    board.on(BoardEvent::OnReconnect, |_: Board| async move {
+        println!("Connection recovered.");
+        Ok(())
+    });
+
+    board.on(BoardEvent::OnReconnectFailed, |board: Board| async move {
+        println!("Connection could not be recovered: giving up.");
+        board.close();
+        Ok(())
+    });
+}
@@ -0,0 +1,34 @@
+//! This example shows how the board is left in a safe state when the program is stopped (Ctrl-C, kill, panic).
+//!
+//! The `#[hermes_five::runtime]` macro installs a shutdown hook by default: on SIGINT, SIGTERM or panic, the animations
+//! are stopped, the registered outputs are reset to their default state and the boards are closed (emitting the
+//! `OnClose` event). A custom callback can be registered to run some code beforehand.
+//!
+//! _This behavior can be opted out using `#[hermes_five::runtime(graceful_shutdown = false)]`._
+
+use hermes_five::devices::Servo;
+use hermes_five::hardware::{Board, BoardEvent};
+use hermes_five::utils::shutdown;
+
+#[hermes_five::runtime]
+async fn main() {
+    shutdown::on_shutdown(|| {
+        println!("Shutting down: the servo goes back to its default position.")
+    });
+
+    let board = Board::run();
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register a servo: registered outputs are reset on shutdown.
+        let mut servo = Servo::new(&board, 9, 90)?;
+        board.register_output("arm", servo.clone());
+
+        println!("Servo is sweeping: press Ctrl-C to stop the program.");
+        servo.sweep(1000);
+        Ok(())
+    });
+
+    board.on(BoardEvent::OnClose, |_: Board| async move {
+        println!("Board closed.");
+        Ok(())
+    });
+}
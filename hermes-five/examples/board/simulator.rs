@@ -0,0 +1,54 @@
+//! This example shows how to run a board without any hardware attached, using a [`VirtualBoard`] simulator.
+//!
+//! The virtual board speaks the device side of the Firmata protocol: it can be used as a transport layer for any
+//! board. A clone of the simulator acts as a handle to inject input pin values (as if a button was pressed) and to
+//! inspect the output pin state (as if a LED was watched).
+
+use hermes_five::devices::{Button, InputEvent, Led};
+use hermes_five::hardware::{Board, BoardEvent};
+use hermes_five::io::{BoardProfile, VirtualBoard};
+use hermes_five::pause;
+
+#[hermes_five::runtime]
+async fn main() {
+    // Simulates an Arduino UNO board.
+    let simulator = VirtualBoard::new(BoardProfile::Uno);
+    let board = Board::from(simulator.clone()).open();
+
+    board.on(BoardEvent::OnReady, move |board: Board| {
+        let simulator = simulator.clone();
+        async move {
+            // Turns the LED on when the button is pressed.
+            let led = Led::new(&board, 13, false)?;
+            let button = Button::new(&board, 2)?;
+            let press_led = led.clone();
+            button.on(InputEvent::OnPress, move |_: ()| {
+                let mut led = press_led.clone();
+                async move {
+                    led.turn_on()?;
+                    Ok(())
+                }
+            });
+            button.on(InputEvent::OnRelease, move |_: ()| {
+                let mut led = led.clone();
+                async move {
+                    led.turn_off()?;
+                    Ok(())
+                }
+            });
+
+            // Simulates a press on the button.
+            simulator.set_pin_value(2, 1)?;
+            pause!(500);
+            println!("LED state while pressed: {}", simulator.get_pin_value(13)?);
+
+            // Simulates the button release.
+            simulator.set_pin_value(2, 0)?;
+            pause!(500);
+            println!("LED state once released: {}", simulator.get_pin_value(13)?);
+
+            board.close();
+            Ok(())
+        }
+    });
+}
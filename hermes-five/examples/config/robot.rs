@@ -0,0 +1,32 @@
+//! This example shows how to load a whole robot (boards, devices and animations) from a configuration file.
+//!
+//! The configuration (see `robot.yaml` next to this file) could be written in JSON or TOML as well: the format is
+//! guessed from the file extension. Requires the `config` feature.
+
+use hermes_five::config::RobotConfig;
+use hermes_five::devices::{Button, Led};
+use hermes_five::pause;
+
+#[hermes_five::runtime]
+async fn main() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/config/robot.yaml");
+
+    // Parses and validates the configuration (errors point to the faulty entry).
+    let config = RobotConfig::from_file(path).unwrap();
+
+    // Opens the boards and builds all devices and animations on them.
+    let registry = config.build().await.unwrap();
+
+    let led = registry.get_device::<Led>("status").unwrap();
+    let button = registry.get_device::<Button>("trigger").unwrap();
+    println!("LED: {}", led);
+    println!("Button: {}", button);
+
+    // Plays the waving animation.
+    let mut animation = registry.get_animation("wave").unwrap();
+    animation.play();
+    pause!(2500);
+
+    button.detach();
+    registry.close();
+}
@@ -0,0 +1,35 @@
+use hermes_five::animations::Easing;
+use hermes_five::devices::{Output, RgbLed};
+use hermes_five::hardware::{Board, BoardEvent};
+use hermes_five::pause;
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register a common cathode RGB LED on pins 9, 10 and 11 (red, green, blue).
+        let mut led = RgbLed::new(&board, [9, 10, 11]).expect("RGB led is instantiated");
+
+        // Set the color using RGB, hex or HSV values.
+        led.set_color([255, 0, 0])?;
+        pause!(1000);
+        led.set_hex("#00FF00")?;
+        pause!(1000);
+        led.set_hsv(240, 100, 100)?;
+        pause!(1000);
+
+        // Fade from blue to orange in 2 seconds.
+        led.animate(vec![255u8, 128, 0], 2000, Easing::SineInOut);
+        pause!(3000);
+
+        // Pulse the current color every 500ms for 5 seconds.
+        led.pulse(500);
+        pause!(5000);
+
+        led.stop();
+        led.turn_off()?;
+
+        Ok(())
+    });
+}
@@ -0,0 +1,34 @@
+use hermes_five::animations::Easing;
+use hermes_five::devices::{Motor, MotorPins, Output};
+use hermes_five::hardware::{Board, BoardEvent};
+use hermes_five::pause;
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register a motor driven by a L298N/TB6612 driver: speed on pin 5, direction on pins 7 and 8.
+        let mut motor = Motor::new(
+            &board,
+            MotorPins::HBridge {
+                pwm: 5,
+                dir: 7,
+                cdir: 8,
+            },
+        )?;
+
+        // Run forward at half speed for 2 seconds, then brake.
+        motor.forward(50)?;
+        pause!(2000);
+        motor.brake()?;
+        pause!(1000);
+
+        // Smoothly accelerate to full speed in reverse, then let the motor coast.
+        motor.animate(-100, 3000, Easing::SineIn);
+        pause!(4000);
+        motor.coast()?;
+
+        Ok(())
+    });
+}
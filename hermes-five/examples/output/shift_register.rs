@@ -0,0 +1,40 @@
+//! Demonstrates how to drive 16 outputs with only 3 board pins, using two daisy-chained 74HC595 shift registers:
+//! leds are created on the registers outputs exactly as on board pins.
+//! <https://docs.arduino.cc/tutorials/communication/guide-to-shift-out/>
+
+use hermes_five::devices::Led;
+use hermes_five::hardware::{Board, BoardEvent, ShiftRegister};
+use hermes_five::pause;
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Two chained registers: data (SER) on pin 2, clock (SRCLK) on pin 3, latch (RCLK) on pin 4.
+        let mut register = ShiftRegister::new(&board, 2, 3, 4, 2)?;
+
+        // Light all the outputs at once (a single shift-out), then switch them off.
+        register.write(&[0xFF, 0xFF])?;
+        pause!(1000);
+        register.write(&[0x00, 0x00])?;
+
+        // Register a led on each output of the second register: each led change shifts out the whole chain.
+        let mut leds = vec![];
+        for pin in 8..16 {
+            leds.push(Led::new(&register, pin, false)?);
+        }
+
+        // Chaser: turn the leds on then off, one after the other.
+        for led in leds.iter_mut() {
+            led.turn_on()?;
+            pause!(100);
+        }
+        for led in leds.iter_mut() {
+            led.turn_off()?;
+            pause!(100);
+        }
+
+        Ok(())
+    });
+}
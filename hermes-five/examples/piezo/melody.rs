@@ -0,0 +1,38 @@
+use hermes_five::devices::{Note, Output, Piezo};
+use hermes_five::hardware::{Board, BoardEvent};
+use hermes_five::pause;
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register a piezo on pin 3.
+        let mut piezo = Piezo::new(&board, 3)?;
+
+        // Play a 440Hz tone for 500ms.
+        piezo.frequency(440, 500)?;
+        pause!(1000);
+
+        // Play a sequence of notes at 120 bpm.
+        piezo.play(
+            vec![
+                Note::from_name("C4", 1.0).unwrap(),
+                Note::from_name("E4", 1.0).unwrap(),
+                Note::from_name("G4", 1.0).unwrap(),
+                Note::rest(1.0),
+                Note::from_name("C5", 2.0).unwrap(),
+            ],
+            120,
+        )?;
+        pause!(4000);
+
+        // Play a RTTTL ringtone for 5 seconds (one octave down: without TONE support, the pin cannot
+        // be toggled above 1000Hz).
+        piezo.play_rtttl("Tetris:d=4,o=4,b=160:e5,8b,8c5,8d5,16e5,16d5,8c5,8b,a,8a,8c5,e5,8d5,8c5,b,8b,8c5,d5,e5,c5,a,2a")?;
+        pause!(5000);
+        piezo.stop();
+
+        Ok(())
+    });
+}
@@ -0,0 +1,33 @@
+//! Demonstrates how to measure precise voltages with an ADS1115 analog-to-digital converter (I2C).
+//! <https://learn.adafruit.com/adafruit-4-channel-adc-breakouts>
+
+use hermes_five::devices::{AnalogInput, InputEvent};
+use hermes_five::hardware::{AdsGain, AdsMode, Board, BoardEvent, ADS1X15};
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // The ADS1115 at the default address 0x48: measures ±4.096V, 16 samples per second.
+        let mut ads = ADS1X15::default(&board)?;
+        ads.set_gain(AdsGain::One);
+        ads.set_mode(AdsMode::SingleEnded);
+        ads.set_data_rate(16)?;
+
+        // Register a sensor on the channel 0 of the converter.
+        let sensor = AnalogInput::new(&ads, "A0")?;
+
+        // Triggered function when the sensor value changes.
+        let converter = ads.clone();
+        sensor.on(InputEvent::OnChange, move |value: u16| {
+            let voltage = converter.to_voltage(value);
+            async move {
+                println!("Value: {} - voltage: {:.4}V", value, voltage);
+                Ok(())
+            }
+        });
+
+        Ok(())
+    });
+}
@@ -0,0 +1,40 @@
+//! Demonstrates the usage of a DHT22 temperature and humidity sensor on pin 7.
+//! The board must run a firmware including DhtFirmata (ConfigurableFirmata).
+
+use hermes_five::devices::{Dht, InputEvent};
+use hermes_five::hardware::{Board, BoardEvent};
+use hermes_five::io::DhtModel;
+use hermes_five::utils::State;
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register a DHT22 sensor on pin 7: read every 5 seconds.
+        let sensor = Dht::new(&board, 7, DhtModel::Dht22)?.set_interval(5000);
+
+        // Triggered function when the temperature or the humidity changes.
+        sensor.on(InputEvent::OnChange, |reading: State| async move {
+            println!("Reading: {}", reading);
+            Ok(())
+        });
+
+        // Triggered function when the temperature changes.
+        sensor.on(
+            InputEvent::OnTemperatureChange,
+            |temperature: f32| async move {
+                println!("Temperature: {:.1}°C", temperature);
+                Ok(())
+            },
+        );
+
+        // Triggered function when the humidity changes.
+        sensor.on(InputEvent::OnHumidityChange, |humidity: f32| async move {
+            println!("Humidity: {:.1}%", humidity);
+            Ok(())
+        });
+
+        Ok(())
+    });
+}
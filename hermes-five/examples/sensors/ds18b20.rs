@@ -0,0 +1,26 @@
+//! Demonstrates the usage of DS18B20 temperature sensors wired on a OneWire bus on pin 4.
+//! The board must run a firmware including OneWireFirmata (ConfigurableFirmata).
+
+use hermes_five::devices::{Ds18b20, InputEvent};
+use hermes_five::hardware::{Board, BoardEvent};
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register the DS18B20 sensors found on the bus of pin 4: read every 2 seconds.
+        let sensors = Ds18b20::new(&board, 4)?.set_interval(2000);
+
+        // Triggered function when the temperature of a sensor changes.
+        sensors.on(
+            InputEvent::OnTemperatureChange,
+            |(address, temperature): ([u8; 8], f32)| async move {
+                println!("Sensor {:02x?}: {:.2}°C", address, temperature);
+                Ok(())
+            },
+        );
+
+        Ok(())
+    });
+}
@@ -0,0 +1,35 @@
+//! Demonstrates the usage of a rotary encoder on Arduino pins 2 and 3 (interrupt pins).
+//! The board must run a firmware including EncoderFirmata (ex: ConfigurableFirmata).
+
+use hermes_five::devices::{Encoder, InputEvent};
+use hermes_five::hardware::{Board, BoardEvent};
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register an encoder on pins 2 and 3.
+        let encoder = Encoder::new(&board, 2, 3)?;
+
+        // Triggered functions when the encoder turns.
+        let clone = encoder.clone();
+        encoder.on(InputEvent::OnClockwise, move |position: i32| {
+            let encoder = clone.clone();
+            async move {
+                println!(
+                    "Clockwise: position {} ({:.1} steps/s)",
+                    position,
+                    encoder.get_velocity()
+                );
+                Ok(())
+            }
+        });
+        encoder.on(InputEvent::OnCounterClockwise, |position: i32| async move {
+            println!("Counter-clockwise: position {}", position);
+            Ok(())
+        });
+
+        Ok(())
+    });
+}
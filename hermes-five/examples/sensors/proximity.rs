@@ -0,0 +1,39 @@
+//! Demonstrates the usage of an ultrasonic proximity sensor (HC-SR04 with trig and echo wired together) on pin 7.
+//! The board must run a firmware including PingFirmata.
+
+use hermes_five::devices::{InputEvent, Proximity};
+use hermes_five::hardware::{Board, BoardEvent};
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register a proximity sensor on pin 7: measure 10 times per second.
+        let sensor = Proximity::new(&board, 7)?
+            .set_interval(100)
+            .set_range([0.0, 20.0]);
+
+        // Triggered function when the distance changes.
+        let clone = sensor.clone();
+        sensor.on(InputEvent::OnChange, move |distance: f32| {
+            let sensor = clone.clone();
+            async move {
+                println!(
+                    "Distance: {:.1}cm ({:.1}in)",
+                    distance,
+                    sensor.get_distance_in()
+                );
+                Ok(())
+            }
+        });
+
+        // Triggered function when an obstacle is closer than 20cm.
+        sensor.on(InputEvent::OnWithinRange, |distance: f32| async move {
+            println!("Watch out: obstacle at {:.1}cm!", distance);
+            Ok(())
+        });
+
+        Ok(())
+    });
+}
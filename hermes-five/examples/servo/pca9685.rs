@@ -10,6 +10,7 @@ async fn main() {
     let board = Board::run();
 
     board.on(BoardEvent::OnReady, |board: Board| async move {
+        // All channels share the PCA9685 frequency: 50Hz by default, as expected by servos.
         let pca9685 = PCA9685::default(&board)?;
 
         // Register servos on channel 0, 1 and 2 of the PCA9685.
@@ -0,0 +1,37 @@
+use hermes_five::devices::{OutputEvent, Stepper};
+use hermes_five::hardware::{Board, BoardEvent};
+use hermes_five::io::{StepSize, StepperConfig, StepperInterface};
+use hermes_five::pause;
+
+#[hermes_five::runtime]
+async fn main() {
+    let board = Board::run();
+
+    board.on(BoardEvent::OnReady, |board: Board| async move {
+        // Register a stepper driven by a A4988/DRV8825 driver: step on pin 2, direction on pin 3.
+        // /!\ The board must run a firmware including AccelStepperFirmata (ex: ConfigurableFirmata).
+        let mut stepper = Stepper::new(
+            &board,
+            StepperConfig {
+                interface: StepperInterface::Driver { step: 2, dir: 3 },
+                step_size: StepSize::Whole,
+                enable_pin: None,
+            },
+        )?;
+
+        stepper.on(OutputEvent::OnMoveComplete, |position: i32| async move {
+            println!("Stepper reached position {}", position);
+            Ok(())
+        });
+
+        // Move one revolution forward (200 steps), then come back smoothly.
+        stepper.set_speed(400.0)?;
+        stepper.step(200)?;
+        pause!(2000);
+        stepper.set_acceleration(200.0)?;
+        stepper.to(0)?;
+        pause!(3000);
+
+        Ok(())
+    });
+}
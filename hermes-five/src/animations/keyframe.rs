@@ -33,6 +33,7 @@ pub struct Keyframe {
     /// The end time of the keyframe in milliseconds.
     end: u64,
     /// The easing function applied during the transition (default: `Easing::Linear`).
+    #[cfg_attr(feature = "serde", serde(default))]
     transition: Easing,
 }
 
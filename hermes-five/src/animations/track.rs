@@ -54,6 +54,19 @@ pub struct Track {
     current: State,
 }
 
+impl From<Box<dyn Output>> for Track {
+    /// Creates a new `Track` associated with the given (boxed) actuator.
+    fn from(device: Box<dyn Output>) -> Self {
+        let history = device.get_state();
+        Self {
+            device,
+            keyframes: vec![],
+            previous: history.clone(),
+            current: history,
+        }
+    }
+}
+
 impl Track {
     /// Creates a new `Track` associated with the given actuator.
     #[allow(private_bounds)]
@@ -194,6 +207,17 @@ mod tests {
         assert_eq!(track.get_keyframes().len(), 1);
     }
 
+    #[test]
+    fn test_track_from_boxed_output() {
+        let actuator: Box<dyn Output> = Box::new(MockOutputDevice::new(5));
+        let track = Track::from(actuator);
+
+        assert_eq!(track.get_keyframes().len(), 0);
+        assert_eq!(track.get_device().get_state().as_integer(), 5);
+        assert_eq!(track.previous.as_integer(), 5);
+        assert_eq!(track.current.as_integer(), 5);
+    }
+
     #[test]
     fn test_get_duration() {
         let actuator = MockOutputDevice::new(5);
@@ -0,0 +1,1117 @@
+//! Loads a whole robot (boards, expanders, devices and animations) from a declarative configuration file.
+//!
+//! A configuration file (JSON, TOML or YAML) describes:
+//! - **boards**: named [`Board`]s, defined by their protocol and transport layer (same format as a serialized [`Board`]),
+//! - **expanders**: named expanders (such as [`PCA9685`]) plugged to a board,
+//! - **devices**: named devices (led, servo, button, etc.) plugged to a board or an expander,
+//! - **animations**: named animations made of segments of tracks (one per output device) of keyframes.
+//!
+//! Loading a configuration file only parses and validates it: the hardware is built by [`RobotConfig::build`] once all
+//! the boards are ready. The resulting [`Registry`] gives access to everything by name.
+//!
+//! Errors are reported as [`ConfigError`](crate::errors::Error::ConfigError) with the path to the bad entry in the file.
+//!
+//! # Example
+//! ```yaml
+//! boards:
+//!   main:
+//!     protocol:
+//!       type: RemoteIo
+//!       transport:
+//!         type: Serial
+//!         port: /dev/ttyACM0
+//! expanders:
+//!   pwm:
+//!     type: PCA9685
+//!     board: main
+//!     address: 0x40
+//! devices:
+//!   status:
+//!     type: Led
+//!     board: main
+//!     pin: 13
+//!   arm:
+//!     type: Servo
+//!     board: pwm
+//!     pin: 0
+//!     default: 90
+//! animations:
+//!   wave:
+//!     segments:
+//!       - repeat: true
+//!         tracks:
+//!           - device: arm
+//!             keyframes:
+//!               - { target: 180, start: 0, end: 1000 }
+//!               - { target: 0, start: 1000, end: 2000, transition: SineInOut }
+//! ```
+//! ```
+//! use hermes_five::config::{ConfigFormat, RobotConfig};
+//! use hermes_five::devices::Led;
+//!
+//! #[hermes_five::runtime]
+//! async fn main() {
+//!     let config = RobotConfig::parse(
+//!         r#"{
+//!             "boards": { "main": { "protocol": { "type": "RemoteIo", "transport": { "type": "VirtualBoard" } } } },
+//!             "devices": { "status": { "type": "Led", "board": "main", "pin": 13, "default": true } }
+//!         }"#,
+//!         ConfigFormat::Json,
+//!     )
+//!     .unwrap();
+//!
+//!     let registry = config.build().await.unwrap();
+//!     let led = registry.get_device::<Led>("status").unwrap();
+//!     assert!(led.is_on());
+//!     registry.close();
+//! }
+//! ```
+
+mod registry;
+
+pub use registry::Registry;
+
+use crate::animations::{Animation, Keyframe, Segment, Track};
+use crate::devices::{
+    AnalogInput, Button, DigitalInput, DigitalOutput, Led, PwmOutput, Servo, ServoType,
+};
+use crate::errors::{ConfigError, Error, UnknownError};
+use crate::hardware::{Board, BoardDevice, Hardware, PCA9685};
+use crate::io::PinIdOrName;
+use crate::utils::Range;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Lists the supported configuration file formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Guesses the configuration format from a file extension (`json`, `toml`, `yaml` or `yml`).
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Represents the declarative description of a robot: its boards, expanders, devices and animations (all named).
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RobotConfig {
+    /// The named boards.
+    #[serde(default)]
+    boards: BTreeMap<String, Board>,
+    /// The named expanders (plugged to a board).
+    #[serde(default)]
+    expanders: BTreeMap<String, ExpanderConfig>,
+    /// The named devices (plugged to a board or an expander).
+    #[serde(default)]
+    devices: BTreeMap<String, DeviceConfig>,
+    /// The named animations (of output devices).
+    #[serde(default)]
+    animations: BTreeMap<String, AnimationConfig>,
+
+    // ########################################
+    // # Volatile utility data.
+    /// The file the configuration comes from (used for error reporting).
+    #[serde(skip)]
+    file: String,
+}
+
+impl RobotConfig {
+    /// Loads a configuration file: the format is guessed from the file extension.
+    ///
+    /// # Errors
+    /// * `ConfigError`: the file cannot be read, its format is unknown or its content is invalid.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use hermes_five::config::RobotConfig;
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let registry = RobotConfig::from_file("robot.yaml").unwrap().build().await.unwrap();
+    /// }
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = path.as_ref().display().to_string();
+        let format = ConfigFormat::from_path(&path).ok_or_else(|| ConfigError {
+            file: file.clone(),
+            entry: String::from("."),
+            info: String::from("unsupported file extension (expected json, toml, yaml or yml)"),
+        })?;
+        let content = std::fs::read_to_string(&path).map_err(|error| ConfigError {
+            file: file.clone(),
+            entry: String::from("."),
+            info: error.to_string(),
+        })?;
+        Self::parse_as(file, &content, format)
+    }
+
+    /// Parses a configuration given as a string in the given format.
+    ///
+    /// # Errors
+    /// * `ConfigError`: the content is invalid.
+    pub fn parse(content: &str, format: ConfigFormat) -> Result<Self, Error> {
+        Self::parse_as(String::from("inline"), content, format)
+    }
+
+    /// Returns the file the configuration comes from.
+    pub fn get_file(&self) -> &str {
+        &self.file
+    }
+
+    /// Returns the board declared under the given name (if any), for instance to register event callbacks before
+    /// the build.
+    pub fn get_board(&self, name: &str) -> Option<&Board> {
+        self.boards.get(name)
+    }
+
+    /// Builds the robot: opens all boards, waits until they are ready (handshake done) then instantiates the
+    /// expanders, devices and animations on them.
+    ///
+    /// The `OnReady` event is emitted on each board once everything is built: its callbacks can look up the
+    /// devices registered on the board (see [`Board::get_device`]).
+    ///
+    /// # Errors
+    /// * `ConfigError`: an entry cannot be built (board connection failure, unknown board or device reference,
+    ///   incompatible pin, etc.). The boards already opened are closed.
+    pub async fn build(self) -> Result<Registry, Error> {
+        let file = self.file.clone();
+        let mut registry = Registry::default();
+
+        // Open all boards simultaneously.
+        let handlers: Vec<_> = self
+            .boards
+            .clone()
+            .into_iter()
+            .map(|(name, board)| {
+                let handler = tokio::task::spawn_blocking(move || board.blocking_open());
+                (name, handler)
+            })
+            .collect();
+        let mut result = Ok(());
+        for (name, handler) in handlers {
+            let board = handler
+                .await
+                .unwrap_or_else(|error| {
+                    Err(UnknownError {
+                        info: error.to_string(),
+                    })
+                })
+                .map_err(|error| config_error(&file, format!("boards.{}", name), error));
+            match board {
+                Ok(board) => registry.add_board(name, board),
+                Err(error) => result = result.and(Err(error)),
+            }
+        }
+
+        if let Err(error) = result.and_then(|_| self.populate(&mut registry)) {
+            registry.blocking_close();
+            return Err(error);
+        }
+        for name in registry.get_board_names() {
+            if let Some(board) = registry.get_board(name) {
+                board.emit_ready();
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Parses the content and keeps track of the path to the faulty entry if any.
+    fn parse_as(file: String, content: &str, format: ConfigFormat) -> Result<Self, Error> {
+        let result = match format {
+            ConfigFormat::Json => {
+                let mut deserializer = serde_json::Deserializer::from_str(content);
+                serde_path_to_error::deserialize(&mut deserializer)
+                    .map_err(|error| (error.path().to_string(), error.inner().to_string()))
+            }
+            ConfigFormat::Toml => serde_path_to_error::deserialize(toml::Deserializer::new(
+                content,
+            ))
+            .map_err(|error| {
+                (
+                    error.path().to_string(),
+                    toml_message(content, error.inner()),
+                )
+            }),
+            ConfigFormat::Yaml => {
+                serde_path_to_error::deserialize(serde_norway::Deserializer::from_str(content))
+                    .map_err(|error| (error.path().to_string(), error.inner().to_string()))
+            }
+        };
+
+        let mut config: RobotConfig = result.map_err(|(entry, info)| ConfigError {
+            file: file.clone(),
+            entry,
+            info,
+        })?;
+        config.file = file;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the cross references between entries before anything is built.
+    fn validate(&self) -> Result<(), Error> {
+        for (name, expander) in &self.expanders {
+            let entry = format!("expanders.{}", name);
+            if self.boards.contains_key(name) {
+                return Err(self.error(entry, "name already used by a board"));
+            }
+            if !self.boards.contains_key(expander.get_board()) {
+                let info = format!("unknown board '{}'", expander.get_board());
+                return Err(self.error(format!("{}.board", entry), info));
+            }
+        }
+
+        for (name, device) in &self.devices {
+            let board = device.get_board();
+            if !self.boards.contains_key(board) && !self.expanders.contains_key(board) {
+                let info = format!("unknown board or expander '{}'", board);
+                return Err(self.error(format!("devices.{}.board", name), info));
+            }
+        }
+
+        for (name, animation) in &self.animations {
+            for (s, segment) in animation.segments.iter().enumerate() {
+                for (t, track) in segment.tracks.iter().enumerate() {
+                    let entry = format!("animations.{}.segments[{}].tracks[{}]", name, s, t);
+                    match self.devices.get(&track.device) {
+                        None => {
+                            let info = format!("unknown device '{}'", track.device);
+                            return Err(self.error(format!("{}.device", entry), info));
+                        }
+                        Some(device) if !device.is_output() => {
+                            let info = format!("device '{}' is not an output", track.device);
+                            return Err(self.error(format!("{}.device", entry), info));
+                        }
+                        _ => {}
+                    }
+                    for (k, keyframe) in track.keyframes.iter().enumerate() {
+                        if keyframe.get_start() > keyframe.get_end() {
+                            return Err(self.error(
+                                format!("{}.keyframes[{}]", entry, k),
+                                "start time must be less than or equal to end time",
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Instantiates the expanders, devices and animations against the (ready) boards of the registry.
+    fn populate(&self, registry: &mut Registry) -> Result<(), Error> {
+        for (name, expander) in &self.expanders {
+            let entry = format!("expanders.{}", name);
+            let board = registry
+                .get_board(expander.get_board())
+                .ok_or_else(|| self.error(&entry, "board not ready"))?;
+            let expander = expander
+                .build(&board)
+                .map_err(|error| config_error(&self.file, &entry, error))?;
+            registry.add_expander(name, expander);
+        }
+
+        for (name, device) in &self.devices {
+            let entry = format!("devices.{}", name);
+            let hardware = registry
+                .get_hardware(device.get_board())
+                .ok_or_else(|| self.error(&entry, "board not ready"))?;
+            device
+                .build(name, &*hardware, registry)
+                .map_err(|error| config_error(&self.file, &entry, error))?;
+
+            // Devices plugged directly to a board are registered on it as well.
+            if let Some(board) = registry.get_board(device.get_board()) {
+                if let Some(output) = registry.get_output(name) {
+                    board.register(name, BoardDevice::Output(output));
+                } else if let Some(input) = registry.get_input(name) {
+                    board.register(name, BoardDevice::Input(input));
+                }
+            }
+        }
+
+        for (name, animation) in &self.animations {
+            let mut segments = vec![];
+            for segment in &animation.segments {
+                let mut tracks = vec![];
+                for track in &segment.tracks {
+                    let device = registry.get_output(&track.device).ok_or_else(|| {
+                        self.error(format!("animations.{}", name), "device not built")
+                    })?;
+                    let track = track
+                        .keyframes
+                        .iter()
+                        .fold(Track::from(device), |track, keyframe| {
+                            track.with_keyframe(keyframe.clone())
+                        });
+                    tracks.push(track);
+                }
+                segments.push(segment.build(tracks));
+            }
+            registry.add_animation(name, Animation::default().set_segments(segments));
+        }
+
+        Ok(())
+    }
+
+    /// Creates a configuration error for the given entry.
+    fn error<E: Into<String>, I: Into<String>>(&self, entry: E, info: I) -> Error {
+        ConfigError {
+            file: self.file.clone(),
+            entry: entry.into(),
+            info: info.into(),
+        }
+    }
+}
+
+/// Wraps an error occurring while building an entry as a configuration error.
+fn config_error<E: Into<String>>(file: &str, entry: E, error: Error) -> Error {
+    ConfigError {
+        file: file.to_string(),
+        entry: entry.into(),
+        info: error.to_string().trim_end_matches('.').to_string(),
+    }
+}
+
+/// Formats a TOML error on a single line (its `Display` renders a multi-line snippet of the file).
+fn toml_message(content: &str, error: &toml::de::Error) -> String {
+    match error.span() {
+        None => error.message().to_string(),
+        Some(span) => {
+            let before = &content[..span.start.min(content.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |index| index + 1) + 1;
+            format!("{} at line {} column {}", error.message(), line, column)
+        }
+    }
+}
+
+// ########################################
+// Entries description.
+
+/// Describes an expander plugged to a board.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", deny_unknown_fields)]
+enum ExpanderConfig {
+    PCA9685 {
+        board: String,
+        #[serde(default = "default_pca9685_address")]
+        address: u8,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        frequency: Option<u16>,
+    },
+}
+
+fn default_pca9685_address() -> u8 {
+    0x40
+}
+
+impl ExpanderConfig {
+    fn get_board(&self) -> &str {
+        match self {
+            ExpanderConfig::PCA9685 { board, .. } => board,
+        }
+    }
+
+    fn build(&self, board: &Board) -> Result<PCA9685, Error> {
+        match self {
+            ExpanderConfig::PCA9685 {
+                address, frequency, ..
+            } => {
+                let mut expander = PCA9685::new(board, *address)?;
+                if let Some(frequency) = frequency {
+                    expander.set_frequency(*frequency)?;
+                }
+                Ok(expander)
+            }
+        }
+    }
+}
+
+/// Describes a pin either by id (13) or by name ("A0").
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum PinConfig {
+    Id(u8),
+    Name(String),
+}
+
+impl PinConfig {
+    /// Returns the id of the pin on the given hardware.
+    fn resolve(&self, board: &dyn Hardware) -> Result<u8, Error> {
+        Ok(board.get_io().read().get_pin(self)?.id)
+    }
+}
+
+impl From<&PinConfig> for PinIdOrName {
+    fn from(pin: &PinConfig) -> Self {
+        match pin {
+            PinConfig::Id(id) => PinIdOrName::Id(*id),
+            PinConfig::Name(name) => PinIdOrName::Name(name.clone()),
+        }
+    }
+}
+
+impl Display for PinConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", PinIdOrName::from(self))
+    }
+}
+
+/// Describes a device plugged to a board or an expander.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", deny_unknown_fields)]
+enum DeviceConfig {
+    Led {
+        board: String,
+        pin: PinConfig,
+        #[serde(default)]
+        default: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        brightness: Option<u8>,
+    },
+    Servo {
+        board: String,
+        pin: PinConfig,
+        #[serde(default)]
+        default: u16,
+        #[serde(default)]
+        servo_type: ServoType,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        range: Option<Range<u16>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pwm_range: Option<Range<u16>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        degree_range: Option<Range<u16>>,
+        #[serde(default)]
+        inverted: bool,
+        #[serde(default)]
+        auto_detach: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detach_delay: Option<usize>,
+    },
+    DigitalOutput {
+        board: String,
+        pin: PinConfig,
+        #[serde(default)]
+        default: bool,
+    },
+    PwmOutput {
+        board: String,
+        pin: PinConfig,
+        #[serde(default)]
+        default: u16,
+    },
+    Button {
+        board: String,
+        pin: PinConfig,
+        #[serde(default)]
+        inverted: bool,
+        #[serde(default)]
+        pullup: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        debounce: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hold_time: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hold_repeat: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        click_interval: Option<u64>,
+    },
+    DigitalInput {
+        board: String,
+        pin: PinConfig,
+    },
+    AnalogInput {
+        board: String,
+        pin: PinConfig,
+    },
+}
+
+impl DeviceConfig {
+    fn get_board(&self) -> &str {
+        match self {
+            DeviceConfig::Led { board, .. }
+            | DeviceConfig::Servo { board, .. }
+            | DeviceConfig::DigitalOutput { board, .. }
+            | DeviceConfig::PwmOutput { board, .. }
+            | DeviceConfig::Button { board, .. }
+            | DeviceConfig::DigitalInput { board, .. }
+            | DeviceConfig::AnalogInput { board, .. } => board,
+        }
+    }
+
+    fn is_output(&self) -> bool {
+        matches!(
+            self,
+            DeviceConfig::Led { .. }
+                | DeviceConfig::Servo { .. }
+                | DeviceConfig::DigitalOutput { .. }
+                | DeviceConfig::PwmOutput { .. }
+        )
+    }
+
+    /// Instantiates the device on the given hardware and registers it.
+    fn build(
+        &self,
+        name: &str,
+        board: &dyn Hardware,
+        registry: &mut Registry,
+    ) -> Result<(), Error> {
+        match self {
+            DeviceConfig::Led {
+                pin,
+                default,
+                brightness,
+                ..
+            } => {
+                let mut led = Led::new(board, pin.resolve(board)?, *default)?;
+                if let Some(brightness) = brightness {
+                    led = led.set_brightness(*brightness)?;
+                }
+                registry.add_output(name, led);
+            }
+            DeviceConfig::Servo {
+                pin,
+                default,
+                servo_type,
+                range,
+                pwm_range,
+                degree_range,
+                inverted,
+                auto_detach,
+                detach_delay,
+                ..
+            } => {
+                let mut servo = Servo::new(board, pin.resolve(board)?, *default)?
+                    .set_type(*servo_type)
+                    .set_inverted(*inverted)
+                    .set_auto_detach(*auto_detach);
+                if let Some(pwm_range) = pwm_range {
+                    servo = servo.set_pwn_range(*pwm_range)?;
+                }
+                if let Some(degree_range) = degree_range {
+                    servo = servo.set_degree_range(*degree_range);
+                }
+                if let Some(range) = range {
+                    servo = servo.set_range(*range);
+                }
+                if let Some(detach_delay) = detach_delay {
+                    servo = servo.set_detach_delay(*detach_delay);
+                }
+                registry.add_output(name, servo);
+            }
+            DeviceConfig::DigitalOutput { pin, default, .. } => {
+                registry.add_output(name, DigitalOutput::new(board, pin, *default)?);
+            }
+            DeviceConfig::PwmOutput { pin, default, .. } => {
+                registry.add_output(name, PwmOutput::new(board, pin, *default)?);
+            }
+            DeviceConfig::Button {
+                pin,
+                inverted,
+                pullup,
+                debounce,
+                hold_time,
+                hold_repeat,
+                click_interval,
+                ..
+            } => {
+                let mut button = match (inverted, pullup) {
+                    (false, false) => Button::new(board, pin)?,
+                    (true, false) => Button::new_inverted(board, pin)?,
+                    (false, true) => Button::new_pullup(board, pin)?,
+                    (true, true) => Button::new_inverted_pullup(board, pin)?,
+                };
+                if let Some(debounce) = debounce {
+                    button = button.set_debounce(*debounce);
+                }
+                if let Some(hold_time) = hold_time {
+                    button = button.set_hold_time(*hold_time);
+                }
+                if let Some(hold_repeat) = hold_repeat {
+                    button = button.set_hold_repeat(*hold_repeat);
+                }
+                if let Some(click_interval) = click_interval {
+                    button = button.set_click_interval(*click_interval);
+                }
+                registry.add_input(name, button);
+            }
+            DeviceConfig::DigitalInput { pin, .. } => {
+                registry.add_input(name, DigitalInput::new(board, pin)?);
+            }
+            DeviceConfig::AnalogInput { pin, .. } => {
+                registry.add_input(name, AnalogInput::new(board, pin)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Describes an animation: an ordered list of segments.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnimationConfig {
+    segments: Vec<SegmentConfig>,
+}
+
+/// Describes an animation segment: a set of tracks (one per output device).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SegmentConfig {
+    #[serde(default)]
+    repeat: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    loopback: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    speed: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fps: Option<u8>,
+    tracks: Vec<TrackConfig>,
+}
+
+impl SegmentConfig {
+    fn build(&self, tracks: Vec<Track>) -> Segment {
+        let mut segment = Segment::default()
+            .set_repeat(self.repeat)
+            .set_tracks(tracks);
+        if let Some(loopback) = self.loopback {
+            segment = segment.set_loopback(loopback);
+        }
+        if let Some(speed) = self.speed {
+            segment = segment.set_speed(speed);
+        }
+        if let Some(fps) = self.fps {
+            segment = segment.set_fps(fps);
+        }
+        segment
+    }
+}
+
+/// Describes an animation track: the keyframes of an output device (referenced by name).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrackConfig {
+    device: String,
+    keyframes: Vec<Keyframe>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::Output;
+    use crate::hardware::BoardEvent;
+    use crate::io::{PinModeId, IO};
+    use crate::pause;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    const JSON: &str = r#"{
+        "boards": {
+            "main": { "protocol": { "type": "RemoteIo", "transport": { "type": "VirtualBoard", "profile": "Uno" } } }
+        },
+        "expanders": {
+            "pwm": { "type": "PCA9685", "board": "main" }
+        },
+        "devices": {
+            "status": { "type": "Led", "board": "main", "pin": "D13", "default": true },
+            "arm": { "type": "Servo", "board": "main", "pin": 9, "default": 90, "range": [10, 170] },
+            "motor": { "type": "PwmOutput", "board": "main", "pin": "D6", "default": 128 },
+            "claw": { "type": "Servo", "board": "pwm", "pin": 0, "default": 45 }
+        },
+        "animations": {
+            "wave": {
+                "segments": [{
+                    "repeat": true,
+                    "fps": 30,
+                    "tracks": [
+                        { "device": "arm", "keyframes": [{ "target": 170, "start": 0, "end": 500 }] },
+                        { "device": "status", "keyframes": [{ "target": 0, "start": 0, "end": 100, "transition": "SineInOut" }] }
+                    ]
+                }]
+            }
+        }
+    }"#;
+
+    const TOML: &str = r#"
+        [boards.main.protocol]
+        type = "RemoteIo"
+        transport = { type = "VirtualBoard", profile = "Nano" }
+
+        [devices.status]
+        type = "Led"
+        board = "main"
+        pin = 13
+
+        [devices.button]
+        type = "Button"
+        board = "main"
+        pin = 2
+        pullup = true
+        debounce = 50
+
+        [[animations.blink.segments]]
+        tracks = [{ device = "status", keyframes = [{ target = 255, start = 0, end = 500 }] }]
+    "#;
+
+    const YAML: &str = r#"
+        boards:
+          main:
+            protocol:
+              type: RemoteIo
+              transport:
+                type: VirtualBoard
+                profile: Mega
+        devices:
+          sensor:
+            type: AnalogInput
+            board: main
+            pin: A0
+          switch:
+            type: DigitalInput
+            board: main
+            pin: 22
+    "#;
+
+    /// Parses the given content and returns the error as a string.
+    fn _parse_error(content: &str, format: ConfigFormat) -> String {
+        RobotConfig::parse(content, format).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_config_format_from_path() {
+        assert_eq!(
+            ConfigFormat::from_path("robot.json"),
+            Some(ConfigFormat::Json)
+        );
+        assert_eq!(
+            ConfigFormat::from_path("robot.toml"),
+            Some(ConfigFormat::Toml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path("robot.YAML"),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::from_path("config/robot.yml"),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(ConfigFormat::from_path("robot.ini"), None);
+        assert_eq!(ConfigFormat::from_path("robot"), None);
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let config = RobotConfig::parse(JSON, ConfigFormat::Json).unwrap();
+        assert_eq!(config.get_file(), "inline");
+        assert_eq!(config.boards.len(), 1);
+        assert_eq!(config.expanders.len(), 1);
+        assert_eq!(config.devices.len(), 4);
+        assert_eq!(config.animations.len(), 1);
+        assert!(config
+            .boards
+            .get("main")
+            .unwrap()
+            .to_string()
+            .contains("transport=VirtualBoard(Uno)"));
+
+        let config = RobotConfig::parse(TOML, ConfigFormat::Toml).unwrap();
+        assert_eq!(config.boards.len(), 1);
+        assert_eq!(config.devices.len(), 2);
+        assert_eq!(config.animations.len(), 1);
+        assert!(config.expanders.is_empty());
+
+        let config = RobotConfig::parse(YAML, ConfigFormat::Yaml).unwrap();
+        assert_eq!(config.boards.len(), 1);
+        assert_eq!(config.devices.len(), 2);
+        assert!(config.animations.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors_report_entry() {
+        let error = _parse_error(
+            r#"{ "devices": { "led": { "type": "Led", "board": "main", "pin": 13, "default": "x" } } }"#,
+            ConfigFormat::Json,
+        );
+        assert!(
+            error.starts_with("Configuration error: inline (devices.led) - invalid type: string \"x\", expected a boolean"),
+            "{}",
+            error
+        );
+
+        let error = _parse_error(
+            "devices:\n  led:\n    type: Led\n    board: main\n    pins: 13\n",
+            ConfigFormat::Yaml,
+        );
+        assert!(
+            error.starts_with("Configuration error: inline (devices.led) - ")
+                && error.contains("unknown field `pins`"),
+            "{}",
+            error
+        );
+
+        let error = _parse_error(
+            "[devices.thing]\ntype = \"Laser\"\nboard = \"main\"\n",
+            ConfigFormat::Toml,
+        );
+        assert!(
+            error.starts_with(
+                "Configuration error: inline (devices.thing.type) - unknown variant `Laser`"
+            ),
+            "{}",
+            error
+        );
+
+        let error = _parse_error("{ \"boards\": ", ConfigFormat::Json);
+        assert!(
+            error.starts_with("Configuration error: inline (boards) - EOF while parsing"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_validate_references() {
+        let error = _parse_error(
+            r#"{ "devices": { "led": { "type": "Led", "board": "main", "pin": 13 } } }"#,
+            ConfigFormat::Json,
+        );
+        assert_eq!(
+            error,
+            "Configuration error: inline (devices.led.board) - unknown board or expander 'main'."
+        );
+
+        let error = _parse_error(
+            r#"{ "expanders": { "pwm": { "type": "PCA9685", "board": "main" } } }"#,
+            ConfigFormat::Json,
+        );
+        assert_eq!(
+            error,
+            "Configuration error: inline (expanders.pwm.board) - unknown board 'main'."
+        );
+
+        let error = _parse_error(
+            r#"{
+                "boards": { "main": { "protocol": { "type": "RemoteIo", "transport": { "type": "VirtualBoard" } } } },
+                "expanders": { "main": { "type": "PCA9685", "board": "main" } }
+            }"#,
+            ConfigFormat::Json,
+        );
+        assert_eq!(
+            error,
+            "Configuration error: inline (expanders.main) - name already used by a board."
+        );
+    }
+
+    #[test]
+    fn test_validate_animations() {
+        let boards = r#""boards": { "main": { "protocol": { "type": "RemoteIo", "transport": { "type": "VirtualBoard" } } } }"#;
+        let devices = r#""devices": {
+            "led": { "type": "Led", "board": "main", "pin": 13 },
+            "button": { "type": "Button", "board": "main", "pin": 2 }
+        }"#;
+
+        let error = _parse_error(
+            &format!(
+                r#"{{ {}, {}, "animations": {{ "a": {{ "segments": [{{ "tracks": [{{ "device": "nope", "keyframes": [] }}] }}] }} }} }}"#,
+                boards, devices
+            ),
+            ConfigFormat::Json,
+        );
+        assert_eq!(
+            error,
+            "Configuration error: inline (animations.a.segments[0].tracks[0].device) - unknown device 'nope'."
+        );
+
+        let error = _parse_error(
+            &format!(
+                r#"{{ {}, {}, "animations": {{ "a": {{ "segments": [{{ "tracks": [{{ "device": "button", "keyframes": [] }}] }}] }} }} }}"#,
+                boards, devices
+            ),
+            ConfigFormat::Json,
+        );
+        assert_eq!(
+            error,
+            "Configuration error: inline (animations.a.segments[0].tracks[0].device) - device 'button' is not an output."
+        );
+
+        let error = _parse_error(
+            &format!(
+                r#"{{ {}, {}, "animations": {{ "a": {{ "segments": [{{ "tracks": [{{ "device": "led", "keyframes": [
+                    {{ "target": 1, "start": 0, "end": 10 }},
+                    {{ "target": 0, "start": 50, "end": 20 }}
+                ] }}] }}] }} }} }}"#,
+                boards, devices
+            ),
+            ConfigFormat::Json,
+        );
+        assert_eq!(
+            error,
+            "Configuration error: inline (animations.a.segments[0].tracks[0].keyframes[1]) - start time must be less than or equal to end time."
+        );
+    }
+
+    #[test]
+    fn test_from_file() {
+        let directory = std::env::temp_dir();
+
+        let path = directory.join("hermes_five_test_robot.yaml");
+        std::fs::write(&path, YAML).unwrap();
+        let config = RobotConfig::from_file(&path).unwrap();
+        assert_eq!(config.get_file(), path.display().to_string());
+        assert_eq!(config.devices.len(), 2);
+
+        let path = directory.join("hermes_five_test_robot_invalid.toml");
+        std::fs::write(&path, "[devices.led]\ntype = \"Led\"\nboard = 3\n").unwrap();
+        let error = RobotConfig::from_file(&path).unwrap_err().to_string();
+        assert!(
+            error.starts_with(&format!(
+                "Configuration error: {} (devices.led) - invalid type: integer `3`, expected a string at line 1 column 1",
+                path.display()
+            )),
+            "{}",
+            error
+        );
+
+        let error = RobotConfig::from_file("robot.ini").unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "Configuration error: robot.ini (.) - unsupported file extension (expected json, toml, yaml or yml)."
+        );
+
+        let error = RobotConfig::from_file(directory.join("hermes_five_missing.json"));
+        assert!(error.is_err());
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_build() {
+        let config = RobotConfig::parse(JSON, ConfigFormat::Json).unwrap();
+        assert!(config.get_board("other").is_none());
+
+        // The boards are ready once everything is built.
+        let ready_flag = Arc::new(AtomicBool::new(false));
+        let moved_ready_flag = ready_flag.clone();
+        config
+            .get_board("main")
+            .unwrap()
+            .on(BoardEvent::OnReady, move |board: Board| {
+                let captured_flag = moved_ready_flag.clone();
+                async move {
+                    captured_flag.store(board.get_device("status").is_some(), Ordering::SeqCst);
+                    Ok(())
+                }
+            });
+        let registry = config.build().await.unwrap();
+        pause!(100);
+        assert!(ready_flag.load(Ordering::SeqCst));
+
+        let board = registry.get_board("main").unwrap();
+        assert!(board.is_connected());
+        assert_eq!(registry.get_board_names(), vec!["main"]);
+        assert!(registry.get_board("other").is_none());
+
+        let expander = registry.get_expander("pwm").unwrap();
+        assert_eq!(expander.get_address(), 0x40);
+        assert!(registry.get_hardware("pwm").is_some());
+
+        let led = registry.get_device::<Led>("status").unwrap();
+        assert!(led.is_on());
+        assert!(registry.get_device::<Servo>("status").is_none());
+        assert!(registry.get_device::<Led>("unknown").is_none());
+
+        let servo = registry.get_device::<Servo>("arm").unwrap();
+        assert_eq!(servo.get_range(), Range::from([10, 170]));
+        assert_eq!(servo.get_state().as_integer(), 90);
+        assert_eq!(
+            board.get_io().read().get_pin(9).unwrap().mode.id,
+            PinModeId::SERVO
+        );
+
+        let motor = registry.get_output("motor").unwrap();
+        assert_eq!(motor.get_state().as_integer(), 128);
+        assert!(registry.get_input("motor").is_none());
+
+        let claw = registry.get_device::<Servo>("claw").unwrap();
+        assert_eq!(claw.get_state().as_integer(), 45);
+
+        // Devices plugged to the board are registered on it (not the ones plugged to the expander).
+        assert_eq!(board.get_devices().len(), 3);
+        assert!(board.get_device("status").is_some());
+        assert!(board.get_device("claw").is_none());
+
+        let mut names = registry.get_device_names();
+        names.sort();
+        assert_eq!(names, vec!["arm", "claw", "motor", "status"]);
+
+        let animation = registry.get_animation("wave").unwrap();
+        assert_eq!(registry.get_animation_names(), vec!["wave"]);
+        assert_eq!(animation.get_segments().len(), 1);
+        assert_eq!(animation.get_segments()[0].get_tracks().len(), 2);
+        assert_eq!(animation.get_segments()[0].get_fps(), 30);
+        assert!(animation.get_segments()[0].is_repeat());
+        assert_eq!(animation.get_segments()[0].get_duration(), 500);
+
+        registry.close();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_build_errors() {
+        let error = RobotConfig::parse(
+            r#"{
+                "boards": { "main": { "protocol": { "type": "RemoteIo", "transport": { "type": "VirtualBoard" } } } },
+                "devices": { "led": { "type": "Led", "board": "main", "pin": 99 } }
+            }"#,
+            ConfigFormat::Json,
+        )
+        .unwrap()
+        .build()
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Configuration error: inline (devices.led) - Hardware error: Unknown pin 99."
+        );
+
+        let error = RobotConfig::parse(
+            r#"{
+                "boards": { "main": { "protocol": { "type": "RemoteIo", "transport": { "type": "VirtualBoard" } } } },
+                "devices": { "led": { "type": "Led", "board": "main", "pin": 0 } }
+            }"#,
+            ConfigFormat::Json,
+        )
+        .unwrap()
+        .build()
+        .await
+        .unwrap_err();
+        assert!(error.to_string().starts_with(
+            "Configuration error: inline (devices.led) - Hardware error: Pin (0) not compatible"
+        ));
+
+        let error = RobotConfig::parse(
+            r#"{ "boards": { "main": { "protocol": { "type": "RemoteIo", "transport": { "type": "Tcp", "address": "127.0.0.1:1" } } } } }"#,
+            ConfigFormat::Json,
+        )
+        .unwrap()
+        .build()
+        .await
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Configuration error: inline (boards.main) - Protocol error:"));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::animations::Animation;
+use crate::devices::{Device, Input, Output};
+use crate::hardware::{Board, Hardware, PCA9685};
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Represents the robot built from a [`RobotConfig`](crate::config::RobotConfig): gives access to its boards,
+/// expanders, devices and animations by name.
+///
+/// All getters return a handle on the entity: devices, boards and animations share their inner state with their
+/// clones, hence acting on the returned value acts on the registered one.
+///
+/// # Example
+/// ```no_run
+/// use hermes_five::config::RobotConfig;
+/// use hermes_five::devices::{Led, Servo};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let registry = RobotConfig::from_file("robot.yaml").unwrap().build().await.unwrap();
+///
+///     let mut led = registry.get_device::<Led>("status").unwrap();
+///     led.blink(500);
+///
+///     let mut animation = registry.get_animation("wave").unwrap();
+///     animation.play();
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    /// The boards by name.
+    boards: HashMap<String, Board>,
+    /// The expanders by name.
+    expanders: HashMap<String, PCA9685>,
+    /// The devices by name (as their concrete type).
+    devices: HashMap<String, Arc<dyn Any + Send + Sync>>,
+    /// The output devices by name.
+    outputs: HashMap<String, Box<dyn Output>>,
+    /// The input devices by name.
+    inputs: HashMap<String, Box<dyn Input>>,
+    /// The animations by name.
+    animations: HashMap<String, Animation>,
+}
+
+impl Registry {
+    /// Returns the board registered under the given name.
+    pub fn get_board(&self, name: &str) -> Option<Board> {
+        self.boards.get(name).cloned()
+    }
+
+    /// Returns the expander registered under the given name.
+    pub fn get_expander(&self, name: &str) -> Option<PCA9685> {
+        self.expanders.get(name).cloned()
+    }
+
+    /// Returns the hardware (board or expander) registered under the given name.
+    pub fn get_hardware(&self, name: &str) -> Option<Box<dyn Hardware>> {
+        match self.boards.get(name) {
+            Some(board) => Some(Box::new(board.clone())),
+            None => self
+                .expanders
+                .get(name)
+                .map(|expander| Box::new(expander.clone()) as Box<dyn Hardware>),
+        }
+    }
+
+    /// Returns the device registered under the given name if it is of the requested type.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use hermes_five::config::Registry;
+    /// use hermes_five::devices::Servo;
+    ///
+    /// fn center(registry: &Registry) {
+    ///     let mut servo = registry.get_device::<Servo>("arm").unwrap();
+    ///     servo.to(90).unwrap();
+    /// }
+    /// ```
+    pub fn get_device<T: Device + Clone + 'static>(&self, name: &str) -> Option<T> {
+        self.devices.get(name)?.downcast_ref::<T>().cloned()
+    }
+
+    /// Returns the output device registered under the given name.
+    pub fn get_output(&self, name: &str) -> Option<Box<dyn Output>> {
+        self.outputs.get(name).cloned()
+    }
+
+    /// Returns the input device registered under the given name.
+    pub fn get_input(&self, name: &str) -> Option<Box<dyn Input>> {
+        self.inputs.get(name).cloned()
+    }
+
+    /// Returns the animation registered under the given name.
+    pub fn get_animation(&self, name: &str) -> Option<Animation> {
+        self.animations.get(name).cloned()
+    }
+
+    /// Returns the names of all registered boards.
+    pub fn get_board_names(&self) -> Vec<&str> {
+        self.boards.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the names of all registered devices.
+    pub fn get_device_names(&self) -> Vec<&str> {
+        self.devices.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the names of all registered animations.
+    pub fn get_animation_names(&self) -> Vec<&str> {
+        self.animations.keys().map(String::as_str).collect()
+    }
+
+    /// Stops all animations and closes all boards (see [`Board::close`]).
+    pub fn close(&self) {
+        self.stop_animations();
+        for board in self.boards.values() {
+            board.clone().close();
+        }
+    }
+
+    // ########################################
+    // Internal helpers used while building.
+
+    pub(crate) fn add_board<S: Into<String>>(&mut self, name: S, board: Board) {
+        self.boards.insert(name.into(), board);
+    }
+
+    pub(crate) fn add_expander<S: Into<String>>(&mut self, name: S, expander: PCA9685) {
+        self.expanders.insert(name.into(), expander);
+    }
+
+    pub(crate) fn add_output<S: Into<String>, T: Output + Clone + 'static>(
+        &mut self,
+        name: S,
+        device: T,
+    ) {
+        let name = name.into();
+        self.outputs.insert(name.clone(), Box::new(device.clone()));
+        self.devices.insert(name, Arc::new(device));
+    }
+
+    pub(crate) fn add_input<S: Into<String>, T: Input + Clone + 'static>(
+        &mut self,
+        name: S,
+        device: T,
+    ) {
+        let name = name.into();
+        self.inputs.insert(name.clone(), Box::new(device.clone()));
+        self.devices.insert(name, Arc::new(device));
+    }
+
+    pub(crate) fn add_animation<S: Into<String>>(&mut self, name: S, animation: Animation) {
+        self.animations.insert(name.into(), animation);
+    }
+
+    /// Blocking version of [`Self::close()`] method (errors are ignored).
+    pub(crate) fn blocking_close(&self) {
+        self.stop_animations();
+        for board in self.boards.values() {
+            let _ = board.clone().blocking_close();
+        }
+    }
+
+    fn stop_animations(&self) {
+        for animation in self.animations.values() {
+            animation.clone().stop();
+        }
+    }
+}
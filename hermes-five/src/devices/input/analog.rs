@@ -2,13 +2,13 @@ use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
 use parking_lot::RwLock;
+use tokio::sync::broadcast::error::RecvError;
 
 use crate::devices::input::{Input, InputEvent};
 use crate::devices::Device;
 use crate::errors::Error;
 use crate::hardware::Hardware;
-use crate::io::{IoProtocol, PinIdOrName, PinModeId};
-use crate::pause;
+use crate::io::{IoProtocol, PinClaim, PinIdOrName, PinModeId};
 use crate::utils::task;
 use crate::utils::{EventHandler, EventManager, State, TaskHandler};
 
@@ -30,6 +30,9 @@ pub struct AnalogInput {
     // # Volatile utility data.
     #[cfg_attr(feature = "serde", serde(skip))]
     protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pin (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    claim: PinClaim,
     /// Inner handler to the task running the button value check.
     #[cfg_attr(feature = "serde", serde(skip))]
     handler: Arc<RwLock<Option<TaskHandler>>>,
@@ -45,6 +48,7 @@ impl AnalogInput {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the AnalogInput pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the AnalogInput pin does not support ANALOG mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new<T: Into<PinIdOrName>>(board: &dyn Hardware, analog_pin: T) -> Result<Self, Error> {
         let pin = board.get_io().read().get_pin(analog_pin)?.clone();
 
@@ -52,6 +56,7 @@ impl AnalogInput {
             pin: pin.id,
             state: Arc::new(RwLock::new(pin.value)),
             protocol: board.get_protocol(),
+            claim: PinClaim::new(board.get_io(), vec![pin.id], "AnalogInput")?,
             handler: Arc::new(RwLock::new(None)),
             events: Default::default(),
         };
@@ -81,28 +86,40 @@ impl AnalogInput {
     /// Manually attaches the AnalogInput with the value change events.
     /// This should never be needed unless you manually `detach()` the AnalogInput first for some reason
     /// and want it to start being reactive to events again.
+    ///
+    /// # Notes
+    /// The AnalogInput is not attached if its pin has been claimed by another device in the meantime.
     pub fn attach(&self) {
+        if self.claim.renew().is_err() {
+            return;
+        }
         if self.handler.read().is_none() {
             let self_clone = self.clone();
+            let mut changes = self.protocol.get_io().read().subscribe();
             *self.handler.write() = Some(
                 task::run(async move {
+                    // Reacts to the pin value changes notified by the protocol.
                     loop {
-                        let pin_value = self_clone
-                            .protocol
-                            .get_io()
-                            .read()
-                            .get_pin(self_clone.pin)?
-                            .value;
+                        let pin_value = match changes.recv().await {
+                            Ok(change) if change.pin == self_clone.pin => change.value,
+                            Ok(_) => continue,
+                            // Some changes were missed: resync with the current value.
+                            Err(RecvError::Lagged(_)) => {
+                                self_clone
+                                    .protocol
+                                    .get_io()
+                                    .read()
+                                    .get_pin(self_clone.pin)?
+                                    .value
+                            }
+                            Err(RecvError::Closed) => break,
+                        };
                         let state_value = *self_clone.state.read();
                         if pin_value != state_value {
                             *self_clone.state.write() = pin_value;
                             self_clone.events.emit(InputEvent::OnChange, pin_value);
                         }
-
-                        // Change can only be done 10x a sec. to avoid bouncing.
-                        pause!(100);
                     }
-                    #[allow(unreachable_code)]
                     Ok(())
                 })
                 .unwrap(),
@@ -111,12 +128,13 @@ impl AnalogInput {
     }
 
     /// Detaches the interval associated with the AnalogInput.
-    /// This means the AnalogInput won't react anymore to value changes.
+    /// This means the AnalogInput won't react anymore to value changes and its pin is released.
     pub fn detach(&self) {
         if let Some(handler) = self.handler.read().as_ref() {
             handler.abort();
         }
-        *self.handler.write() = None
+        *self.handler.write() = None;
+        self.claim.release();
     }
 
     /// Registers a callback to be executed on a given event.
@@ -180,7 +198,11 @@ impl Display for AnalogInput {
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
-impl Device for AnalogInput {}
+impl Device for AnalogInput {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Input for AnalogInput {
@@ -194,6 +216,7 @@ mod tests {
     use crate::devices::input::analog::AnalogInput;
     use crate::devices::input::Input;
     use crate::devices::input::InputEvent;
+    use crate::devices::Device;
     use crate::hardware::Board;
     use crate::mocks::plugin_io::MockIoProtocol;
     use crate::pause;
@@ -207,6 +230,7 @@ mod tests {
         assert!(sensor.is_ok());
         let sensor = sensor.unwrap();
         assert_eq!(sensor.get_pin(), 14);
+        assert_eq!(sensor.get_pins(), vec![14]);
         assert_eq!(sensor.get_state().as_integer(), 100);
         sensor.detach();
 
@@ -257,9 +281,8 @@ mod tests {
             .protocol
             .get_io()
             .write()
-            .get_pin_mut(pin)
-            .unwrap()
-            .value = 0xFF;
+            .set_pin_value(sensor.get_pin(), 0xFF)
+            .unwrap();
 
         pause!(500);
         assert_eq!(change_flag.load(Ordering::SeqCst), 0xFF);
@@ -1,13 +1,15 @@
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
+use std::time::Duration;
 
 use parking_lot::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{sleep_until, Instant};
 
 use crate::devices::{Device, Input, InputEvent};
 use crate::errors::Error;
 use crate::hardware::Hardware;
-use crate::io::{IoProtocol, PinIdOrName, PinModeId};
-use crate::pause;
+use crate::io::{IoProtocol, PinClaim, PinIdOrName, PinModeId};
 use crate::utils::{task, EventHandler, EventManager, State, TaskHandler};
 
 /// Represents a simple push button as an input of the board.
@@ -16,6 +18,9 @@ use crate::utils::{task, EventHandler, EventManager, State, TaskHandler};
 /// This structure is very similar to [`DigitalInput`](crate::devices::DigitalInput) but exposes convenience methods to handle two sorts of buttons:
 /// - pull-down: when the button is configured with a pin to ground by default (ie button press => pin becomes high)
 /// - pull-up: when the button is configured with a pin to +Vin by default (ie button press => pin becomes low)
+///
+/// On top of press/release, the button detects the following gestures (see [`Button::on`]): hold, hold repeat
+/// and multi-clicks (double and triple clicks). The timings are configurable with the various setters.
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug)]
 pub struct Button {
@@ -30,11 +35,22 @@ pub struct Button {
     invert: bool,
     /// Defines a PULL-UP mode button.
     pullup: bool,
+    /// The time (in ms) a pin value must remain stable to be considered (default: 20ms).
+    debounce: u64,
+    /// The time (in ms) the button must be pressed to be considered held (default: 500ms).
+    hold_time: u64,
+    /// The interval (in ms) between repeated hold events while the button is held (default: 200ms - 0 means no repeat).
+    hold_repeat: u64,
+    /// The maximum time (in ms) between two clicks for a multi-click (default: 300ms).
+    click_interval: u64,
 
     // ########################################
     // # Volatile utility data.
     #[cfg_attr(feature = "serde", serde(skip))]
     protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pin (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    claim: PinClaim,
     /// Inner handler to the task running the button value check.
     #[cfg_attr(feature = "serde", serde(skip))]
     handler: Arc<RwLock<Option<TaskHandler>>>,
@@ -53,13 +69,19 @@ impl Button {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the Button pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the Button pin does not support INPUT mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new<T: Into<PinIdOrName>>(board: &dyn Hardware, pin: T) -> Result<Self, Error> {
         Self {
             pin: 0,
             state: Arc::new(RwLock::new(false)),
             invert: false,
             pullup: false,
+            debounce: 20,
+            hold_time: 500,
+            hold_repeat: 200,
+            click_interval: 300,
             protocol: board.get_protocol(),
+            claim: Default::default(),
             handler: Arc::new(RwLock::new(None)),
             events: Default::default(),
         }
@@ -76,13 +98,19 @@ impl Button {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the Button pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the Button pin does not support INPUT mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new_inverted<T: Into<PinIdOrName>>(board: &dyn Hardware, pin: T) -> Result<Self, Error> {
         Self {
             pin: 0,
             state: Arc::new(RwLock::new(false)),
             invert: true,
             pullup: false,
+            debounce: 20,
+            hold_time: 500,
+            hold_repeat: 200,
+            click_interval: 300,
             protocol: board.get_protocol(),
+            claim: Default::default(),
             handler: Arc::new(RwLock::new(None)),
             events: Default::default(),
         }
@@ -98,13 +126,19 @@ impl Button {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the Button pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the Button pin does not support INPUT mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new_pullup<T: Into<PinIdOrName>>(board: &dyn Hardware, pin: T) -> Result<Self, Error> {
         Self {
             pin: 0,
             state: Arc::new(RwLock::new(false)),
             invert: false,
             pullup: true,
+            debounce: 20,
+            hold_time: 500,
+            hold_repeat: 200,
+            click_interval: 300,
             protocol: board.get_protocol(),
+            claim: Default::default(),
             handler: Arc::new(RwLock::new(None)),
             events: Default::default(),
         }
@@ -122,6 +156,7 @@ impl Button {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the Button pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the Button pin does not support INPUT mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new_inverted_pullup<T: Into<PinIdOrName>>(
         board: &dyn Hardware,
         pin: T,
@@ -131,7 +166,12 @@ impl Button {
             state: Arc::new(RwLock::new(false)),
             invert: true,
             pullup: true,
+            debounce: 20,
+            hold_time: 500,
+            hold_repeat: 200,
+            click_interval: 300,
             protocol: board.get_protocol(),
+            claim: Default::default(),
             handler: Arc::new(RwLock::new(None)),
             events: Default::default(),
         }
@@ -150,6 +190,9 @@ impl Button {
         self.pin = pin.id;
         *self.state.write() = pin.value != 0;
 
+        // Claim the pin ownership.
+        self.claim = PinClaim::new(self.protocol.get_io(), vec![self.pin], "Button")?;
+
         // Set pin mode to INPUT/PULLUP.
         match self.pullup {
             true => {
@@ -187,51 +230,115 @@ impl Button {
         self.invert
     }
 
+    /// Returns the time (in ms) a pin value must remain stable to be considered.
+    pub fn get_debounce(&self) -> u64 {
+        self.debounce
+    }
+
+    /// Sets the time (in ms) a pin value must remain stable to be considered (0 means no debounce).
+    pub fn set_debounce(mut self, debounce: u64) -> Self {
+        self.debounce = debounce;
+        self.refresh()
+    }
+
+    /// Returns the time (in ms) the button must be pressed to be considered held.
+    pub fn get_hold_time(&self) -> u64 {
+        self.hold_time
+    }
+
+    /// Sets the time (in ms) the button must be pressed to be considered held.
+    pub fn set_hold_time(mut self, hold_time: u64) -> Self {
+        self.hold_time = hold_time;
+        self.refresh()
+    }
+
+    /// Returns the interval (in ms) between repeated hold events while the button is held.
+    pub fn get_hold_repeat(&self) -> u64 {
+        self.hold_repeat
+    }
+
+    /// Sets the interval (in ms) between repeated hold events while the button is held (0 means no repeat).
+    pub fn set_hold_repeat(mut self, hold_repeat: u64) -> Self {
+        self.hold_repeat = hold_repeat;
+        self.refresh()
+    }
+
+    /// Returns the maximum time (in ms) between two clicks for a multi-click.
+    pub fn get_click_interval(&self) -> u64 {
+        self.click_interval
+    }
+
+    /// Sets the maximum time (in ms) between two clicks for a multi-click.
+    pub fn set_click_interval(mut self, click_interval: u64) -> Self {
+        self.click_interval = click_interval;
+        self.refresh()
+    }
+
     // ########################################
     // Event related functions
 
     /// Manually attaches the button with the value change events.
     /// This should never be needed unless you manually `detach()` the button first for some reason
     /// and want it to start being reactive to events again.
+    ///
+    /// # Notes
+    /// The button is not attached if its pin has been claimed by another device in the meantime.
     pub fn attach(&self) {
+        if self.claim.renew().is_err() {
+            return;
+        }
         if self.handler.read().is_none() {
             let self_clone = self.clone();
+            let mut changes = self.protocol.get_io().read().subscribe();
             *self.handler.write() = Some(
                 task::run(async move {
+                    let mut tracker = PressTracker::default();
+                    // The pin value waiting for the debounce time to be elapsed (if any).
+                    let mut pending: Option<(bool, Instant)> = None;
                     loop {
-                        let pin_value = self_clone
-                            .protocol
-                            .get_io()
-                            .read()
-                            .get_pin(self_clone.pin)?
-                            .value
-                            != 0;
-                        let state_value = *self_clone.state.read();
-                        if pin_value != state_value {
-                            *self_clone.state.write() = pin_value;
-
-                            // Depending on logical inversion mode, pin_value is inverted.
-                            match self_clone.invert {
-                                false => self_clone.events.emit(InputEvent::OnChange, pin_value),
-                                true => self_clone.events.emit(InputEvent::OnChange, !pin_value),
-                            };
-
-                            match self_clone.pullup {
-                                true => match pin_value {
-                                    true => self_clone.events.emit(InputEvent::OnRelease, ()),
-                                    false => self_clone.events.emit(InputEvent::OnPress, ()),
-                                },
-                                false => match pin_value {
-                                    true => self_clone.events.emit(InputEvent::OnPress, ()),
-                                    false => self_clone.events.emit(InputEvent::OnRelease, ()),
-                                },
-                            };
+                        // Wakes up on the next pin value change, or on the next due timer.
+                        let deadline = match (pending, tracker.hold_deadline) {
+                            (Some((_, debounce)), Some(hold)) => Some(debounce.min(hold)),
+                            (Some((_, debounce)), None) => Some(debounce),
+                            (None, hold) => hold,
+                        };
+                        let received = tokio::select! {
+                            received = changes.recv() => Some(received),
+                            _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => None,
+                        };
+
+                        // Reacts to the pin value changes notified by the protocol.
+                        let pin_value = match received {
+                            Some(Ok(change)) if change.pin == self_clone.pin => {
+                                Some(change.value != 0)
+                            }
+                            Some(Ok(_)) | None => None,
+                            // Some changes were missed: resync with the current value.
+                            Some(Err(RecvError::Lagged(_))) => Some(
+                                self_clone
+                                    .protocol
+                                    .get_io()
+                                    .read()
+                                    .get_pin(self_clone.pin)?
+                                    .value
+                                    != 0,
+                            ),
+                            Some(Err(RecvError::Closed)) => break,
+                        };
+                        if let Some(pin_value) = pin_value {
+                            let debounce = Duration::from_millis(self_clone.debounce);
+                            pending = Some((pin_value, Instant::now() + debounce));
                         }
 
-                        // Change can only be done 10x a sec. to avoid bouncing.
-                        pause!(100);
+                        let now = Instant::now();
+                        if let Some((pin_value, _)) = pending.filter(|(_, at)| *at <= now) {
+                            pending = None;
+                            self_clone.update(pin_value, &mut tracker);
+                        }
+                        if tracker.hold_deadline.is_some_and(|at| at <= now) {
+                            self_clone.hold(&mut tracker);
+                        }
                     }
-                    #[allow(unreachable_code)]
                     Ok(())
                 })
                 .unwrap(),
@@ -240,12 +347,13 @@ impl Button {
     }
 
     /// Detaches the interval associated with the button.
-    /// This means the button won't react anymore to value changes.
+    /// This means the button won't react anymore to value changes and its pin is released.
     pub fn detach(&self) {
         if let Some(handler) = self.handler.read().as_ref() {
             handler.abort();
         }
-        *self.handler.write() = None
+        *self.handler.write() = None;
+        self.claim.release();
     }
 
     /// Registers a callback to be executed on a given event on the Button.
@@ -255,8 +363,18 @@ impl Button {
     ///   _The callback must receive the following parameter: `|value: u16| { ... }`_
     /// - **`InputEvent::OnRelease` | `released`:** Triggered when the button value changes.     
     ///   _The callback must receive the void parameter: `|_:()| { ... }`_
+    /// - **`InputEvent::OnPressDuration` | `press_duration`:** Triggered when the button is released.     
+    ///   _The callback must receive the press duration (in ms): `|duration: u64| { ... }`_
     /// - **`InputEvent::OnPress` | `pressed`:** Triggered when the button value changes.     
     ///   _The callback must receive the void parameter: `|_:()| { ... }`_
+    /// - **`InputEvent::OnHold` | `hold`:** Triggered when the button has been pressed for `hold_time`.     
+    ///   _The callback must receive the void parameter: `|_:()| { ... }`_
+    /// - **`InputEvent::OnHoldRepeat` | `hold_repeat`:** Triggered every `hold_repeat` while the button is held.     
+    ///   _The callback must receive the number of repeats: `|count: u32| { ... }`_
+    /// - **`InputEvent::OnDoubleClick` | `double_click`:** Triggered on the second click (within `click_interval`).     
+    ///   _The callback must receive the void parameter: `|_:()| { ... }`_
+    /// - **`InputEvent::OnTripleClick` | `triple_click`:** Triggered on the third click (within `click_interval`).     
+    ///   _The callback must receive the void parameter: `|_:()| { ... }`_
     ///
     /// # Example
     ///
@@ -299,6 +417,105 @@ impl Button {
     {
         self.events.on(event, callback)
     }
+
+    /// Restarts the listener (if attached) for a configuration change to be considered.
+    fn refresh(self) -> Self {
+        let handler = self.handler.write().take();
+        if let Some(handler) = handler {
+            handler.abort();
+            self.attach();
+        }
+        self
+    }
+
+    /// Updates the button with a new (debounced) pin value and emits the related events.
+    fn update(&self, pin_value: bool, tracker: &mut PressTracker) {
+        if pin_value == *self.state.read() {
+            return;
+        }
+        *self.state.write() = pin_value;
+
+        // Depending on logical inversion mode, pin_value is inverted.
+        match self.invert {
+            false => self.events.emit(InputEvent::OnChange, pin_value),
+            true => self.events.emit(InputEvent::OnChange, !pin_value),
+        };
+
+        let now = Instant::now();
+        // Depending on pull-up mode, a press sets the pin LOW or HIGH.
+        match pin_value != self.pullup {
+            true => {
+                self.events.emit(InputEvent::OnPress, ());
+                tracker.pressed_at = Some(now);
+                tracker.held = false;
+                tracker.repeats = 0;
+                tracker.hold_deadline = Some(now + Duration::from_millis(self.hold_time));
+            }
+            false => {
+                self.events.emit(InputEvent::OnRelease, ());
+                if let Some(pressed_at) = tracker.pressed_at.take() {
+                    let duration = (now - pressed_at).as_millis() as u64;
+                    self.events.emit(InputEvent::OnPressDuration, duration);
+                }
+                tracker.hold_deadline = None;
+
+                // A held button does not count as a click.
+                if tracker.held {
+                    tracker.clicks = 0;
+                    return;
+                }
+                let interval = Duration::from_millis(self.click_interval);
+                tracker.clicks = match tracker.last_click {
+                    Some(at) if now - at <= interval => tracker.clicks + 1,
+                    _ => 1,
+                };
+                tracker.last_click = Some(now);
+                match tracker.clicks {
+                    2 => self.events.emit(InputEvent::OnDoubleClick, ()),
+                    3 => {
+                        self.events.emit(InputEvent::OnTripleClick, ());
+                        tracker.clicks = 0;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Emits the hold (or hold repeat) event and schedules the next repeat.
+    fn hold(&self, tracker: &mut PressTracker) {
+        match tracker.held {
+            false => {
+                tracker.held = true;
+                self.events.emit(InputEvent::OnHold, ());
+            }
+            true => {
+                tracker.repeats += 1;
+                self.events.emit(InputEvent::OnHoldRepeat, tracker.repeats);
+            }
+        }
+        tracker.hold_deadline = match self.hold_repeat {
+            0 => None,
+            repeat => Some(Instant::now() + Duration::from_millis(repeat)),
+        };
+    }
+}
+
+/// Tracks the button presses to detect the hold and multi-click gestures.
+#[derive(Debug, Default)]
+struct PressTracker {
+    /// When the button has been pressed (if currently pressed).
+    pressed_at: Option<Instant>,
+    /// Indicates the current press is a hold.
+    held: bool,
+    /// When the next hold (or hold repeat) event is due.
+    hold_deadline: Option<Instant>,
+    /// The number of hold repeats since the button is held.
+    repeats: u32,
+    /// The number of consecutive clicks.
+    clicks: u8,
+    /// When the last click happened.
+    last_click: Option<Instant>,
 }
 
 impl Display for Button {
@@ -315,7 +532,11 @@ impl Display for Button {
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
-impl Device for Button {}
+impl Device for Button {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Input for Button {
@@ -329,10 +550,11 @@ impl Input for Button {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 
     use crate::hardware::Board;
     use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
 
     use super::*;
 
@@ -344,6 +566,7 @@ mod tests {
         assert!(button.is_ok());
         let button = button.unwrap();
         assert_eq!(button.get_pin(), 4);
+        assert_eq!(button.get_pins(), vec![4]);
         assert_eq!(button.get_state().as_bool(), true);
         assert!(!button.is_inverted());
         assert!(!button.is_pullup());
@@ -409,7 +632,12 @@ mod tests {
                 state: Arc::new(RwLock::new(false)),
                 invert: true,
                 pullup: false,
+                debounce: 0,
+                hold_time: 500,
+                hold_repeat: 200,
+                click_interval: 300,
                 protocol: board.get_protocol(),
+                claim: Default::default(),
                 handler: Arc::new(RwLock::new(None)),
                 events: Default::default(),
             },
@@ -420,8 +648,13 @@ mod tests {
         let button = button.unwrap();
         assert_eq!(button.get_pin(), 13);
         assert!(button.handler.read().is_some());
+        assert_eq!(
+            button.protocol.get_io().read().get_pin_owner(13).unwrap(),
+            "Button"
+        );
         button.detach();
         assert!(button.handler.read().is_none());
+        assert!(button.protocol.get_io().read().get_pin_owner(13).is_none());
         board.close();
     }
 
@@ -485,9 +718,8 @@ mod tests {
             .protocol
             .get_io()
             .write()
-            .get_pin_mut(5)
-            .unwrap()
-            .value = 0xFF;
+            .set_pin_value(5, 0xFF)
+            .unwrap();
 
         pause!(500);
 
@@ -500,9 +732,8 @@ mod tests {
             .protocol
             .get_io()
             .write()
-            .get_pin_mut(5)
-            .unwrap()
-            .value = 0;
+            .set_pin_value(5, 0)
+            .unwrap();
 
         pause!(500);
 
@@ -560,9 +791,8 @@ mod tests {
             .protocol
             .get_io()
             .write()
-            .get_pin_mut(5)
-            .unwrap()
-            .value = 0xFF;
+            .set_pin_value(5, 0xFF)
+            .unwrap();
 
         pause!(500);
 
@@ -575,9 +805,8 @@ mod tests {
             .protocol
             .get_io()
             .write()
-            .get_pin_mut(5)
-            .unwrap()
-            .value = 0;
+            .set_pin_value(5, 0)
+            .unwrap();
 
         pause!(500);
 
@@ -663,6 +892,208 @@ mod tests {
     //     board.close();
     // }
 
+    #[hermes_five_macros::test]
+    fn test_button_settings() {
+        let board = Board::new(MockIoProtocol::default());
+        let button = Button::new(&board, 5).unwrap();
+        assert_eq!(button.get_debounce(), 20);
+        assert_eq!(button.get_hold_time(), 500);
+        assert_eq!(button.get_hold_repeat(), 200);
+        assert_eq!(button.get_click_interval(), 300);
+
+        let button = button
+            .set_debounce(10)
+            .set_hold_time(1000)
+            .set_hold_repeat(0)
+            .set_click_interval(200);
+        assert_eq!(button.get_debounce(), 10);
+        assert_eq!(button.get_hold_time(), 1000);
+        assert_eq!(button.get_hold_repeat(), 0);
+        assert_eq!(button.get_click_interval(), 200);
+        assert!(button.handler.read().is_some());
+
+        button.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    fn test_button_debounce() {
+        let board = Board::new(MockIoProtocol::default());
+        let button = Button::new(&board, 5).unwrap().set_debounce(100);
+
+        let press_count = Arc::new(AtomicU32::new(0));
+        let moved_press_count = press_count.clone();
+        button.on(InputEvent::OnPress, move |_: ()| {
+            let captured_count = moved_press_count.clone();
+            async move {
+                captured_count.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        // Bouncing values are ignored.
+        for value in [1, 0, 1, 0, 1] {
+            button
+                .protocol
+                .get_io()
+                .write()
+                .set_pin_value(5, value)
+                .unwrap();
+            pause!(10);
+        }
+        assert!(!button.get_state().as_bool());
+        pause!(300);
+        assert!(button.get_state().as_bool());
+        assert_eq!(press_count.load(Ordering::SeqCst), 1);
+
+        button.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    fn test_button_hold() {
+        let board = Board::new(MockIoProtocol::default());
+        let button = Button::new(&board, 5)
+            .unwrap()
+            .set_debounce(0)
+            .set_hold_time(200)
+            .set_hold_repeat(100);
+
+        // HOLD
+        let hold_flag = Arc::new(AtomicBool::new(false));
+        let moved_hold_flag = hold_flag.clone();
+        button.on(InputEvent::OnHold, move |_: ()| {
+            let captured_flag = moved_hold_flag.clone();
+            async move {
+                captured_flag.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        // HOLD REPEAT
+        let repeat_count = Arc::new(AtomicU32::new(0));
+        let moved_repeat_count = repeat_count.clone();
+        button.on(InputEvent::OnHoldRepeat, move |count: u32| {
+            let captured_count = moved_repeat_count.clone();
+            async move {
+                captured_count.store(count, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        // PRESS DURATION
+        let duration = Arc::new(AtomicU64::new(0));
+        let moved_duration = duration.clone();
+        button.on(InputEvent::OnPressDuration, move |value: u64| {
+            let captured_duration = moved_duration.clone();
+            async move {
+                captured_duration.store(value, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        button
+            .protocol
+            .get_io()
+            .write()
+            .set_pin_value(5, 1)
+            .unwrap();
+        pause!(100);
+        assert!(!hold_flag.load(Ordering::SeqCst));
+        pause!(600);
+        assert!(hold_flag.load(Ordering::SeqCst));
+        assert!(repeat_count.load(Ordering::SeqCst) >= 1);
+
+        button
+            .protocol
+            .get_io()
+            .write()
+            .set_pin_value(5, 0)
+            .unwrap();
+        pause!(200);
+        // A repeat was emitted before the release: the press lasted at least hold_time + hold_repeat.
+        let duration = duration.load(Ordering::SeqCst);
+        assert!((300..5000).contains(&duration), "duration: {}", duration);
+
+        // No more repeat once released.
+        let count = repeat_count.load(Ordering::SeqCst);
+        pause!(250);
+        assert_eq!(repeat_count.load(Ordering::SeqCst), count);
+
+        button.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    fn test_button_multi_click() {
+        let board = Board::new(MockIoProtocol::default());
+        let button = Button::new(&board, 5).unwrap().set_debounce(0);
+
+        // DOUBLE CLICK
+        let double_flag = Arc::new(AtomicBool::new(false));
+        let moved_double_flag = double_flag.clone();
+        button.on(InputEvent::OnDoubleClick, move |_: ()| {
+            let captured_flag = moved_double_flag.clone();
+            async move {
+                captured_flag.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        // TRIPLE CLICK
+        let triple_flag = Arc::new(AtomicBool::new(false));
+        let moved_triple_flag = triple_flag.clone();
+        button.on(InputEvent::OnTripleClick, move |_: ()| {
+            let captured_flag = moved_triple_flag.clone();
+            async move {
+                captured_flag.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        for click in 1..=3 {
+            button
+                .protocol
+                .get_io()
+                .write()
+                .set_pin_value(5, 1)
+                .unwrap();
+            pause!(30);
+            button
+                .protocol
+                .get_io()
+                .write()
+                .set_pin_value(5, 0)
+                .unwrap();
+            pause!(30);
+            assert_eq!(double_flag.load(Ordering::SeqCst), click >= 2);
+            assert_eq!(triple_flag.load(Ordering::SeqCst), click == 3);
+        }
+
+        // Clicks too far apart are not multi-clicks.
+        double_flag.store(false, Ordering::SeqCst);
+        for _ in 0..2 {
+            button
+                .protocol
+                .get_io()
+                .write()
+                .set_pin_value(5, 1)
+                .unwrap();
+            pause!(30);
+            button
+                .protocol
+                .get_io()
+                .write()
+                .set_pin_value(5, 0)
+                .unwrap();
+            pause!(400);
+        }
+        assert!(!double_flag.load(Ordering::SeqCst));
+
+        button.detach();
+        board.close();
+    }
+
     #[hermes_five_macros::test]
     fn test_button_display() {
         let board = Board::new(MockIoProtocol::default());
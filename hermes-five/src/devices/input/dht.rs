@@ -0,0 +1,495 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::devices::input::{Input, InputEvent};
+use crate::devices::Device;
+use crate::errors::Error;
+use crate::errors::HardwareError::{IncompatiblePin, SensorFailure};
+use crate::hardware::Hardware;
+use crate::io::{DhtModel, DhtReading, IoProtocol, PinClaim, PinIdOrName, PinModeId};
+use crate::utils::{task, EventHandler, EventManager, State, TaskHandler};
+
+/// Represents a DHT temperature and humidity sensor (DHT11, DHT22/AM2302): an [`Input`] [`Device`]
+/// read through the ConfigurableFirmata DHT feature.
+///
+/// The sensor state is an object holding both the `temperature` (in °C) and the `humidity` (in %): the sensor
+/// is read at a regular interval (see [`Dht::set_interval`]), which can not be shorter than what the model supports.
+///
+/// # Notes
+/// The firmware running on the board must include the DhtFirmata feature.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Dht {
+    // ########################################
+    // # Basics
+    /// The pin (id) of the [`Board`] the sensor is attached to.
+    pin: u8,
+    /// The sensor model.
+    model: DhtModel,
+    /// The last valid reading: temperature (in °C) and humidity (in %).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    state: Arc<RwLock<(f32, f32)>>,
+    /// The interval (in ms) between two readings (default: the model minimum).
+    interval: u64,
+
+    // ########################################
+    // # Volatile utility data.
+    /// The status of the last failed reading (if the last reading failed).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    failure: Arc<RwLock<Option<u8>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pin (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    claim: PinClaim,
+    /// Inner handler to the task running the readings.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+    /// The event manager for the sensor.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: EventManager,
+}
+
+impl Dht {
+    /// Creates an instance of a [`Dht`] sensor attached to a given board.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if the pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if the pin does not support DHT mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
+    /// * `NotSupported`: this function will bail an error if the board cannot read DHT sensors.
+    pub fn new<T: Into<PinIdOrName>>(
+        board: &dyn Hardware,
+        pin: T,
+        model: DhtModel,
+    ) -> Result<Self, Error> {
+        let pin = board.get_io().read().get_pin(pin)?.clone();
+
+        pin.supports_mode(PinModeId::DHT).ok_or(IncompatiblePin {
+            mode: PinModeId::DHT,
+            pin: pin.id,
+            context: "create DHT sensor",
+        })?;
+
+        let mut sensor = Self {
+            pin: pin.id,
+            model,
+            state: Arc::new(RwLock::new((0.0, 0.0))),
+            interval: Self::min_interval(model),
+            failure: Arc::new(RwLock::new(None)),
+            protocol: board.get_protocol(),
+            claim: PinClaim::new(board.get_io(), vec![pin.id], "Dht")?,
+            handler: Arc::new(RwLock::new(None)),
+            events: Default::default(),
+        };
+
+        sensor.protocol.set_pin_mode(sensor.pin, PinModeId::DHT)?;
+
+        // Check the board is able to read the sensor.
+        sensor.protocol.dht_read(sensor.pin, sensor.model)?;
+
+        // Attaches the event handler.
+        sensor.attach()?;
+
+        Ok(sensor)
+    }
+
+    // ########################################
+    // Getters and Setters.
+
+    /// Returns the pin (id) used by the device.
+    pub fn get_pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Returns the sensor model.
+    pub fn get_model(&self) -> DhtModel {
+        self.model
+    }
+
+    /// Returns the last valid temperature (in °C).
+    pub fn get_temperature(&self) -> f32 {
+        self.state.read().0
+    }
+
+    /// Returns the last valid relative humidity (in %).
+    pub fn get_humidity(&self) -> f32 {
+        self.state.read().1
+    }
+
+    /// Returns the last reading as an object holding the `temperature` (in °C) and the `humidity` (in %).
+    ///
+    /// # Errors
+    /// * `SensorFailure`: this function will bail an error if the last reading failed (checksum error, timeout, etc.).
+    pub fn get_reading(&self) -> Result<State, Error> {
+        match *self.failure.read() {
+            None => Ok(self.get_state()),
+            Some(status) => Err(Error::from(SensorFailure {
+                pin: self.pin,
+                info: Self::describe_failure(status),
+            })),
+        }
+    }
+
+    /// Returns the interval (in ms) between two readings.
+    pub fn get_interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Sets the interval (in ms) between two readings (ie. the sampling frequency).
+    /// NOTE: the interval can not be shorter than what the sensor supports (1s for DHT11, 2s for DHT22).
+    pub fn set_interval(mut self, interval: u64) -> Self {
+        self.interval = interval.max(Self::min_interval(self.model));
+        self.refresh()
+    }
+
+    // ########################################
+    // Event related functions
+
+    /// Manually attaches the sensor with the reading events.
+    /// This should never be needed unless you manually `detach()` the sensor first for some reason
+    /// and want it to start being reactive to events again.
+    ///
+    /// # Errors
+    /// * `PinInUse`: this function will bail an error if the pin has been claimed by another device in the meantime.
+    pub fn attach(&self) -> Result<(), Error> {
+        self.claim.renew()?;
+        if self.handler.read().is_none() {
+            let mut self_clone = self.clone();
+            let mut readings = self.protocol.get_io().read().subscribe_dht_readings();
+            *self.handler.write() = Some(task::run(async move {
+                let mut ticker = tokio::time::interval(Duration::from_millis(self_clone.interval));
+                loop {
+                    tokio::select! {
+                        // Reads the sensor at a regular interval.
+                        _ = ticker.tick() => {
+                            self_clone.protocol.dht_read(self_clone.pin, self_clone.model)?;
+                        }
+                        // Reacts to the readings notified by the protocol.
+                        received = readings.recv() => match received {
+                            Ok(reading) if reading.pin == self_clone.pin => self_clone.update(reading),
+                            Ok(_) | Err(RecvError::Lagged(_)) => {}
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+                Ok(())
+            })?);
+        }
+        Ok(())
+    }
+
+    /// Detaches the interval associated with the sensor.
+    /// This means the sensor won't be read anymore and its pin is released.
+    pub fn detach(&self) {
+        if let Some(handler) = self.handler.read().as_ref() {
+            handler.abort();
+        }
+        *self.handler.write() = None;
+        self.claim.release();
+    }
+
+    /// Registers a callback to be executed on a given event.
+    ///
+    /// Available events for a DHT sensor are:
+    /// - **`InputEvent::OnChange` | `change`**: Triggered when the temperature or the humidity changes.
+    ///   _The callback must receive the following parameter: `|reading: State| { ... }` (a `State::Object`)_
+    /// - **`InputEvent::OnTemperatureChange` | `temperature_change`**: Triggered when the temperature changes.
+    ///   _The callback must receive the following parameter: `|temperature: f32| { ... }` (in °C)_
+    /// - **`InputEvent::OnHumidityChange` | `humidity_change`**: Triggered when the humidity changes.
+    ///   _The callback must receive the following parameter: `|humidity: f32| { ... }` (in %)_
+    ///
+    /// Failed readings trigger no event: see [`Dht::get_reading`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hermes_five::hardware::{Board, BoardEvent};
+    /// use hermes_five::devices::{Dht, InputEvent};
+    /// use hermes_five::io::DhtModel;
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let board = Board::run();
+    ///     board.on(BoardEvent::OnReady, |board: Board| async move {
+    ///
+    ///         // Register a DHT22 sensor on pin 7.
+    ///         let sensor = Dht::new(&board, 7, DhtModel::Dht22)?;
+    ///         // Triggered function when the temperature changes.
+    ///         sensor.on(InputEvent::OnTemperatureChange, |temperature: f32| async move {
+    ///             println!("Temperature: {:.1}°C", temperature);
+    ///             Ok(())
+    ///         });
+    ///
+    ///         // The above code will run forever listening to the InputEvent,
+    ///         // until we detach the device and close the board.
+    ///         sensor.detach();
+    ///         board.close();
+    ///
+    ///         Ok(())
+    ///     });
+    /// }
+    /// ```
+    pub fn on<S, F, T, Fut>(&self, event: S, callback: F) -> EventHandler
+    where
+        S: Into<String>,
+        T: 'static + Send + Sync + Clone,
+        F: FnMut(T) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.events.on(event, callback)
+    }
+
+    /// Restarts the listener (if attached) for a configuration change to be considered.
+    fn refresh(self) -> Self {
+        let handler = self.handler.write().take();
+        if let Some(handler) = handler {
+            handler.abort();
+            // The pin is still claimed: attaching again cannot fail.
+            let _ = self.attach();
+        }
+        self
+    }
+
+    /// Updates the sensor with a new reading and emits the related events.
+    fn update(&self, reading: DhtReading) {
+        // A failed reading is recorded but does not override the last valid values.
+        if reading.status != 0 {
+            *self.failure.write() = Some(reading.status);
+            return;
+        }
+        *self.failure.write() = None;
+
+        let (temperature, humidity) = *self.state.read();
+        if reading.temperature == temperature && reading.humidity == humidity {
+            return;
+        }
+        *self.state.write() = (reading.temperature, reading.humidity);
+        self.events.emit(InputEvent::OnChange, self.get_state());
+        if reading.temperature != temperature {
+            self.events
+                .emit(InputEvent::OnTemperatureChange, reading.temperature);
+        }
+        if reading.humidity != humidity {
+            self.events
+                .emit(InputEvent::OnHumidityChange, reading.humidity);
+        }
+    }
+
+    /// Returns the minimum interval (in ms) between two readings supported by a sensor model.
+    fn min_interval(model: DhtModel) -> u64 {
+        match model {
+            DhtModel::Dht11 => 1000,
+            DhtModel::Dht22 => 2000,
+        }
+    }
+
+    /// Describes a failed reading status as reported by the DhtFirmata feature.
+    fn describe_failure(status: u8) -> String {
+        match status {
+            1 => String::from("checksum error"),
+            2 => String::from("timeout"),
+            status => format!("read error (status {})", status),
+        }
+    }
+}
+
+impl Display for Dht {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (temperature, humidity) = *self.state.read();
+        write!(
+            f,
+            "Dht (pin={}) [model={:?}, temperature={:.1}°C, humidity={:.1}%, interval={}ms]",
+            self.pin, self.model, temperature, humidity, self.interval
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Device for Dht {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Input for Dht {
+    fn get_state(&self) -> State {
+        let (temperature, humidity) = *self.state.read();
+        State::Object(HashMap::from([
+            (String::from("temperature"), State::from(temperature)),
+            (String::from("humidity"), State::from(humidity)),
+        ]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use crate::hardware::{Board, PCA9685};
+    use crate::io::IO;
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    use super::*;
+
+    /// Creates a board whose digital pins support the DHT mode.
+    fn _create_board() -> Board {
+        Board::new(MockIoProtocol::with_mode(2..=7, PinModeId::DHT))
+    }
+
+    /// Simulates a reading reported by the board for the sensor on pin 7.
+    fn _report(board: &Board, status: u8, temperature: f32, humidity: f32) {
+        board.get_io().write().set_dht_reading(DhtReading {
+            pin: 7,
+            status,
+            temperature,
+            humidity,
+        });
+    }
+
+    #[hermes_five_macros::test]
+    fn test_new_dht() {
+        let board = _create_board();
+        let sensor = Dht::new(&board, 7, DhtModel::Dht22).unwrap();
+        assert_eq!(sensor.get_pin(), 7);
+        assert_eq!(sensor.get_pins(), vec![7]);
+        assert_eq!(sensor.get_model(), DhtModel::Dht22);
+        assert_eq!(sensor.get_temperature(), 0.0);
+        assert_eq!(sensor.get_humidity(), 0.0);
+        assert_eq!(sensor.get_interval(), 2000);
+        assert_eq!(
+            board.get_io().read().get_pin(7).unwrap().mode.id,
+            PinModeId::DHT
+        );
+
+        // Pin already in use.
+        assert!(Dht::new(&board, 7, DhtModel::Dht22).is_err());
+        // Incompatible pin.
+        assert!(Dht::new(&board, 13, DhtModel::Dht22).is_err());
+        // Unknown pin.
+        assert!(Dht::new(&board, 42, DhtModel::Dht22).is_err());
+        // DHT sensors are not supported by the PCA9685.
+        let pca9685 = PCA9685::default(&board).unwrap();
+        assert!(Dht::new(&pca9685, 0, DhtModel::Dht22).is_err());
+
+        // The interval can not be shorter than the model minimum.
+        let sensor = sensor.set_interval(500);
+        assert_eq!(sensor.get_interval(), 2000);
+        let sensor = sensor.set_interval(5000);
+        assert_eq!(sensor.get_interval(), 5000);
+
+        let other = Dht::new(&board, 6, DhtModel::Dht11).unwrap();
+        assert_eq!(other.get_interval(), 1000);
+        let other = other.set_interval(500);
+        assert_eq!(other.get_interval(), 1000);
+
+        other.detach();
+
+        sensor.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_dht_events() {
+        let board = _create_board();
+        let sensor = Dht::new(&board, 7, DhtModel::Dht22).unwrap();
+
+        let change = Arc::new(AtomicU32::new(0));
+        let moved_change = change.clone();
+        sensor.on(InputEvent::OnChange, move |_: State| {
+            let captured_change = moved_change.clone();
+            async move {
+                captured_change.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        let temperature = Arc::new(AtomicU32::new(0));
+        let moved_temperature = temperature.clone();
+        sensor.on(InputEvent::OnTemperatureChange, move |value: f32| {
+            let captured_temperature = moved_temperature.clone();
+            async move {
+                captured_temperature.store((value * 10.0) as u32, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        let humidity = Arc::new(AtomicU32::new(0));
+        let moved_humidity = humidity.clone();
+        sensor.on(InputEvent::OnHumidityChange, move |value: f32| {
+            let captured_humidity = moved_humidity.clone();
+            async move {
+                captured_humidity.store((value * 10.0) as u32, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        // A valid reading: both quantities change.
+        _report(&board, 0, 21.5, 45.5);
+        pause!(50);
+        assert_eq!(sensor.get_temperature(), 21.5);
+        assert_eq!(sensor.get_humidity(), 45.5);
+        assert_eq!(change.load(Ordering::SeqCst), 1);
+        assert_eq!(temperature.load(Ordering::SeqCst), 215);
+        assert_eq!(humidity.load(Ordering::SeqCst), 455);
+        let reading = sensor.get_reading().unwrap().as_object();
+        assert_eq!(reading.get("temperature").unwrap().as_float(), 21.5);
+        assert_eq!(reading.get("humidity").unwrap().as_float(), 45.5);
+
+        // Only the humidity changes.
+        _report(&board, 0, 21.5, 50.0);
+        pause!(50);
+        assert_eq!(change.load(Ordering::SeqCst), 2);
+        assert_eq!(temperature.load(Ordering::SeqCst), 215);
+        assert_eq!(humidity.load(Ordering::SeqCst), 500);
+
+        // A failed reading: the error is surfaced, the values are kept.
+        _report(&board, 1, 0.0, 0.0);
+        pause!(50);
+        assert_eq!(sensor.get_temperature(), 21.5);
+        assert_eq!(change.load(Ordering::SeqCst), 2);
+        let result = sensor.get_reading();
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Sensor on pin (7) failed - checksum error."
+        );
+
+        // Readings for another pin are ignored.
+        board.get_io().write().set_dht_reading(DhtReading {
+            pin: 6,
+            status: 0,
+            temperature: 30.0,
+            humidity: 30.0,
+        });
+        pause!(50);
+        assert_eq!(sensor.get_temperature(), 21.5);
+        assert!(sensor.get_reading().is_err());
+
+        // A valid reading clears the failure.
+        _report(&board, 0, 21.5, 50.0);
+        pause!(50);
+        assert!(sensor.get_reading().is_ok());
+        assert_eq!(change.load(Ordering::SeqCst), 2);
+
+        sensor.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    fn test_dht_display() {
+        let board = _create_board();
+        let sensor = Dht::new(&board, 7, DhtModel::Dht11).unwrap();
+        assert_eq!(
+            format!("{}", sensor),
+            "Dht (pin=7) [model=Dht11, temperature=0.0°C, humidity=0.0%, interval=1000ms]"
+        );
+        sensor.detach();
+        board.close();
+    }
+}
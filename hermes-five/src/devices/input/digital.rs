@@ -2,13 +2,13 @@ use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
 use parking_lot::RwLock;
+use tokio::sync::broadcast::error::RecvError;
 
 use crate::devices::input::{Input, InputEvent};
 use crate::devices::Device;
 use crate::errors::Error;
 use crate::hardware::Hardware;
-use crate::io::{IoProtocol, PinIdOrName, PinModeId};
-use crate::pause;
+use crate::io::{IoProtocol, PinClaim, PinIdOrName, PinModeId};
 use crate::utils::{task, EventHandler, EventManager, State, TaskHandler};
 
 /// Represents a digital sensor of unspecified type: an [`Input`] [`Device`] that reads digital values
@@ -29,6 +29,9 @@ pub struct DigitalInput {
     // # Volatile utility data.
     #[cfg_attr(feature = "serde", serde(skip))]
     protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pin (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    claim: PinClaim,
     /// Inner handler to the task running the button value check.
     #[cfg_attr(feature = "serde", serde(skip))]
     handler: Arc<RwLock<Option<TaskHandler>>>,
@@ -43,6 +46,7 @@ impl DigitalInput {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the DigitalInput pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the DigitalInput pin does not support ANALOG mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new<T: Into<PinIdOrName>>(board: &dyn Hardware, pin: T) -> Result<Self, Error> {
         let pin = board.get_io().read().get_pin(pin)?.clone();
 
@@ -50,6 +54,7 @@ impl DigitalInput {
             pin: pin.id,
             state: Arc::new(RwLock::new(pin.value != 0)),
             protocol: board.get_protocol(),
+            claim: PinClaim::new(board.get_io(), vec![pin.id], "DigitalInput")?,
             handler: Arc::new(RwLock::new(None)),
             events: Default::default(),
         };
@@ -80,19 +85,35 @@ impl DigitalInput {
     /// Manually attaches the DigitalInput with the value change events.
     /// This should never be needed unless you manually `detach()` the DigitalInput first for some reason
     /// and want it to start being reactive to events again.
+    ///
+    /// # Notes
+    /// The DigitalInput is not attached if its pin has been claimed by another device in the meantime.
     pub fn attach(&self) {
+        if self.claim.renew().is_err() {
+            return;
+        }
         if self.handler.read().is_none() {
             let self_clone = self.clone();
+            let mut changes = self.protocol.get_io().read().subscribe();
             *self.handler.write() = Some(
                 task::run(async move {
+                    // Reacts to the pin value changes notified by the protocol.
                     loop {
-                        let pin_value = self_clone
-                            .protocol
-                            .get_io()
-                            .read()
-                            .get_pin(self_clone.pin)?
-                            .value
-                            != 0;
+                        let pin_value = match changes.recv().await {
+                            Ok(change) if change.pin == self_clone.pin => change.value != 0,
+                            Ok(_) => continue,
+                            // Some changes were missed: resync with the current value.
+                            Err(RecvError::Lagged(_)) => {
+                                self_clone
+                                    .protocol
+                                    .get_io()
+                                    .read()
+                                    .get_pin(self_clone.pin)?
+                                    .value
+                                    != 0
+                            }
+                            Err(RecvError::Closed) => break,
+                        };
                         let state_value = *self_clone.state.read();
                         if pin_value != state_value {
                             *self_clone.state.write() = pin_value;
@@ -102,11 +123,7 @@ impl DigitalInput {
                                 false => self_clone.events.emit(InputEvent::OnLow, ()),
                             }
                         }
-
-                        // Change can only be done 10x a sec. to avoid bouncing.
-                        pause!(100);
                     }
-                    #[allow(unreachable_code)]
                     Ok(())
                 })
                 .unwrap(),
@@ -115,12 +132,13 @@ impl DigitalInput {
     }
 
     /// Detaches the interval associated with the DigitalInput.
-    /// This means the DigitalInput won't react anymore to value changes.
+    /// This means the DigitalInput won't react anymore to value changes and its pin is released.
     pub fn detach(&self) {
         if let Some(handler) = self.handler.read().as_ref() {
             handler.abort();
         }
-        *self.handler.write() = None
+        *self.handler.write() = None;
+        self.claim.release();
     }
 
     /// Registers a callback to be executed on a given event on the DigitalInput.
@@ -188,7 +206,11 @@ impl Display for DigitalInput {
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
-impl Device for DigitalInput {}
+impl Device for DigitalInput {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Input for DigitalInput {
@@ -202,6 +224,7 @@ mod tests {
     use crate::devices::input::digital::DigitalInput;
     use crate::devices::input::Input;
     use crate::devices::input::InputEvent;
+    use crate::devices::Device;
     use crate::hardware::Board;
     use crate::mocks::plugin_io::MockIoProtocol;
     use crate::pause;
@@ -213,6 +236,7 @@ mod tests {
         let board = Board::new(MockIoProtocol::default());
         let sensor = DigitalInput::new(&board, 2).unwrap();
         assert_eq!(sensor.get_pin(), 2);
+        assert_eq!(sensor.get_pins(), vec![2]);
         assert!(sensor.get_state().as_bool());
         sensor.detach();
 
@@ -224,6 +248,26 @@ mod tests {
         board.close();
     }
 
+    #[hermes_five_macros::test]
+    fn test_digital_claim() {
+        let board = Board::new(MockIoProtocol::default());
+        let sensor = DigitalInput::new(&board, 2).unwrap();
+        assert!(DigitalInput::new(&board, 2).is_err());
+
+        // A detached sensor releases its pin: it cannot attach back once the pin is used elsewhere.
+        sensor.detach();
+        let other = DigitalInput::new(&board, 2).unwrap();
+        sensor.attach();
+        assert!(sensor.handler.read().is_none());
+
+        other.detach();
+        sensor.attach();
+        assert!(sensor.handler.read().is_some());
+
+        sensor.detach();
+        board.close();
+    }
+
     #[hermes_five_macros::test]
     fn test_digital_display() {
         let board = Board::new(MockIoProtocol::default());
@@ -285,9 +329,8 @@ mod tests {
             .protocol
             .get_io()
             .write()
-            .get_pin_mut(5)
-            .unwrap()
-            .value = 0xFF;
+            .set_pin_value(5, 0xFF)
+            .unwrap();
 
         pause!(500);
 
@@ -300,9 +343,8 @@ mod tests {
             .protocol
             .get_io()
             .write()
-            .get_pin_mut(5)
-            .unwrap()
-            .value = 0;
+            .set_pin_value(5, 0)
+            .unwrap();
 
         pause!(500);
 
@@ -0,0 +1,559 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::devices::input::{Input, InputEvent};
+use crate::devices::Device;
+use crate::errors::Error;
+use crate::errors::HardwareError::IncompatiblePin;
+use crate::hardware::Hardware;
+use crate::io::{IoProtocol, OneWireReply, PinClaim, PinIdOrName, PinModeId};
+use crate::utils::{task, EventHandler, EventManager, State, TaskHandler};
+
+/// The family code of the DS18B20 (first byte of its address).
+const FAMILY_CODE: u8 = 0x28;
+/// The command starting a temperature conversion.
+const CONVERT_TEMPERATURE: u8 = 0x44;
+/// The command reading the scratchpad (where the converted temperature is stored).
+const READ_SCRATCHPAD: u8 = 0xBE;
+/// The size (in bytes) of the scratchpad: [temperature (2 bytes), TH, TL, config, reserved (3 bytes), CRC].
+const SCRATCHPAD_SIZE: u16 = 9;
+/// The maximum duration (in ms) of a temperature conversion (at 12-bit resolution).
+const CONVERSION_TIME: u64 = 750;
+
+/// The sensors found on a bus: their address and their last temperature (in °C).
+type Sensors = Vec<([u8; 8], f32)>;
+
+/// Represents DS18B20 thermometers: an [`Input`] [`Device`] measuring the temperatures of all the DS18B20
+/// sensors found on a OneWire bus.
+///
+/// The sensors state is an object holding the temperature (in °C) of each sensor, by address (as an hex string):
+/// the sensors are read at a regular interval (see [`Ds18b20::set_interval`]). Each reading reports the
+/// temperature converted during the previous interval.
+///
+/// # Notes
+/// The firmware running on the board must include the OneWireFirmata feature.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Ds18b20 {
+    // ########################################
+    // # Basics
+    /// The pin (id) of the [`Board`] used by the OneWire bus.
+    pin: u8,
+    /// The sensors found on the bus.
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    state: Arc<RwLock<Sensors>>,
+    /// The interval (in ms) between two readings (default: 1000ms).
+    interval: u64,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pin (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    claim: PinClaim,
+    /// Inner handler to the task running the readings.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+    /// The event manager for the sensors.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: EventManager,
+}
+
+impl Ds18b20 {
+    /// Creates an instance of [`Ds18b20`] thermometers attached to the OneWire bus of a given pin:
+    /// the sensors on the bus are searched right away.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if the pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if the pin does not support ONEWIRE mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
+    /// * `NotSupported`: this function will bail an error if the board has no OneWire bus.
+    pub fn new<T: Into<PinIdOrName>>(board: &dyn Hardware, pin: T) -> Result<Self, Error> {
+        let pin = board.get_io().read().get_pin(pin)?.clone();
+
+        pin.supports_mode(PinModeId::ONEWIRE)
+            .ok_or(IncompatiblePin {
+                mode: PinModeId::ONEWIRE,
+                pin: pin.id,
+                context: "create DS18B20 sensor",
+            })?;
+
+        let mut sensor = Self {
+            pin: pin.id,
+            state: Arc::new(RwLock::new(vec![])),
+            interval: 1000,
+            protocol: board.get_protocol(),
+            claim: PinClaim::new(board.get_io(), vec![pin.id], "Ds18b20")?,
+            handler: Arc::new(RwLock::new(None)),
+            events: Default::default(),
+        };
+
+        // The sensors may be powered through the data line (parasitic power).
+        sensor.protocol.onewire_config(sensor.pin, true)?;
+
+        // Attaches the event handler (which searches the sensors on the bus).
+        sensor.attach()?;
+
+        Ok(sensor)
+    }
+
+    // ########################################
+    // Getters and Setters.
+
+    /// Returns the pin (id) used by the device.
+    pub fn get_pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Returns the addresses of the sensors found on the bus.
+    pub fn get_addresses(&self) -> Vec<[u8; 8]> {
+        self.state
+            .read()
+            .iter()
+            .map(|(address, _)| *address)
+            .collect()
+    }
+
+    /// Returns the last temperature (in °C) of the sensor at the given address (if found on the bus).
+    pub fn get_temperature(&self, address: [u8; 8]) -> Option<f32> {
+        self.state
+            .read()
+            .iter()
+            .find(|(candidate, _)| *candidate == address)
+            .map(|(_, temperature)| *temperature)
+    }
+
+    /// Returns the last temperature (in °C) of each sensor found on the bus, along with its address.
+    pub fn get_temperatures(&self) -> Vec<([u8; 8], f32)> {
+        self.state.read().clone()
+    }
+
+    /// Returns the interval (in ms) between two readings.
+    pub fn get_interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Sets the interval (in ms) between two readings (ie. the sampling frequency).
+    /// NOTE: the interval can not be shorter than a temperature conversion (750ms).
+    pub fn set_interval(mut self, interval: u64) -> Self {
+        self.interval = interval.max(CONVERSION_TIME);
+        self.refresh()
+    }
+
+    // ########################################
+    // Event related functions
+
+    /// Manually attaches the sensors with the temperature events.
+    /// This should never be needed unless you manually `detach()` the sensors first for some reason
+    /// and want them to start being reactive to events again.
+    ///
+    /// # Errors
+    /// * `PinInUse`: this function will bail an error if the pin has been claimed by another device in the meantime.
+    pub fn attach(&self) -> Result<(), Error> {
+        self.claim.renew()?;
+        if self.handler.read().is_none() {
+            let mut self_clone = self.clone();
+            let mut replies = self.protocol.get_io().read().subscribe_onewire_replies();
+            *self.handler.write() = Some(task::run(async move {
+                let mut ticker = tokio::time::interval(Duration::from_millis(self_clone.interval));
+                // The sensors which have been asked to convert their temperature.
+                let mut converting = vec![];
+                loop {
+                    tokio::select! {
+                        // Reads the sensors at a regular interval.
+                        _ = ticker.tick() => self_clone.measure(&mut converting)?,
+                        // Reacts to the replies notified by the protocol.
+                        received = replies.recv() => match received {
+                            Ok(OneWireReply::Search { pin, addresses }) if pin == self_clone.pin => {
+                                self_clone.enumerate(addresses)
+                            }
+                            Ok(OneWireReply::Read { pin, correlation_id, data }) if pin == self_clone.pin => {
+                                self_clone.update(correlation_id, &data)
+                            }
+                            Ok(_) | Err(RecvError::Lagged(_)) => {}
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+                Ok(())
+            })?);
+        }
+        Ok(())
+    }
+
+    /// Detaches the interval associated with the sensors.
+    /// This means the sensors won't be read anymore and the pin is released.
+    pub fn detach(&self) {
+        if let Some(handler) = self.handler.read().as_ref() {
+            handler.abort();
+        }
+        *self.handler.write() = None;
+        self.claim.release();
+    }
+
+    /// Registers a callback to be executed on a given event.
+    ///
+    /// Available events for DS18B20 sensors are:
+    /// - **`InputEvent::OnChange` | `change`**: Triggered when the temperature of any sensor changes.
+    ///   _The callback must receive the following parameter: `|temperatures: State| { ... }` (a `State::Object`)_
+    /// - **`InputEvent::OnTemperatureChange` | `temperature_change`**: Triggered when the temperature of a sensor changes.
+    ///   _The callback must receive the following parameter: `|(address, temperature): ([u8; 8], f32)| { ... }` (in °C)_
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hermes_five::hardware::{Board, BoardEvent};
+    /// use hermes_five::devices::{Ds18b20, InputEvent};
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let board = Board::run();
+    ///     board.on(BoardEvent::OnReady, |board: Board| async move {
+    ///
+    ///         // Register the DS18B20 sensors on the OneWire bus of pin 4.
+    ///         let sensors = Ds18b20::new(&board, 4)?;
+    ///         // Triggered function when the temperature of a sensor changes.
+    ///         sensors.on(InputEvent::OnTemperatureChange, |(address, temperature): ([u8; 8], f32)| async move {
+    ///             println!("Sensor {:02x?}: {:.2}°C", address, temperature);
+    ///             Ok(())
+    ///         });
+    ///
+    ///         // The above code will run forever listening to the InputEvent,
+    ///         // until we detach the device and close the board.
+    ///         sensors.detach();
+    ///         board.close();
+    ///
+    ///         Ok(())
+    ///     });
+    /// }
+    /// ```
+    pub fn on<S, F, T, Fut>(&self, event: S, callback: F) -> EventHandler
+    where
+        S: Into<String>,
+        T: 'static + Send + Sync + Clone,
+        F: FnMut(T) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.events.on(event, callback)
+    }
+
+    /// Restarts the listener (if attached) for a configuration change to be considered.
+    fn refresh(self) -> Self {
+        let handler = self.handler.write().take();
+        if let Some(handler) = handler {
+            handler.abort();
+            // The pin is still claimed: attaching again cannot fail.
+            let _ = self.attach();
+        }
+        self
+    }
+
+    /// Reads the temperatures converted since the previous call, then starts a new conversion on each sensor.
+    /// The bus is searched (again) as long as no sensor has been found.
+    fn measure(&mut self, converting: &mut Vec<[u8; 8]>) -> Result<(), Error> {
+        let addresses = self.get_addresses();
+        if addresses.is_empty() {
+            return self.protocol.onewire_search(self.pin);
+        }
+        for (index, address) in addresses.iter().enumerate() {
+            if converting.contains(address) {
+                self.protocol.onewire_reset(self.pin)?;
+                self.protocol
+                    .onewire_write(self.pin, *address, &[READ_SCRATCHPAD])?;
+                self.protocol
+                    .onewire_read(self.pin, SCRATCHPAD_SIZE, index as u16)?;
+            }
+            self.protocol.onewire_reset(self.pin)?;
+            self.protocol
+                .onewire_write(self.pin, *address, &[CONVERT_TEMPERATURE])?;
+        }
+        *converting = addresses;
+        Ok(())
+    }
+
+    /// Registers the DS18B20 sensors among the devices found on the bus.
+    fn enumerate(&self, addresses: Vec<[u8; 8]>) {
+        let sensors = addresses
+            .into_iter()
+            .filter(|address| address[0] == FAMILY_CODE && crc8(&address[..7]) == address[7])
+            .map(|address| (address, self.get_temperature(address).unwrap_or_default()))
+            .collect();
+        *self.state.write() = sensors;
+    }
+
+    /// Updates a sensor with its scratchpad (the correlation id being its index) and emits the related events.
+    fn update(&self, correlation_id: u16, scratchpad: &[u8]) {
+        // A corrupted reading is ignored.
+        if scratchpad.len() != SCRATCHPAD_SIZE as usize || crc8(&scratchpad[..8]) != scratchpad[8] {
+            return;
+        }
+        // The temperature is a signed 16-bit value in 1/16th of °C.
+        let temperature = i16::from_le_bytes([scratchpad[0], scratchpad[1]]) as f32 / 16.0;
+
+        let address = match self.state.write().get_mut(correlation_id as usize) {
+            Some(sensor) if sensor.1 != temperature => {
+                sensor.1 = temperature;
+                sensor.0
+            }
+            _ => return,
+        };
+        self.events.emit(InputEvent::OnChange, self.get_state());
+        self.events
+            .emit(InputEvent::OnTemperatureChange, (address, temperature));
+    }
+}
+
+/// Computes the Dallas/Maxim CRC-8 used by the OneWire devices to check their address and data.
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |mut crc, &byte| {
+        let mut byte = byte;
+        for _ in 0..8 {
+            let mix = (crc ^ byte) & 0x01;
+            crc >>= 1;
+            if mix != 0 {
+                crc ^= 0x8C;
+            }
+            byte >>= 1;
+        }
+        crc
+    })
+}
+
+/// Formats an address as an hex string (ex: "28ff641e0b16030e").
+fn format_address(address: &[u8; 8]) -> String {
+    address.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl Display for Ds18b20 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Ds18b20 (pin={}) [sensors={}, interval={}ms]",
+            self.pin,
+            self.state.read().len(),
+            self.interval
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Device for Ds18b20 {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Input for Ds18b20 {
+    fn get_state(&self) -> State {
+        State::Object(HashMap::from_iter(self.state.read().iter().map(
+            |(address, temperature)| (format_address(address), State::from(*temperature)),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use crate::hardware::{Board, PCA9685};
+    use crate::io::IO;
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    use super::*;
+
+    const SENSOR_1: [u8; 8] = [0x28, 0xFF, 0x64, 0x1E, 0x0B, 0x16, 0x03, 0x0E];
+    const SENSOR_2: [u8; 8] = [0x28, 0x61, 0x64, 0x12, 0x3C, 0x7C, 0x2F, 0x27];
+
+    /// Creates a board whose digital pins support the ONEWIRE mode.
+    fn _create_board() -> Board {
+        Board::new(MockIoProtocol::with_mode(2..=7, PinModeId::ONEWIRE))
+    }
+
+    /// Simulates a reply reported by the board for the bus on pin 4.
+    fn _report(board: &Board, reply: OneWireReply) {
+        board.get_io().write().set_onewire_reply(reply);
+    }
+
+    #[test]
+    fn test_crc8() {
+        assert_eq!(crc8(&[]), 0);
+        assert_eq!(crc8(&SENSOR_1[..7]), SENSOR_1[7]);
+        assert_eq!(crc8(&SENSOR_2[..7]), SENSOR_2[7]);
+        assert_eq!(
+            crc8(&[0x58, 0x01, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10]),
+            0xC2
+        );
+        assert_eq!(format_address(&SENSOR_1), "28ff641e0b16030e");
+    }
+
+    #[hermes_five_macros::test]
+    fn test_new_ds18b20() {
+        let board = _create_board();
+        let sensors = Ds18b20::new(&board, 4).unwrap();
+        assert_eq!(sensors.get_pin(), 4);
+        assert_eq!(sensors.get_pins(), vec![4]);
+        assert!(sensors.get_addresses().is_empty());
+        assert_eq!(sensors.get_interval(), 1000);
+        assert_eq!(sensors.get_state(), State::Object(HashMap::new()));
+        assert_eq!(
+            board.get_io().read().get_pin(4).unwrap().mode.id,
+            PinModeId::ONEWIRE
+        );
+
+        // Pin already in use.
+        assert!(Ds18b20::new(&board, 4).is_err());
+        // Incompatible pin.
+        assert!(Ds18b20::new(&board, 13).is_err());
+        // Unknown pin.
+        assert!(Ds18b20::new(&board, 42).is_err());
+        // No OneWire bus on the PCA9685.
+        let pca9685 = PCA9685::default(&board).unwrap();
+        assert!(Ds18b20::new(&pca9685, 0).is_err());
+
+        // The interval can not be shorter than a temperature conversion.
+        let sensors = sensors.set_interval(100);
+        assert_eq!(sensors.get_interval(), 750);
+        let sensors = sensors.set_interval(2000);
+        assert_eq!(sensors.get_interval(), 2000);
+
+        sensors.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_ds18b20_events() {
+        let board = _create_board();
+        let sensors = Ds18b20::new(&board, 4).unwrap();
+
+        let change = Arc::new(AtomicU32::new(0));
+        let moved_change = change.clone();
+        sensors.on(InputEvent::OnChange, move |_: State| {
+            let captured_change = moved_change.clone();
+            async move {
+                captured_change.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        let temperature = Arc::new(AtomicU32::new(0));
+        let moved_temperature = temperature.clone();
+        sensors.on(
+            InputEvent::OnTemperatureChange,
+            move |(address, value): ([u8; 8], f32)| {
+                let captured_temperature = moved_temperature.clone();
+                async move {
+                    if address == SENSOR_2 {
+                        captured_temperature.store((value * 100.0) as u32, Ordering::SeqCst);
+                    }
+                    Ok(())
+                }
+            },
+        );
+
+        // The search finds two DS18B20, a device of another family and a corrupted address.
+        _report(
+            &board,
+            OneWireReply::Search {
+                pin: 4,
+                addresses: vec![
+                    SENSOR_1,
+                    [0x10, 0xFF, 0x64, 0x1E, 0x0B, 0x16, 0x03, 0x0E],
+                    SENSOR_2,
+                    [0x28, 0xFF, 0x64, 0x1E, 0x0B, 0x16, 0x03, 0x00],
+                ],
+            },
+        );
+        pause!(50);
+        assert_eq!(sensors.get_addresses(), vec![SENSOR_1, SENSOR_2]);
+        assert_eq!(sensors.get_temperature(SENSOR_2), Some(0.0));
+        assert_eq!(sensors.get_temperature([0; 8]), None);
+
+        // The second sensor reports 21.5°C (344/16).
+        _report(
+            &board,
+            OneWireReply::Read {
+                pin: 4,
+                correlation_id: 1,
+                data: vec![0x58, 0x01, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0xC2],
+            },
+        );
+        pause!(50);
+        assert_eq!(sensors.get_temperature(SENSOR_2), Some(21.5));
+        assert_eq!(sensors.get_temperature(SENSOR_1), Some(0.0));
+        assert_eq!(change.load(Ordering::SeqCst), 1);
+        assert_eq!(temperature.load(Ordering::SeqCst), 2150);
+        assert_eq!(
+            sensors.get_temperatures(),
+            vec![(SENSOR_1, 0.0), (SENSOR_2, 21.5)]
+        );
+        assert_eq!(
+            sensors
+                .get_state()
+                .as_object()
+                .get("286164123c7c2f27")
+                .unwrap()
+                .as_float(),
+            21.5
+        );
+
+        // The first sensor reports -10.125°C (-162/16).
+        _report(
+            &board,
+            OneWireReply::Read {
+                pin: 4,
+                correlation_id: 0,
+                data: vec![0x5E, 0xFF, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0x6A],
+            },
+        );
+        pause!(50);
+        assert_eq!(sensors.get_temperature(SENSOR_1), Some(-10.125));
+        assert_eq!(change.load(Ordering::SeqCst), 2);
+        assert_eq!(temperature.load(Ordering::SeqCst), 2150);
+
+        // Corrupted readings, unknown sensors and other buses are ignored.
+        for reply in [
+            OneWireReply::Read {
+                pin: 4,
+                correlation_id: 1,
+                data: vec![0x58, 0x02, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0xC2],
+            },
+            OneWireReply::Read {
+                pin: 4,
+                correlation_id: 5,
+                data: vec![0x5E, 0xFF, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0x6A],
+            },
+            OneWireReply::Read {
+                pin: 5,
+                correlation_id: 1,
+                data: vec![0x5E, 0xFF, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0x6A],
+            },
+        ] {
+            _report(&board, reply);
+        }
+        pause!(50);
+        assert_eq!(sensors.get_temperature(SENSOR_2), Some(21.5));
+        assert_eq!(change.load(Ordering::SeqCst), 2);
+
+        sensors.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    fn test_ds18b20_display() {
+        let board = _create_board();
+        let sensors = Ds18b20::new(&board, 4).unwrap();
+        assert_eq!(
+            format!("{}", sensors),
+            "Ds18b20 (pin=4) [sensors=0, interval=1000ms]"
+        );
+        sensors.detach();
+        board.close();
+    }
+}
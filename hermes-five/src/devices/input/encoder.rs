@@ -0,0 +1,509 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+
+use crate::devices::input::{Input, InputEvent};
+use crate::devices::Device;
+use crate::errors::Error;
+use crate::errors::HardwareError::{IncompatiblePin, NotSupported};
+use crate::hardware::Hardware;
+use crate::io::{EncoderCommand, IoData, IoProtocol, PinClaim, PinIdOrName, PinModeId};
+use crate::utils::{task, EventHandler, EventManager, State, TaskHandler};
+
+/// The maximum number of encoders a board can read simultaneously (EncoderFirmata limitation).
+const MAX_ENCODERS: u8 = 5;
+
+/// Defines the rotation direction of an [`Encoder`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EncoderDirection {
+    /// The encoder has not moved yet.
+    #[default]
+    Stopped,
+    /// The encoder position increases.
+    Clockwise,
+    /// The encoder position decreases.
+    CounterClockwise,
+}
+
+/// Represents a rotary (quadrature) encoder: an [`Input`] [`Device`] that reads its position from
+/// two ENCODER compatible pins through the EncoderFirmata feature.
+/// <https://github.com/firmata/protocol/blob/master/encoder.md>
+///
+/// The encoder state is its position (in steps): the board reports the position changes automatically.
+///
+/// # Notes
+/// The firmware running on the board must include the EncoderFirmata feature (ex: ConfigurableFirmata).
+/// For best results, the pins should support interrupts.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Encoder {
+    // ########################################
+    // # Basics
+    /// The pins (id) of the [`Board`] used to read the encoder (A and B).
+    pins: [u8; 2],
+    /// The encoder id on the board (0-4).
+    id: u8,
+    /// The current encoder position.
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    state: Arc<RwLock<i32>>,
+    /// The last rotation direction.
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    direction: Arc<RwLock<EncoderDirection>>,
+    /// The last rotation velocity (in steps per second).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    velocity: Arc<RwLock<f32>>,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pins (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    claim: PinClaim,
+    /// The ownership of the encoder id (shared by all clones: released when the last one is dropped).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _slot: EncoderSlot,
+    /// Inner handler to the task listening to the position changes.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+    /// The event manager for the encoder.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: EventManager,
+}
+
+impl Encoder {
+    /// Creates an instance of an [`Encoder`] attached to a given board.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if a pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if a pin does not support ENCODER mode.
+    /// * `PinInUse`: this function will bail an error if a pin is already used by another device.
+    /// * `NotSupported`: this function will bail an error if the board already reads 5 encoders,
+    ///   or cannot read encoders at all.
+    pub fn new<T: Into<PinIdOrName>>(
+        board: &dyn Hardware,
+        pin_a: T,
+        pin_b: T,
+    ) -> Result<Self, Error> {
+        let mut pins = [0; 2];
+        {
+            let hardware = board.get_io().read();
+            for (pin, target) in [pin_a.into(), pin_b.into()]
+                .into_iter()
+                .zip(pins.iter_mut())
+            {
+                let pin = hardware.get_pin(pin)?;
+                pin.supports_mode(PinModeId::ENCODER)
+                    .ok_or(IncompatiblePin {
+                        mode: PinModeId::ENCODER,
+                        pin: pin.id,
+                        context: "create encoder",
+                    })?;
+                *target = pin.id;
+            }
+        }
+
+        // Claim the pins ownership and an encoder id.
+        let claim = PinClaim::new(board.get_io(), pins.to_vec(), "Encoder")?;
+        let slot = EncoderSlot::new(board.get_io())?;
+
+        let mut encoder = Self {
+            pins,
+            id: slot.0.id,
+            state: Arc::new(RwLock::new(0)),
+            direction: Arc::new(RwLock::new(EncoderDirection::Stopped)),
+            velocity: Arc::new(RwLock::new(0.0)),
+            protocol: board.get_protocol(),
+            claim,
+            _slot: slot,
+            handler: Arc::new(RwLock::new(None)),
+            events: Default::default(),
+        };
+
+        // Attach the encoder on the board (this starts the position reporting).
+        encoder
+            .protocol
+            .encoder(encoder.id, EncoderCommand::Attach(pins))?;
+
+        // Attaches the event handler.
+        encoder.attach()?;
+
+        Ok(encoder)
+    }
+
+    /// Resets the encoder position to 0.
+    pub fn reset(&mut self) -> Result<&Self, Error> {
+        *self.state.write() = 0;
+        *self.direction.write() = EncoderDirection::Stopped;
+        *self.velocity.write() = 0.0;
+        self.protocol.encoder(self.id, EncoderCommand::Reset)?;
+        Ok(self)
+    }
+
+    // ########################################
+    // Getters.
+
+    /// Returns the pins (id) used by the device (A and B).
+    pub fn get_pins_pair(&self) -> [u8; 2] {
+        self.pins
+    }
+
+    /// Returns the encoder id on the board.
+    pub fn get_id(&self) -> u8 {
+        self.id
+    }
+
+    /// Returns the current encoder position (in steps).
+    pub fn get_position(&self) -> i32 {
+        *self.state.read()
+    }
+
+    /// Returns the last rotation direction.
+    pub fn get_direction(&self) -> EncoderDirection {
+        *self.direction.read()
+    }
+
+    /// Returns the rotation velocity (in steps per second) measured between the two last position reports:
+    /// negative values mean the encoder turns counter-clockwise.
+    pub fn get_velocity(&self) -> f32 {
+        *self.velocity.read()
+    }
+
+    // ########################################
+    // Event related functions
+
+    /// Manually attaches the encoder with the position change events.
+    /// This should never be needed unless you manually `detach()` the encoder first for some reason
+    /// and want it to start being reactive to events again.
+    ///
+    /// # Errors
+    /// * `PinInUse`: this function will bail an error if a pin has been claimed by another device in the meantime.
+    pub fn attach(&self) -> Result<(), Error> {
+        self.claim.renew()?;
+        if self.handler.read().is_none() {
+            let self_clone = self.clone();
+            let mut changes = self.protocol.get_io().read().subscribe_encoder_changes();
+            *self.handler.write() = Some(task::run(async move {
+                let mut last_change = Instant::now();
+                // Reacts to the position changes notified by the protocol.
+                loop {
+                    let position = match changes.recv().await {
+                        Ok(change) if change.id == self_clone.id => change.position,
+                        Ok(_) => continue,
+                        // Some changes were missed: resync with the current position.
+                        Err(RecvError::Lagged(_)) => {
+                            match self_clone
+                                .protocol
+                                .get_io()
+                                .read()
+                                .encoders
+                                .get(&self_clone.id)
+                            {
+                                Some(position) => *position,
+                                None => continue,
+                            }
+                        }
+                        Err(RecvError::Closed) => break,
+                    };
+                    let previous = *self_clone.state.read();
+                    if position != previous {
+                        let now = Instant::now();
+                        self_clone.update(position, previous, (now - last_change).as_secs_f32());
+                        last_change = now;
+                    }
+                }
+                Ok(())
+            })?);
+        }
+        Ok(())
+    }
+
+    /// Detaches the interval associated with the encoder.
+    /// This means the encoder won't react anymore to position changes and its pins are released.
+    pub fn detach(&self) {
+        if let Some(handler) = self.handler.read().as_ref() {
+            handler.abort();
+        }
+        *self.handler.write() = None;
+        self.claim.release();
+    }
+
+    /// Registers a callback to be executed on a given event.
+    ///
+    /// Available events for an encoder are:
+    /// - **`InputEvent::OnChange` | `change`**: Triggered when the encoder position changes.
+    ///   _The callback must receive the following parameter: `|position: i32| { ... }`_
+    /// - **`InputEvent::OnClockwise` | `clockwise`**: Triggered when the encoder turns clockwise.
+    ///   _The callback must receive the following parameter: `|position: i32| { ... }`_
+    /// - **`InputEvent::OnCounterClockwise` | `counter_clockwise`**: Triggered when the encoder turns counter-clockwise.
+    ///   _The callback must receive the following parameter: `|position: i32| { ... }`_
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hermes_five::hardware::{Board, BoardEvent};
+    /// use hermes_five::devices::{Encoder, InputEvent};
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let board = Board::run();
+    ///     board.on(BoardEvent::OnReady, |board: Board| async move {
+    ///
+    ///         // Register an encoder on pins 2 and 3.
+    ///         let encoder = Encoder::new(&board, 2, 3)?;
+    ///         // Triggered function when the encoder turns clockwise.
+    ///         encoder.on(InputEvent::OnClockwise, |position: i32| async move {
+    ///             println!("Encoder turned clockwise: {}", position);
+    ///             Ok(())
+    ///         });
+    ///
+    ///         // The above code will run forever listening to the InputEvent,
+    ///         // until we detach the device and close the board.
+    ///         encoder.detach();
+    ///         board.close();
+    ///
+    ///         Ok(())
+    ///     });
+    /// }
+    /// ```
+    pub fn on<S, F, T, Fut>(&self, event: S, callback: F) -> EventHandler
+    where
+        S: Into<String>,
+        T: 'static + Send + Sync + Clone,
+        F: FnMut(T) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.events.on(event, callback)
+    }
+
+    /// Updates the encoder with a new position (reached after `elapsed` seconds) and emits the related events.
+    fn update(&self, position: i32, previous: i32, elapsed: f32) {
+        let delta = position as f32 - previous as f32;
+        *self.state.write() = position;
+        *self.velocity.write() = match elapsed > 0.0 {
+            true => delta / elapsed,
+            false => 0.0,
+        };
+        let direction = match delta > 0.0 {
+            true => EncoderDirection::Clockwise,
+            false => EncoderDirection::CounterClockwise,
+        };
+        *self.direction.write() = direction;
+
+        self.events.emit(InputEvent::OnChange, position);
+        match direction {
+            EncoderDirection::Clockwise => self.events.emit(InputEvent::OnClockwise, position),
+            _ => self.events.emit(InputEvent::OnCounterClockwise, position),
+        }
+    }
+}
+
+impl Display for Encoder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Encoder #{} (pins={:?}) [position={}, direction={:?}]",
+            self.id,
+            self.pins,
+            self.state.read(),
+            self.direction.read(),
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Device for Encoder {
+    fn get_pins(&self) -> Vec<u8> {
+        self.pins.to_vec()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Input for Encoder {
+    fn get_state(&self) -> State {
+        State::from(*self.state.read())
+    }
+}
+
+/// Represents the ownership of an encoder id on the board.
+///
+/// The slot is shared by all clones of the encoder: the id is released when the last one is dropped.
+#[derive(Clone, Debug, Default)]
+struct EncoderSlot(Arc<EncoderSlotInner>);
+
+#[derive(Debug, Default)]
+struct EncoderSlotInner {
+    io: Arc<RwLock<IoData>>,
+    id: u8,
+}
+
+impl EncoderSlot {
+    /// Reserves the first encoder id available on the given [`IoData`].
+    fn new(io: &Arc<RwLock<IoData>>) -> Result<Self, Error> {
+        let mut lock = io.write();
+        let id = (0..MAX_ENCODERS)
+            .find(|id| !lock.encoders.contains_key(id))
+            .ok_or(NotSupported {
+                context: "create more than 5 encoders",
+            })?;
+        lock.encoders.insert(id, 0);
+        Ok(Self(Arc::new(EncoderSlotInner { io: io.clone(), id })))
+    }
+}
+
+impl Drop for EncoderSlotInner {
+    fn drop(&mut self) {
+        self.io.write().encoders.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use crate::hardware::{Board, PCA9685};
+    use crate::io::IO;
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    use super::*;
+
+    /// Creates a board whose digital pins support the ENCODER mode.
+    fn _create_board() -> Board {
+        Board::new(MockIoProtocol::with_mode(2..=7, PinModeId::ENCODER))
+    }
+
+    #[hermes_five_macros::test]
+    fn test_new_encoder() {
+        let board = _create_board();
+        let encoder = Encoder::new(&board, 2, 3).unwrap();
+        assert_eq!(encoder.get_id(), 0);
+        assert_eq!(encoder.get_pins(), vec![2, 3]);
+        assert_eq!(encoder.get_pins_pair(), [2, 3]);
+        assert_eq!(encoder.get_position(), 0);
+        assert_eq!(encoder.get_direction(), EncoderDirection::Stopped);
+        assert_eq!(encoder.get_velocity(), 0.0);
+        assert_eq!(encoder.get_state().as_signed_integer(), 0);
+        assert_eq!(
+            board.get_io().read().get_pin(2).unwrap().mode.id,
+            PinModeId::ENCODER
+        );
+
+        // Pins already in use.
+        assert!(Encoder::new(&board, 3, 4).is_err());
+        // Incompatible pins.
+        assert!(Encoder::new(&board, 4, 8).is_err());
+        // Unknown pin.
+        assert!(Encoder::new(&board, 4, 42).is_err());
+        // Encoders are not supported by the PCA9685.
+        let pca9685 = PCA9685::default(&board).unwrap();
+        assert!(Encoder::new(&pca9685, 0, 1).is_err());
+
+        let other = Encoder::new(&board, "D4", "D5").unwrap();
+        assert_eq!(other.get_id(), 1);
+        assert_eq!(other.get_pins(), vec![4, 5]);
+
+        encoder.detach();
+        other.detach();
+        board.close();
+    }
+
+    #[test]
+    fn test_encoder_slots() {
+        let board = Board::new(MockIoProtocol::default());
+        let slots: Vec<EncoderSlot> = (0..MAX_ENCODERS)
+            .map(|_| EncoderSlot::new(board.get_io()).unwrap())
+            .collect();
+        assert_eq!(slots[4].0.id, 4);
+        assert!(EncoderSlot::new(board.get_io()).is_err());
+
+        // The id is released when the last clone is dropped.
+        let slot = slots[1].clone();
+        drop(slots);
+        assert_eq!(board.get_io().read().encoders.len(), 1);
+        drop(slot);
+        assert!(board.get_io().read().encoders.is_empty());
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_encoder_events() {
+        let board = _create_board();
+        let mut encoder = Encoder::new(&board, 2, 3).unwrap();
+
+        let changed = Arc::new(AtomicI32::new(0));
+        let moved_changed = changed.clone();
+        encoder.on(InputEvent::OnChange, move |position: i32| {
+            let captured_changed = moved_changed.clone();
+            async move {
+                captured_changed.store(position, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        let clockwise = Arc::new(AtomicI32::new(0));
+        let moved_clockwise = clockwise.clone();
+        encoder.on(InputEvent::OnClockwise, move |position: i32| {
+            let captured_clockwise = moved_clockwise.clone();
+            async move {
+                captured_clockwise.store(position, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        let counter_clockwise = Arc::new(AtomicI32::new(0));
+        let moved_counter_clockwise = counter_clockwise.clone();
+        encoder.on(InputEvent::OnCounterClockwise, move |position: i32| {
+            let captured_counter_clockwise = moved_counter_clockwise.clone();
+            async move {
+                captured_counter_clockwise.store(position, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        // Another encoder id does not interfere.
+        board.get_io().write().set_encoder_position(3, 42);
+        pause!(50);
+        assert_eq!(encoder.get_position(), 0);
+
+        board.get_io().write().set_encoder_position(0, 10);
+        pause!(50);
+        assert_eq!(encoder.get_position(), 10);
+        assert_eq!(encoder.get_direction(), EncoderDirection::Clockwise);
+        assert!(encoder.get_velocity() > 0.0);
+        assert_eq!(changed.load(Ordering::SeqCst), 10);
+        assert_eq!(clockwise.load(Ordering::SeqCst), 10);
+
+        board.get_io().write().set_encoder_position(0, -5);
+        pause!(50);
+        assert_eq!(encoder.get_position(), -5);
+        assert_eq!(encoder.get_direction(), EncoderDirection::CounterClockwise);
+        assert!(encoder.get_velocity() < 0.0);
+        assert_eq!(changed.load(Ordering::SeqCst), -5);
+        assert_eq!(counter_clockwise.load(Ordering::SeqCst), -5);
+
+        encoder.reset().unwrap();
+        assert_eq!(encoder.get_position(), 0);
+        assert_eq!(encoder.get_direction(), EncoderDirection::Stopped);
+
+        // A detached encoder does not react to changes anymore.
+        encoder.detach();
+        board.get_io().write().set_encoder_position(0, 20);
+        pause!(50);
+        assert_eq!(encoder.get_position(), 0);
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    fn test_encoder_display() {
+        let board = _create_board();
+        let encoder = Encoder::new(&board, 2, 3).unwrap();
+        assert_eq!(
+            format!("{}", encoder),
+            "Encoder #0 (pins=[2, 3]) [position=0, direction=Stopped]"
+        );
+        encoder.detach();
+        board.close();
+    }
+}
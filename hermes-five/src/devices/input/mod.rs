@@ -3,7 +3,11 @@ use crate::utils::State;
 
 pub mod analog;
 pub mod button;
+pub mod dht;
 pub mod digital;
+pub mod ds18b20;
+pub mod encoder;
+pub mod proximity;
 
 /// A trait for devices that can sense or measure data: they "input" some data into the board.
 ///
@@ -24,10 +28,30 @@ pub enum InputEvent {
     OnPress,
     /// Triggered when the button is released.
     OnRelease,
+    /// Triggered when the button is released, with the duration it has been pressed.
+    OnPressDuration,
     /// Triggered when a value changes to HIGH.
     OnHigh,
     /// Triggered when a value changes to LOW.
     OnLow,
+    /// Triggered when the button has been held for a given time.
+    OnHold,
+    /// Triggered repeatedly while the button is held.
+    OnHoldRepeat,
+    /// Triggered when the button is clicked twice in a row.
+    OnDoubleClick,
+    /// Triggered when the button is clicked three times in a row.
+    OnTripleClick,
+    /// Triggered when the encoder turns clockwise.
+    OnClockwise,
+    /// Triggered when the encoder turns counter-clockwise.
+    OnCounterClockwise,
+    /// Triggered when the measured value is within a given range.
+    OnWithinRange,
+    /// Triggered when the measured temperature changes.
+    OnTemperatureChange,
+    /// Triggered when the measured humidity changes.
+    OnHumidityChange,
 }
 
 /// Convert events to string to facilitate usage with [`EventManager`](crate::utils::EventManager).
@@ -37,8 +61,18 @@ impl From<InputEvent> for String {
             InputEvent::OnChange => "change",
             InputEvent::OnPress => "press",
             InputEvent::OnRelease => "release",
+            InputEvent::OnPressDuration => "press_duration",
             InputEvent::OnHigh => "high",
             InputEvent::OnLow => "low",
+            InputEvent::OnHold => "hold",
+            InputEvent::OnHoldRepeat => "hold_repeat",
+            InputEvent::OnDoubleClick => "double_click",
+            InputEvent::OnTripleClick => "triple_click",
+            InputEvent::OnClockwise => "clockwise",
+            InputEvent::OnCounterClockwise => "counter_clockwise",
+            InputEvent::OnWithinRange => "within_range",
+            InputEvent::OnTemperatureChange => "temperature_change",
+            InputEvent::OnHumidityChange => "humidity_change",
         };
         event.into()
     }
@@ -0,0 +1,399 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::devices::input::{Input, InputEvent};
+use crate::devices::Device;
+use crate::errors::Error;
+use crate::errors::HardwareError::IncompatiblePin;
+use crate::hardware::Hardware;
+use crate::io::{IoProtocol, PinClaim, PinIdOrName, PinModeId};
+use crate::utils::{task, EventHandler, EventManager, Range, State, TaskHandler};
+
+/// The duration (in µs) of the trigger pulse sent to the sensor.
+const PULSE: u32 = 5;
+/// The maximum duration (in µs) to wait for the echo (beyond the sensors maximum range).
+const TIMEOUT: u32 = 1_000_000;
+/// The echo duration (in µs) per cm of distance: the sound travels the distance twice at ~343m/s.
+const MICROSECONDS_PER_CM: f32 = 58.3;
+
+/// Represents an ultrasonic proximity sensor (HC-SR04 with its trig and echo pins wired together, PING))),
+/// etc.): an [`Input`] [`Device`] that measures the distance to an obstacle through the PingFirmata feature.
+///
+/// The sensor state is the distance (in cm): the sensor is pinged at a regular interval (see [`Proximity::set_interval`]).
+///
+/// # Notes
+/// The firmware running on the board must include the PingFirmata feature.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Proximity {
+    // ########################################
+    // # Basics
+    /// The pin (id) of the [`Board`] used to ping the sensor.
+    pin: u8,
+    /// The current distance (in cm).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    state: Arc<RwLock<f32>>,
+    /// The interval (in ms) between two measures (default: 100ms).
+    interval: u64,
+    /// The range of distances (in cm) triggering the `OnWithinRange` event (default: none).
+    range: Option<Range<f32>>,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pin (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    claim: PinClaim,
+    /// Inner handler to the task running the measures.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+    /// The event manager for the sensor.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: EventManager,
+}
+
+impl Proximity {
+    /// Creates an instance of a [`Proximity`] sensor attached to a given board.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if the pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if the pin supports neither SONAR nor OUTPUT mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
+    /// * `NotSupported`: this function will bail an error if the board cannot ping.
+    pub fn new<T: Into<PinIdOrName>>(board: &dyn Hardware, pin: T) -> Result<Self, Error> {
+        let pin = board.get_io().read().get_pin(pin)?.clone();
+
+        // PingFirmata drives the pin by itself: the SONAR mode is used when available.
+        let mode = match pin.supports_mode(PinModeId::SONAR) {
+            Some(_) => PinModeId::SONAR,
+            None => {
+                pin.supports_mode(PinModeId::OUTPUT)
+                    .ok_or(IncompatiblePin {
+                        mode: PinModeId::SONAR,
+                        pin: pin.id,
+                        context: "create proximity sensor",
+                    })?
+                    .id
+            }
+        };
+
+        let mut sensor = Self {
+            pin: pin.id,
+            state: Arc::new(RwLock::new(0.0)),
+            interval: 100,
+            range: None,
+            protocol: board.get_protocol(),
+            claim: PinClaim::new(board.get_io(), vec![pin.id], "Proximity")?,
+            handler: Arc::new(RwLock::new(None)),
+            events: Default::default(),
+        };
+
+        sensor.protocol.set_pin_mode(sensor.pin, mode)?;
+
+        // Check the board is able to ping.
+        sensor.protocol.ping_read(sensor.pin, PULSE, TIMEOUT)?;
+
+        // Attaches the event handler.
+        sensor.attach()?;
+
+        Ok(sensor)
+    }
+
+    // ########################################
+    // Getters and Setters.
+
+    /// Returns the pin (id) used by the device.
+    pub fn get_pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Returns the last measured distance (in cm).
+    pub fn get_distance_cm(&self) -> f32 {
+        *self.state.read()
+    }
+
+    /// Returns the last measured distance (in inches).
+    pub fn get_distance_in(&self) -> f32 {
+        *self.state.read() / 2.54
+    }
+
+    /// Returns the interval (in ms) between two measures.
+    pub fn get_interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Sets the interval (in ms) between two measures (ie. the sampling frequency).
+    /// NOTE: the sensors usually need at least 60ms between two measures.
+    pub fn set_interval(mut self, interval: u64) -> Self {
+        self.interval = interval.max(1);
+        self.refresh()
+    }
+
+    /// Returns the range of distances (in cm) triggering the `OnWithinRange` event.
+    pub fn get_range(&self) -> Option<Range<f32>> {
+        self.range
+    }
+
+    /// Sets the range of distances (in cm) triggering the `OnWithinRange` event.
+    pub fn set_range<R: Into<Range<f32>>>(mut self, range: R) -> Self {
+        self.range = Some(range.into());
+        self.refresh()
+    }
+
+    // ########################################
+    // Event related functions
+
+    /// Manually attaches the sensor with the distance change events.
+    /// This should never be needed unless you manually `detach()` the sensor first for some reason
+    /// and want it to start being reactive to events again.
+    ///
+    /// # Errors
+    /// * `PinInUse`: this function will bail an error if the pin has been claimed by another device in the meantime.
+    pub fn attach(&self) -> Result<(), Error> {
+        self.claim.renew()?;
+        if self.handler.read().is_none() {
+            let mut self_clone = self.clone();
+            let mut changes = self.protocol.get_io().read().subscribe();
+            *self.handler.write() = Some(task::run(async move {
+                let mut ticker = tokio::time::interval(Duration::from_millis(self_clone.interval));
+                loop {
+                    tokio::select! {
+                        // Pings the sensor at a regular interval.
+                        _ = ticker.tick() => {
+                            self_clone.protocol.ping_read(self_clone.pin, PULSE, TIMEOUT)?;
+                        }
+                        // Reacts to the echo durations notified by the protocol.
+                        received = changes.recv() => match received {
+                            Ok(change) if change.pin == self_clone.pin => self_clone.update(change.value),
+                            Ok(_) | Err(RecvError::Lagged(_)) => {}
+                            Err(RecvError::Closed) => break,
+                        }
+                    }
+                }
+                Ok(())
+            })?);
+        }
+        Ok(())
+    }
+
+    /// Detaches the interval associated with the sensor.
+    /// This means the sensor won't measure the distance anymore and its pin is released.
+    pub fn detach(&self) {
+        if let Some(handler) = self.handler.read().as_ref() {
+            handler.abort();
+        }
+        *self.handler.write() = None;
+        self.claim.release();
+    }
+
+    /// Registers a callback to be executed on a given event.
+    ///
+    /// Available events for a proximity sensor are:
+    /// - **`InputEvent::OnChange` | `change`**: Triggered when the measured distance changes.
+    ///   _The callback must receive the following parameter: `|distance: f32| { ... }` (in cm)_
+    /// - **`InputEvent::OnWithinRange` | `within_range`**: Triggered when the measured distance is within the range (see [`Proximity::set_range`]).
+    ///   _The callback must receive the following parameter: `|distance: f32| { ... }` (in cm)_
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hermes_five::hardware::{Board, BoardEvent};
+    /// use hermes_five::devices::{InputEvent, Proximity};
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let board = Board::run();
+    ///     board.on(BoardEvent::OnReady, |board: Board| async move {
+    ///
+    ///         // Register a proximity sensor on pin 7.
+    ///         let sensor = Proximity::new(&board, 7)?.set_range([0.0, 20.0]);
+    ///         // Triggered function when an obstacle is closer than 20cm.
+    ///         sensor.on(InputEvent::OnWithinRange, |distance: f32| async move {
+    ///             println!("Obstacle at {:.1}cm", distance);
+    ///             Ok(())
+    ///         });
+    ///
+    ///         // The above code will run forever listening to the InputEvent,
+    ///         // until we detach the device and close the board.
+    ///         sensor.detach();
+    ///         board.close();
+    ///
+    ///         Ok(())
+    ///     });
+    /// }
+    /// ```
+    pub fn on<S, F, T, Fut>(&self, event: S, callback: F) -> EventHandler
+    where
+        S: Into<String>,
+        T: 'static + Send + Sync + Clone,
+        F: FnMut(T) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.events.on(event, callback)
+    }
+
+    /// Restarts the listener (if attached) for a configuration change to be considered.
+    fn refresh(self) -> Self {
+        let handler = self.handler.write().take();
+        if let Some(handler) = handler {
+            handler.abort();
+            // The pin is still claimed: attaching again cannot fail.
+            let _ = self.attach();
+        }
+        self
+    }
+
+    /// Updates the sensor with a new echo duration (in µs) and emits the related events.
+    fn update(&self, duration: u16) {
+        // No echo received: no obstacle within the sensor range.
+        if duration == 0 {
+            return;
+        }
+        let distance = duration as f32 / MICROSECONDS_PER_CM;
+        if distance != *self.state.read() {
+            *self.state.write() = distance;
+            self.events.emit(InputEvent::OnChange, distance);
+        }
+        if let Some(range) = self.range {
+            if distance >= range.start && distance <= range.end {
+                self.events.emit(InputEvent::OnWithinRange, distance);
+            }
+        }
+    }
+}
+
+impl Display for Proximity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Proximity (pin={}) [distance={:.1}cm, interval={}ms]",
+            self.pin,
+            self.state.read(),
+            self.interval
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Device for Proximity {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Input for Proximity {
+    fn get_state(&self) -> State {
+        State::from(*self.state.read())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use crate::hardware::{Board, PCA9685};
+    use crate::io::IO;
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    use super::*;
+
+    #[hermes_five_macros::test]
+    fn test_new_proximity() {
+        let board = Board::new(MockIoProtocol::default());
+        let sensor = Proximity::new(&board, 7).unwrap();
+        assert_eq!(sensor.get_pin(), 7);
+        assert_eq!(sensor.get_pins(), vec![7]);
+        assert_eq!(sensor.get_distance_cm(), 0.0);
+        assert_eq!(sensor.get_state().as_float(), 0.0);
+        assert_eq!(sensor.get_interval(), 100);
+        assert_eq!(sensor.get_range(), None);
+        assert_eq!(
+            board.get_io().read().get_pin(7).unwrap().mode.id,
+            PinModeId::OUTPUT
+        );
+
+        // Pin already in use.
+        assert!(Proximity::new(&board, 7).is_err());
+        // Incompatible pin.
+        assert!(Proximity::new(&board, 0).is_err());
+        // Unknown pin.
+        assert!(Proximity::new(&board, 42).is_err());
+        // Pings are not supported by the PCA9685.
+        let pca9685 = PCA9685::default(&board).unwrap();
+        assert!(Proximity::new(&pca9685, 0).is_err());
+
+        let sensor = sensor.set_interval(60).set_range([0.0, 20.0]);
+        assert_eq!(sensor.get_interval(), 60);
+        assert_eq!(sensor.get_range(), Some(Range::from([0.0, 20.0])));
+
+        sensor.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_proximity_events() {
+        let board = Board::new(MockIoProtocol::default());
+        let sensor = Proximity::new(&board, 7).unwrap().set_range([0.0, 20.0]);
+
+        let change = Arc::new(AtomicU32::new(0));
+        let moved_change = change.clone();
+        sensor.on(InputEvent::OnChange, move |distance: f32| {
+            let captured_change = moved_change.clone();
+            async move {
+                captured_change.store(distance.round() as u32, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        let within = Arc::new(AtomicU32::new(0));
+        let moved_within = within.clone();
+        sensor.on(InputEvent::OnWithinRange, move |_: f32| {
+            let captured_within = moved_within.clone();
+            async move {
+                captured_within.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        // Simulate an echo of 1749µs (30cm): out of range.
+        board.get_io().write().set_pin_value(7, 1749).unwrap();
+        pause!(50);
+        assert_eq!(sensor.get_distance_cm().round(), 30.0);
+        assert_eq!(sensor.get_distance_in().round(), 12.0);
+        assert_eq!(change.load(Ordering::SeqCst), 30);
+        assert_eq!(within.load(Ordering::SeqCst), 0);
+
+        // Simulate an echo of 583µs (10cm): within range.
+        board.get_io().write().set_pin_value(7, 583).unwrap();
+        pause!(50);
+        assert_eq!(sensor.get_distance_cm().round(), 10.0);
+        assert_eq!(change.load(Ordering::SeqCst), 10);
+        assert_eq!(within.load(Ordering::SeqCst), 1);
+
+        // No echo: the distance is unchanged.
+        board.get_io().write().set_pin_value(7, 0).unwrap();
+        pause!(50);
+        assert_eq!(sensor.get_distance_cm().round(), 10.0);
+
+        sensor.detach();
+        board.close();
+    }
+
+    #[hermes_five_macros::test]
+    fn test_proximity_display() {
+        let board = Board::new(MockIoProtocol::default());
+        let sensor = Proximity::new(&board, 7).unwrap();
+        assert_eq!(
+            format!("{}", sensor),
+            "Proximity (pin=7) [distance=0.0cm, interval=100ms]"
+        );
+        sensor.detach();
+        board.close();
+    }
+}
@@ -6,15 +6,23 @@ mod output;
 // Input devices re-exports
 pub use crate::devices::input::analog::AnalogInput;
 pub use crate::devices::input::button::Button;
+pub use crate::devices::input::dht::Dht;
 pub use crate::devices::input::digital::DigitalInput;
+pub use crate::devices::input::ds18b20::Ds18b20;
+pub use crate::devices::input::encoder::{Encoder, EncoderDirection};
+pub use crate::devices::input::proximity::Proximity;
 pub use crate::devices::input::{Input, InputEvent};
 // Output devices re-exports
 pub use crate::devices::output::digital::DigitalOutput;
 pub use crate::devices::output::led::Led;
+pub use crate::devices::output::motor::{Motor, MotorPins};
+pub use crate::devices::output::piezo::{Note, Piezo};
 pub use crate::devices::output::pwm::PwmOutput;
+pub use crate::devices::output::rgb::RgbLed;
 pub use crate::devices::output::servo::Servo;
 pub use crate::devices::output::servo::ServoType;
-pub use crate::devices::output::Output;
+pub use crate::devices::output::stepper::Stepper;
+pub use crate::devices::output::{Output, OutputEvent};
 
 use dyn_clone::DynClone;
 use std::fmt::{Debug, Display};
@@ -28,7 +36,12 @@ use std::fmt::{Debug, Display};
 /// Implementors of this trait are required to be `Debug`, `DynClone`, `Send`, and `Sync`.
 /// This ensures that devices can be cloned and used safely in multithreaded and async environments.
 #[cfg_attr(feature = "serde", typetag::serde(tag = "type"))]
-pub trait Device: Debug + Display + DynClone + Send + Sync {}
+pub trait Device: Debug + Display + DynClone + Send + Sync {
+    /// Returns the pins (id) of the hardware the device uses (default: none).
+    fn get_pins(&self) -> Vec<u8> {
+        vec![]
+    }
+}
 dyn_clone::clone_trait_object!(Device);
 
 #[cfg(feature = "serde")]
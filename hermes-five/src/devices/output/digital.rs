@@ -7,7 +7,7 @@ use crate::animations::{Animation, Easing, Keyframe, Track};
 use crate::devices::{Device, Output};
 use crate::errors::{Error, HardwareError, StateError};
 use crate::hardware::Hardware;
-use crate::io::{IoProtocol, Pin, PinIdOrName, PinModeId};
+use crate::io::{IoProtocol, Pin, PinClaim, PinIdOrName, PinModeId};
 use crate::utils::State;
 
 /// Represents a digital actuator of unspecified type: an [`Output`] [`Device`] that write digital values
@@ -30,9 +30,12 @@ pub struct DigitalOutput {
     // # Volatile utility data.
     #[cfg_attr(feature = "serde", serde(skip))]
     protocol: Box<dyn IoProtocol>,
-    /// Inner handler to the task running the animation.
+    /// The ownership of the pin (shared by all clones: released when the last one is dropped).
     #[cfg_attr(feature = "serde", serde(skip))]
-    animation: Arc<Option<Animation>>,
+    _claim: PinClaim,
+    /// Inner handler to the task running the animation (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    animation: Arc<RwLock<Option<Animation>>>,
 }
 
 impl DigitalOutput {
@@ -41,6 +44,7 @@ impl DigitalOutput {
     /// # Errors
     /// * `HardwareError::UnknownPin`: this function will bail an error if the pin does not exist for this board.
     /// * `HardwareError::IncompatiblePin`: this function will bail an error if the pin does not support OUTPUT mode.
+    /// * `HardwareError::* `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new<T: Into<PinIdOrName>>(
         board: &dyn Hardware,
         pin: T,
@@ -53,7 +57,8 @@ impl DigitalOutput {
             state: Arc::new(RwLock::new(default)),
             default,
             protocol: board.get_protocol(),
-            animation: Arc::new(None),
+            _claim: PinClaim::new(board.get_io(), vec![pin.id], "DigitalOutput")?,
+            animation: Arc::new(RwLock::new(None)),
         };
 
         // Set pin mode to OUTPUT.
@@ -125,7 +130,11 @@ impl Display for DigitalOutput {
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
-impl Device for DigitalOutput {}
+impl Device for DigitalOutput {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Output for DigitalOutput {
@@ -168,18 +177,17 @@ impl Output for DigitalOutput {
                 .with_keyframe(Keyframe::new(state, 0, duration).set_transition(transition)),
         );
         animation.play();
-        self.animation = Arc::new(Some(animation));
+        *self.animation.write() = Some(animation);
     }
 
     fn is_busy(&self) -> bool {
-        self.animation.is_some()
+        self.animation.read().is_some()
     }
 
     fn stop(&mut self) {
-        if let Some(animation) = Arc::get_mut(&mut self.animation).and_then(Option::as_mut) {
+        if let Some(mut animation) = self.animation.write().take() {
             animation.stop();
         }
-        self.animation = Arc::new(None);
     }
 }
 
@@ -187,7 +195,7 @@ impl Output for DigitalOutput {
 mod tests {
     use crate::animations::Easing;
     use crate::devices::output::digital::DigitalOutput;
-    use crate::devices::Output;
+    use crate::devices::{Device, Output};
     use crate::hardware::Board;
     use crate::io::PinModeId;
     use crate::mocks::plugin_io::MockIoProtocol;
@@ -199,13 +207,14 @@ mod tests {
         let board = Board::new(MockIoProtocol::default());
 
         // Default LOW state.
-        let output = DigitalOutput::new(&board, 13, false).unwrap();
-        assert_eq!(output.get_pin(), 13);
-        assert!(!*output.state.read());
-        assert!(!output.get_state().as_bool());
-        assert!(!output.get_default().as_bool());
-        assert!(output.is_low());
-        assert!(!output.is_high());
+        let first = DigitalOutput::new(&board, 13, false).unwrap();
+        assert_eq!(first.get_pin(), 13);
+        assert_eq!(first.get_pins(), vec![13]);
+        assert!(!*first.state.read());
+        assert!(!first.get_state().as_bool());
+        assert!(!first.get_default().as_bool());
+        assert!(first.is_low());
+        assert!(!first.is_high());
 
         // Default HIGH state.
         let output = DigitalOutput::new(&board, 4, true).unwrap();
@@ -216,6 +225,11 @@ mod tests {
         assert!(output.is_high());
         assert!(!output.is_low());
 
+        // The pin 13 is already in use...
+        assert!(DigitalOutput::new(&board, "D13", true).is_err());
+        // ... until the first output is dropped.
+        drop(first);
+
         // Created from pin name
         let output = DigitalOutput::new(&board, "D13", true).unwrap();
         assert_eq!(output.get_pin(), 13);
@@ -8,7 +8,7 @@ use crate::devices::{Device, Output};
 use crate::errors::HardwareError::IncompatiblePin;
 use crate::errors::{Error, StateError};
 use crate::hardware::Hardware;
-use crate::io::{IoProtocol, Pin, PinMode, PinModeId};
+use crate::io::{IoProtocol, Pin, PinClaim, PinMode, PinModeId};
 use crate::utils::{Scalable, State};
 
 /// Represents a LED controlled by a digital pin.
@@ -40,9 +40,12 @@ pub struct Led {
     pwm_mode: Option<PinMode>,
     #[cfg_attr(feature = "serde", serde(skip))]
     protocol: Box<dyn IoProtocol>,
-    /// Inner handler to the task running the animation.
+    /// The ownership of the pin (shared by all clones: released when the last one is dropped).
     #[cfg_attr(feature = "serde", serde(skip))]
-    animation: Arc<Option<Animation>>,
+    _claim: PinClaim,
+    /// Inner handler to the task running the animation (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    animation: Arc<RwLock<Option<Animation>>>,
 }
 
 impl Led {
@@ -51,6 +54,7 @@ impl Led {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the pin does not exist for this board.
     /// * `IncompatibleMode`: this function will bail an error if the pin does not support OUTPUT or PWM mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new(board: &dyn Hardware, pin: u8, default: bool) -> Result<Self, Error> {
         let mut protocol = board.get_protocol();
 
@@ -60,6 +64,9 @@ impl Led {
             hardware.get_pin(pin)?.clone()
         };
 
+        // Claim the pin ownership.
+        let claim = PinClaim::new(protocol.get_io(), vec![pin], "Led")?;
+
         // Get the PWM mode if any
         let pwm_mode = hardware_pin.supports_mode(PinModeId::PWM);
 
@@ -83,7 +90,8 @@ impl Led {
             brightness: 0xFF,
             pwm_mode,
             protocol,
-            animation: Arc::new(None),
+            _claim: claim,
+            animation: Arc::new(RwLock::new(None)),
         };
 
         led.reset()?;
@@ -123,7 +131,7 @@ impl Led {
             .set_repeat(true),
         );
         animation.play();
-        self.animation = Arc::new(Some(animation));
+        *self.animation.write() = Some(animation);
 
         self
     }
@@ -140,7 +148,7 @@ impl Led {
             .set_repeat(true),
         );
         animation.play();
-        self.animation = Arc::new(Some(animation));
+        *self.animation.write() = Some(animation);
 
         self
     }
@@ -226,7 +234,11 @@ impl Display for Led {
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
-impl Device for Led {}
+impl Device for Led {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Output for Led {
@@ -271,16 +283,15 @@ impl Output for Led {
                 .with_keyframe(Keyframe::new(state, 0, duration).set_transition(transition)),
         );
         animation.play();
-        self.animation = Arc::new(Some(animation));
+        *self.animation.write() = Some(animation);
     }
     fn is_busy(&self) -> bool {
-        self.animation.is_some()
+        self.animation.read().is_some()
     }
     fn stop(&mut self) {
-        if let Some(animation) = Arc::get_mut(&mut self.animation).and_then(Option::as_mut) {
+        if let Some(mut animation) = self.animation.write().take() {
             animation.stop();
         }
-        self.animation = Arc::new(None);
     }
 }
 
@@ -301,6 +312,7 @@ mod tests {
     fn test_led_creation() {
         let led = _setup_led(13);
         assert_eq!(led.get_pin(), 13); // Ensure the correct pin is set
+        assert_eq!(led.get_pins(), vec![13]);
         assert_eq!(*led.state.read(), 0); // Initial state should be 0 (OFF)
         assert_eq!(led.brightness, 0xFF); // Default brightness should be 255
     }
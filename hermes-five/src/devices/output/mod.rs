@@ -5,8 +5,12 @@ use crate::utils::{Scalable, State};
 
 pub mod digital;
 pub mod led;
+pub mod motor;
+pub mod piezo;
 pub mod pwm;
+pub mod rgb;
 pub mod servo;
+pub mod stepper;
 
 /// A trait for devices that can act on the world: the board "outputs" some state onto them.
 ///
@@ -60,6 +64,22 @@ pub trait Output: Device {
 }
 dyn_clone::clone_trait_object!(Output);
 
+/// Lists all events an Output type device can emit/listen.
+pub enum OutputEvent {
+    /// Triggered when a stepper has reached its target position.
+    OnMoveComplete,
+}
+
+/// Convert events to string to facilitate usage with [`EventManager`](crate::utils::EventManager).
+impl From<OutputEvent> for String {
+    fn from(value: OutputEvent) -> Self {
+        let event = match value {
+            OutputEvent::OnMoveComplete => "move_complete",
+        };
+        event.into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::mocks::output_device::MockOutputDevice;
@@ -0,0 +1,478 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::animations::{Animation, Easing, Keyframe, Track};
+use crate::devices::{Device, Output};
+use crate::errors::HardwareError::IncompatiblePin;
+use crate::errors::{Error, StateError};
+use crate::hardware::Hardware;
+use crate::io::{IoProtocol, PinClaim, PinModeId};
+use crate::utils::{Scalable, State};
+
+/// Defines how a [`Motor`] is wired to its driver.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MotorPins {
+    /// One PWM pin for the speed and one digital pin for the direction (ex: Arduino motor shield).
+    /// Such drivers cannot brake: braking coasts the motor instead.
+    Directional { pwm: u8, dir: u8 },
+    /// One PWM pin for the speed and two digital pins for the direction (ex: L298N, TB6612).
+    HBridge { pwm: u8, dir: u8, cdir: u8 },
+    /// Two PWM pins, one per direction (ex: DRV8833, L9110).
+    DualPwm { forward: u8, reverse: u8 },
+}
+
+impl MotorPins {
+    /// Returns the PWM pins of the layout.
+    fn pwm_pins(&self) -> Vec<u8> {
+        match *self {
+            MotorPins::Directional { pwm, .. } | MotorPins::HBridge { pwm, .. } => vec![pwm],
+            MotorPins::DualPwm { forward, reverse } => vec![forward, reverse],
+        }
+    }
+
+    /// Returns the digital (direction) pins of the layout.
+    fn digital_pins(&self) -> Vec<u8> {
+        match *self {
+            MotorPins::Directional { dir, .. } => vec![dir],
+            MotorPins::HBridge { dir, cdir, .. } => vec![dir, cdir],
+            MotorPins::DualPwm { .. } => vec![],
+        }
+    }
+}
+
+/// Represents a DC motor controlled through a motor driver (H-bridge).
+///
+/// The motor state is its speed: a signed percentage of its max speed (-100% to 100%), negative
+/// values meaning the motor runs in reverse.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Motor {
+    // ########################################
+    // # Basics
+    /// The pins of the [`Board`] used to control the motor driver.
+    pins: MotorPins,
+    /// The current motor speed (-100% to 100%).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    state: Arc<RwLock<i8>>,
+
+    // ########################################
+    // # Settings
+    /// Indicates if the motor is braking (rather than coasting) when its speed is 0.
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    braking: Arc<RwLock<bool>>,
+
+    // ########################################
+    // # Volatile utility data.
+    /// Caches the max output value of the PWM pins depending on resolution.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    max_value: u16,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pins (shared by all clones: released when the last one is dropped).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _claim: PinClaim,
+    /// Inner handler to the task running the animation (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    animation: Arc<RwLock<Option<Animation>>>,
+}
+
+impl Motor {
+    /// Creates an instance of a motor attached to a given board.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if a pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if a speed pin does not support PWM mode
+    ///   or a direction pin does not support OUTPUT mode.
+    /// * `PinInUse`: this function will bail an error if a pin is already used by another device.
+    pub fn new(board: &dyn Hardware, pins: MotorPins) -> Result<Self, Error> {
+        let mut protocol = board.get_protocol();
+
+        // Check the pins compatibility and retrieve the PWM max value.
+        let mut max_value = u16::MAX;
+        {
+            let hardware = protocol.get_io().read();
+            for pin in pins.pwm_pins() {
+                let pwm_mode = hardware.get_pin(pin)?.supports_mode(PinModeId::PWM).ok_or(
+                    IncompatiblePin {
+                        mode: PinModeId::PWM,
+                        pin,
+                        context: "create motor",
+                    },
+                )?;
+                max_value = max_value.min(pwm_mode.get_max_possible_value());
+            }
+            for pin in pins.digital_pins() {
+                hardware
+                    .get_pin(pin)?
+                    .supports_mode(PinModeId::OUTPUT)
+                    .ok_or(IncompatiblePin {
+                        mode: PinModeId::OUTPUT,
+                        pin,
+                        context: "create motor",
+                    })?;
+            }
+        }
+
+        // Claim the pins ownership.
+        let all_pins = [pins.pwm_pins(), pins.digital_pins()].concat();
+        let claim = PinClaim::new(protocol.get_io(), all_pins, "Motor")?;
+
+        for pin in pins.pwm_pins() {
+            protocol.set_pin_mode(pin, PinModeId::PWM)?;
+        }
+        for pin in pins.digital_pins() {
+            protocol.set_pin_mode(pin, PinModeId::OUTPUT)?;
+        }
+
+        let mut motor = Self {
+            pins,
+            state: Arc::new(RwLock::new(0)),
+            braking: Arc::new(RwLock::new(false)),
+            max_value,
+            protocol,
+            _claim: claim,
+            animation: Arc::new(RwLock::new(None)),
+        };
+
+        motor.reset()?;
+
+        Ok(motor)
+    }
+
+    /// Runs the motor forward at the given speed (0-100%).
+    /// NOTE: everything above 100 is considered 100%.
+    pub fn forward(&mut self, speed: u8) -> Result<&Self, Error> {
+        self.set_state(State::Signed(speed.min(100) as i64))?;
+        Ok(self)
+    }
+
+    /// Runs the motor in reverse at the given speed (0-100%).
+    /// NOTE: everything above 100 is considered 100%.
+    pub fn reverse(&mut self, speed: u8) -> Result<&Self, Error> {
+        self.set_state(State::Signed(-(speed.min(100) as i64)))?;
+        Ok(self)
+    }
+
+    /// Actively brakes the motor (short-brake): the motor stops quickly.
+    ///
+    /// # Notes
+    /// [`MotorPins::Directional`] drivers cannot brake: the motor coasts instead.
+    pub fn brake(&mut self) -> Result<&Self, Error> {
+        let max = self.max_value;
+        match self.pins {
+            MotorPins::Directional { pwm, .. } => self.protocol.analog_write(pwm, 0)?,
+            MotorPins::HBridge { pwm, dir, cdir } => {
+                self.protocol.digital_write(dir, true)?;
+                self.protocol.digital_write(cdir, true)?;
+                self.protocol.analog_write(pwm, max)?;
+            }
+            MotorPins::DualPwm { forward, reverse } => {
+                self.protocol.analog_write(forward, max)?;
+                self.protocol.analog_write(reverse, max)?;
+            }
+        }
+        *self.state.write() = 0;
+        *self.braking.write() = !matches!(self.pins, MotorPins::Directional { .. });
+        Ok(self)
+    }
+
+    /// Releases the motor (no power): the motor stops by itself.
+    pub fn coast(&mut self) -> Result<&Self, Error> {
+        self.set_state(State::Signed(0))?;
+        Ok(self)
+    }
+
+    // ########################################
+    // Getters.
+
+    /// Returns the pins layout used by the device.
+    pub fn get_pins_layout(&self) -> MotorPins {
+        self.pins
+    }
+
+    /// Returns the current speed (-100% to 100%): negative values mean the motor runs in reverse.
+    pub fn get_speed(&self) -> i8 {
+        *self.state.read()
+    }
+
+    /// Indicates if the motor is currently running.
+    pub fn is_running(&self) -> bool {
+        self.state.read().ne(&0)
+    }
+
+    /// Indicates if the motor is currently braking.
+    pub fn is_braking(&self) -> bool {
+        *self.braking.read()
+    }
+}
+
+impl Display for Motor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Motor (pins={:?}) [speed={}%, braking={}]",
+            self.get_pins(),
+            self.state.read(),
+            self.braking.read()
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Device for Motor {
+    fn get_pins(&self) -> Vec<u8> {
+        [self.pins.pwm_pins(), self.pins.digital_pins()].concat()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Output for Motor {
+    /// Returns  the actuator current state.
+    fn get_state(&self) -> State {
+        State::Signed(*self.state.read() as i64)
+    }
+
+    /// Internal only: you should rather use [`Self::forward()`], [`Self::reverse()`], [`Self::coast()`] functions.
+    fn set_state(&mut self, state: State) -> Result<State, Error> {
+        let speed = match state {
+            State::Integer(value) => Ok(value.min(100) as i8),
+            State::Signed(value) => Ok(value.clamp(-100, 100) as i8),
+            State::Float(value) => Ok(value.clamp(-100.0, 100.0).round() as i8),
+            _ => Err(StateError),
+        }?;
+
+        let value: u16 = speed.unsigned_abs().scale(0, 100, 0, self.max_value);
+        let forward = speed >= 0;
+        match self.pins {
+            MotorPins::Directional { pwm, dir } => {
+                self.protocol.digital_write(dir, forward)?;
+                self.protocol.analog_write(pwm, value)?;
+            }
+            MotorPins::HBridge { pwm, dir, cdir } => {
+                self.protocol.digital_write(dir, speed > 0)?;
+                self.protocol.digital_write(cdir, speed < 0)?;
+                self.protocol.analog_write(pwm, value)?;
+            }
+            MotorPins::DualPwm {
+                forward: forward_pin,
+                reverse: reverse_pin,
+            } => {
+                let (forward_value, reverse_value) = match forward {
+                    true => (value, 0),
+                    false => (0, value),
+                };
+                self.protocol.analog_write(forward_pin, forward_value)?;
+                self.protocol.analog_write(reverse_pin, reverse_value)?;
+            }
+        }
+
+        *self.state.write() = speed;
+        *self.braking.write() = false;
+        Ok(State::Signed(speed as i64))
+    }
+    fn get_default(&self) -> State {
+        State::Signed(0)
+    }
+    fn animate<S: Into<State>>(&mut self, state: S, duration: u64, transition: Easing) {
+        let mut animation = Animation::from(
+            Track::new(self.clone())
+                .with_keyframe(Keyframe::new(state, 0, duration).set_transition(transition)),
+        );
+        animation.play();
+        *self.animation.write() = Some(animation);
+    }
+    fn is_busy(&self) -> bool {
+        self.animation.read().is_some()
+    }
+    fn stop(&mut self) {
+        if let Some(mut animation) = self.animation.write().take() {
+            animation.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hardware::{Board, PCA9685};
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    use super::*;
+
+    fn _get_values(board: &dyn Hardware, pins: &[u8]) -> Vec<u16> {
+        let io = board.get_io().read();
+        pins.iter()
+            .map(|pin| io.get_pin(*pin).unwrap().value)
+            .collect()
+    }
+
+    #[test]
+    fn test_motor_creation() {
+        let board = Board::new(MockIoProtocol::default());
+        let motor = Motor::new(&board, MotorPins::Directional { pwm: 8, dir: 2 }).unwrap();
+        assert_eq!(motor.get_pins(), vec![8, 2]);
+        assert_eq!(
+            motor.get_pins_layout(),
+            MotorPins::Directional { pwm: 8, dir: 2 }
+        );
+        assert_eq!(motor.get_speed(), 0);
+        assert_eq!(motor.max_value, 255);
+        assert!(!motor.is_running());
+        assert!(!motor.is_braking());
+
+        // Pins already in use.
+        assert!(Motor::new(
+            &board,
+            MotorPins::DualPwm {
+                forward: 8,
+                reverse: 11
+            }
+        )
+        .is_err());
+        // Incompatible pins.
+        assert!(Motor::new(&board, MotorPins::Directional { pwm: 3, dir: 4 }).is_err());
+        assert!(Motor::new(&board, MotorPins::Directional { pwm: 11, dir: 0 }).is_err());
+        // Unknown pin.
+        assert!(Motor::new(&board, MotorPins::Directional { pwm: 11, dir: 42 }).is_err());
+    }
+
+    #[test]
+    fn test_directional() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut motor = Motor::new(&board, MotorPins::Directional { pwm: 8, dir: 2 }).unwrap();
+
+        motor.forward(50).unwrap();
+        assert_eq!(motor.get_speed(), 50);
+        assert!(motor.is_running());
+        assert_eq!(_get_values(&board, &[8, 2]), vec![128, 1]);
+
+        motor.reverse(120).unwrap();
+        assert_eq!(motor.get_speed(), -100);
+        assert_eq!(_get_values(&board, &[8, 2]), vec![255, 0]);
+
+        // Directional drivers cannot brake.
+        motor.brake().unwrap();
+        assert_eq!(motor.get_speed(), 0);
+        assert!(!motor.is_braking());
+        assert_eq!(_get_values(&board, &[8]), vec![0]);
+
+        motor.coast().unwrap();
+        assert!(!motor.is_braking());
+        assert!(!motor.is_running());
+        assert_eq!(_get_values(&board, &[8]), vec![0]);
+    }
+
+    #[test]
+    fn test_hbridge() {
+        let board = Board::new(MockIoProtocol::default());
+        let pins = MotorPins::HBridge {
+            pwm: 8,
+            dir: 2,
+            cdir: 3,
+        };
+        let mut motor = Motor::new(&board, pins).unwrap();
+        assert_eq!(motor.get_pins(), vec![8, 2, 3]);
+
+        motor.forward(100).unwrap();
+        assert_eq!(_get_values(&board, &[8, 2, 3]), vec![255, 1, 0]);
+        motor.reverse(20).unwrap();
+        assert_eq!(_get_values(&board, &[8, 2, 3]), vec![51, 0, 1]);
+        motor.brake().unwrap();
+        assert!(motor.is_braking());
+        assert_eq!(_get_values(&board, &[8, 2, 3]), vec![255, 1, 1]);
+        motor.coast().unwrap();
+        assert!(!motor.is_braking());
+        assert_eq!(_get_values(&board, &[8, 2, 3]), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_dual_pwm_on_pca9685() {
+        let board = Board::new(MockIoProtocol::default());
+        let pca9685 = PCA9685::default(&board).unwrap();
+        let pins = MotorPins::DualPwm {
+            forward: 0,
+            reverse: 1,
+        };
+        let mut motor = Motor::new(&pca9685, pins).unwrap();
+        assert_eq!(motor.get_pins(), vec![0, 1]);
+
+        motor.forward(100).unwrap();
+        assert_eq!(_get_values(&pca9685, &[0, 1]), vec![4095, 0]);
+        motor.reverse(50).unwrap();
+        assert_eq!(_get_values(&pca9685, &[0, 1]), vec![0, 2048]);
+        motor.brake().unwrap();
+        assert_eq!(_get_values(&pca9685, &[0, 1]), vec![4095, 4095]);
+        motor.coast().unwrap();
+        assert_eq!(_get_values(&pca9685, &[0, 1]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_hbridge_on_pca9685() {
+        let board = Board::new(MockIoProtocol::default());
+        let pca9685 = PCA9685::default(&board).unwrap();
+        let pins = MotorPins::HBridge {
+            pwm: 0,
+            dir: 1,
+            cdir: 2,
+        };
+        let mut motor = Motor::new(&pca9685, pins).unwrap();
+        motor.reverse(100).unwrap();
+        assert_eq!(_get_values(&pca9685, &[0, 1, 2]), vec![4095, 0, 4095]);
+    }
+
+    #[test]
+    fn test_set_state() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut motor = Motor::new(&board, MotorPins::Directional { pwm: 8, dir: 2 }).unwrap();
+
+        assert_eq!(
+            motor.set_state(State::Signed(-30)).unwrap(),
+            State::Signed(-30)
+        );
+        assert_eq!(motor.get_state(), State::Signed(-30));
+        assert_eq!(
+            motor.set_state(State::Integer(300)).unwrap(),
+            State::Signed(100)
+        );
+        assert_eq!(
+            motor.set_state(State::Float(-42.6)).unwrap(),
+            State::Signed(-43)
+        );
+        assert_eq!(
+            motor.set_state(State::Signed(-200)).unwrap(),
+            State::Signed(-100)
+        );
+        assert!(motor.set_state(State::Boolean(true)).is_err());
+        assert_eq!(motor.get_default(), State::Signed(0));
+        assert!(motor.reset().is_ok());
+        assert_eq!(motor.get_speed(), 0);
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_animation() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut motor = Motor::new(&board, MotorPins::Directional { pwm: 8, dir: 2 }).unwrap();
+        assert!(!motor.is_busy());
+        motor.stop(); // Stop something not started should not fail.
+        motor.animate(State::Signed(-100), 500, Easing::Linear);
+        pause!(100);
+        assert!(motor.is_busy());
+        assert!(motor.get_speed() < 0);
+        motor.stop();
+        assert!(!motor.is_busy());
+    }
+
+    #[test]
+    fn test_display_impl() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut motor = Motor::new(&board, MotorPins::Directional { pwm: 8, dir: 2 }).unwrap();
+        motor.reverse(25).unwrap();
+        assert_eq!(
+            format!("{}", motor),
+            "Motor (pins=[8, 2]) [speed=-25%, braking=false]"
+        );
+    }
+}
@@ -0,0 +1,757 @@
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tokio::time::{Duration, Instant};
+
+use crate::animations::{Animation, Easing, Keyframe, Track};
+use crate::devices::{Device, Output};
+use crate::errors::HardwareError::{IncompatiblePin, NotSupported};
+use crate::errors::{Error, StateError, UnknownError};
+use crate::hardware::Hardware;
+use crate::io::{IoProtocol, Pin, PinClaim, PinModeId};
+use crate::utils::{task, State, TaskHandler};
+
+/// Represents a note of a melody: a frequency and a duration in beats.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Note {
+    /// The note frequency in Hz (0 for a rest).
+    pub frequency: u16,
+    /// The note duration in beats (a quarter note lasts 1 beat).
+    pub beats: f32,
+}
+
+impl Note {
+    /// Creates a note of a given frequency (in Hz) and duration (in beats).
+    pub fn new(frequency: u16, beats: f32) -> Self {
+        Self { frequency, beats }
+    }
+
+    /// Creates a rest (silence) of a given duration (in beats).
+    pub fn rest(beats: f32) -> Self {
+        Self::new(0, beats)
+    }
+
+    /// Creates a note from its name in scientific pitch notation (ex: `C4`, `F#5` or `Bb3`), or `R`
+    /// for a rest. Returns `None` if the name is invalid.
+    pub fn from_name(name: &str, beats: f32) -> Option<Self> {
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("r") {
+            return Some(Self::rest(beats));
+        }
+
+        let mut chars = name.chars();
+        let mut semitone = semitone(chars.next()?)? as i32;
+        let rest = chars.as_str();
+        let octave = match rest.chars().next()? {
+            '#' => {
+                semitone += 1;
+                &rest[1..]
+            }
+            'b' => {
+                semitone -= 1;
+                &rest[1..]
+            }
+            _ => rest,
+        };
+        let octave = octave.parse::<u8>().ok().filter(|octave| *octave <= 8)?;
+        Some(Self::new(frequency(semitone, octave), beats))
+    }
+}
+
+/// Returns the semitone of a note letter in its octave (C=0, D=2, ..., B=11).
+fn semitone(letter: char) -> Option<u8> {
+    match letter.to_ascii_lowercase() {
+        'c' => Some(0),
+        'd' => Some(2),
+        'e' => Some(4),
+        'f' => Some(5),
+        'g' => Some(7),
+        'a' => Some(9),
+        'b' | 'h' => Some(11),
+        _ => None,
+    }
+}
+
+/// Returns the frequency (in Hz) of a note in equal temperament tuning (A4 = 440Hz).
+fn frequency(semitone: i32, octave: u8) -> u16 {
+    // MIDI note number: A4 is 69.
+    let number = (octave as i32 + 1) * 12 + semitone;
+    (440.0 * 2f64.powf((number - 69) as f64 / 12.0)).round() as u16
+}
+
+/// Parses a RTTTL (Ring Tone Text Transfer Language) melody into its notes and tempo (in beats per minute).
+///
+/// Format: `name:d=<duration>,o=<octave>,b=<tempo>:<notes>` where each note is written
+/// `[duration]<letter>[#][.][octave][.]`: `8c#6.` is a dotted eighth C#6 for instance.
+fn parse_rtttl(rtttl: &str) -> Result<(Vec<Note>, u16), Error> {
+    let invalid = |info: &str| UnknownError {
+        info: format!("Invalid RTTTL: {}", info),
+    };
+
+    let sections: Vec<&str> = rtttl.trim().splitn(3, ':').collect();
+    if sections.len() != 3 {
+        return Err(invalid("expected 'name:settings:notes'"));
+    }
+
+    // Default settings.
+    let (mut duration, mut octave, mut tempo) = (4u16, 6u8, 63u16);
+    for setting in sections[1].split(',').filter(|s| !s.trim().is_empty()) {
+        let (key, value) = setting
+            .split_once('=')
+            .ok_or_else(|| invalid(&format!("unknown setting '{}'", setting)))?;
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "d" => duration = value.parse().map_err(|_| invalid("invalid duration"))?,
+            "o" => octave = value.parse().map_err(|_| invalid("invalid octave"))?,
+            "b" => tempo = value.parse().map_err(|_| invalid("invalid tempo"))?,
+            _ => return Err(invalid(&format!("unknown setting '{}'", setting))),
+        }
+    }
+    if duration == 0 || tempo == 0 {
+        return Err(invalid("duration and tempo must be positive"));
+    }
+
+    let mut notes = vec![];
+    for token in sections[2]
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        let token = token.to_ascii_lowercase();
+        let digits = token.chars().take_while(char::is_ascii_digit).count();
+        let note_duration = match digits {
+            0 => duration,
+            _ => token[..digits]
+                .parse()
+                .ok()
+                .filter(|d| *d > 0)
+                .ok_or_else(|| invalid(&format!("invalid note '{}'", token)))?,
+        };
+
+        let mut chars = token[digits..].chars().peekable();
+        let letter = chars
+            .next()
+            .ok_or_else(|| invalid(&format!("invalid note '{}'", token)))?;
+        let mut semitone = match letter {
+            'p' => None,
+            letter => Some(
+                semitone(letter).ok_or_else(|| invalid(&format!("invalid note '{}'", token)))?
+                    as i32,
+            ),
+        };
+        if chars.next_if_eq(&'#').is_some() {
+            semitone = semitone.map(|semitone| semitone + 1);
+        }
+        let mut dotted = chars.next_if_eq(&'.').is_some();
+        let note_octave: String = chars.by_ref().take_while(char::is_ascii_digit).collect();
+        let note_octave = match note_octave.is_empty() {
+            true => octave,
+            false => note_octave
+                .parse()
+                .map_err(|_| invalid(&format!("invalid note '{}'", token)))?,
+        };
+        dotted |= token.ends_with('.');
+
+        let mut beats = 4.0 / note_duration as f32;
+        if dotted {
+            beats *= 1.5;
+        }
+        notes.push(match semitone {
+            None => Note::rest(beats),
+            Some(semitone) => Note::new(frequency(semitone, note_octave), beats),
+        });
+    }
+
+    Ok((notes, tempo))
+}
+
+/// Represents a piezo buzzer controlled by a digital pin.
+/// There are two kinds of pins that can be used:
+/// - TONE: the tone is generated by the board firmware
+/// - OUTPUT: the tone is generated by toggling the pin at the requested frequency, up to
+///   [`Piezo::MAX_TOGGLE_FREQUENCY`] (each half period costs a digital write through the transport)
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Piezo {
+    // ########################################
+    // # Basics
+    /// The pin (id) of the [`Board`] used to control the piezo.
+    pin: u8,
+    /// The current frequency played (in Hz - 0 when silent).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    state: Arc<RwLock<u16>>,
+
+    // ########################################
+    // # Volatile utility data.
+    /// Indicates if the pin supports TONE mode (memoization use only).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    tone_mode: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pin (shared by all clones: released when the last one is dropped).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _claim: PinClaim,
+    /// Inner handler to the task playing the sound (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+    /// Inner handler to the task running the animation (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    animation: Arc<RwLock<Option<Animation>>>,
+}
+
+impl Piezo {
+    /// The highest frequency (in Hz) a piezo can play without TONE support: each half period is
+    /// a digital write (3 bytes), which a serial link at 57600 bauds barely sustains at 1kHz.
+    pub const MAX_TOGGLE_FREQUENCY: u16 = 1000;
+
+    /// Creates an instance of a piezo attached to a given board.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if the pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if the pin does not support TONE or OUTPUT mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
+    pub fn new(board: &dyn Hardware, pin: u8) -> Result<Self, Error> {
+        let mut protocol = board.get_protocol();
+
+        // Get the hardware corresponding pin.
+        let hardware_pin = {
+            let hardware = protocol.get_io().read();
+            hardware.get_pin(pin)?.clone()
+        };
+
+        // Use TONE mode if available, OUTPUT otherwise.
+        let tone_mode = hardware_pin.supports_mode(PinModeId::TONE).is_some();
+        let pin_mode = match tone_mode {
+            true => PinModeId::TONE,
+            false => PinModeId::OUTPUT,
+        };
+        hardware_pin
+            .supports_mode(pin_mode)
+            .ok_or(IncompatiblePin {
+                mode: pin_mode,
+                pin,
+                context: "create piezo",
+            })?;
+
+        // Claim the pin ownership.
+        let claim = PinClaim::new(protocol.get_io(), vec![pin], "Piezo")?;
+
+        protocol.set_pin_mode(pin, pin_mode)?;
+
+        let mut piezo = Self {
+            pin,
+            state: Arc::new(RwLock::new(0)),
+            tone_mode,
+            protocol,
+            _claim: claim,
+            handler: Arc::new(RwLock::new(None)),
+            animation: Arc::new(RwLock::new(None)),
+        };
+
+        piezo.reset()?;
+
+        Ok(piezo)
+    }
+
+    /// Plays a frequency (in Hz) for a duration (in ms).
+    /// This runs in a task and can be stopped by calling [`Piezo::stop()`].
+    ///
+    /// # Errors
+    /// * `NotSupported`: this function will bail an error if the frequency is above
+    ///   [`Piezo::MAX_TOGGLE_FREQUENCY`] while the pin does not support TONE mode.
+    pub fn frequency(&mut self, frequency: u16, duration: u64) -> Result<&Self, Error> {
+        self.check_frequency(frequency)?;
+        self.run(vec![(frequency, Some(duration))])?;
+        Ok(self)
+    }
+
+    /// Plays a sequence of notes at the given tempo (in beats per minute).
+    /// This runs in a task and can be stopped by calling [`Piezo::stop()`].
+    ///
+    /// # Errors
+    /// * `NotSupported`: this function will bail an error if a note is above
+    ///   [`Piezo::MAX_TOGGLE_FREQUENCY`] while the pin does not support TONE mode.
+    ///
+    /// # Example
+    /// ```
+    /// use hermes_five::devices::{Note, Piezo};
+    /// use hermes_five::hardware::{Board, BoardEvent};
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let board = Board::run();
+    ///     board.on(BoardEvent::OnReady, |board: Board| async move {
+    ///         let mut piezo = Piezo::new(&board, 3)?;
+    ///         piezo.play(vec![
+    ///             Note::from_name("C4", 1.0).unwrap(),
+    ///             Note::from_name("E4", 1.0).unwrap(),
+    ///             Note::from_name("G4", 2.0).unwrap(),
+    ///         ], 120)?;
+    ///         Ok(())
+    ///     });
+    /// }
+    /// ```
+    pub fn play(&mut self, notes: Vec<Note>, tempo: u16) -> Result<&Self, Error> {
+        for note in &notes {
+            self.check_frequency(note.frequency)?;
+        }
+        let beat = 60_000.0 / tempo.max(1) as f32;
+        let sequence = notes
+            .into_iter()
+            .flat_map(|note| {
+                let duration = (note.beats.max(0.0) * beat).round() as u64;
+                // Leave a small silence after each note for them to be distinguishable.
+                let sound = duration * 9 / 10;
+                match note.frequency {
+                    0 => vec![(0, Some(duration))],
+                    frequency => vec![(frequency, Some(sound)), (0, Some(duration - sound))],
+                }
+            })
+            .collect();
+        self.run(sequence)?;
+        Ok(self)
+    }
+
+    /// Plays a RTTTL (Ring Tone Text Transfer Language) melody.
+    /// This runs in a task and can be stopped by calling [`Piezo::stop()`].
+    ///
+    /// # Errors
+    /// * `UnknownError`: this function will bail an error if the melody cannot be parsed.
+    /// * `NotSupported`: this function will bail an error if a note is above
+    ///   [`Piezo::MAX_TOGGLE_FREQUENCY`] while the pin does not support TONE mode.
+    pub fn play_rtttl(&mut self, rtttl: &str) -> Result<&Self, Error> {
+        let (notes, tempo) = parse_rtttl(rtttl)?;
+        self.play(notes, tempo)
+    }
+
+    // ########################################
+    // Getters.
+
+    /// Returns the pin (id) used by the device.
+    pub fn get_pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Returns the [`Pin`] information.
+    pub fn get_pin_info(&self) -> Result<Pin, Error> {
+        let lock = self.protocol.get_io().read();
+        Ok(lock.get_pin(self.pin)?.clone())
+    }
+
+    /// Indicates if the tone is generated by the board firmware (TONE mode) rather than by toggling
+    /// a digital output.
+    pub fn is_tone_mode(&self) -> bool {
+        self.tone_mode
+    }
+
+    /// Indicates if the piezo is currently playing a sound.
+    pub fn is_playing(&self) -> bool {
+        self.state.read().gt(&0)
+    }
+
+    // ########################################
+    // Internals.
+
+    /// Internal only: checks the frequency can be played.
+    fn check_frequency(&self, frequency: u16) -> Result<(), Error> {
+        if !self.tone_mode && frequency > Self::MAX_TOGGLE_FREQUENCY {
+            return Err(Error::from(NotSupported {
+                context: "play a frequency above Piezo::MAX_TOGGLE_FREQUENCY without TONE mode",
+            }));
+        }
+        Ok(())
+    }
+
+    /// Internal only: plays a sequence of (frequency, duration) in a task (a `None` duration lasts
+    /// until stopped).
+    fn run(&mut self, sequence: Vec<(u16, Option<u64>)>) -> Result<(), Error> {
+        self.abort();
+        let mut self_clone = self.clone();
+        *self.handler.write() = Some(task::run(async move {
+            for (frequency, duration) in sequence {
+                self_clone.emit(frequency, duration).await?;
+            }
+            self_clone.silence()
+        })?);
+        Ok(())
+    }
+
+    /// Internal only: emits a frequency for a duration (a `None` duration lasts until stopped).
+    async fn emit(&mut self, frequency: u16, duration: Option<u64>) -> Result<(), Error> {
+        let end = duration.map(|duration| Instant::now() + Duration::from_millis(duration));
+        match (frequency, self.tone_mode) {
+            (0, _) => self.silence()?,
+            (_, true) => {
+                self.protocol.tone(self.pin, frequency, 0)?;
+                *self.state.write() = frequency;
+            }
+            (_, false) => {
+                *self.state.write() = frequency;
+                // The runtime timers are too coarse (1ms) for audio frequencies: the pin is toggled from
+                // the blocking thread pool, until this task completes or is aborted.
+                let toggling = Toggling::default();
+                let running = toggling.0.clone();
+                let mut self_clone = self.clone();
+                let end = end.map(Instant::into_std);
+                return tokio::task::spawn_blocking(move || {
+                    self_clone.toggle(frequency, end, &running)
+                })
+                .await
+                .unwrap_or_else(|error| {
+                    Err(UnknownError {
+                        info: error.to_string(),
+                    })
+                });
+            }
+        }
+        match end {
+            None => std::future::pending().await,
+            Some(end) => tokio::time::sleep_until(end).await,
+        }
+        Ok(())
+    }
+
+    /// Internal only: toggles the pin at the given frequency, until the end (if any) or until stopped.
+    fn toggle(
+        &mut self,
+        frequency: u16,
+        end: Option<std::time::Instant>,
+        running: &AtomicBool,
+    ) -> Result<(), Error> {
+        let half_period = Duration::from_nanos(500_000_000 / frequency as u64);
+        let mut level = false;
+        let mut edge = std::time::Instant::now();
+        while running.load(Ordering::SeqCst) && end.is_none_or(|end| edge < end) {
+            level = !level;
+            self.protocol.digital_write(self.pin, level)?;
+            // Edges are scheduled from the start: the time spent writing does not drift the frequency.
+            edge += half_period;
+            std::thread::sleep(edge.saturating_duration_since(std::time::Instant::now()));
+        }
+        // Never leave the pin high: it may have been toggled right after being silenced.
+        if level {
+            self.protocol.digital_write(self.pin, false)?;
+        }
+        Ok(())
+    }
+
+    /// Internal only: silences the piezo.
+    fn silence(&mut self) -> Result<(), Error> {
+        match self.tone_mode {
+            true => self.protocol.tone(self.pin, 0, 0)?,
+            false => self.protocol.digital_write(self.pin, false)?,
+        }
+        *self.state.write() = 0;
+        Ok(())
+    }
+
+    /// Internal only: aborts the task playing the sound, if any.
+    fn abort(&mut self) {
+        let handler = self.handler.write().take();
+        if let Some(handler) = handler {
+            handler.abort();
+        }
+    }
+}
+
+/// Keeps a pin toggling while alive: dropped when the task playing the sound ends or is aborted.
+struct Toggling(Arc<AtomicBool>);
+
+impl Default for Toggling {
+    fn default() -> Self {
+        Self(Arc::new(AtomicBool::new(true)))
+    }
+}
+
+impl Drop for Toggling {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Display for Piezo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Piezo (pin={}) [state={}, tone_mode={}]",
+            self.pin,
+            self.state.read(),
+            self.tone_mode
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Device for Piezo {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Output for Piezo {
+    /// Returns  the actuator current state.
+    fn get_state(&self) -> State {
+        (*self.state.read()).into()
+    }
+
+    /// Internal only: you should rather use [`Self::frequency()`], [`Self::play()`] functions.
+    fn set_state(&mut self, state: State) -> Result<State, Error> {
+        let frequency = match state {
+            State::Boolean(false) => Ok(0),
+            State::Integer(value) => Ok(value.min(u16::MAX as u64) as u16),
+            State::Float(value) => Ok(value as u16),
+            State::Signed(value) => Ok(value.clamp(0, u16::MAX as i64) as u16),
+            _ => Err(StateError),
+        }?;
+
+        self.check_frequency(frequency)?;
+        self.abort();
+        match (frequency, self.tone_mode) {
+            (0, _) => self.silence()?,
+            (_, true) => {
+                self.protocol.tone(self.pin, frequency, 0)?;
+                *self.state.write() = frequency;
+            }
+            // The pin has to be toggled continuously.
+            (_, false) => {
+                self.run(vec![(frequency, None)])?;
+                *self.state.write() = frequency;
+            }
+        }
+        Ok(frequency.into())
+    }
+    fn get_default(&self) -> State {
+        State::Integer(0)
+    }
+    fn animate<S: Into<State>>(&mut self, state: S, duration: u64, transition: Easing) {
+        let mut animation = Animation::from(
+            Track::new(self.clone())
+                .with_keyframe(Keyframe::new(state, 0, duration).set_transition(transition)),
+        );
+        animation.play();
+        *self.animation.write() = Some(animation);
+    }
+    fn is_busy(&self) -> bool {
+        self.animation.read().is_some()
+            || self
+                .handler
+                .read()
+                .as_ref()
+                .is_some_and(|handler| !handler.is_finished())
+    }
+    fn stop(&mut self) {
+        if let Some(mut animation) = self.animation.write().take() {
+            animation.stop();
+        }
+        self.abort();
+        let _ = self.silence();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hardware::{Board, PCA9685};
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    use super::*;
+
+    /// Creates a board where the pin 8 supports the TONE mode.
+    fn _setup_tone_board() -> Board {
+        Board::new(MockIoProtocol::with_mode([8], PinModeId::TONE))
+    }
+
+    #[test]
+    fn test_note() {
+        assert_eq!(Note::new(440, 1.0), Note::from_name("A4", 1.0).unwrap());
+        assert_eq!(Note::from_name("C4", 0.5).unwrap(), Note::new(262, 0.5));
+        assert_eq!(Note::from_name("c#4", 1.0).unwrap().frequency, 277);
+        assert_eq!(Note::from_name("Db4", 1.0).unwrap().frequency, 277);
+        assert_eq!(Note::from_name("A5", 1.0).unwrap().frequency, 880);
+        assert_eq!(Note::from_name("R", 2.0).unwrap(), Note::rest(2.0));
+        assert!(Note::from_name("X4", 1.0).is_none());
+        assert!(Note::from_name("A", 1.0).is_none());
+        assert!(Note::from_name("A9", 1.0).is_none());
+        assert!(Note::from_name("", 1.0).is_none());
+    }
+
+    #[test]
+    fn test_parse_rtttl() {
+        let (notes, tempo) = parse_rtttl("Test:d=4,o=5,b=100:8c,c#6,2p,e.,16g7.").unwrap();
+        assert_eq!(tempo, 100);
+        assert_eq!(
+            notes,
+            vec![
+                Note::new(523, 0.5),
+                Note::new(1109, 1.0),
+                Note::rest(2.0),
+                Note::new(659, 1.5),
+                Note::new(3136, 0.375),
+            ]
+        );
+
+        // Default settings.
+        let (notes, tempo) = parse_rtttl("::a, 8h").unwrap();
+        assert_eq!(tempo, 63);
+        assert_eq!(notes, vec![Note::new(1760, 1.0), Note::new(1976, 0.5)]);
+
+        assert!(parse_rtttl("no settings").is_err());
+        assert!(parse_rtttl("Test:x=4:c").is_err());
+        assert!(parse_rtttl("Test:d=0:c").is_err());
+        assert!(parse_rtttl("Test:b=fast:c").is_err());
+        assert!(parse_rtttl("Test:d=4:0c").is_err());
+        assert!(parse_rtttl("Test:d=4:x").is_err());
+    }
+
+    #[test]
+    fn test_piezo_creation() {
+        let board = Board::new(MockIoProtocol::default());
+        let piezo = Piezo::new(&board, 13).unwrap();
+        assert_eq!(piezo.get_pin(), 13);
+        assert_eq!(piezo.get_pins(), vec![13]);
+        assert!(!piezo.is_tone_mode());
+        assert!(!piezo.is_playing());
+        assert_eq!(piezo.get_pin_info().unwrap().mode.id, PinModeId::OUTPUT);
+
+        let board = _setup_tone_board();
+        let piezo = Piezo::new(&board, 8).unwrap();
+        assert!(piezo.is_tone_mode());
+        assert_eq!(piezo.get_pin_info().unwrap().mode.id, PinModeId::TONE);
+
+        // Incompatible pin.
+        assert!(Piezo::new(&board, 0).is_err());
+        // Pin in use.
+        assert!(Piezo::new(&board, 8).is_err());
+        // The PCA9685 does not support tones: the output is toggled instead.
+        let pca9685 = PCA9685::default(&board).unwrap();
+        assert!(!Piezo::new(&pca9685, 0).unwrap().is_tone_mode());
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_tone_mode() {
+        let board = _setup_tone_board();
+        let mut piezo = Piezo::new(&board, 8).unwrap();
+
+        assert!(piezo.set_state(State::Integer(440)).is_ok());
+        assert_eq!(piezo.get_state().as_integer(), 440);
+        assert_eq!(piezo.get_pin_info().unwrap().value, 440);
+        assert!(!piezo.is_busy());
+        assert!(piezo.set_state(State::Boolean(false)).is_ok());
+        assert_eq!(piezo.get_pin_info().unwrap().value, 0);
+        assert!(piezo.set_state(State::Boolean(true)).is_err());
+
+        piezo.frequency(880, 100).unwrap();
+        pause!(50);
+        assert!(piezo.is_busy());
+        assert!(piezo.is_playing());
+        assert_eq!(piezo.get_pin_info().unwrap().value, 880);
+        pause!(100);
+        assert!(!piezo.is_busy());
+        assert!(!piezo.is_playing());
+        assert_eq!(piezo.get_pin_info().unwrap().value, 0);
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_digital_fallback() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut piezo = Piezo::new(&board, 13).unwrap();
+
+        piezo.set_state(State::Integer(100)).unwrap();
+        assert!(piezo.is_busy());
+        assert!(piezo.is_playing());
+        // The pin is being toggled.
+        let mut values = vec![];
+        for _ in 0..10 {
+            values.push(piezo.get_pin_info().unwrap().value);
+            pause!(2);
+        }
+        assert!(values.contains(&0) && values.contains(&1));
+
+        piezo.stop();
+        pause!(20);
+        assert!(!piezo.is_busy());
+        assert!(!piezo.is_playing());
+        assert_eq!(piezo.get_pin_info().unwrap().value, 0);
+
+        // Audio frequencies are toggled as well (not limited by the runtime timers resolution).
+        piezo.frequency(880, 50).unwrap();
+        pause!(10);
+        assert!(piezo.is_playing());
+        pause!(100);
+        assert!(!piezo.is_busy());
+        assert_eq!(piezo.get_pin_info().unwrap().value, 0);
+
+        // Above the limit, a TONE pin is required.
+        assert_eq!(
+            piezo.frequency(2000, 50).unwrap_err().to_string(),
+            "Hardware error: Operation not supported by the hardware - play a frequency above Piezo::MAX_TOGGLE_FREQUENCY without TONE mode."
+        );
+        assert!(piezo.set_state(State::Integer(2000)).is_err());
+        assert!(piezo
+            .play(vec![Note::new(440, 1.0), Note::new(1760, 1.0)], 120)
+            .is_err());
+        assert!(!piezo.is_busy());
+
+        let board = _setup_tone_board();
+        let mut piezo = Piezo::new(&board, 8).unwrap();
+        assert!(piezo.frequency(2000, 50).is_ok());
+        piezo.stop();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_play() {
+        let board = _setup_tone_board();
+        let mut piezo = Piezo::new(&board, 8).unwrap();
+
+        // 600 bpm: 100ms per beat.
+        piezo
+            .play(
+                vec![Note::new(440, 1.0), Note::rest(1.0), Note::new(880, 1.0)],
+                600,
+            )
+            .unwrap();
+        pause!(50);
+        assert_eq!(piezo.get_state().as_integer(), 440);
+        pause!(100);
+        assert_eq!(piezo.get_state().as_integer(), 0);
+        pause!(100);
+        assert_eq!(piezo.get_state().as_integer(), 880);
+        piezo.stop();
+        assert!(!piezo.is_busy());
+        assert_eq!(piezo.get_state().as_integer(), 0);
+
+        assert!(piezo.play_rtttl("Test:d=4,o=5,b=600:c,e,g").is_ok());
+        pause!(50);
+        assert_eq!(piezo.get_state().as_integer(), 523);
+        piezo.stop();
+        assert!(piezo.play_rtttl("invalid").is_err());
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_animation() {
+        let board = _setup_tone_board();
+        let mut piezo = Piezo::new(&board, 8).unwrap();
+        assert!(!piezo.is_busy());
+        piezo.animate(1000u16, 500, Easing::Linear);
+        pause!(100);
+        assert!(piezo.is_busy());
+        assert!(piezo.is_playing());
+        piezo.stop();
+        assert!(!piezo.is_busy());
+        assert!(!piezo.is_playing());
+    }
+
+    #[test]
+    fn test_display_impl() {
+        let board = Board::new(MockIoProtocol::default());
+        let piezo = Piezo::new(&board, 13).unwrap();
+        assert_eq!(
+            format!("{}", piezo),
+            "Piezo (pin=13) [state=0, tone_mode=false]"
+        );
+    }
+}
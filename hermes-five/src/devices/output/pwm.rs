@@ -8,7 +8,7 @@ use crate::devices::{Device, Output};
 use crate::errors::HardwareError::IncompatiblePin;
 use crate::errors::{Error, StateError};
 use crate::hardware::Hardware;
-use crate::io::{IoProtocol, Pin, PinIdOrName, PinModeId};
+use crate::io::{IoProtocol, Pin, PinClaim, PinIdOrName, PinModeId};
 use crate::utils::State;
 
 /// Represents an analog actuator of unspecified type: an [`Output`] [`Device`] that write analog values from a PWM compatible pin.
@@ -34,9 +34,12 @@ pub struct PwmOutput {
     /// The protocol used by the board to communicate with the device.
     #[cfg_attr(feature = "serde", serde(skip))]
     protocol: Box<dyn IoProtocol>,
-    /// Inner handler to the task running the animation.
+    /// The ownership of the pin (shared by all clones: released when the last one is dropped).
     #[cfg_attr(feature = "serde", serde(skip))]
-    animation: Arc<Option<Animation>>,
+    _claim: PinClaim,
+    /// Inner handler to the task running the animation (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    animation: Arc<RwLock<Option<Animation>>>,
 }
 
 impl PwmOutput {
@@ -45,6 +48,7 @@ impl PwmOutput {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the pin does not support PWM mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new<T: Into<PinIdOrName>>(
         board: &dyn Hardware,
         pin: T,
@@ -58,7 +62,8 @@ impl PwmOutput {
             default,
             max_value: 0,
             protocol: board.get_protocol(),
-            animation: Arc::new(None),
+            _claim: PinClaim::new(board.get_io(), vec![pin.id], "PwmOutput")?,
+            animation: Arc::new(RwLock::new(None)),
         };
 
         // Set pin mode to PWM.
@@ -132,7 +137,11 @@ impl Display for PwmOutput {
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
-impl Device for PwmOutput {}
+impl Device for PwmOutput {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Output for PwmOutput {
@@ -175,16 +184,15 @@ impl Output for PwmOutput {
                 .with_keyframe(Keyframe::new(state, 0, duration).set_transition(transition)),
         );
         animation.play();
-        self.animation = Arc::new(Some(animation));
+        *self.animation.write() = Some(animation);
     }
     fn is_busy(&self) -> bool {
-        self.animation.is_some()
+        self.animation.read().is_some()
     }
     fn stop(&mut self) {
-        if let Some(animation) = Arc::get_mut(&mut self.animation).and_then(Option::as_mut) {
+        if let Some(mut animation) = self.animation.write().take() {
             animation.stop();
         }
-        self.animation = Arc::new(None);
     }
 }
 
@@ -192,7 +200,7 @@ impl Output for PwmOutput {
 mod tests {
     use crate::animations::Easing;
     use crate::devices::output::pwm::PwmOutput;
-    use crate::devices::Output;
+    use crate::devices::{Device, Output};
     use crate::hardware::Board;
     use crate::io::PinModeId;
     use crate::mocks::plugin_io::MockIoProtocol;
@@ -206,11 +214,14 @@ mod tests {
         // Default LOW state.
         let output = PwmOutput::new(&board, 8, 0).unwrap();
         assert_eq!(output.get_pin(), 8);
+        assert_eq!(output.get_pins(), vec![8]);
         assert_eq!(*output.state.read(), 0);
         assert_eq!(output.get_state().as_integer(), 0);
         assert_eq!(output.get_default().as_integer(), 0);
 
-        // Default HIGH state.
+        // Default HIGH state (once the pin has been released).
+        assert!(PwmOutput::new(&board, 8, 50).is_err());
+        drop(output);
         let output = PwmOutput::new(&board, 8, 50).unwrap();
         assert_eq!(output.get_pin(), 8);
         assert_eq!(*output.state.read(), 50);
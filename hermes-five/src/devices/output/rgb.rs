@@ -0,0 +1,655 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+use crate::animations::{Animation, Easing, Keyframe, Segment, Track};
+use crate::devices::{Device, Output};
+use crate::errors::HardwareError::IncompatiblePin;
+use crate::errors::{Error, StateError};
+use crate::hardware::Hardware;
+use crate::io::{IoProtocol, PinClaim, PinModeId};
+use crate::utils::{Scalable, State};
+
+/// Represents a RGB LED controlled by three PWM pins (red, green and blue).
+///
+/// The LED color is represented by a `State::Array` of the three channels values (0-255 each): it
+/// can also be set using a `State::Object` (`{"red": 255, "green": 0, "blue": 0}`) or an hex string
+/// (`"#FF0000"`).
+/// The three pins must support PWM: they can be board pins or [`PCA9685`](crate::hardware::PCA9685) channels.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct RgbLed {
+    // ########################################
+    // # Basics
+    /// The pins (id) of the [`Board`] used to control the LED (red, green, blue).
+    pins: [u8; 3],
+    /// The current LED color.
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    state: Arc<RwLock<[u8; 3]>>,
+    /// The LED default color (default: [0, 0, 0] - OFF).
+    default: [u8; 3],
+
+    // ########################################
+    // # Settings
+    /// The color used when the LED is turned ON (default: [255, 255, 255] - white).
+    color: [u8; 3],
+    /// The LED brightness in percentage (0-100%).
+    brightness: u8,
+    /// Indicates if the LED is a common anode one (the pins outputs are inverted).
+    common_anode: bool,
+
+    // ########################################
+    // # Volatile utility data.
+    /// Caches the max output value of each pin depending on resolution.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    max_values: [u16; 3],
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pins (shared by all clones: released when the last one is dropped).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _claim: PinClaim,
+    /// Inner handler to the task running the animation (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    animation: Arc<RwLock<Option<Animation>>>,
+}
+
+impl RgbLed {
+    /// Creates an instance of a common cathode RGB LED attached to a given board.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if a pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if a pin does not support PWM mode.
+    /// * `PinInUse`: this function will bail an error if a pin is already used by another device.
+    pub fn new(board: &dyn Hardware, pins: [u8; 3]) -> Result<Self, Error> {
+        Self::create(board, pins, false)
+    }
+
+    /// Creates an instance of a common anode RGB LED attached to a given board.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if a pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if a pin does not support PWM mode.
+    /// * `PinInUse`: this function will bail an error if a pin is already used by another device.
+    pub fn new_common_anode(board: &dyn Hardware, pins: [u8; 3]) -> Result<Self, Error> {
+        Self::create(board, pins, true)
+    }
+
+    fn create(board: &dyn Hardware, pins: [u8; 3], common_anode: bool) -> Result<Self, Error> {
+        let mut protocol = board.get_protocol();
+
+        // Retrieve the PWM max value of each pin.
+        let mut max_values = [0; 3];
+        {
+            let hardware = protocol.get_io().read();
+            for (index, pin) in pins.iter().enumerate() {
+                let pwm_mode = hardware
+                    .get_pin(*pin)?
+                    .supports_mode(PinModeId::PWM)
+                    .ok_or(IncompatiblePin {
+                        mode: PinModeId::PWM,
+                        pin: *pin,
+                        context: "create RGB LED",
+                    })?;
+                max_values[index] = pwm_mode.get_max_possible_value();
+            }
+        }
+
+        // Claim the pins ownership.
+        let claim = PinClaim::new(protocol.get_io(), pins.to_vec(), "RgbLed")?;
+
+        for pin in pins {
+            protocol.set_pin_mode(pin, PinModeId::PWM)?;
+        }
+
+        let mut led = Self {
+            pins,
+            state: Arc::new(RwLock::new([0; 3])),
+            default: [0; 3],
+            color: [0xFF; 3],
+            brightness: 100,
+            common_anode,
+            max_values,
+            protocol,
+            _claim: claim,
+            animation: Arc::new(RwLock::new(None)),
+        };
+
+        led.reset()?;
+
+        Ok(led)
+    }
+
+    /// Turns the LED on (using the last color set).
+    pub fn turn_on(&mut self) -> Result<&Self, Error> {
+        self.set_state(State::Boolean(true))?;
+        Ok(self)
+    }
+
+    /// Turns the LED off.
+    pub fn turn_off(&mut self) -> Result<&Self, Error> {
+        self.set_state(State::Boolean(false))?;
+        Ok(self)
+    }
+
+    /// Toggles the current state, if on then turn off, if off then turn on.
+    pub fn toggle(&mut self) -> Result<&Self, Error> {
+        match self.is_on() {
+            true => self.turn_off(),
+            false => self.turn_on(),
+        }
+    }
+
+    /// Sets the LED color (red, green, blue: 0-255 each) and turns it on.
+    pub fn set_color(&mut self, color: [u8; 3]) -> Result<&Self, Error> {
+        self.color = color;
+        self.set_state(color.to_vec().into())?;
+        Ok(self)
+    }
+
+    /// Sets the LED color from a hex string (`#RRGGBB`, `RRGGBB`, `#RGB` or `RGB`) and turns it on.
+    ///
+    /// # Errors
+    /// * `StateError`: this function will bail an error if the string is not a valid hex color.
+    pub fn set_hex(&mut self, hex: &str) -> Result<&Self, Error> {
+        let color = hex_to_rgb(hex).ok_or(StateError)?;
+        self.set_color(color)
+    }
+
+    /// Sets the LED color from HSV values and turns it on.
+    ///
+    /// # Arguments
+    /// * `hue`: the hue in degrees (0-360)
+    /// * `saturation`: the saturation in percent (0-100)
+    /// * `value`: the value in percent (0-100)
+    pub fn set_hsv(&mut self, hue: u16, saturation: u8, value: u8) -> Result<&Self, Error> {
+        self.set_color(hsv_to_rgb(hue, saturation, value))
+    }
+
+    /// Blinks the LED on/off in phases of milliseconds duration.
+    /// This is an animation and can be stopped by calling [`RgbLed::stop()`].
+    pub fn blink(&mut self, ms: u64) -> &Self {
+        let mut animation = Animation::from(
+            Segment::from(
+                Track::new(self.clone())
+                    .with_keyframe(Keyframe::new(true, 0, ms))
+                    .with_keyframe(Keyframe::new(false, ms, ms * 2)),
+            )
+            .set_repeat(true),
+        );
+        animation.play();
+        *self.animation.write() = Some(animation);
+
+        self
+    }
+
+    /// Pulses the LED on/off (using fading) in phases of ms (milliseconds) duration.
+    /// This is an animation and can be stopped by calling [`RgbLed::stop()`].
+    pub fn pulse(&mut self, ms: u64) -> &Self {
+        let mut animation = Animation::from(
+            Segment::from(
+                Track::new(self.clone())
+                    .with_keyframe(Keyframe::new(self.color.to_vec(), 0, ms))
+                    .with_keyframe(Keyframe::new(vec![0u8; 3], ms, ms * 2)),
+            )
+            .set_repeat(true),
+        );
+        animation.play();
+        *self.animation.write() = Some(animation);
+
+        self
+    }
+
+    // ########################################
+    // Getters.
+
+    /// Returns the current LED color (red, green, blue).
+    pub fn get_color(&self) -> [u8; 3] {
+        *self.state.read()
+    }
+
+    /// Returns the current LED color as a hex string (`#RRGGBB`).
+    pub fn get_hex(&self) -> String {
+        rgb_to_hex(*self.state.read())
+    }
+
+    /// Returns the LED brightness in percentage (0-100%).
+    pub fn get_brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Set the LED brightness (integer between 0-100) in percent of the max brightness. If a number
+    /// higher than 100 is used, the brightness is set to 100%.
+    pub fn set_brightness(mut self, brightness: u8) -> Result<Self, Error> {
+        self.brightness = brightness.clamp(0, 100);
+        // Refresh the output with the new brightness.
+        let color = *self.state.read();
+        self.set_state(color.to_vec().into())?;
+        Ok(self)
+    }
+
+    /// Indicates if the LED is a common anode one.
+    pub fn is_common_anode(&self) -> bool {
+        self.common_anode
+    }
+
+    /// Indicates if the LED is current ON (regardless its color and brightness).
+    pub fn is_on(&self) -> bool {
+        self.state.read().iter().any(|channel| *channel > 0)
+    }
+
+    /// Indicates if the LED is current OFF.
+    pub fn is_off(&self) -> bool {
+        !self.is_on()
+    }
+
+    /// Internal only: converts a state to the corresponding color.
+    fn to_color(&self, state: &State) -> Result<[u8; 3], Error> {
+        let channel = |value: &State| value.as_float().round().clamp(0.0, 255.0) as u8;
+        match state {
+            State::Boolean(true) => Ok(self.color),
+            State::Boolean(false) => Ok([0; 3]),
+            State::Array(values) if values.len() == 3 => Ok([
+                channel(&values[0]),
+                channel(&values[1]),
+                channel(&values[2]),
+            ]),
+            State::Object(values) => {
+                let get = |long: &str, short: &str| {
+                    values
+                        .get(long)
+                        .or(values.get(short))
+                        .map(channel)
+                        .unwrap_or(0)
+                };
+                Ok([get("red", "r"), get("green", "g"), get("blue", "b")])
+            }
+            State::String(hex) => hex_to_rgb(hex).ok_or(StateError),
+            _ => Err(StateError),
+        }
+    }
+}
+
+/// Converts a hex string (`#RRGGBB`, `RRGGBB`, `#RGB` or `RGB`) to a color.
+fn hex_to_rgb(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.trim().trim_start_matches('#');
+    if !hex.is_ascii() {
+        return None;
+    }
+    let expanded = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |index: usize| u8::from_str_radix(&expanded[index..index + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Converts a color to a hex string (`#RRGGBB`).
+fn rgb_to_hex(color: [u8; 3]) -> String {
+    format!("#{:02X}{:02X}{:02X}", color[0], color[1], color[2])
+}
+
+/// Converts HSV values (hue: 0-360, saturation: 0-100, value: 0-100) to a color.
+fn hsv_to_rgb(hue: u16, saturation: u8, value: u8) -> [u8; 3] {
+    let hue = (hue % 360) as f64;
+    let saturation = saturation.clamp(0, 100) as f64 / 100.0;
+    let value = value.clamp(0, 100) as f64 / 100.0;
+
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = value - chroma;
+
+    let (r, g, b) = match hue as u16 {
+        0..60 => (chroma, x, 0.0),
+        60..120 => (x, chroma, 0.0),
+        120..180 => (0.0, chroma, x),
+        180..240 => (0.0, x, chroma),
+        240..300 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let channel = |value: f64| ((value + m) * 255.0).round() as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+impl Display for RgbLed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "RGB LED (pins={:?}) [state={}, default={}, brightness={}%]",
+            self.pins,
+            rgb_to_hex(*self.state.read()),
+            rgb_to_hex(self.default),
+            self.brightness
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Device for RgbLed {
+    fn get_pins(&self) -> Vec<u8> {
+        self.pins.to_vec()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Output for RgbLed {
+    /// Returns  the actuator current state.
+    fn get_state(&self) -> State {
+        self.state.read().to_vec().into()
+    }
+
+    /// Internal only: you should rather use [`Self::turn_on()`], [`Self::turn_off()`], [`Self::set_color()`] functions.
+    fn set_state(&mut self, state: State) -> Result<State, Error> {
+        let color = self.to_color(&state)?;
+
+        for ((pin, max), channel) in self.pins.iter().zip(self.max_values).zip(color) {
+            let high: u16 = self.brightness.scale(0, 100, 0, max);
+            let value: u16 = channel.scale(0, 255, 0, high);
+            let value = match self.common_anode {
+                true => max - value,
+                false => value,
+            };
+            self.protocol.analog_write(*pin, value)?;
+        }
+
+        *self.state.write() = color;
+        Ok(color.to_vec().into())
+    }
+    fn get_default(&self) -> State {
+        self.default.to_vec().into()
+    }
+    fn animate<S: Into<State>>(&mut self, state: S, duration: u64, transition: Easing) {
+        let mut animation = Animation::from(
+            Track::new(self.clone())
+                .with_keyframe(Keyframe::new(state, 0, duration).set_transition(transition)),
+        );
+        animation.play();
+        *self.animation.write() = Some(animation);
+    }
+    fn is_busy(&self) -> bool {
+        self.animation.read().is_some()
+    }
+    fn stop(&mut self) {
+        if let Some(mut animation) = self.animation.write().take() {
+            animation.stop();
+        }
+    }
+    /// Internal only: interpolates each color channel between the previous and target colors.
+    fn scale_state(&mut self, previous: State, target: State, progress: f32) -> State {
+        // Booleans (blink) are not interpolated.
+        if let State::Boolean(_) = target {
+            return match progress {
+                0.0 => previous,
+                _ => target,
+            };
+        }
+        match (self.to_color(&previous), self.to_color(&target)) {
+            (Ok(from), Ok(to)) => (0..3)
+                .map(|index| progress.scale::<u8>(0, 1, from[index], to[index]))
+                .collect(),
+            _ => match progress {
+                0.0 => previous,
+                _ => target,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::devices::Led;
+    use crate::hardware::{Board, PCA9685};
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    use super::*;
+
+    fn _setup_rgb_led() -> RgbLed {
+        let board = Board::new(MockIoProtocol::default());
+        let pca9685 = PCA9685::default(&board).unwrap();
+        RgbLed::new(&pca9685, [0, 1, 2]).unwrap()
+    }
+
+    #[test]
+    fn test_rgb_led_creation() {
+        let led = _setup_rgb_led();
+        assert_eq!(led.get_pins(), vec![0, 1, 2]);
+        assert_eq!(*led.state.read(), [0, 0, 0]);
+        assert_eq!(led.color, [255, 255, 255]);
+        assert_eq!(led.get_brightness(), 100);
+        assert!(!led.is_common_anode());
+        assert_eq!(led.max_values, [4095, 4095, 4095]);
+    }
+
+    #[test]
+    fn test_rgb_led_incompatible_pin() {
+        let board = Board::new(MockIoProtocol::default());
+        // Pin 13 does not support PWM.
+        let led = RgbLed::new(&board, [8, 11, 13]);
+        assert!(led.is_err());
+        // Pins are not claimed on failure.
+        assert!(Led::new(&board, 8, false).is_ok());
+    }
+
+    #[test]
+    fn test_rgb_led_pins_in_use() {
+        let board = Board::new(MockIoProtocol::default());
+        let pca9685 = PCA9685::default(&board).unwrap();
+        let led = RgbLed::new(&pca9685, [0, 1, 2]).unwrap();
+        assert!(RgbLed::new(&pca9685, [2, 3, 4]).is_err());
+        drop(led);
+        assert!(RgbLed::new(&pca9685, [2, 3, 4]).is_ok());
+    }
+
+    #[test]
+    fn test_turn_on_off_toggle() {
+        let mut led = _setup_rgb_led();
+        assert!(led.is_off());
+        assert!(led.turn_on().is_ok());
+        assert!(led.is_on());
+        assert_eq!(led.get_color(), [255, 255, 255]);
+        assert!(led.turn_off().is_ok());
+        assert!(led.is_off());
+        assert_eq!(led.get_color(), [0, 0, 0]);
+        assert!(led.toggle().is_ok());
+        assert!(led.is_on());
+        assert!(led.toggle().is_ok());
+        assert!(led.is_off());
+    }
+
+    #[test]
+    fn test_set_color() {
+        let mut led = _setup_rgb_led();
+        assert!(led.set_color([255, 128, 0]).is_ok());
+        assert_eq!(led.get_color(), [255, 128, 0]);
+        assert_eq!(led.get_hex(), "#FF8000");
+        assert_eq!(led.get_state(), State::from(vec![255u8, 128, 0]));
+
+        // Turning off then on restores the last color.
+        led.turn_off().unwrap();
+        led.turn_on().unwrap();
+        assert_eq!(led.get_color(), [255, 128, 0]);
+
+        assert!(led.set_hex("#00ff7f").is_ok());
+        assert_eq!(led.get_color(), [0, 255, 127]);
+        assert!(led.set_hex("f0a").is_ok());
+        assert_eq!(led.get_color(), [255, 0, 170]);
+        assert!(led.set_hex("#12345").is_err());
+        assert!(led.set_hex("#zzzzzz").is_err());
+        assert_eq!(led.get_color(), [255, 0, 170]);
+
+        assert!(led.set_hsv(120, 100, 100).is_ok());
+        assert_eq!(led.get_color(), [0, 255, 0]);
+    }
+
+    #[test]
+    fn test_set_state() {
+        let mut led = _setup_rgb_led();
+
+        assert!(led.set_state(vec![10u8, 20, 30].into()).is_ok());
+        assert_eq!(led.get_color(), [10, 20, 30]);
+        assert!(led
+            .set_state(State::Array(vec![
+                State::Float(300.0),
+                State::Signed(-5),
+                State::Integer(40)
+            ]))
+            .is_ok());
+        assert_eq!(led.get_color(), [255, 0, 40]);
+
+        let object = HashMap::from([
+            (String::from("red"), State::Integer(1)),
+            (String::from("g"), State::Integer(2)),
+            (String::from("blue"), State::Integer(3)),
+        ]);
+        assert!(led.set_state(State::Object(object)).is_ok());
+        assert_eq!(led.get_color(), [1, 2, 3]);
+
+        assert!(led
+            .set_state(State::String(String::from("#0000FF")))
+            .is_ok());
+        assert_eq!(led.get_color(), [0, 0, 255]);
+
+        assert!(led.set_state(State::Boolean(true)).is_ok());
+        assert_eq!(led.get_color(), [255, 255, 255]);
+
+        // Incorrect state type.
+        assert!(led.set_state(State::Integer(42)).is_err());
+        assert!(led.set_state(vec![1u8, 2].into()).is_err());
+        assert!(led
+            .set_state(State::String(String::from("incorrect format")))
+            .is_err());
+    }
+
+    #[test]
+    fn test_pins_output() {
+        let board = Board::new(MockIoProtocol::default());
+        let pca9685 = PCA9685::default(&board).unwrap();
+        let get_values = || {
+            let io = pca9685.get_protocol().get_io().read().clone();
+            [0, 1, 2].map(|pin| io.get_pin(pin).unwrap().value)
+        };
+
+        let mut led = RgbLed::new(&pca9685, [0, 1, 2]).unwrap();
+        led.set_color([255, 128, 0]).unwrap();
+        assert_eq!(get_values(), [4095, 2056, 0]);
+
+        let led = led.set_brightness(50).unwrap();
+        assert_eq!(led.get_color(), [255, 128, 0]);
+        assert_eq!(get_values(), [2048, 1028, 0]);
+        let led = led.set_brightness(120).unwrap();
+        assert_eq!(led.get_brightness(), 100);
+        drop(led);
+
+        let mut led = RgbLed::new_common_anode(&pca9685, [0, 1, 2]).unwrap();
+        assert!(led.is_common_anode());
+        assert_eq!(get_values(), [4095, 4095, 4095]);
+        led.set_color([255, 128, 0]).unwrap();
+        assert_eq!(get_values(), [0, 2039, 4095]);
+    }
+
+    #[test]
+    fn test_color_conversions() {
+        assert_eq!(hex_to_rgb("#FFFFFF"), Some([255, 255, 255]));
+        assert_eq!(hex_to_rgb(" 102030 "), Some([16, 32, 48]));
+        assert_eq!(hex_to_rgb("#abc"), Some([170, 187, 204]));
+        assert_eq!(hex_to_rgb("#abcd"), None);
+        assert_eq!(hex_to_rgb("#éabcd"), None);
+        assert_eq!(rgb_to_hex([1, 171, 255]), "#01ABFF");
+
+        assert_eq!(hsv_to_rgb(0, 100, 100), [255, 0, 0]);
+        assert_eq!(hsv_to_rgb(60, 100, 100), [255, 255, 0]);
+        assert_eq!(hsv_to_rgb(180, 100, 100), [0, 255, 255]);
+        assert_eq!(hsv_to_rgb(240, 100, 50), [0, 0, 128]);
+        assert_eq!(hsv_to_rgb(300, 100, 100), [255, 0, 255]);
+        assert_eq!(hsv_to_rgb(360, 0, 100), [255, 255, 255]);
+        assert_eq!(hsv_to_rgb(30, 100, 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_scale_state() {
+        let mut led = _setup_rgb_led();
+
+        let from = State::from(vec![0u8, 100, 255]);
+        let to = State::from(vec![200u8, 0, 255]);
+        assert_eq!(led.scale_state(from.clone(), to.clone(), 0.0), from);
+        assert_eq!(
+            led.scale_state(from.clone(), to.clone(), 0.5),
+            State::from(vec![100u8, 50, 255])
+        );
+        assert_eq!(led.scale_state(from.clone(), to.clone(), 1.0), to);
+        // Hex strings are interpolated too.
+        assert_eq!(
+            led.scale_state(
+                State::String(String::from("#000000")),
+                State::String(String::from("#FF0000")),
+                0.5
+            ),
+            State::from(vec![128u8, 0, 0])
+        );
+        // Booleans are not interpolated.
+        assert_eq!(
+            led.scale_state(State::Boolean(false), State::Boolean(true), 0.5),
+            State::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn test_default_value() {
+        let led = _setup_rgb_led();
+        assert_eq!(led.get_default(), State::from(vec![0u8, 0, 0]));
+        assert_eq!(led.get_state(), led.get_default());
+    }
+
+    #[hermes_five_macros::test]
+    fn test_rgb_led_blink() {
+        let mut led = _setup_rgb_led();
+        assert!(!led.is_busy());
+        led.stop(); // Stop something not started should not fail.
+        led.blink(50);
+        pause!(100);
+        assert!(led.is_busy()); // Animation is currently running.
+        led.stop();
+        assert!(!led.is_busy());
+    }
+
+    #[hermes_five_macros::test]
+    fn test_rgb_led_pulse() {
+        let mut led = _setup_rgb_led();
+        assert!(!led.is_busy());
+        led.pulse(50);
+        pause!(100);
+        assert!(led.is_busy()); // Animation is currently running.
+        led.stop();
+        assert!(!led.is_busy());
+    }
+
+    #[hermes_five_macros::test]
+    fn test_animation() {
+        let mut led = _setup_rgb_led();
+        led.animate(vec![255u8, 0, 0], 100, Easing::Linear);
+        pause!(50);
+        assert!(led.is_busy()); // Animation is currently running.
+        let color = led.get_color();
+        assert!(color[0] > 0 && color[0] < 255);
+        assert_eq!(color[1..], [0, 0]);
+        // Leave the animation time to end, even on a busy machine.
+        pause!(500);
+        assert_eq!(led.get_color(), [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_display_impl() {
+        let mut led = _setup_rgb_led();
+        led.set_color([255, 0, 16]).unwrap();
+        let display_str = format!("{}", led);
+        assert_eq!(
+            display_str,
+            "RGB LED (pins=[0, 1, 2]) [state=#FF0010, default=#000000, brightness=100%]"
+        );
+    }
+}
@@ -9,7 +9,7 @@ use crate::devices::{Device, Output};
 use crate::errors::HardwareError::IncompatiblePin;
 use crate::errors::{Error, StateError};
 use crate::hardware::Hardware;
-use crate::io::{IoProtocol, Pin, PinModeId};
+use crate::io::{IoProtocol, Pin, PinClaim, PinModeId};
 use crate::utils::{task, Range, Scalable, State};
 use crate::{pause, pause_sync};
 
@@ -74,9 +74,12 @@ pub struct Servo {
     previous: u16,
     #[cfg_attr(feature = "serde", serde(skip))]
     protocol: Box<dyn IoProtocol>,
-    /// Inner handler to the task running the animation.
+    /// The ownership of the pin (shared by all clones: released when the last one is dropped).
     #[cfg_attr(feature = "serde", serde(skip))]
-    animation: Arc<Option<Animation>>,
+    _claim: PinClaim,
+    /// Inner handler to the task running the animation (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    animation: Arc<RwLock<Option<Animation>>>,
     #[cfg_attr(feature = "serde", serde(skip))]
     last_move: Arc<RwLock<Option<SystemTime>>>,
 }
@@ -87,6 +90,7 @@ impl Servo {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the pin does not support SERVO mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new(board: &dyn Hardware, pin: u8, default: u16) -> Result<Self, Error> {
         Self::create(board, pin, default, false)
     }
@@ -96,6 +100,7 @@ impl Servo {
     /// # Errors
     /// * `UnknownPin`: this function will bail an error if the pin does not exist for this board.
     /// * `IncompatiblePin`: this function will bail an error if the pin does not support SERVO mode.
+    /// * `PinInUse`: this function will bail an error if the pin is already used by another device.
     pub fn new_inverted(board: &dyn Hardware, pin: u8, default: u16) -> Result<Self, Error> {
         Self::create(board, pin, default, true)
     }
@@ -117,7 +122,8 @@ impl Servo {
             detach_delay: 20000,
             previous: u16::MAX, // Ensure previous out-of-range: forces default at start
             protocol: board.get_protocol(),
-            animation: Arc::new(None),
+            _claim: PinClaim::new(board.get_io(), vec![pin], "Servo")?,
+            animation: Arc::new(RwLock::new(None)),
             last_move: Arc::new(RwLock::new(None)),
         };
 
@@ -163,7 +169,7 @@ impl Servo {
             .set_repeat(true),
         );
         animation.play();
-        self.animation = Arc::new(Some(animation));
+        *self.animation.write() = Some(animation);
 
         self
     }
@@ -347,7 +353,11 @@ impl Display for Servo {
 }
 
 #[cfg_attr(feature = "serde", typetag::serde)]
-impl Device for Servo {}
+impl Device for Servo {
+    fn get_pins(&self) -> Vec<u8> {
+        vec![self.pin]
+    }
+}
 
 #[cfg_attr(feature = "serde", typetag::serde)]
 impl Output for Servo {
@@ -431,23 +441,22 @@ impl Output for Servo {
                 .with_keyframe(Keyframe::new(state, 0, duration).set_transition(transition)),
         );
         animation.play();
-        self.animation = Arc::new(Some(animation));
+        *self.animation.write() = Some(animation);
     }
     fn is_busy(&self) -> bool {
-        self.animation.is_some()
+        self.animation.read().is_some()
     }
     fn stop(&mut self) {
-        if let Some(animation) = Arc::get_mut(&mut self.animation).and_then(Option::as_mut) {
+        if let Some(mut animation) = self.animation.write().take() {
             animation.stop();
         }
-        self.animation = Arc::new(None);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::animations::Easing;
-    use crate::devices::{Output, Servo};
+    use crate::devices::{Device, Output, Servo};
     use crate::hardware::Board;
     use crate::io::PinModeId;
     use crate::mocks::plugin_io::MockIoProtocol;
@@ -466,11 +475,17 @@ mod tests {
 
         let servo = Servo::new(&board, 12, 90).unwrap();
         assert_eq!(servo.get_pin(), 12);
+        assert_eq!(servo.get_pins(), vec![12]);
         assert_eq!(*servo.state.read(), 90);
         assert!(!servo.is_inverted());
 
+        // The pin is in use until the servo is dropped.
+        assert!(Servo::new_inverted(&board, 12, 90).is_err());
+        drop(servo);
+
         let inverted_servo = Servo::new_inverted(&board, 12, 90).unwrap();
         assert!(inverted_servo.is_inverted());
+        drop(inverted_servo);
 
         let servo = Servo::new(&board, 12, 66).unwrap();
         assert_eq!(servo.get_default(), State::Integer(66));
@@ -0,0 +1,550 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::animations::{Animation, Easing, Keyframe, Track};
+use crate::devices::{Device, Output, OutputEvent};
+use crate::errors::HardwareError::{IncompatiblePin, NotSupported};
+use crate::errors::{Error, StateError};
+use crate::hardware::Hardware;
+use crate::io::{IoData, IoProtocol, PinClaim, PinModeId, StepperCommand, StepperConfig};
+use crate::utils::{task, EventHandler, EventManager, State, TaskHandler};
+
+/// The maximum number of steppers a board can drive simultaneously (AccelStepperFirmata limitation).
+const MAX_STEPPERS: u8 = 10;
+
+/// Represents a stepper motor driven by the board through the AccelStepperFirmata feature.
+/// <https://github.com/firmata/protocol/blob/master/accelStepperFirmata.md>
+///
+/// The stepper state is its targeted position (in steps, relative to the zero position): the board
+/// reports the reached position once the move is complete (see [`OutputEvent::OnMoveComplete`]).
+///
+/// # Notes
+/// The firmware running on the board must include the AccelStepperFirmata feature (ex: ConfigurableFirmata).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Stepper {
+    // ########################################
+    // # Basics
+    /// The stepper id on the board (0-9).
+    id: u8,
+    /// The stepper wiring configuration.
+    config: StepperConfig,
+    /// The targeted position (in steps).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    state: Arc<RwLock<i32>>,
+
+    // ########################################
+    // # Settings
+    /// The max speed (in steps per second).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    speed: Arc<RwLock<f32>>,
+    /// The acceleration (in steps per second²: 0 means no acceleration).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    acceleration: Arc<RwLock<f32>>,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    /// The ownership of the pins (shared by all clones: released when the last one is dropped).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _claim: PinClaim,
+    /// The ownership of the stepper id (shared by all clones: released when the last one is dropped).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _slot: StepperSlot,
+    /// Inner handler to the task waiting for the move completion (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+    /// Inner handler to the task running the animation (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    animation: Arc<RwLock<Option<Animation>>>,
+    /// The event manager for the stepper.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: EventManager,
+}
+
+impl Stepper {
+    /// Creates an instance of a stepper attached to a given board.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: this function will bail an error if a pin does not exist for this board.
+    /// * `IncompatiblePin`: this function will bail an error if a pin does not support STEPPER mode.
+    /// * `PinInUse`: this function will bail an error if a pin is already used by another device.
+    /// * `NotSupported`: this function will bail an error if the board already drives 10 steppers,
+    ///   or cannot drive steppers at all.
+    pub fn new(board: &dyn Hardware, config: StepperConfig) -> Result<Self, Error> {
+        let mut protocol = board.get_protocol();
+
+        // Check the pins compatibility.
+        {
+            let hardware = protocol.get_io().read();
+            for pin in config.get_pins() {
+                hardware
+                    .get_pin(pin)?
+                    .supports_mode(PinModeId::STEPPER)
+                    .ok_or(IncompatiblePin {
+                        mode: PinModeId::STEPPER,
+                        pin,
+                        context: "create stepper",
+                    })?;
+            }
+        }
+
+        // Claim the pins ownership and a stepper id.
+        let claim = PinClaim::new(protocol.get_io(), config.get_pins(), "Stepper")?;
+        let slot = StepperSlot::new(protocol.get_io())?;
+
+        protocol.stepper(slot.0.id, StepperCommand::Config(config))?;
+
+        Ok(Self {
+            id: slot.0.id,
+            config,
+            state: Arc::new(RwLock::new(0)),
+            speed: Arc::new(RwLock::new(0.0)),
+            acceleration: Arc::new(RwLock::new(0.0)),
+            protocol,
+            _claim: claim,
+            _slot: slot,
+            handler: Arc::new(RwLock::new(None)),
+            animation: Arc::new(RwLock::new(None)),
+            events: Default::default(),
+        })
+    }
+
+    /// Moves the stepper by the given number of steps (negative values move backward).
+    pub fn step(&mut self, steps: i32) -> Result<&Self, Error> {
+        let target = self.get_position().saturating_add(steps);
+        self.move_with(StepperCommand::Step(steps), target)?;
+        Ok(self)
+    }
+
+    /// Moves the stepper to the given absolute position (in steps, relative to the zero position).
+    pub fn to(&mut self, position: i32) -> Result<&Self, Error> {
+        self.set_state(State::Signed(position as i64))?;
+        Ok(self)
+    }
+
+    /// Sets the current position as the zero position.
+    pub fn zero(&mut self) -> Result<&Self, Error> {
+        self.protocol.stepper(self.id, StepperCommand::Zero)?;
+        *self.state.write() = 0;
+        Ok(self)
+    }
+
+    /// Sets the stepper max speed (in steps per second).
+    pub fn set_speed(&mut self, speed: f32) -> Result<&Self, Error> {
+        self.protocol
+            .stepper(self.id, StepperCommand::Speed(speed))?;
+        *self.speed.write() = speed;
+        Ok(self)
+    }
+
+    /// Sets the stepper acceleration (in steps per second²: 0 means no acceleration).
+    pub fn set_acceleration(&mut self, acceleration: f32) -> Result<&Self, Error> {
+        self.protocol
+            .stepper(self.id, StepperCommand::Acceleration(acceleration))?;
+        *self.acceleration.write() = acceleration;
+        Ok(self)
+    }
+
+    /// Enables or disables the stepper outputs (requires an enable pin in the configuration).
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<&Self, Error> {
+        self.protocol
+            .stepper(self.id, StepperCommand::Enable(enabled))?;
+        Ok(self)
+    }
+
+    // ########################################
+    // Getters.
+
+    /// Returns the stepper id on the board.
+    pub fn get_id(&self) -> u8 {
+        self.id
+    }
+
+    /// Returns the stepper wiring configuration.
+    pub fn get_config(&self) -> StepperConfig {
+        self.config
+    }
+
+    /// Returns the last position (in steps) reported by the board.
+    pub fn get_position(&self) -> i32 {
+        self.protocol
+            .get_io()
+            .read()
+            .steppers
+            .get(&self.id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the targeted position (in steps).
+    pub fn get_target(&self) -> i32 {
+        *self.state.read()
+    }
+
+    /// Returns the stepper max speed (in steps per second).
+    pub fn get_speed(&self) -> f32 {
+        *self.speed.read()
+    }
+
+    /// Returns the stepper acceleration (in steps per second²).
+    pub fn get_acceleration(&self) -> f32 {
+        *self.acceleration.read()
+    }
+
+    /// Indicates if the stepper is currently moving (ie. waiting for the board to report the move completion).
+    pub fn is_moving(&self) -> bool {
+        self.handler
+            .read()
+            .as_ref()
+            .is_some_and(|handler| !handler.is_finished())
+    }
+
+    // ########################################
+    // Event related functions
+
+    /// Registers a callback to be executed on a given event.
+    ///
+    /// Available events for a stepper are:
+    /// * `OnMoveComplete`: Triggered when the stepper has reached its target position.
+    ///   The callback must receive the following parameter: `|position: i32| { ... }`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hermes_five::devices::{OutputEvent, Stepper};
+    /// use hermes_five::hardware::{Board, BoardEvent};
+    /// use hermes_five::io::{StepperConfig, StepperInterface, StepSize};
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let board = Board::run();
+    ///     board.on(BoardEvent::OnReady, |board: Board| async move {
+    ///         let config = StepperConfig {
+    ///             interface: StepperInterface::Driver { step: 2, dir: 3 },
+    ///             step_size: StepSize::Whole,
+    ///             enable_pin: None,
+    ///         };
+    ///         let mut stepper = Stepper::new(&board, config)?;
+    ///         stepper.on(OutputEvent::OnMoveComplete, |position: i32| async move {
+    ///             println!("Stepper reached position {}", position);
+    ///             Ok(())
+    ///         });
+    ///         stepper.set_speed(400.0)?;
+    ///         stepper.to(2000)?;
+    ///         Ok(())
+    ///     });
+    /// }
+    /// ```
+    pub fn on<S, F, T, Fut>(&self, event: S, callback: F) -> EventHandler
+    where
+        S: Into<String>,
+        T: 'static + Send + Sync + Clone,
+        F: FnMut(T) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        self.events.on(event, callback)
+    }
+
+    /// Private helper: sends a move command and waits (in a task) for the board to report its completion.
+    fn move_with(&mut self, command: StepperCommand, target: i32) -> Result<(), Error> {
+        self.abort();
+        *self.state.write() = target;
+
+        // Subscribe before sending the command: the board may report the completion right away.
+        let mut moves = self.protocol.get_io().read().subscribe_stepper_moves();
+        self.protocol.stepper(self.id, command)?;
+
+        let id = self.id;
+        let events = self.events.clone();
+        *self.handler.write() = Some(task::run(async move {
+            loop {
+                match moves.recv().await {
+                    Ok(reply) if reply.id == id => {
+                        events.emit(OutputEvent::OnMoveComplete, reply.position);
+                        break;
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })?);
+        Ok(())
+    }
+
+    /// Private helper: aborts the task waiting for the move completion, if any.
+    fn abort(&self) {
+        if let Some(handler) = self.handler.write().take() {
+            handler.abort();
+        }
+    }
+}
+
+impl Display for Stepper {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Stepper #{} (pins={:?}) [position={}, target={}, speed={}]",
+            self.id,
+            self.get_pins(),
+            self.get_position(),
+            self.state.read(),
+            self.speed.read()
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Device for Stepper {
+    fn get_pins(&self) -> Vec<u8> {
+        self.config.get_pins()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl Output for Stepper {
+    /// Returns  the actuator current state (the targeted position).
+    fn get_state(&self) -> State {
+        State::Signed(*self.state.read() as i64)
+    }
+
+    /// Internal only: you should rather use [`Self::to()`], [`Self::step()`] functions.
+    fn set_state(&mut self, state: State) -> Result<State, Error> {
+        let position = match state {
+            State::Integer(value) => Ok(value.min(i32::MAX as u64) as i32),
+            State::Signed(value) => Ok(value.clamp(i32::MIN as i64, i32::MAX as i64) as i32),
+            State::Float(value) => Ok(value.round() as i32),
+            _ => Err(StateError),
+        }?;
+        self.move_with(StepperCommand::To(position), position)?;
+        Ok(State::Signed(position as i64))
+    }
+    fn get_default(&self) -> State {
+        State::Signed(0)
+    }
+    fn animate<S: Into<State>>(&mut self, state: S, duration: u64, transition: Easing) {
+        let mut animation = Animation::from(
+            Track::new(self.clone())
+                .with_keyframe(Keyframe::new(state, 0, duration).set_transition(transition)),
+        );
+        animation.play();
+        *self.animation.write() = Some(animation);
+    }
+    fn is_busy(&self) -> bool {
+        self.animation.read().is_some() || self.is_moving()
+    }
+    /// Stops the current animation and the stepper (decelerating if an acceleration is set).
+    fn stop(&mut self) {
+        if let Some(mut animation) = self.animation.write().take() {
+            animation.stop();
+        }
+        self.abort();
+        let _ = self.protocol.stepper(self.id, StepperCommand::Stop);
+    }
+}
+
+/// Represents the ownership of a stepper id on the board.
+///
+/// The slot is shared by all clones of the stepper: the id is released when the last one is dropped.
+#[derive(Clone, Debug, Default)]
+struct StepperSlot(Arc<StepperSlotInner>);
+
+#[derive(Debug, Default)]
+struct StepperSlotInner {
+    io: Arc<RwLock<IoData>>,
+    id: u8,
+}
+
+impl StepperSlot {
+    /// Reserves the first stepper id available on the given [`IoData`].
+    fn new(io: &Arc<RwLock<IoData>>) -> Result<Self, Error> {
+        let mut lock = io.write();
+        let id = (0..MAX_STEPPERS)
+            .find(|id| !lock.steppers.contains_key(id))
+            .ok_or(NotSupported {
+                context: "create more than 10 steppers",
+            })?;
+        lock.steppers.insert(id, 0);
+        Ok(Self(Arc::new(StepperSlotInner { io: io.clone(), id })))
+    }
+}
+
+impl Drop for StepperSlotInner {
+    fn drop(&mut self) {
+        self.io.write().steppers.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use crate::hardware::{Board, PCA9685};
+    use crate::io::{StepSize, StepperInterface, IO};
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    use super::*;
+
+    /// Creates a board whose digital pins support the STEPPER mode.
+    fn _create_board() -> Board {
+        Board::new(MockIoProtocol::with_mode(2..=7, PinModeId::STEPPER))
+    }
+
+    fn _driver_config(step: u8, dir: u8) -> StepperConfig {
+        StepperConfig {
+            interface: StepperInterface::Driver { step, dir },
+            step_size: StepSize::Whole,
+            enable_pin: None,
+        }
+    }
+
+    #[test]
+    fn test_stepper_creation() {
+        let board = _create_board();
+        let config = StepperConfig {
+            interface: StepperInterface::FourWire([2, 3, 4, 5]),
+            step_size: StepSize::Half,
+            enable_pin: Some(6),
+        };
+        let stepper = Stepper::new(&board, config).unwrap();
+        assert_eq!(stepper.get_id(), 0);
+        assert_eq!(stepper.get_config(), config);
+        assert_eq!(stepper.get_pins(), vec![2, 3, 4, 5, 6]);
+        assert_eq!(stepper.get_position(), 0);
+        assert_eq!(stepper.get_target(), 0);
+        assert!(!stepper.is_moving());
+        assert_eq!(
+            board.get_io().read().get_pin(2).unwrap().mode.id,
+            PinModeId::STEPPER
+        );
+
+        // Pins already in use.
+        assert!(Stepper::new(&board, _driver_config(6, 7)).is_err());
+        // Incompatible pins.
+        assert!(Stepper::new(&board, _driver_config(7, 8)).is_err());
+        // Unknown pin.
+        assert!(Stepper::new(&board, _driver_config(7, 42)).is_err());
+
+        // Steppers are not supported by the PCA9685.
+        let pca9685 = PCA9685::default(&board).unwrap();
+        assert!(Stepper::new(&pca9685, _driver_config(0, 1)).is_err());
+
+        // A failed creation does not hold a stepper id.
+        assert_eq!(board.get_io().read().steppers.len(), 1);
+    }
+
+    #[test]
+    fn test_stepper_slots() {
+        let board = Board::new(MockIoProtocol::default());
+        let slots: Vec<StepperSlot> = (0..MAX_STEPPERS)
+            .map(|_| StepperSlot::new(board.get_io()).unwrap())
+            .collect();
+        assert_eq!(slots[9].0.id, 9);
+        assert!(StepperSlot::new(board.get_io()).is_err());
+
+        // The id is released when the last clone is dropped.
+        let slot = slots[3].clone();
+        drop(slots);
+        assert_eq!(board.get_io().read().steppers.len(), 1);
+        drop(slot);
+        assert!(board.get_io().read().steppers.is_empty());
+        assert_eq!(StepperSlot::new(board.get_io()).unwrap().0.id, 0);
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_stepper_moves() {
+        let board = _create_board();
+        let mut stepper = Stepper::new(&board, _driver_config(2, 3)).unwrap();
+
+        let completed = Arc::new(AtomicI32::new(0));
+        let moved_completed = completed.clone();
+        stepper.on(OutputEvent::OnMoveComplete, move |position: i32| {
+            let captured_completed = moved_completed.clone();
+            async move {
+                captured_completed.store(position, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        stepper.to(200).unwrap();
+        assert_eq!(stepper.get_target(), 200);
+        pause!(50);
+        assert_eq!(stepper.get_position(), 200);
+        assert_eq!(completed.load(Ordering::SeqCst), 200);
+        assert!(!stepper.is_moving());
+
+        stepper.step(-50).unwrap();
+        assert_eq!(stepper.get_target(), 150);
+        pause!(50);
+        assert_eq!(stepper.get_position(), 150);
+        assert_eq!(completed.load(Ordering::SeqCst), 150);
+
+        stepper.zero().unwrap();
+        assert_eq!(stepper.get_position(), 0);
+        assert_eq!(stepper.get_target(), 0);
+    }
+
+    #[test]
+    fn test_stepper_settings() {
+        let board = _create_board();
+        let mut stepper = Stepper::new(&board, _driver_config(2, 3)).unwrap();
+        stepper.set_speed(400.0).unwrap();
+        stepper.set_acceleration(100.5).unwrap();
+        assert!(stepper.set_enabled(false).is_ok());
+        assert_eq!(stepper.get_speed(), 400.0);
+        assert_eq!(stepper.get_acceleration(), 100.5);
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_set_state() {
+        let board = _create_board();
+        let mut stepper = Stepper::new(&board, _driver_config(2, 3)).unwrap();
+
+        assert_eq!(
+            stepper.set_state(State::Signed(-30)).unwrap(),
+            State::Signed(-30)
+        );
+        assert_eq!(stepper.get_state(), State::Signed(-30));
+        assert_eq!(
+            stepper.set_state(State::Integer(300)).unwrap(),
+            State::Signed(300)
+        );
+        assert_eq!(
+            stepper.set_state(State::Float(-42.6)).unwrap(),
+            State::Signed(-43)
+        );
+        assert!(stepper.set_state(State::Boolean(true)).is_err());
+        assert_eq!(stepper.get_default(), State::Signed(0));
+        assert!(stepper.reset().is_ok());
+        assert_eq!(stepper.get_target(), 0);
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_animation() {
+        let board = _create_board();
+        let mut stepper = Stepper::new(&board, _driver_config(2, 3)).unwrap();
+        stepper.stop(); // Stop something not started should not fail.
+        stepper.animate(State::Signed(1000), 500, Easing::Linear);
+        pause!(100);
+        assert!(stepper.is_busy());
+        assert!(stepper.get_target() > 0);
+        stepper.stop();
+        assert!(!stepper.is_busy());
+    }
+
+    #[test]
+    fn test_display_impl() {
+        let board = _create_board();
+        let stepper = Stepper::new(&board, _driver_config(2, 3)).unwrap();
+        assert_eq!(
+            format!("{}", stepper),
+            "Stepper #0 (pins=[2, 3]) [position=0, target=0, speed=0]"
+        );
+    }
+}
@@ -17,6 +17,12 @@ pub enum Error {
     ProtocolError { source: ProtocolError },
     /// Hardware error: {source}.
     HardwareError { source: HardwareError },
+    /// Configuration error: {file} ({entry}) - {info}.
+    ConfigError {
+        file: String,
+        entry: String,
+        info: String,
+    },
     /// Unknown error: {info}.
     UnknownError { info: String },
 }
@@ -27,6 +33,9 @@ impl From<std::io::Error> for Error {
         let info = match error.kind() {
             std::io::ErrorKind::NotFound => String::from("Board not found or already in use"),
             std::io::ErrorKind::PermissionDenied => String::from("Board access denied"),
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => {
+                return Self::from(ProtocolError::Timeout)
+            }
             _ => error.to_string(),
         };
         Self::ProtocolError {
@@ -62,6 +71,10 @@ pub enum ProtocolError {
     IoException { info: String },
     /// Connection has not been initialized
     NotInitialized,
+    /// Connection lost
+    ConnectionLost,
+    /// Operation timed out
+    Timeout,
     /// Not enough bytes received - '{operation}' expected {expected} bytes, {received} received
     MessageTooShort {
         operation: &'static str,
@@ -83,13 +96,21 @@ pub enum HardwareError {
     },
     /// Unknown pin {pin}
     UnknownPin { pin: PinIdOrName },
+    /// Pin ({pin}) already in use by {owner}
+    PinInUse { pin: u8, owner: String },
+    /// Operation not supported by the hardware - {context}
+    NotSupported { context: &'static str },
+    /// Sensor on pin ({pin}) failed - {info}
+    SensorFailure { pin: u8, info: String },
 }
 
 #[cfg(test)]
 mod tests {
     use std::io;
 
-    use crate::errors::HardwareError::{IncompatiblePin, UnknownPin};
+    use crate::errors::HardwareError::{
+        IncompatiblePin, NotSupported, PinInUse, SensorFailure, UnknownPin,
+    };
 
     use super::*;
 
@@ -119,6 +140,16 @@ mod tests {
             "Hardware error: Pin (1) not compatible with mode (SERVO) - test context."
         );
 
+        let config_error = ConfigError {
+            file: "robot.yaml".to_string(),
+            entry: "devices.led.pin".to_string(),
+            info: "invalid type: string \"x\", expected u8".to_string(),
+        };
+        assert_eq!(
+            format!("{}", config_error),
+            "Configuration error: robot.yaml (devices.led.pin) - invalid type: string \"x\", expected u8."
+        );
+
         let unknown_error = UnknownError {
             info: "Some unknown error".to_string(),
         };
@@ -140,6 +171,14 @@ mod tests {
         let io_error = io::Error::new(io::ErrorKind::PermissionDenied, "error");
         let error: Error = io_error.into();
         assert_eq!(format!("{}", error), "Protocol error: Board access denied.");
+
+        let io_error = io::Error::new(io::ErrorKind::TimedOut, "error");
+        let error: Error = io_error.into();
+        assert_eq!(format!("{}", error), "Protocol error: Operation timed out.");
+
+        let io_error = io::Error::new(io::ErrorKind::WouldBlock, "error");
+        let error: Error = io_error.into();
+        assert_eq!(format!("{}", error), "Protocol error: Operation timed out.");
     }
 
     #[test]
@@ -159,6 +198,35 @@ mod tests {
         };
         let error: Error = hardware_error.into();
         assert_eq!(format!("{}", error), "Hardware error: Unknown pin 42.");
+
+        let hardware_error = PinInUse {
+            pin: 13,
+            owner: String::from("Led"),
+        };
+        let error: Error = hardware_error.into();
+        assert_eq!(
+            format!("{}", error),
+            "Hardware error: Pin (13) already in use by Led."
+        );
+
+        let hardware_error = NotSupported {
+            context: "control stepper",
+        };
+        let error: Error = hardware_error.into();
+        assert_eq!(
+            format!("{}", error),
+            "Hardware error: Operation not supported by the hardware - control stepper."
+        );
+
+        let hardware_error = SensorFailure {
+            pin: 7,
+            info: String::from("checksum error"),
+        };
+        let error: Error = hardware_error.into();
+        assert_eq!(
+            format!("{}", error),
+            "Hardware error: Sensor on pin (7) failed - checksum error."
+        );
     }
 
     #[test]
@@ -0,0 +1,763 @@
+// ***********
+// All information are relative to ADS1115 / ADS1015 datasheets:
+// https://www.ti.com/lit/ds/symlink/ads1115.pdf
+// https://www.ti.com/lit/ds/symlink/ads1015.pdf
+
+use crate::errors::{Error, HardwareError, UnknownError};
+use crate::hardware::{Board, Expander, Hardware, I2cDevice};
+use crate::io::{IoData, IoProtocol, Pin, PinMode, PinModeId, IO};
+use crate::pause;
+use crate::pause_sync;
+use crate::utils::{task, Range, TaskHandler};
+use parking_lot::RwLock;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// Defines the model of an ADS1X15 analog-to-digital converter.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AdsModel {
+    /// ADS1015: 12-bit conversions, from 128 to 3300 samples per second.
+    Ads1015,
+    /// ADS1115: 16-bit conversions, from 8 to 860 samples per second.
+    #[default]
+    Ads1115,
+}
+
+impl AdsModel {
+    /// Returns the data rates (in samples per second) supported by the model.
+    pub fn get_data_rates(&self) -> &'static [u16] {
+        match self {
+            AdsModel::Ads1015 => &[128, 250, 490, 920, 1600, 2400, 3300],
+            AdsModel::Ads1115 => &[8, 16, 32, 64, 128, 250, 475, 860],
+        }
+    }
+
+    // The power-up data rate: 1600 SPS for the ADS1015, 128 SPS for the ADS1115.
+    fn default_data_rate(&self) -> u16 {
+        self.get_data_rates()[4]
+    }
+
+    // Returns the conversions resolution (in bits).
+    fn resolution(&self) -> u8 {
+        match self {
+            AdsModel::Ads1015 => 12,
+            AdsModel::Ads1115 => 16,
+        }
+    }
+}
+
+/// Defines the gain of the programmable amplifier of an ADS1X15, hence its full-scale range.
+///
+/// _Note: the full-scale range does not allow to measure more than VDD + 0.3V on an input._
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AdsGain {
+    /// ±6.144V
+    TwoThirds = 0,
+    /// ±4.096V
+    One = 1,
+    /// ±2.048V
+    #[default]
+    Two = 2,
+    /// ±1.024V
+    Four = 3,
+    /// ±0.512V
+    Eight = 4,
+    /// ±0.256V
+    Sixteen = 5,
+}
+
+impl AdsGain {
+    /// Returns the full-scale range (in V) of the conversions.
+    pub fn get_full_scale(&self) -> f32 {
+        match self {
+            AdsGain::TwoThirds => 6.144,
+            AdsGain::One => 4.096,
+            AdsGain::Two => 2.048,
+            AdsGain::Four => 1.024,
+            AdsGain::Eight => 0.512,
+            AdsGain::Sixteen => 0.256,
+        }
+    }
+}
+
+/// Defines what the channels of an ADS1X15 measure.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AdsMode {
+    /// Channels 0-3 measure AIN0-AIN3 against the ground: values go from 0 (0V) to the maximum (full-scale).
+    #[default]
+    SingleEnded,
+    /// Channels 0-3 measure AIN0-AIN1, AIN0-AIN3, AIN1-AIN3 and AIN2-AIN3: values go from 0 (-full-scale)
+    /// to the maximum (+full-scale), the middle value meaning 0V.
+    Differential,
+}
+
+/// Represents an ADS1115 (or ADS1015) 4 channels analog-to-digital converter: its channels are exposed
+/// as ANALOG pins (A0-A3).
+///
+/// The gain, the data rate and the mode (single-ended vs differential) are shared by all channels.
+/// The reported channels are converted one after the other at the sampling interval (see [`IoProtocol::sampling_interval`])
+/// hence analog devices can be created on the converter pins exactly as on a [`Board`].
+///
+/// # Example
+/// ```no_run
+/// use hermes_five::devices::{AnalogInput, InputEvent};
+/// use hermes_five::hardware::{AdsGain, Board, BoardEvent, ADS1X15};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let board = Board::run();
+///     board.on(BoardEvent::OnReady, |board: Board| async move {
+///         let mut ads = ADS1X15::default(&board)?;
+///         ads.set_gain(AdsGain::One);
+///         let converter = ads.clone();
+///         let sensor = AnalogInput::new(&ads, 0)?;
+///         sensor.on(InputEvent::OnChange, move |value: u16| {
+///             let voltage = converter.to_voltage(value);
+///             async move {
+///                 println!("Voltage: {:.4}V", voltage);
+///                 Ok(())
+///             }
+///         });
+///         Ok(())
+///     });
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct ADS1X15 {
+    // Address (default 0x48).
+    address: u8,
+    // Model of the converter (default ADS1115).
+    model: AdsModel,
+    // Gain of the amplifier shared by all channels (default ±2.048V).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    gain: Arc<RwLock<AdsGain>>,
+    // Data rate in samples per second shared by all channels (default 128 SPS for ADS1115).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    data_rate: Arc<RwLock<u16>>,
+    // Single-ended or differential channels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    mode: Arc<RwLock<AdsMode>>,
+    // Interval (in ms) between two conversions of the reported channels.
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    interval: Arc<RwLock<u16>>,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data: Arc<RwLock<IoData>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    // Inner handler to the task converting the reported channels.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+}
+
+impl ADS1X15 {
+    // Registers.
+    const CONVERSION: u8 = 0x00;
+    const CONFIG: u8 = 0x01;
+    // Magic bits (of the config register).
+    const START: u16 = 0x8000; // Starts a conversion
+    const SINGLE_SHOT: u16 = 0x0100; // Powers down after a conversion
+    const COMPARATOR_OFF: u16 = 0x0003; // Disables the ALERT/RDY pin
+
+    // Defaults.
+    const DEFAULT_INTERVAL: u16 = 100; // Between two conversions of the reported channels in ms
+
+    fn _build_ads1x15_data(model: AdsModel) -> IoData {
+        let name = match model {
+            AdsModel::Ads1015 => "ADS1015",
+            AdsModel::Ads1115 => "ADS1115",
+        };
+        let mut data = IoData {
+            protocol_version: name.to_string(),
+            firmware_name: name.to_string(),
+            firmware_version: "n/a".to_string(),
+            connected: false,
+            ..Default::default()
+        };
+
+        for id in 0..4 {
+            let analog = PinMode {
+                id: PinModeId::ANALOG,
+                resolution: model.resolution(),
+            };
+            data.pins.insert(
+                id,
+                Pin {
+                    id,
+                    name: format!("A{}", id),
+                    mode: analog,
+                    supported_modes: vec![analog],
+                    channel: Some(id),
+                    value: 0,
+                },
+            );
+        }
+
+        data
+    }
+
+    pub fn default(board: &Board) -> Result<Self, Error> {
+        ADS1X15::new(board, 0x48, AdsModel::Ads1115)
+    }
+
+    pub fn new(board: &dyn Hardware, address: u8, model: AdsModel) -> Result<Self, Error> {
+        let protocol = board.get_protocol();
+        let mut expander = Self {
+            address,
+            model,
+            gain: Default::default(),
+            data_rate: Arc::new(RwLock::new(model.default_data_rate())),
+            mode: Default::default(),
+            interval: Arc::new(RwLock::new(ADS1X15::DEFAULT_INTERVAL)),
+            data: Arc::new(RwLock::new(ADS1X15::_build_ads1x15_data(model))),
+            protocol,
+            handler: Arc::new(RwLock::new(None)),
+        };
+        IoProtocol::open(&mut expander)?;
+        Ok(expander)
+    }
+
+    pub fn get_address(&self) -> u8 {
+        self.address
+    }
+
+    pub fn get_model(&self) -> AdsModel {
+        self.model
+    }
+
+    pub fn get_gain(&self) -> AdsGain {
+        *self.gain.read()
+    }
+
+    /// Sets the gain of the amplifier for all channels (applies from the next conversion).
+    pub fn set_gain(&mut self, gain: AdsGain) -> &Self {
+        *self.gain.write() = gain;
+        self
+    }
+
+    pub fn get_data_rate(&self) -> u16 {
+        *self.data_rate.read()
+    }
+
+    /// Sets the data rate (in samples per second) for all channels: the slower the rate, the less noisy
+    /// the conversions (see [`AdsModel::get_data_rates`]).
+    ///
+    /// # Errors
+    /// * `UnknownError`: the data rate is not supported by the model.
+    pub fn set_data_rate(&mut self, data_rate: u16) -> Result<&Self, Error> {
+        let data_rates = self.model.get_data_rates();
+        if !data_rates.contains(&data_rate) {
+            return Err(UnknownError {
+                info: format!("Data rate must be one of {:?} SPS", data_rates),
+            });
+        }
+        *self.data_rate.write() = data_rate;
+        Ok(self)
+    }
+
+    pub fn get_mode(&self) -> AdsMode {
+        *self.mode.read()
+    }
+
+    /// Sets what the channels measure (applies from the next conversion).
+    pub fn set_mode(&mut self, mode: AdsMode) -> &Self {
+        *self.mode.write() = mode;
+        self
+    }
+
+    /// Converts a value read on a channel to a voltage (in V), according to the current gain and mode.
+    pub fn to_voltage(&self, value: u16) -> f32 {
+        let half_scale = 1 << (self.model.resolution() - 1);
+        let value = match self.get_mode() {
+            AdsMode::SingleEnded => value as i32,
+            AdsMode::Differential => value as i32 - half_scale,
+        };
+        value as f32 * self.get_gain().get_full_scale() / half_scale as f32
+    }
+
+    // Returns the config register value starting a single-shot conversion of the channel.
+    fn config(&self, channel: u8) -> u16 {
+        let mux = match self.get_mode() {
+            AdsMode::SingleEnded => 0b100 | channel,
+            AdsMode::Differential => channel,
+        };
+        let data_rate = self
+            .model
+            .get_data_rates()
+            .iter()
+            .position(|&data_rate| data_rate == self.get_data_rate())
+            .unwrap_or_default();
+        ADS1X15::START
+            | (mux as u16) << 12
+            | (self.get_gain() as u16) << 9
+            | ADS1X15::SINGLE_SHOT
+            | (data_rate as u16) << 5
+            | ADS1X15::COMPARATOR_OFF
+    }
+
+    // Returns the duration (in ms) of a conversion, with a margin for the oscillator drift.
+    fn conversion_time(&self) -> u16 {
+        1000 / self.get_data_rate() + 1
+    }
+
+    // Converts a conversion register value to a pin value, according to the current mode.
+    fn decode(&self, raw: u16) -> u16 {
+        let (value, half_scale) = match self.model {
+            AdsModel::Ads1015 => ((raw as i16 >> 4) as i32, 2048),
+            AdsModel::Ads1115 => (raw as i16 as i32, 32768),
+        };
+        match self.get_mode() {
+            // Negative values are only noise around 0V.
+            AdsMode::SingleEnded => value.max(0) as u16,
+            AdsMode::Differential => (value + half_scale) as u16,
+        }
+    }
+
+    // Starts the conversion of the channel.
+    fn start_conversion(&self, channel: u8) -> Result<(), Error> {
+        self.i2c_device()
+            .write_u16_be(ADS1X15::CONFIG, self.config(channel))
+    }
+
+    // Reads the result of the conversion and stores it in the pin of the channel (the subscribers are
+    // notified of the changes).
+    fn read_conversion(&self, channel: u8) -> Result<u16, Error> {
+        let raw = self.i2c_device().read_u16_be(ADS1X15::CONVERSION)?;
+        let value = self.decode(raw);
+        self.data.write().set_pin_value(channel, value)?;
+        Ok(value)
+    }
+
+    // Starts the task converting the reported channels one after the other.
+    fn start_reporting(&mut self) -> Result<(), Error> {
+        let self_clone = self.clone();
+        *self.handler.write() = Some(task::run(async move {
+            loop {
+                let channels = self_clone.data.read().analog_reported_channels.clone();
+                if channels.is_empty() {
+                    break;
+                }
+                for channel in channels {
+                    self_clone.start_conversion(channel)?;
+                    pause!(self_clone.conversion_time());
+                    self_clone.read_conversion(channel)?;
+                }
+                let interval = *self_clone.interval.read();
+                pause!(interval);
+            }
+            Ok(())
+        })?);
+        Ok(())
+    }
+
+    // Stops the task converting the reported channels.
+    fn stop_reporting(&mut self) {
+        if let Some(handler) = self.handler.write().take() {
+            handler.abort();
+        }
+    }
+
+    // Register-level access to the chip, on the bus of the parent board.
+    fn i2c_device(&self) -> I2cDevice {
+        I2cDevice::from_protocol(self.protocol.clone(), self.address)
+    }
+}
+
+impl Expander for ADS1X15 {}
+
+impl Hardware for ADS1X15 {
+    fn get_protocol(&self) -> Box<dyn IoProtocol> {
+        Box::new(self.clone())
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    fn set_protocol(&mut self, protocol: Box<dyn IoProtocol>) {
+        self.protocol = protocol;
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl IoProtocol for ADS1X15 {
+    fn open(&mut self) -> Result<(), Error> {
+        self.i2c_config(0)?;
+        self.data.write().connected = true;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        self.data.write().analog_reported_channels.clear();
+        self.stop_reporting();
+        self.data.write().connected = false;
+        Ok(())
+    }
+
+    /// Sets the analog reporting `state` of the channel: the reported channels are converted one after the
+    /// other at the sampling interval.
+    fn report_analog(&mut self, channel: u8, state: bool) -> Result<(), Error> {
+        let reporting = {
+            let mut lock = self.data.write();
+            lock.get_pin(channel)?;
+            lock.analog_reported_channels
+                .retain(|&reported| reported != channel);
+            if state {
+                lock.analog_reported_channels.push(channel);
+            }
+            !lock.analog_reported_channels.is_empty()
+        };
+        let running = self.handler.read().is_some();
+        match (reporting, running) {
+            (true, false) => self.start_reporting(),
+            (false, true) => {
+                self.stop_reporting();
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The ADS1X15 has no digital input.
+    fn report_digital(&mut self, pin: u8, _: bool) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::INPUT,
+            context: "report digital value",
+        }
+        .into())
+    }
+
+    /// Sets the interval (in ms) between two conversions of the reported channels (default 100ms).
+    fn sampling_interval(&mut self, interval: u16) -> Result<(), Error> {
+        *self.interval.write() = interval;
+        Ok(())
+    }
+}
+
+impl IO for ADS1X15 {
+    fn get_io(&self) -> &Arc<RwLock<IoData>> {
+        &self.data
+    }
+
+    fn is_connected(&self) -> bool {
+        self.data.read().connected
+    }
+
+    /// Checks the mode of the pin: the channels can only be used as ANALOG inputs.
+    ///
+    /// # Errors
+    /// * `IncompatiblePin`: the mode is not supported.
+    fn set_pin_mode(&mut self, pin: u8, mode: PinModeId) -> Result<(), Error> {
+        let _mode = self.data.read().get_pin(pin)?.supports_mode(mode).ok_or(
+            HardwareError::IncompatiblePin {
+                pin,
+                mode,
+                context: "try to set pin mode",
+            },
+        )?;
+        self.data.write().get_pin_mut(pin)?.mode = _mode;
+        Ok(())
+    }
+
+    /// The ADS1X15 has no digital output.
+    fn digital_write(&mut self, pin: u8, _: bool) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::OUTPUT,
+            context: "write digital value",
+        }
+        .into())
+    }
+
+    /// The ADS1X15 has no PWM output.
+    fn analog_write(&mut self, pin: u8, _: u16) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::PWM,
+            context: "write analog value",
+        }
+        .into())
+    }
+
+    /// The ADS1X15 has no digital input.
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::INPUT,
+            context: "read digital value",
+        }
+        .into())
+    }
+
+    /// Converts the channel of the pin now (blocks for the duration of the conversion).
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        self.data.read().get_pin(pin)?;
+        self.start_conversion(pin)?;
+        pause_sync!(self.conversion_time());
+        self.read_conversion(pin)
+    }
+
+    /// The ADS1X15 cannot control servos.
+    fn servo_config(&mut self, pin: u8, _: Range<u16>) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::SERVO,
+            context: "configure servo",
+        }
+        .into())
+    }
+
+    /// The ADS1X15 cannot shift out data.
+    fn shift_out(&mut self, data_pin: u8, _: u8, _: u8, _: &[u8]) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin: data_pin,
+            mode: PinModeId::SHIFT,
+            context: "shift out data",
+        }
+        .into())
+    }
+
+    fn i2c_config(&mut self, delay: u16) -> Result<(), Error> {
+        self.protocol.i2c_config(delay)
+    }
+
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error> {
+        self.protocol.i2c_read(address, register, size)
+    }
+
+    fn i2c_read_continuously(
+        &mut self,
+        address: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error> {
+        self.protocol.i2c_read_continuously(address, register, size)
+    }
+
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error> {
+        self.protocol.i2c_stop_reading(address)
+    }
+
+    fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error> {
+        self.protocol.i2c_write(address, data)
+    }
+}
+
+impl Display for ADS1X15 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let data = self.data.read();
+        write!(
+            f,
+            "{} [firmware={}, version={}, protocol={}, transport=I2C]",
+            self.get_name(),
+            data.firmware_name,
+            data.firmware_version,
+            data.protocol_version,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{AnalogInput, Input};
+    use crate::io::{BoardProfile, DhtModel, EncoderCommand, StepperCommand, VirtualBoard};
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::utils::State;
+
+    fn _create_expander(model: AdsModel) -> (VirtualBoard, Board, ADS1X15) {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let board = Board::from(simulator.clone()).blocking_open().unwrap();
+        let expander = ADS1X15::new(&board, 0x48, model).unwrap();
+        (simulator, board, expander)
+    }
+
+    #[test]
+    fn test_helper() {
+        let data = ADS1X15::_build_ads1x15_data(AdsModel::Ads1015);
+        assert_eq!(data.firmware_name, "ADS1015");
+        assert_eq!(data.protocol_version, "ADS1015");
+        assert_eq!(data.pins.len(), 4);
+        assert_eq!(data.get_pin("A3").unwrap().channel, Some(3));
+        assert_eq!(data.get_pin(0).unwrap().mode.resolution, 12);
+
+        let data = ADS1X15::_build_ads1x15_data(AdsModel::Ads1115);
+        assert_eq!(data.firmware_name, "ADS1115");
+        assert_eq!(data.get_pin(0).unwrap().mode.id, PinModeId::ANALOG);
+        assert_eq!(data.get_pin(0).unwrap().mode.resolution, 16);
+    }
+
+    #[test]
+    fn test_initialization() {
+        let board = Board::new(MockIoProtocol::default());
+        let expander = ADS1X15::default(&board).unwrap();
+        assert_eq!(expander.get_address(), 0x48);
+        assert_eq!(expander.get_model(), AdsModel::Ads1115);
+        assert_eq!(expander.get_gain(), AdsGain::Two);
+        assert_eq!(expander.get_data_rate(), 128);
+        assert_eq!(expander.get_mode(), AdsMode::SingleEnded);
+        assert!(expander.is_connected());
+
+        let expander = ADS1X15::new(&board, 0x49, AdsModel::Ads1015).unwrap();
+        assert_eq!(expander.get_address(), 0x49);
+        assert_eq!(expander.get_data_rate(), 1600);
+    }
+
+    #[test]
+    fn test_settings() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = ADS1X15::default(&board).unwrap();
+
+        // Single-ended channel 0, ±2.048V, single-shot, 128 SPS, no comparator.
+        assert_eq!(expander.config(0), 0b1100_0101_1000_0011);
+        assert_eq!(expander.conversion_time(), 8);
+
+        expander.set_gain(AdsGain::Sixteen);
+        expander.set_mode(AdsMode::Differential);
+        assert!(expander.set_data_rate(860).is_ok());
+        assert_eq!(expander.config(3), 0b1011_1011_1110_0011);
+        assert_eq!(expander.conversion_time(), 2);
+
+        let result = expander.set_data_rate(100);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Unknown error: Data rate must be one of [8, 16, 32, 64, 128, 250, 475, 860] SPS."
+        );
+        assert_eq!(expander.get_data_rate(), 860);
+
+        // The settings are shared by all clones.
+        let clone = expander.clone();
+        assert!(expander.set_data_rate(8).is_ok());
+        assert_eq!(clone.get_data_rate(), 8);
+        assert_eq!(clone.get_gain(), AdsGain::Sixteen);
+        assert_eq!(clone.get_mode(), AdsMode::Differential);
+    }
+
+    #[test]
+    fn test_decode() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = ADS1X15::default(&board).unwrap();
+        assert_eq!(expander.decode(0x4000), 16384);
+        assert_eq!(expander.decode(0xFF00), 0);
+        assert!((expander.to_voltage(16384) - 1.024).abs() < 0.0001);
+
+        expander.set_mode(AdsMode::Differential);
+        assert_eq!(expander.decode(0x4000), 49152);
+        assert_eq!(expander.decode(0xFF00), 32512);
+        assert!((expander.to_voltage(16384) + 1.024).abs() < 0.0001);
+
+        let mut expander = ADS1X15::new(&board, 0x49, AdsModel::Ads1015).unwrap();
+        assert_eq!(expander.decode(0x7FF0), 2047);
+        assert_eq!(expander.decode(0x8000), 0);
+        assert!((expander.to_voltage(1024) - 1.024).abs() < 0.0001);
+        expander.set_mode(AdsMode::Differential);
+        assert_eq!(expander.decode(0x8000), 0);
+        assert_eq!(expander.decode(0x0000), 2048);
+    }
+
+    #[test]
+    fn test_analog_read() {
+        let (simulator, board, mut expander) = _create_expander(AdsModel::Ads1115);
+
+        // The simulated memory is byte-addressed: the config register overlaps the low byte of the conversion one.
+        simulator.set_i2c_data(0x48, ADS1X15::CONVERSION, &[0x12, 0x34]);
+        assert_eq!(expander.analog_read(2).unwrap(), 0x12E5);
+        assert_eq!(expander.get_io().read().get_pin(2).unwrap().value, 0x12E5);
+        // The conversion of the channel has been started.
+        assert_eq!(
+            simulator.get_i2c_data(0x48, ADS1X15::CONFIG, 2),
+            vec![0b1110_0101, 0b1000_0011]
+        );
+
+        assert!(expander.analog_read(4).is_err());
+        board.blocking_close().unwrap();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_analog_input() {
+        let (simulator, board, mut expander) = _create_expander(AdsModel::Ads1015);
+        assert!(expander.sampling_interval(10).is_ok());
+
+        let sensor = AnalogInput::new(&expander, "A1").unwrap();
+        assert_eq!(expander.get_io().read().analog_reported_channels, vec![1]);
+        assert!(AnalogInput::new(&expander, 1).is_err());
+
+        // The simulated memory is byte-addressed: the config register (0xD583) overlaps the low byte of the conversion one.
+        simulator.set_i2c_data(0x48, ADS1X15::CONVERSION, &[0x10]);
+        pause!(100);
+        assert_eq!(sensor.get_state(), State::Integer(0x10D));
+
+        simulator.set_i2c_data(0x48, ADS1X15::CONVERSION, &[0x20]);
+        pause!(100);
+        assert_eq!(sensor.get_state(), State::Integer(0x20D));
+
+        // Reporting stops with the last reported channel.
+        assert!(expander.report_analog(1, false).is_ok());
+        assert!(expander.handler.read().is_none());
+        assert!(expander.report_analog(1, true).is_ok());
+        assert!(expander.handler.read().is_some());
+
+        sensor.detach();
+        assert!(expander.close().is_ok());
+        assert!(expander.handler.read().is_none());
+        assert!(!expander.is_connected());
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_set_pin_mode() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = ADS1X15::default(&board).unwrap();
+        assert!(expander.set_pin_mode(0, PinModeId::ANALOG).is_ok());
+        let result = expander.set_pin_mode(0, PinModeId::INPUT);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Pin (0) not compatible with mode (INPUT) - try to set pin mode."
+        );
+        assert!(expander.set_pin_mode(4, PinModeId::ANALOG).is_err());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = ADS1X15::default(&board).unwrap();
+
+        assert!(expander.report_digital(0, true).is_err());
+        assert!(expander.digital_write(0, true).is_err());
+        assert!(expander.digital_read(0).is_err());
+        assert!(expander.analog_write(0, 128).is_err());
+        assert!(expander.servo_config(0, Range::from([500, 2500])).is_err());
+        assert!(expander.tone(0, 440, 100).is_err());
+        assert!(expander.shift_out(0, 1, 2, &[0xFF]).is_err());
+        assert!(expander.stepper(0, StepperCommand::Zero).is_err());
+        assert!(expander.encoder(0, EncoderCommand::Attach([0, 1])).is_err());
+        assert!(expander.ping_read(0, 5, 1000).is_err());
+        assert!(expander.dht_read(0, DhtModel::Dht22).is_err());
+        assert!(expander.onewire_config(0, true).is_err());
+        assert!(expander.onewire_search(0).is_err());
+        assert!(expander.onewire_reset(0).is_err());
+        assert!(expander.onewire_select(0, [0; 8]).is_err());
+        assert!(expander.onewire_read(0, 9, 0).is_err());
+        assert!(expander.onewire_write(0, [0; 8], &[0x44]).is_err());
+        assert!(expander.onewire_delay(0, 1000).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let board = Board::new(MockIoProtocol::default());
+        let expander = ADS1X15::default(&board).unwrap();
+
+        assert_eq!(
+            format!("{}", expander),
+            "ADS1X15 [firmware=ADS1115, version=n/a, protocol=ADS1115, transport=I2C]"
+        );
+        assert_eq!(
+            expander.get_protocol().to_string(),
+            "ADS1X15 [firmware=ADS1115, version=n/a, protocol=ADS1115, transport=I2C]"
+        );
+    }
+}
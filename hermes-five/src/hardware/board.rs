@@ -1,10 +1,12 @@
+use crate::devices::{Input, Output};
 use crate::errors::Error;
 use crate::hardware::Hardware;
+use crate::io::{DhtModel, EncoderCommand, IoProtocol, PinModeId, StepperCommand};
 use crate::io::{IoData, IoTransport, RemoteIo, IO};
-use crate::io::{IoProtocol, PinModeId};
-use crate::utils::{task, Range};
+use crate::utils::{shutdown, task, Range};
 use crate::utils::{EventHandler, EventManager};
 use parking_lot::RwLock;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::sync::Arc;
 
@@ -14,6 +16,12 @@ pub enum BoardEvent {
     OnReady,
     /// Triggered when the board connexion is closed (gracefully).
     OnClose,
+    /// Triggered when the board connexion is lost (requires a transport layer able to detect it, such as [`AutoReconnect`](crate::io::AutoReconnect)).
+    OnDisconnect,
+    /// Triggered when the board connexion has been recovered after a loss (handshake done, pin modes and reporting restored).
+    OnReconnect,
+    /// Triggered when the board connexion could not be recovered after a loss (the transport layer gave up).
+    OnReconnectFailed,
 }
 
 /// Convert events to string to facilitate usage with [`EventManager`].
@@ -22,11 +30,42 @@ impl From<BoardEvent> for String {
         let event = match value {
             BoardEvent::OnReady => "ready",
             BoardEvent::OnClose => "close",
+            BoardEvent::OnDisconnect => "disconnect",
+            BoardEvent::OnReconnect => "reconnect",
+            BoardEvent::OnReconnectFailed => "reconnect_failed",
         };
         event.into()
     }
 }
 
+/// Represents a device registered on a [`Board`] (see [`Board::register`]).
+#[derive(Clone, Debug)]
+pub enum BoardDevice {
+    /// An actuator: it can be reset to its default state or stopped.
+    Output(Box<dyn Output>),
+    /// A sensor.
+    Input(Box<dyn Input>),
+}
+
+impl BoardDevice {
+    /// Returns the pins (id) used by the device.
+    pub fn get_pins(&self) -> Vec<u8> {
+        match self {
+            BoardDevice::Output(device) => device.get_pins(),
+            BoardDevice::Input(device) => device.get_pins(),
+        }
+    }
+}
+
+impl Display for BoardDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardDevice::Output(device) => write!(f, "{}", device),
+            BoardDevice::Input(device) => write!(f, "{}", device),
+        }
+    }
+}
+
 /// Represents a physical board (Arduino most-likely) where your [`crate::devices::Device`] can be attached and controlled through this API.
 /// The board gives access to [`IoData`] through a communication [`IoProtocol`].
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -37,6 +76,12 @@ pub struct Board {
     events: EventManager,
     /// The inner protocol used by this Board.
     protocol: Box<dyn IoProtocol>,
+    /// The devices registered on the board (by name).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    devices: Arc<RwLock<BTreeMap<String, BoardDevice>>>,
+    /// The handlers forwarding the protocol events while the board is open (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    forwarders: Arc<RwLock<Vec<EventHandler>>>,
 }
 
 impl Default for Board {
@@ -84,6 +129,8 @@ impl<T: IoTransport> From<T> for Board {
         Self {
             events: Default::default(),
             protocol: Box::new(RemoteIo::from(transport)),
+            devices: Default::default(),
+            forwarders: Default::default(),
         }
     }
 }
@@ -127,6 +174,8 @@ impl Board {
         Self {
             events: EventManager::default(),
             protocol: Box::new(protocol),
+            devices: Default::default(),
+            forwarders: Default::default(),
         }
     }
 
@@ -157,12 +206,10 @@ impl Board {
     /// }
     /// ```
     pub fn open(self) -> Self {
-        let events_clone = self.events.clone();
         let callback_board = self.clone();
 
         task::run(async move {
-            let board = callback_board.blocking_open()?;
-            events_clone.emit(BoardEvent::OnReady, board);
+            callback_board.blocking_open()?.emit_ready();
             Ok(())
         })
         .expect("Task failed");
@@ -212,18 +259,36 @@ impl Board {
     /// Blocking version of [`Self::open()`] method.
     pub fn blocking_open(mut self) -> Result<Self, Error> {
         self.protocol.open()?;
+        self.forward_protocol_events();
+        shutdown::track(&self);
         // trace!"Board is ready: {:#?}", self.get_io());
         Ok(self)
     }
 
+    /// Emits the `OnReady` event for a board opened in a blocking way.
+    pub(crate) fn emit_ready(&self) {
+        self.events.emit(BoardEvent::OnReady, self.clone());
+    }
+
     /// Blocking version of [`Self::close()`] method.
     pub fn blocking_close(mut self) -> Result<Self, Error> {
-        // Detach all pins.
+        // Put the registered devices back to their default state.
+        let _ = self.reset_all();
+
+        // Detach all other pins.
+        let used: Vec<u8> = self
+            .devices
+            .read()
+            .values()
+            .flat_map(BoardDevice::get_pins)
+            .collect();
         let pins: Vec<u8> = self.get_io().read().pins.keys().copied().collect();
-        for id in pins {
+        for id in pins.into_iter().filter(|id| !used.contains(id)) {
             let _ = self.set_pin_mode(id, PinModeId::OUTPUT);
         }
         self.protocol.close()?;
+        self.unforward_protocol_events();
+        shutdown::untrack(&self);
         // trace!"Board is closed");
         Ok(self)
     }
@@ -235,6 +300,12 @@ impl Board {
     ///    _The callback must receive the following parameter: `|_: Board| { ... }`_
     /// - **`OnClose` | `close`:** Triggered when the board is disconnected.        
     ///    _The callback must receive the following parameter: `|_: Board| { ... }`_
+    /// - **`OnDisconnect` | `disconnect`:** Triggered when the board connexion is lost.    
+    ///    _The callback must receive the following parameter: `|_: Board| { ... }`_
+    /// - **`OnReconnect` | `reconnect`:** Triggered when the board connexion has been recovered.    
+    ///    _The callback must receive the following parameter: `|_: Board| { ... }`_
+    /// - **`OnReconnectFailed` | `reconnect_failed`:** Triggered when the board connexion could not be recovered.    
+    ///    _The callback must receive the following parameter: `|_: Board| { ... }`_
     ///
     /// # Example
     ///
@@ -259,6 +330,155 @@ impl Board {
     {
         self.events.on(event, callback)
     }
+
+    /// Registers a device on the board under the given name (replacing any device previously registered with that name).
+    ///
+    /// Registered devices can be looked up by name ([`Self::get_device`]) or by pin ([`Self::get_device_at`]),
+    /// are handled by bulk operations ([`Self::reset_all`], [`Self::stop_all`]) and are put back to their default
+    /// state when the board closes.
+    ///
+    /// # Example
+    /// ```
+    /// use hermes_five::devices::{Button, Led};
+    /// use hermes_five::hardware::{Board, BoardDevice, BoardEvent};
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let board = Board::run();
+    ///     board.on(BoardEvent::OnReady, |board: Board| async move {
+    ///         let led = Led::new(&board, 13, false)?;
+    ///         board.register("status", BoardDevice::Output(Box::new(led.clone())));
+    ///         // Same as:
+    ///         board.register_output("status", led);
+    ///
+    ///         board.register_input("trigger", Button::new(&board, 2)?);
+    ///         Ok(())
+    ///     });
+    /// }
+    /// ```
+    pub fn register<S: Into<String>>(&self, name: S, device: BoardDevice) {
+        self.devices.write().insert(name.into(), device);
+    }
+
+    /// Registers an output device on the board under the given name (see [`Self::register`]).
+    pub fn register_output<S: Into<String>, T: Output + 'static>(&self, name: S, device: T) {
+        self.register(name, BoardDevice::Output(Box::new(device)));
+    }
+
+    /// Registers an input device on the board under the given name (see [`Self::register`]).
+    pub fn register_input<S: Into<String>, T: Input + 'static>(&self, name: S, device: T) {
+        self.register(name, BoardDevice::Input(Box::new(device)));
+    }
+
+    /// Unregisters the device registered under the given name: returns it if any.
+    pub fn unregister(&self, name: &str) -> Option<BoardDevice> {
+        self.devices.write().remove(name)
+    }
+
+    /// Returns the device registered under the given name.
+    pub fn get_device(&self, name: &str) -> Option<BoardDevice> {
+        self.devices.read().get(name).cloned()
+    }
+
+    /// Returns the (first) registered device using the given pin.
+    pub fn get_device_at(&self, pin: u8) -> Option<BoardDevice> {
+        self.devices
+            .read()
+            .values()
+            .find(|device| device.get_pins().contains(&pin))
+            .cloned()
+    }
+
+    /// Returns all registered devices with their name (ordered by name).
+    pub fn get_devices(&self) -> Vec<(String, BoardDevice)> {
+        self.devices
+            .read()
+            .iter()
+            .map(|(name, device)| (name.clone(), device.clone()))
+            .collect()
+    }
+
+    /// Resets all registered output devices to their default state (see [`Output::reset`]).
+    ///
+    /// # Errors
+    /// All devices are reset even if one fails: the first error encountered (if any) is returned.
+    pub fn reset_all(&self) -> Result<(), Error> {
+        let mut result = Ok(());
+        for device in self.devices.write().values_mut() {
+            if let BoardDevice::Output(output) = device {
+                if let Err(error) = output.reset() {
+                    result = result.and(Err(error));
+                }
+            }
+        }
+        result
+    }
+
+    /// Stops the current animation (if any) of all registered output devices (see [`Output::stop`]).
+    pub fn stop_all(&self) {
+        for device in self.devices.write().values_mut() {
+            if let BoardDevice::Output(output) = device {
+                output.stop();
+            }
+        }
+    }
+
+    /// Stops and resets the registered devices, then closes the board (see [`shutdown`]).
+    pub(crate) fn shutdown(&self) -> Result<(), Error> {
+        self.stop_all();
+        let board = self.clone().blocking_close()?;
+        self.events.emit(BoardEvent::OnClose, board);
+        Ok(())
+    }
+
+    /// Indicates if both boards are the same (ie clones of each other).
+    pub(crate) fn is_same(&self, other: &Board) -> bool {
+        Arc::ptr_eq(&self.devices, &other.devices)
+    }
+
+    /// Re-emits the connection status events of the protocol (if any) as board events (once only, until
+    /// the board is closed).
+    ///
+    /// The handlers live in the protocol event manager: they must not own the board (hence its protocol),
+    /// the emitted board is the open board tracked for shutdown.
+    fn forward_protocol_events(&self) {
+        let Some(protocol_events) = self.protocol.get_events() else {
+            return;
+        };
+        let mut forwarders = self.forwarders.write();
+        if !forwarders.is_empty() {
+            return;
+        }
+        for event in [
+            BoardEvent::OnDisconnect,
+            BoardEvent::OnReconnect,
+            BoardEvent::OnReconnectFailed,
+        ] {
+            let event = String::from(event);
+            let devices = Arc::downgrade(&self.devices);
+            forwarders.push(protocol_events.on(event.clone(), move |_: ()| {
+                let event = event.clone();
+                let board = devices.upgrade().and_then(|devices| {
+                    shutdown::find_tracked(|board| Arc::ptr_eq(&board.devices, &devices))
+                });
+                async move {
+                    if let Some(board) = board {
+                        board.events.emit(event, board.clone());
+                    }
+                    Ok(())
+                }
+            }));
+        }
+    }
+
+    /// Stops forwarding the protocol events (see [`Board::forward_protocol_events`]).
+    fn unforward_protocol_events(&self) {
+        if let Some(protocol_events) = self.protocol.get_events() {
+            for handler in self.forwarders.write().drain(..) {
+                protocol_events.unregister(handler);
+            }
+        }
+    }
 }
 
 impl Hardware for Board {
@@ -312,26 +532,96 @@ impl IO for Board {
         self.protocol.analog_write(pin, level)
     }
 
-    #[cfg(not(tarpaulin_include))]
-    fn digital_read(&mut self, _: u8) -> Result<bool, Error> {
-        unimplemented!()
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        self.protocol.digital_read(pin)
     }
 
-    #[cfg(not(tarpaulin_include))]
-    fn analog_read(&mut self, _: u8) -> Result<u16, Error> {
-        unimplemented!()
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        self.protocol.analog_read(pin)
     }
 
     fn servo_config(&mut self, pin: u8, pwm_range: Range<u16>) -> Result<(), Error> {
         self.protocol.servo_config(pin, pwm_range)
     }
 
+    fn stepper(&mut self, id: u8, command: StepperCommand) -> Result<(), Error> {
+        self.protocol.stepper(id, command)
+    }
+
+    fn encoder(&mut self, id: u8, command: EncoderCommand) -> Result<(), Error> {
+        self.protocol.encoder(id, command)
+    }
+
+    fn dht_read(&mut self, pin: u8, model: DhtModel) -> Result<(), Error> {
+        self.protocol.dht_read(pin, model)
+    }
+
+    fn onewire_config(&mut self, pin: u8, parasitic_power: bool) -> Result<(), Error> {
+        self.protocol.onewire_config(pin, parasitic_power)
+    }
+
+    fn onewire_search(&mut self, pin: u8) -> Result<(), Error> {
+        self.protocol.onewire_search(pin)
+    }
+
+    fn onewire_reset(&mut self, pin: u8) -> Result<(), Error> {
+        self.protocol.onewire_reset(pin)
+    }
+
+    fn onewire_select(&mut self, pin: u8, address: [u8; 8]) -> Result<(), Error> {
+        self.protocol.onewire_select(pin, address)
+    }
+
+    fn onewire_read(&mut self, pin: u8, size: u16, correlation_id: u16) -> Result<(), Error> {
+        self.protocol.onewire_read(pin, size, correlation_id)
+    }
+
+    fn onewire_write(&mut self, pin: u8, address: [u8; 8], data: &[u8]) -> Result<(), Error> {
+        self.protocol.onewire_write(pin, address, data)
+    }
+
+    fn onewire_delay(&mut self, pin: u8, delay: u32) -> Result<(), Error> {
+        self.protocol.onewire_delay(pin, delay)
+    }
+
+    fn ping_read(&mut self, pin: u8, pulse: u32, timeout: u32) -> Result<(), Error> {
+        self.protocol.ping_read(pin, pulse, timeout)
+    }
+
+    fn tone(&mut self, pin: u8, frequency: u16, duration: u16) -> Result<(), Error> {
+        self.protocol.tone(pin, frequency, duration)
+    }
+
+    fn shift_out(
+        &mut self,
+        data_pin: u8,
+        clock_pin: u8,
+        latch_pin: u8,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.protocol
+            .shift_out(data_pin, clock_pin, latch_pin, data)
+    }
+
     fn i2c_config(&mut self, delay: u16) -> Result<(), Error> {
         self.protocol.i2c_config(delay)
     }
 
-    fn i2c_read(&mut self, address: u8, size: u16) -> Result<(), Error> {
-        self.protocol.i2c_read(address, size)
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error> {
+        self.protocol.i2c_read(address, register, size)
+    }
+
+    fn i2c_read_continuously(
+        &mut self,
+        address: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error> {
+        self.protocol.i2c_read_continuously(address, register, size)
+    }
+
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error> {
+        self.protocol.i2c_stop_reading(address)
     }
 
     fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error> {
@@ -348,12 +638,13 @@ impl Display for Board {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::devices::{Button, Led, Servo};
     use crate::io::Serial;
     use crate::io::IO;
     use crate::mocks::plugin_io::MockIoProtocol;
     use crate::mocks::transport_layer::MockTransportLayer;
     use crate::pause;
-    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
     use std::sync::Arc;
 
     #[test]
@@ -435,6 +726,53 @@ mod tests {
         assert!(board.is_connected());
     }
 
+    #[hermes_five_macros::test]
+    async fn test_board_connection_events() {
+        let mut transport = MockTransportLayer {
+            read_index: 10,
+            ..Default::default()
+        };
+        // Result for query firmware
+        transport.read_buf[10..15].copy_from_slice(&[0xF0, 0x79, 0x01, 0x0C, 0xF7]);
+        // Result for report capabilities
+        transport.read_buf[15..26].copy_from_slice(&[
+            0xF0, 0x6C, 0x00, 0x08, 0x7F, 0x00, 0x08, 0x01, 0x08, 0x7F, 0xF7,
+        ]);
+        // Result for analog mapping
+        transport.read_buf[26..32].copy_from_slice(&[0xF0, 0x6A, 0x7F, 0x7F, 0x7F, 0xF7]);
+
+        let board = Board::from(transport).blocking_open().unwrap();
+        let count = Arc::new(AtomicU8::new(0));
+        let moved_count = count.clone();
+        board.on(BoardEvent::OnDisconnect, move |board: Board| {
+            let captured_count = moved_count.clone();
+            async move {
+                captured_count.fetch_add(1, Ordering::SeqCst);
+                assert!(board.is_connected());
+                Ok(())
+            }
+        });
+        let protocol = board.get_protocol();
+        let protocol_events = protocol.get_events().unwrap();
+
+        // Protocol connection events are forwarded as board events.
+        protocol_events.emit(BoardEvent::OnDisconnect, ());
+        pause!(100);
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // The events are forwarded once only, even if the board is opened again.
+        board.forward_protocol_events();
+        protocol_events.emit(BoardEvent::OnDisconnect, ());
+        pause!(100);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        // The events are not forwarded anymore once the board is closed.
+        board.blocking_close().unwrap();
+        protocol_events.emit(BoardEvent::OnDisconnect, ());
+        pause!(100);
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
     #[hermes_five_macros::test]
     async fn test_board_close() {
         let flag = Arc::new(AtomicBool::new(false));
@@ -463,6 +801,102 @@ mod tests {
         board.close();
     }
 
+    #[hermes_five_macros::test]
+    async fn test_board_registry() {
+        let board = Board::new(MockIoProtocol::default());
+        assert!(board.get_devices().is_empty());
+
+        let led = Led::new(&board, 13, false).unwrap();
+        let servo = Servo::new(&board, 12, 90).unwrap();
+        let button = Button::new(&board, 4).unwrap();
+        board.register_output("status", led.clone());
+        board.register_output("arm", servo.clone());
+        board.register_input("trigger", button.clone());
+
+        // Lookup by name.
+        assert!(matches!(
+            board.get_device("status"),
+            Some(BoardDevice::Output(_))
+        ));
+        assert!(matches!(
+            board.get_device("trigger"),
+            Some(BoardDevice::Input(_))
+        ));
+        assert!(board.get_device("unknown").is_none());
+
+        // Lookup by pin.
+        let device = board.get_device_at(12).unwrap();
+        assert_eq!(device.get_pins(), vec![12]);
+        assert!(device.to_string().starts_with("SERVO (pin=12)"));
+        assert!(board.get_device_at(2).is_none());
+
+        // Iteration (ordered by name).
+        let names: Vec<String> = board
+            .get_devices()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["arm", "status", "trigger"]);
+
+        // Registering the same name replaces the device.
+        board.register("status", BoardDevice::Output(Box::new(servo)));
+        assert_eq!(board.get_device("status").unwrap().get_pins(), vec![12]);
+        assert_eq!(board.get_devices().len(), 3);
+
+        assert!(board.unregister("status").is_some());
+        assert!(board.unregister("status").is_none());
+        assert_eq!(board.get_devices().len(), 2);
+        button.detach();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_board_reset_and_stop_all() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut led = Led::new(&board, 13, false).unwrap();
+        let mut servo = Servo::new(&board, 12, 90).unwrap();
+        board.register_output("status", led.clone());
+        board.register_output("arm", servo.clone());
+        let button = Button::new(&board, 4).unwrap();
+        board.register_input("trigger", button.clone());
+
+        led.turn_on().unwrap();
+        servo.to(180).unwrap();
+        assert!(led.is_on());
+        assert_eq!(servo.get_state().as_integer(), 180);
+
+        board.reset_all().unwrap();
+        assert!(led.is_off());
+        assert_eq!(servo.get_state().as_integer(), 90);
+
+        led.blink(50);
+        assert!(led.is_busy());
+        board.stop_all();
+        assert!(!led.is_busy());
+        button.detach();
+    }
+
+    #[test]
+    fn test_board_close_resets_devices() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut servo = Servo::new(&board, 12, 90).unwrap();
+        servo.to(180).unwrap();
+        board.register_output("arm", servo.clone());
+
+        let board = board.blocking_close().unwrap();
+
+        // Registered devices are reset to default (and keep their mode)...
+        assert_eq!(servo.get_state().as_integer(), 90);
+        assert_eq!(
+            board.get_io().read().get_pin(12).unwrap().mode.id,
+            PinModeId::SERVO
+        );
+        // ... while other pins are detached.
+        assert_eq!(
+            board.get_io().read().get_pin(13).unwrap().mode.id,
+            PinModeId::OUTPUT
+        );
+    }
+
     #[test]
     fn test_board_get_hardware() {
         let board = Board::new(MockIoProtocol::default());
@@ -0,0 +1,367 @@
+use std::fmt::{Display, Formatter};
+
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+use crate::errors::{Error, ProtocolError};
+use crate::hardware::Hardware;
+use crate::io::{I2CReply, IoProtocol};
+
+/// Represents a generic device plugged on the I2C bus of a board, accessed through its registers.
+///
+/// This is the building block for I2C chip drivers: it hides the raw [`crate::io::IO::i2c_write`] / [`crate::io::IO::i2c_read`]
+/// messages behind register-level operations, and matches each read with the reply of this very
+/// device and register (other replies received on the bus meanwhile are left untouched).
+///
+/// # Example
+/// ```no_run
+/// use hermes_five::hardware::{Board, BoardEvent, I2cDevice};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let board = Board::run();
+///     board.on(BoardEvent::OnReady, |board: Board| async move {
+///         let mut device = I2cDevice::new(&board, 0x68)?;
+///         let id = device.read_u8(0x75)?;
+///         println!("Device id: 0x{:02X}", id);
+///         Ok(())
+///     });
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct I2cDevice {
+    // Address of the device on the bus.
+    address: u8,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+}
+
+impl I2cDevice {
+    /// Creates an instance of an I2C device at the given `address`, on the I2C bus of the given board.
+    ///
+    /// # Errors
+    /// * The I2C bus of the board could not be configured.
+    pub fn new(board: &dyn Hardware, address: u8) -> Result<Self, Error> {
+        let mut protocol = board.get_protocol();
+        protocol.i2c_config(0)?;
+        Ok(Self { address, protocol })
+    }
+
+    /// Creates an instance of an I2C device on an already configured bus.
+    pub(crate) fn from_protocol(protocol: Box<dyn IoProtocol>, address: u8) -> Self {
+        Self { address, protocol }
+    }
+
+    /// Returns the address of the device on the bus.
+    pub fn get_address(&self) -> u8 {
+        self.address
+    }
+
+    // ########################################
+    // Write helpers
+
+    /// Writes raw `data` to the device (no register pointer is set beforehand).
+    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        let payload: Vec<u16> = data.iter().map(|&byte| byte as u16).collect();
+        self.protocol.i2c_write(self.address, &payload)
+    }
+
+    /// Writes the `data` block, starting at the given `register`.
+    pub fn write_block(&mut self, register: u8, data: &[u8]) -> Result<(), Error> {
+        let payload: Vec<u8> = [register].iter().chain(data).copied().collect();
+        self.write(&payload)
+    }
+
+    /// Writes a single byte `value` in the given `register`.
+    pub fn write_u8(&mut self, register: u8, value: u8) -> Result<(), Error> {
+        self.write_block(register, &[value])
+    }
+
+    /// Writes a 16-bit `value` in big-endian order (MSB first), starting at the given `register`.
+    pub fn write_u16_be(&mut self, register: u8, value: u16) -> Result<(), Error> {
+        self.write_block(register, &value.to_be_bytes())
+    }
+
+    /// Writes a 16-bit `value` in little-endian order (LSB first), starting at the given `register`.
+    pub fn write_u16_le(&mut self, register: u8, value: u16) -> Result<(), Error> {
+        self.write_block(register, &value.to_le_bytes())
+    }
+
+    // ########################################
+    // Read helpers
+
+    /// Reads `size` raw bytes from the device (no register pointer is set beforehand).
+    ///
+    /// # Errors
+    /// * `MessageTooShort`: the device replied with less than `size` bytes.
+    pub fn read(&mut self, size: u16) -> Result<Vec<u8>, Error> {
+        self.read_reply(None, size)
+    }
+
+    /// Reads a block of `size` bytes, starting at the given `register`.
+    ///
+    /// # Errors
+    /// * `MessageTooShort`: the device replied with less than `size` bytes.
+    pub fn read_block(&mut self, register: u8, size: u16) -> Result<Vec<u8>, Error> {
+        self.read_reply(Some(register), size)
+    }
+
+    fn read_reply(&mut self, register: Option<u8>, size: u16) -> Result<Vec<u8>, Error> {
+        self.protocol.i2c_read(self.address, register, size)?;
+        let data = self
+            .protocol
+            .get_io()
+            .write()
+            .take_i2c_reply(self.address, register)
+            .map(|reply| reply.data)
+            .unwrap_or_default();
+
+        if data.len() < size as usize {
+            return Err(Error::from(ProtocolError::MessageTooShort {
+                operation: "read I2C register",
+                expected: size as usize,
+                received: data.len(),
+            }));
+        }
+        Ok(data)
+    }
+
+    /// Reads a single byte from the given `register`.
+    pub fn read_u8(&mut self, register: u8) -> Result<u8, Error> {
+        Ok(self.read_block(register, 1)?[0])
+    }
+
+    /// Reads a 16-bit value stored in big-endian order (MSB first), starting at the given `register`.
+    pub fn read_u16_be(&mut self, register: u8) -> Result<u16, Error> {
+        let data = self.read_block(register, 2)?;
+        Ok(u16::from_be_bytes([data[0], data[1]]))
+    }
+
+    /// Reads a 16-bit value stored in little-endian order (LSB first), starting at the given `register`.
+    pub fn read_u16_le(&mut self, register: u8) -> Result<u16, Error> {
+        let data = self.read_block(register, 2)?;
+        Ok(u16::from_le_bytes([data[0], data[1]]))
+    }
+
+    /// Asks the board to read `size` bytes starting at the given `register` continuously (at the sampling
+    /// interval of the board): the values are received through the returned [`I2cSubscription`].
+    pub fn read_continuously(&mut self, register: u8, size: u16) -> Result<I2cSubscription, Error> {
+        // Subscribes first: the first reply may arrive right after the request.
+        let receiver = self.protocol.get_io().read().subscribe_i2c_replies();
+        self.protocol
+            .i2c_read_continuously(self.address, Some(register), size)?;
+        Ok(I2cSubscription {
+            address: self.address,
+            register,
+            receiver,
+            protocol: self.protocol.clone(),
+        })
+    }
+
+    // ########################################
+    // Bit helpers
+
+    /// Replaces the bits selected by `mask` in the given `register` with the ones of `bits`
+    /// (read-modify-write): the other bits are left unchanged.
+    pub fn update_bits(&mut self, register: u8, mask: u8, bits: u8) -> Result<(), Error> {
+        let value = self.read_u8(register)?;
+        self.write_u8(register, (value & !mask) | (bits & mask))
+    }
+
+    /// Sets the bits selected by `mask` in the given `register`.
+    pub fn set_bits(&mut self, register: u8, mask: u8) -> Result<(), Error> {
+        self.update_bits(register, mask, mask)
+    }
+
+    /// Clears the bits selected by `mask` in the given `register`.
+    pub fn clear_bits(&mut self, register: u8, mask: u8) -> Result<(), Error> {
+        self.update_bits(register, mask, 0)
+    }
+}
+
+impl Display for I2cDevice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "I2cDevice (address=0x{:02X})", self.address)
+    }
+}
+
+/// Represents a continuous read of the registers of an [`I2cDevice`] (see [`I2cDevice::read_continuously`]):
+/// only the replies of this device and register are received.
+///
+/// _Note: the board stops all the continuous reads of a device at once: stopping a subscription
+/// stops the other ones on the same device as well._
+#[derive(Debug)]
+pub struct I2cSubscription {
+    address: u8,
+    register: u8,
+    receiver: broadcast::Receiver<I2CReply>,
+    protocol: Box<dyn IoProtocol>,
+}
+
+impl I2cSubscription {
+    /// Returns the address of the device read.
+    pub fn get_address(&self) -> u8 {
+        self.address
+    }
+
+    /// Returns the first register read.
+    pub fn get_register(&self) -> u8 {
+        self.register
+    }
+
+    /// Waits for the next value read from the device: returns `None` once the board has gone.
+    pub async fn recv(&mut self) -> Option<Vec<u8>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(reply) if self.matches(&reply) => return Some(reply.data),
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Returns the next value already read from the device, if any (does not wait).
+    pub fn try_recv(&mut self) -> Option<Vec<u8>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(reply) if self.matches(&reply) => return Some(reply.data),
+                Ok(_) | Err(TryRecvError::Lagged(_)) => {}
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Stops reading the device continuously.
+    pub fn stop(mut self) -> Result<(), Error> {
+        self.protocol.i2c_stop_reading(self.address)
+    }
+
+    fn matches(&self, reply: &I2CReply) -> bool {
+        reply.address == self.address && reply.register == self.register
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::Board;
+    use crate::io::{BoardProfile, VirtualBoard, IO};
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+
+    fn _create_device() -> (VirtualBoard, Board, I2cDevice) {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let board = Board::from(simulator.clone()).blocking_open().unwrap();
+        let device = I2cDevice::new(&board, 0x40).unwrap();
+        (simulator, board, device)
+    }
+
+    #[test]
+    fn test_new() {
+        let board = Board::new(MockIoProtocol::default());
+        let device = I2cDevice::new(&board, 0x68).unwrap();
+        assert_eq!(device.get_address(), 0x68);
+        assert_eq!(device.to_string(), "I2cDevice (address=0x68)");
+    }
+
+    #[test]
+    fn test_write() {
+        let (simulator, board, mut device) = _create_device();
+
+        device.write(&[0x10, 0xAA]).unwrap();
+        assert_eq!(simulator.get_i2c_data(0x40, 0x10, 1), vec![0xAA]);
+        device.write_u8(0x11, 0xBB).unwrap();
+        assert_eq!(simulator.get_i2c_data(0x40, 0x11, 1), vec![0xBB]);
+        device.write_block(0x20, &[0x01, 0x02, 0x03]).unwrap();
+        assert_eq!(
+            simulator.get_i2c_data(0x40, 0x20, 3),
+            vec![0x01, 0x02, 0x03]
+        );
+        device.write_u16_be(0x30, 0x1234).unwrap();
+        assert_eq!(simulator.get_i2c_data(0x40, 0x30, 2), vec![0x12, 0x34]);
+        device.write_u16_le(0x30, 0x1234).unwrap();
+        assert_eq!(simulator.get_i2c_data(0x40, 0x30, 2), vec![0x34, 0x12]);
+
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_read() {
+        let (simulator, board, mut device) = _create_device();
+
+        simulator.set_i2c_data(0x40, 0x20, &[0x12, 0x34, 0x56]);
+        assert_eq!(device.read_u8(0x20).unwrap(), 0x12);
+        assert_eq!(device.read_block(0x20, 3).unwrap(), vec![0x12, 0x34, 0x56]);
+        assert_eq!(device.read_u16_be(0x20).unwrap(), 0x1234);
+        assert_eq!(device.read_u16_le(0x21).unwrap(), 0x5634);
+
+        // Raw reads start at the register pointer of the device.
+        device.write(&[0x21]).unwrap();
+        assert_eq!(device.read(2).unwrap(), vec![0x34, 0x56]);
+
+        // Replies are consumed once matched.
+        assert!(board.get_io().read().i2c_data.is_empty());
+
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_read_failure() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut device = I2cDevice::new(&board, 0x40).unwrap();
+        let result = device.read_u8(0x20);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Protocol error: Not enough bytes received - 'read I2C register' expected 1 bytes, 0 received."
+        );
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_read_continuously() {
+        let (simulator, board, mut device) = _create_device();
+
+        simulator.set_i2c_data(0x40, 0x20, &[0x12, 0x34]);
+        let mut subscription = device.read_continuously(0x20, 2).unwrap();
+        assert_eq!(subscription.get_address(), 0x40);
+        assert_eq!(subscription.get_register(), 0x20);
+        assert_eq!(subscription.recv().await.unwrap(), vec![0x12, 0x34]);
+
+        // Replies of other devices or registers are filtered out.
+        let mut other = I2cDevice::new(&board, 0x41).unwrap();
+        let _other_subscription = other.read_continuously(0x20, 2).unwrap();
+        simulator.set_i2c_data(0x41, 0x20, &[0xFF]);
+        simulator.set_i2c_data(0x40, 0x21, &[0x56]);
+        assert_eq!(subscription.recv().await.unwrap(), vec![0x12, 0x56]);
+
+        // Stopped reads are not reported anymore.
+        subscription.stop().unwrap();
+        pause!(100);
+        simulator.set_i2c_data(0x40, 0x20, &[0x78]);
+        pause!(100);
+        let lock = board.get_io().read();
+        assert!(lock.i2c_data.iter().all(|reply| reply.data[0] != 0x78));
+        drop(lock);
+
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_bits() {
+        let (simulator, board, mut device) = _create_device();
+
+        simulator.set_i2c_data(0x40, 0x00, &[0b1010_0000]);
+        device.set_bits(0x00, 0b0000_0011).unwrap();
+        assert_eq!(simulator.get_i2c_data(0x40, 0x00, 1), vec![0b1010_0011]);
+        device.clear_bits(0x00, 0b1000_0001).unwrap();
+        assert_eq!(simulator.get_i2c_data(0x40, 0x00, 1), vec![0b0010_0010]);
+        device.update_bits(0x00, 0b1111_0000, 0b0101_1111).unwrap();
+        assert_eq!(simulator.get_i2c_data(0x40, 0x00, 1), vec![0b0101_0010]);
+
+        board.blocking_close().unwrap();
+    }
+}
@@ -0,0 +1,653 @@
+// ***********
+// All information are relative to MCP23017 datasheet:
+// https://ww1.microchip.com/downloads/en/devicedoc/20001952c.pdf
+
+use crate::errors::{Error, HardwareError};
+use crate::hardware::{Board, Expander, Hardware, I2cDevice};
+use crate::io::{IoData, IoProtocol, Pin, PinMode, PinModeId, IO};
+use crate::utils::{task, Range, TaskHandler};
+use parking_lot::RwLock;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Represents a MCP23017 16 pins GPIO expander: pins 0-7 are the port A (GPA0-GPA7), pins 8-15 the
+/// port B (GPB0-GPB7).
+///
+/// Each pin can be configured as an OUTPUT, an INPUT or an INPUT with internal pull-up (PULLUP),
+/// and the polarity of its input can be inverted (see [`MCP23017::set_polarity`]). The inputs are
+/// reported by a continuous read of the chip (see [`IoProtocol::report_digital`]) hence devices can
+/// be created on the expander pins exactly as on a [`Board`].
+///
+/// # Example
+/// ```no_run
+/// use hermes_five::devices::{Button, InputEvent, Led};
+/// use hermes_five::hardware::{Board, BoardEvent, MCP23017};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let board = Board::run();
+///     board.on(BoardEvent::OnReady, |board: Board| async move {
+///         let expander = MCP23017::default(&board)?;
+///         let mut led = Led::new(&expander, 0, false)?;
+///         let button = Button::new_pullup(&expander, 8)?;
+///         button.on(InputEvent::OnPress, move |_: ()| {
+///             let mut led = led.clone();
+///             async move {
+///                 led.toggle()?;
+///                 Ok(())
+///             }
+///         });
+///         Ok(())
+///     });
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct MCP23017 {
+    // Address (default 0x20).
+    address: u8,
+
+    // ########################################
+    // # Volatile utility data.
+    // Registers content (one bit per pin: port A in the low byte, port B in the high byte).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    direction: Arc<RwLock<u16>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pullups: Arc<RwLock<u16>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    polarity: Arc<RwLock<u16>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    latch: Arc<RwLock<u16>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data: Arc<RwLock<IoData>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    // Inner handler to the task reporting the inputs values.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+}
+
+impl MCP23017 {
+    // Registers (IOCON.BANK = 0: each port B register follows its port A counterpart).
+    const IODIRA: u8 = 0x00;
+    const IPOLA: u8 = 0x02;
+    const GPPUA: u8 = 0x0C;
+    const GPIOA: u8 = 0x12;
+    const OLATA: u8 = 0x14;
+
+    fn _build_mcp23017_data() -> IoData {
+        let mut data = IoData {
+            protocol_version: "MCP23017".to_string(),
+            firmware_name: "MCP23017".to_string(),
+            firmware_version: "n/a".to_string(),
+            connected: false,
+            ..Default::default()
+        };
+
+        for id in 0..16 {
+            // All pins are inputs at power-up.
+            let input = PinMode {
+                id: PinModeId::INPUT,
+                resolution: 1,
+            };
+            data.pins.insert(
+                id,
+                Pin {
+                    id,
+                    name: format!("GP{}{}", if id < 8 { 'A' } else { 'B' }, id % 8),
+                    mode: input,
+                    supported_modes: vec![
+                        PinMode {
+                            id: PinModeId::OUTPUT,
+                            resolution: 1,
+                        },
+                        input,
+                        PinMode {
+                            id: PinModeId::PULLUP,
+                            resolution: 1,
+                        },
+                    ],
+                    channel: None,
+                    value: 0,
+                },
+            );
+        }
+
+        data
+    }
+
+    pub fn default(board: &Board) -> Result<Self, Error> {
+        MCP23017::new(board, 0x20)
+    }
+
+    pub fn new(board: &dyn Hardware, address: u8) -> Result<Self, Error> {
+        let protocol = board.get_protocol();
+        let mut expander = Self {
+            address,
+            direction: Arc::new(RwLock::new(0xFFFF)),
+            pullups: Default::default(),
+            polarity: Default::default(),
+            latch: Default::default(),
+            data: Arc::new(RwLock::new(MCP23017::_build_mcp23017_data())),
+            protocol,
+            handler: Arc::new(RwLock::new(None)),
+        };
+        IoProtocol::open(&mut expander)?;
+        Ok(expander)
+    }
+
+    pub fn get_address(&self) -> u8 {
+        self.address
+    }
+
+    /// Returns true if the input value of the pin is inverted.
+    pub fn get_polarity(&self, pin: u8) -> bool {
+        (*self.polarity.read())
+            .checked_shr(pin as u32)
+            .is_some_and(|polarity| polarity & 1 != 0)
+    }
+
+    /// Inverts (or not) the input value of the pin: an inverted input reads HIGH when its level is LOW.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: the pin does not exist.
+    pub fn set_polarity(&mut self, pin: u8, inverted: bool) -> Result<&Self, Error> {
+        self.data.read().get_pin(pin)?;
+        let polarity = MCP23017::update_bit(&self.polarity, pin, inverted);
+        self.i2c_device().write_u16_le(MCP23017::IPOLA, polarity)?;
+        Ok(self)
+    }
+
+    // Sets the bit of the pin in the cached register, and returns the new register value.
+    fn update_bit(register: &Arc<RwLock<u16>>, pin: u8, value: bool) -> u16 {
+        let mut register = register.write();
+        match value {
+            true => *register |= 1 << pin,
+            false => *register &= !(1 << pin),
+        }
+        *register
+    }
+
+    // Stores the values read from the GPIO registers in the input pins (the subscribers are notified of the changes).
+    fn update_inputs(data: &Arc<RwLock<IoData>>, values: u16) -> Result<(), Error> {
+        let mut data = data.write();
+        for pin in 0..16 {
+            if matches!(
+                data.get_pin(pin)?.mode.id,
+                PinModeId::INPUT | PinModeId::PULLUP
+            ) {
+                data.set_pin_value(pin, (values >> pin) & 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Starts the continuous read of the GPIO registers, and the task reporting the inputs values.
+    fn start_reporting(&mut self) -> Result<(), Error> {
+        let mut replies = self.protocol.get_io().read().subscribe_i2c_replies();
+        self.protocol
+            .i2c_read_continuously(self.address, Some(MCP23017::GPIOA), 2)?;
+
+        let address = self.address;
+        let data = self.data.clone();
+        *self.handler.write() = Some(task::run(async move {
+            loop {
+                match replies.recv().await {
+                    Ok(reply)
+                        if reply.address == address
+                            && reply.register == MCP23017::GPIOA
+                            && reply.data.len() >= 2 =>
+                    {
+                        let values = u16::from_le_bytes([reply.data[0], reply.data[1]]);
+                        MCP23017::update_inputs(&data, values)?;
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })?);
+        Ok(())
+    }
+
+    // Stops the continuous read of the GPIO registers.
+    fn stop_reporting(&mut self) -> Result<(), Error> {
+        if let Some(handler) = self.handler.write().take() {
+            handler.abort();
+        }
+        self.protocol.i2c_stop_reading(self.address)
+    }
+
+    // Register-level access to the chip, on the bus of the parent board.
+    fn i2c_device(&self) -> I2cDevice {
+        I2cDevice::from_protocol(self.protocol.clone(), self.address)
+    }
+}
+
+impl Expander for MCP23017 {}
+
+impl Hardware for MCP23017 {
+    fn get_protocol(&self) -> Box<dyn IoProtocol> {
+        Box::new(self.clone())
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    fn set_protocol(&mut self, protocol: Box<dyn IoProtocol>) {
+        self.protocol = protocol;
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl IoProtocol for MCP23017 {
+    fn open(&mut self) -> Result<(), Error> {
+        self.i2c_config(0)?;
+        // Output latches are set before the directions, so that outputs do not glitch.
+        let mut device = self.i2c_device();
+        device.write_u16_le(MCP23017::OLATA, *self.latch.read())?;
+        device.write_u16_le(MCP23017::GPPUA, *self.pullups.read())?;
+        device.write_u16_le(MCP23017::IPOLA, *self.polarity.read())?;
+        device.write_u16_le(MCP23017::IODIRA, *self.direction.read())?;
+        self.data.write().connected = true;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        self.data.write().digital_reported_pins.clear();
+        if self.handler.read().is_some() {
+            self.stop_reporting()?;
+        }
+        self.data.write().connected = false;
+        Ok(())
+    }
+
+    /// The MCP23017 has no analog input.
+    fn report_analog(&mut self, _: u8, _: bool) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "report analog value",
+        }
+        .into())
+    }
+
+    /// Sets the digital reporting `state` of the pin: the inputs are reported by a continuous read of the
+    /// GPIO registers while at least one pin is reported.
+    fn report_digital(&mut self, pin: u8, state: bool) -> Result<(), Error> {
+        let reporting = {
+            let mut lock = self.data.write();
+            lock.get_pin(pin)?;
+            lock.digital_reported_pins
+                .retain(|&reported| reported != pin);
+            if state {
+                lock.digital_reported_pins.push(pin);
+            }
+            !lock.digital_reported_pins.is_empty()
+        };
+        let running = self.handler.read().is_some();
+        match (reporting, running) {
+            (true, false) => self.start_reporting(),
+            (false, true) => self.stop_reporting(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets the sampling interval of the parent board (hence of the inputs reporting).
+    fn sampling_interval(&mut self, interval: u16) -> Result<(), Error> {
+        self.protocol.sampling_interval(interval)
+    }
+}
+
+impl IO for MCP23017 {
+    fn get_io(&self) -> &Arc<RwLock<IoData>> {
+        &self.data
+    }
+
+    fn is_connected(&self) -> bool {
+        self.data.read().connected
+    }
+
+    /// Sets the direction (and the pull-up) of the pin.
+    ///
+    /// # Errors
+    /// * `IncompatiblePin`: the mode is not supported.
+    fn set_pin_mode(&mut self, pin: u8, mode: PinModeId) -> Result<(), Error> {
+        let _mode = self.data.read().get_pin(pin)?.supports_mode(mode).ok_or(
+            HardwareError::IncompatiblePin {
+                pin,
+                mode,
+                context: "try to set pin mode",
+            },
+        )?;
+
+        let pullups = MCP23017::update_bit(&self.pullups, pin, mode == PinModeId::PULLUP);
+        let direction = MCP23017::update_bit(&self.direction, pin, mode != PinModeId::OUTPUT);
+        let mut device = self.i2c_device();
+        device.write_u16_le(MCP23017::GPPUA, pullups)?;
+        device.write_u16_le(MCP23017::IODIRA, direction)?;
+
+        self.data.write().get_pin_mut(pin)?.mode = _mode;
+        Ok(())
+    }
+
+    fn digital_write(&mut self, pin: u8, level: bool) -> Result<(), Error> {
+        self.data.write().get_pin_mut(pin)?.value = level as u16;
+        let latch = MCP23017::update_bit(&self.latch, pin, level);
+        self.i2c_device().write_u16_le(MCP23017::OLATA, latch)
+    }
+
+    /// The MCP23017 has no PWM output.
+    fn analog_write(&mut self, pin: u8, _: u16) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::PWM,
+            context: "write analog value",
+        }
+        .into())
+    }
+
+    /// Reads the level of the pin from the chip: the values of all the input pins are updated at once.
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        self.data.read().get_pin(pin)?;
+        let values = self.i2c_device().read_u16_le(MCP23017::GPIOA)?;
+        MCP23017::update_inputs(&self.data, values)?;
+        Ok((values >> pin) & 1 != 0)
+    }
+
+    /// The MCP23017 has no analog input.
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::ANALOG,
+            context: "read analog value",
+        }
+        .into())
+    }
+
+    /// The MCP23017 cannot control servos.
+    fn servo_config(&mut self, pin: u8, _: Range<u16>) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::SERVO,
+            context: "configure servo",
+        }
+        .into())
+    }
+
+    /// The MCP23017 cannot shift out data.
+    fn shift_out(&mut self, data_pin: u8, _: u8, _: u8, _: &[u8]) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin: data_pin,
+            mode: PinModeId::SHIFT,
+            context: "shift out data",
+        }
+        .into())
+    }
+
+    fn i2c_config(&mut self, delay: u16) -> Result<(), Error> {
+        self.protocol.i2c_config(delay)
+    }
+
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error> {
+        self.protocol.i2c_read(address, register, size)
+    }
+
+    fn i2c_read_continuously(
+        &mut self,
+        address: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error> {
+        self.protocol.i2c_read_continuously(address, register, size)
+    }
+
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error> {
+        self.protocol.i2c_stop_reading(address)
+    }
+
+    fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error> {
+        self.protocol.i2c_write(address, data)
+    }
+}
+
+impl Display for MCP23017 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let data = self.data.read();
+        write!(
+            f,
+            "{} [firmware={}, version={}, protocol={}, transport=I2C]",
+            self.get_name(),
+            data.firmware_name,
+            data.firmware_version,
+            data.protocol_version,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{Button, DigitalInput, Input, Led};
+    use crate::io::{BoardProfile, DhtModel, EncoderCommand, StepperCommand, VirtualBoard};
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+    use crate::utils::State;
+
+    fn _create_expander() -> (VirtualBoard, Board, MCP23017) {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let board = Board::from(simulator.clone()).blocking_open().unwrap();
+        let expander = MCP23017::default(&board).unwrap();
+        (simulator, board, expander)
+    }
+
+    #[test]
+    fn test_helper() {
+        let data = MCP23017::_build_mcp23017_data();
+        assert_eq!(data.firmware_name, "MCP23017");
+        assert_eq!(data.protocol_version, "MCP23017");
+        assert_eq!(data.pins.len(), 16);
+        assert_eq!(data.get_pin("GPA0").unwrap().id, 0);
+        assert_eq!(data.get_pin("GPB7").unwrap().id, 15);
+        assert_eq!(data.get_pin(3).unwrap().mode.id, PinModeId::INPUT);
+    }
+
+    #[test]
+    fn test_initialization() {
+        let (simulator, board, expander) = _create_expander();
+        assert_eq!(expander.get_address(), 0x20);
+        assert!(expander.is_connected());
+        // All pins are inputs.
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::IODIRA, 2),
+            vec![0xFF, 0xFF]
+        );
+
+        let expander = MCP23017::new(&board, 0x21).unwrap();
+        assert_eq!(expander.get_address(), 0x21);
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_set_pin_mode() {
+        let (simulator, board, mut expander) = _create_expander();
+
+        assert!(expander.set_pin_mode(0, PinModeId::OUTPUT).is_ok());
+        assert!(expander.set_pin_mode(9, PinModeId::OUTPUT).is_ok());
+        assert!(expander.set_pin_mode(10, PinModeId::PULLUP).is_ok());
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::IODIRA, 2),
+            vec![0b1111_1110, 0b1111_1101]
+        );
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::GPPUA, 2),
+            vec![0b0000_0000, 0b0000_0100]
+        );
+        assert_eq!(
+            expander.get_io().read().get_pin(10).unwrap().mode.id,
+            PinModeId::PULLUP
+        );
+
+        // Back to a plain input.
+        assert!(expander.set_pin_mode(10, PinModeId::INPUT).is_ok());
+        assert_eq!(simulator.get_i2c_data(0x20, MCP23017::GPPUA, 2), vec![0, 0]);
+
+        // The registers are shared by all clones.
+        let mut clone = expander.clone();
+        assert!(clone.set_pin_mode(1, PinModeId::OUTPUT).is_ok());
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::IODIRA, 2),
+            vec![0b1111_1100, 0b1111_1101]
+        );
+
+        let result = expander.set_pin_mode(2, PinModeId::PWM);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Pin (2) not compatible with mode (PWM) - try to set pin mode."
+        );
+        assert!(expander.set_pin_mode(16, PinModeId::OUTPUT).is_err());
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_digital_write() {
+        let (simulator, board, mut expander) = _create_expander();
+
+        assert!(expander.digital_write(1, true).is_ok());
+        assert!(expander.digital_write(15, true).is_ok());
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::OLATA, 2),
+            vec![0b0000_0010, 0b1000_0000]
+        );
+        assert_eq!(expander.get_io().read().get_pin(1).unwrap().value, 1);
+
+        assert!(expander.digital_write(1, false).is_ok());
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::OLATA, 2),
+            vec![0b0000_0000, 0b1000_0000]
+        );
+        assert!(expander.digital_write(16, true).is_err());
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_digital_read() {
+        let (simulator, board, mut expander) = _create_expander();
+        assert!(expander.set_pin_mode(0, PinModeId::OUTPUT).is_ok());
+
+        simulator.set_i2c_data(0x20, MCP23017::GPIOA, &[0b0000_0101, 0b0000_0001]);
+        assert!(expander.digital_read(2).unwrap());
+        assert!(expander.digital_read(8).unwrap());
+        assert!(!expander.digital_read(3).unwrap());
+
+        // The values of the inputs are updated at once, not the ones of the outputs.
+        let lock = expander.get_io().read();
+        assert_eq!(lock.get_pin(2).unwrap().value, 1);
+        assert_eq!(lock.get_pin(8).unwrap().value, 1);
+        assert_eq!(lock.get_pin(0).unwrap().value, 0);
+        drop(lock);
+
+        assert!(expander.digital_read(16).is_err());
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_polarity() {
+        let (simulator, board, mut expander) = _create_expander();
+
+        assert!(expander.set_polarity(3, true).is_ok());
+        assert!(expander.set_polarity(12, true).is_ok());
+        assert!(expander.get_polarity(3));
+        assert!(!expander.get_polarity(4));
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::IPOLA, 2),
+            vec![0b0000_1000, 0b0001_0000]
+        );
+
+        assert!(expander.set_polarity(3, false).is_ok());
+        assert!(!expander.get_polarity(3));
+        assert!(expander.set_polarity(16, true).is_err());
+        board.blocking_close().unwrap();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_devices() {
+        let (simulator, board, mut expander) = _create_expander();
+
+        // Outputs.
+        let mut led = Led::new(&expander, 4, false).unwrap();
+        assert!(led.turn_on().is_ok());
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::OLATA, 1),
+            vec![0b0001_0000]
+        );
+        assert!(Led::new(&expander, 4, false).is_err());
+
+        // Inputs are reported when their value changes.
+        let input = DigitalInput::new(&expander, 9).unwrap();
+        let button = Button::new_pullup(&expander, "GPB2").unwrap();
+        assert_eq!(
+            simulator.get_i2c_data(0x20, MCP23017::GPPUA, 2),
+            vec![0b0000_0000, 0b0000_0100]
+        );
+        assert_eq!(expander.get_io().read().digital_reported_pins, vec![9, 10]);
+
+        simulator.set_i2c_data(0x20, MCP23017::GPIOA, &[0b1111_1111, 0b0000_0110]);
+        pause!(200);
+        assert_eq!(input.get_state(), State::Boolean(true));
+        assert_eq!(button.get_state(), State::Boolean(false));
+
+        simulator.set_i2c_data(0x20, MCP23017::GPIOA + 1, &[0b0000_0000]);
+        pause!(200);
+        assert_eq!(input.get_state(), State::Boolean(false));
+        assert_eq!(button.get_state(), State::Boolean(true));
+
+        // Reporting stops with the last reported pin.
+        assert!(expander.report_digital(9, false).is_ok());
+        assert!(expander.handler.read().is_some());
+        assert!(expander.report_digital(10, false).is_ok());
+        assert!(expander.handler.read().is_none());
+
+        input.detach();
+        button.detach();
+        assert!(expander.close().is_ok());
+        assert!(!expander.is_connected());
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = MCP23017::default(&board).unwrap();
+
+        assert!(expander.report_analog(0, true).is_err());
+        assert!(expander.analog_write(0, 128).is_err());
+        assert!(expander.analog_read(0).is_err());
+        assert!(expander.servo_config(0, Range::from([500, 2500])).is_err());
+        assert!(expander.tone(0, 440, 100).is_err());
+        assert!(expander.shift_out(0, 1, 2, &[0xFF]).is_err());
+        assert!(expander.stepper(0, StepperCommand::Zero).is_err());
+        assert!(expander.encoder(0, EncoderCommand::Attach([0, 1])).is_err());
+        assert!(expander.ping_read(0, 5, 1000).is_err());
+        assert!(expander.dht_read(0, DhtModel::Dht22).is_err());
+        assert!(expander.onewire_config(0, true).is_err());
+        assert!(expander.onewire_search(0).is_err());
+        assert!(expander.onewire_reset(0).is_err());
+        assert!(expander.onewire_select(0, [0; 8]).is_err());
+        assert!(expander.onewire_read(0, 9, 0).is_err());
+        assert!(expander.onewire_write(0, [0; 8], &[0x44]).is_err());
+        assert!(expander.onewire_delay(0, 1000).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let board = Board::new(MockIoProtocol::default());
+        let expander = MCP23017::default(&board).unwrap();
+
+        assert_eq!(
+            format!("{}", expander),
+            "MCP23017 [firmware=MCP23017, version=n/a, protocol=MCP23017, transport=I2C]"
+        );
+        assert_eq!(
+            expander.get_protocol().to_string(),
+            "MCP23017 [firmware=MCP23017, version=n/a, protocol=MCP23017, transport=I2C]"
+        );
+    }
+}
@@ -1,12 +1,25 @@
 //! Defines pieces of hardware that can be remotely controlled through IO exchange messages.
 
+mod ads1x15;
 mod board;
+mod i2c;
+mod mcp23017;
 mod pca9685;
+mod pcf8574;
+mod shift_register;
+mod tca9548a;
 
 use crate::io::{IoProtocol, IO};
+pub use ads1x15::{AdsGain, AdsMode, AdsModel, ADS1X15};
 pub use board::Board;
+pub use board::BoardDevice;
 pub use board::BoardEvent;
+pub use i2c::I2cDevice;
+pub use mcp23017::MCP23017;
 pub use pca9685::PCA9685;
+pub use pcf8574::PCF8574;
+pub use shift_register::ShiftRegister;
+pub use tca9548a::{TCA9548AChannel, TCA9548A};
 
 /// You most likely don't need this function (outside this crate).
 pub trait Hardware: IO {
@@ -3,9 +3,9 @@
 // https://www.digikey.jp/htmldatasheets/production/2459480/0/0/1/pca9685.html
 
 use crate::errors::{Error, HardwareError, UnknownError};
-use crate::hardware::{Board, Expander, Hardware};
+use crate::hardware::{Board, Expander, Hardware, I2cDevice};
 use crate::io::{IoData, IoProtocol, Pin, PinMode, PinModeId, IO};
-use crate::utils::{Range, Scalable};
+use crate::utils::Range;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
@@ -16,13 +16,14 @@ use std::sync::Arc;
 pub struct PCA9685 {
     // Address (default 0x40).
     address: u8,
-    // Frequency in Mhz (default 50Mhz).
-    frequency: u16,
+    // PWM frequency in Hz shared by all channels (default 50Hz).
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    frequency: Arc<RwLock<u16>>,
 
     // ########################################
     // # Volatile utility data.
     #[cfg_attr(feature = "serde", serde(skip))]
-    servo_configs: HashMap<u8, Range<u16>>,
+    servo_configs: Arc<RwLock<HashMap<u8, Range<u16>>>>,
     #[cfg_attr(feature = "serde", serde(skip))]
     data: Arc<RwLock<IoData>>,
     #[cfg_attr(feature = "serde", serde(skip))]
@@ -43,17 +44,19 @@ impl PCA9685 {
     const MIN_FREQUENCY: u16 = 24; // Minimum frequency in Hz
     const MAX_FREQUENCY: u16 = 1526; // Maximum frequency in Hz
     const OSC_CLOCK: f32 = 25_000_000.0; // PCA9685 clock frequency
+    const STEPS: u16 = 4096; // 12-bit resolution of a PWM period
+
+    // Defaults.
+    const DEFAULT_FREQUENCY: u16 = 50; // Suitable for servos (and LEDs as well) in Hz
+    const DEFAULT_SERVO_PULSE: u16 = 2400; // Longest servo pulse with no configured range in µs
 
     fn _build_pca9685_data() -> IoData {
         let mut data = IoData {
-            pins: Default::default(),
-            i2c_data: vec![],
-            digital_reported_pins: vec![],
-            analog_reported_channels: vec![],
             protocol_version: "PCA9685".to_string(),
             firmware_name: "PCA9685".to_string(),
             firmware_version: "n/a".to_string(),
             connected: false,
+            ..Default::default()
         };
 
         for id in 0..16 {
@@ -70,7 +73,7 @@ impl PCA9685 {
                         },
                         PinMode {
                             id: PinModeId::PWM,
-                            resolution: 8,
+                            resolution: 12,
                         },
                         PinMode {
                             id: PinModeId::SERVO,
@@ -78,7 +81,7 @@ impl PCA9685 {
                         },
                         PinMode {
                             id: PinModeId::ANALOG,
-                            resolution: 8,
+                            resolution: 12,
                         },
                         PinMode {
                             id: PinModeId::UNSUPPORTED,
@@ -102,7 +105,7 @@ impl PCA9685 {
         let protocol = board.get_protocol();
         let mut expander = Self {
             address,
-            frequency: 50,
+            frequency: Arc::new(RwLock::new(PCA9685::DEFAULT_FREQUENCY)),
             servo_configs: Default::default(),
             data: Arc::new(RwLock::new(PCA9685::_build_pca9685_data())),
             protocol,
@@ -116,10 +119,12 @@ impl PCA9685 {
     }
 
     pub fn get_frequency(&self) -> u16 {
-        self.frequency
+        *self.frequency.read()
     }
 
     // Sets the PWM frequency (in Hz) for the entire PCA9685: from 24 to 1526 Hz.
+    // All 16 channels share this frequency: it is not changed when configuring a pin, and is rejected
+    // if the pulses of the servos attached to the chip would not fit in a period anymore.
     pub fn set_frequency(&mut self, frequency: u16) -> Result<&Self, Error> {
         // Validate frequency range
         if !(Self::MIN_FREQUENCY..=Self::MAX_FREQUENCY).contains(&frequency) {
@@ -132,7 +137,26 @@ impl PCA9685 {
             });
         };
 
-        self.frequency = frequency;
+        // Validate the attached servos still work at this frequency.
+        let incompatible = self
+            .data
+            .read()
+            .pins
+            .values()
+            .filter(|pin| pin.mode.id == PinModeId::SERVO)
+            .map(|pin| pin.id)
+            .filter(|&pin| !self.fits_servo_pulse(pin, frequency))
+            .min();
+        if let Some(pin) = incompatible {
+            return Err(HardwareError::IncompatiblePin {
+                pin,
+                mode: PinModeId::SERVO,
+                context: "servo pulses do not fit the PCA9685 frequency",
+            }
+            .into());
+        }
+
+        *self.frequency.write() = frequency;
 
         // 7.3.1 Mode register 1, MODE1 - Reset / Sleep
         // Sets the register mode to reset, than sleep.
@@ -140,11 +164,7 @@ impl PCA9685 {
         self.write_to_reg(PCA9685::MODE1, PCA9685::SLEEP)?;
 
         // 7.3.5 PWM frequency PRE_SCALE
-        // prescale = round((osc_clock / (4096 x rate)) - 1) - with PCA9685 clock at 25Mhz
-        // Calculate the prescale value for the desired frequency
-        let prescale = ((PCA9685::OSC_CLOCK / (4096.0 * self.frequency as f32)) + 0.5 - 1.0)
-            .clamp(3.0, 255.0) as u8;
-        self.write_to_reg(PCA9685::PRESCALE, prescale)?;
+        self.write_to_reg(PCA9685::PRESCALE, PCA9685::prescale(frequency))?;
 
         // Wake up and restart in auto-increment mode
         self.write_to_reg(PCA9685::MODE1, PCA9685::RESET)?;
@@ -162,19 +182,48 @@ impl PCA9685 {
         Ok(self)
     }
 
+    // prescale = round((osc_clock / (4096 x rate)) - 1) - with PCA9685 clock at 25Mhz
+    fn prescale(frequency: u16) -> u8 {
+        ((PCA9685::OSC_CLOCK / (PCA9685::STEPS as f32 * frequency as f32)) + 0.5 - 1.0)
+            .clamp(3.0, 255.0) as u8
+    }
+
+    // Returns the actual output frequency (in Hz) for a requested one, as rounded by the prescale.
+    fn actual_frequency(frequency: u16) -> f32 {
+        PCA9685::OSC_CLOCK / (PCA9685::STEPS as f32 * (PCA9685::prescale(frequency) as f32 + 1.0))
+    }
+
+    // Checks the longest pulse of the servo on the given pin fits in a period at the given frequency.
+    fn fits_servo_pulse(&self, pin: u8, frequency: u16) -> bool {
+        let pulse = self
+            .servo_configs
+            .read()
+            .get(&pin)
+            .map_or(PCA9685::DEFAULT_SERVO_PULSE, |range| range.end);
+        (pulse as f32) < 1_000_000.0 / PCA9685::actual_frequency(frequency)
+    }
+
+    // Converts a servo pulse width (in µs) to a number of steps of a period at the current frequency.
+    fn servo_steps(&self, pin: u8, pulse: u16) -> u16 {
+        let pulse = match self.servo_configs.read().get(&pin) {
+            Some(range) => pulse.clamp(range.start, range.end),
+            None => pulse,
+        };
+        let period = 1_000_000.0 / PCA9685::actual_frequency(self.get_frequency());
+        ((pulse as f32 * PCA9685::STEPS as f32 / period).round() as u16).min(PCA9685::STEPS - 1)
+    }
+
     pub fn write_to_reg(&mut self, register: u8, value: u8) -> Result<(), Error> {
-        self.protocol
-            .i2c_write(self.address, &[register as u16, value as u16])
+        self.i2c_device().write_u8(register, value)
     }
 
     pub fn read_from_reg(&mut self, register: u8) -> Result<u8, Error> {
-        self.i2c_write(self.address, &[register as u16])?;
-        self.i2c_read(self.address, 1)?;
-        let register_value = {
-            let lock = self.protocol.get_io().read();
-            *lock.i2c_data.last().unwrap().data.last().unwrap()
-        };
-        Ok(register_value)
+        self.i2c_device().read_u8(register)
+    }
+
+    // Register-level access to the chip, on the bus of the parent board.
+    fn i2c_device(&self) -> I2cDevice {
+        I2cDevice::from_protocol(self.protocol.clone(), self.address)
     }
 }
 
@@ -195,6 +244,7 @@ impl Hardware for PCA9685 {
 impl IoProtocol for PCA9685 {
     fn open(&mut self) -> Result<(), Error> {
         self.i2c_config(0)?;
+        self.set_frequency(self.get_frequency())?;
         self.data.write().connected = true;
         Ok(())
     }
@@ -230,72 +280,63 @@ impl IO for PCA9685 {
         self.data.read().connected
     }
 
+    /// Sets the mode of the pin: the frequency of the chip is left unchanged (see [`PCA9685::set_frequency`]).
+    ///
+    /// # Errors
+    /// * `IncompatiblePin`: the mode is not supported, or the pin is set as a servo while the pulses do
+    ///   not fit in a period at the current frequency.
     fn set_pin_mode(&mut self, pin: u8, mode: PinModeId) -> Result<(), Error> {
-        {
-            let mut lock = self.data.write();
-            let pin_instance = lock.get_pin_mut(pin)?;
-            let _mode = pin_instance
-                .supports_mode(mode)
-                .ok_or(HardwareError::IncompatiblePin {
-                    pin,
-                    mode,
-                    context: "try to set pin mode",
-                })?;
-            pin_instance.mode = _mode;
+        let _mode = self.data.read().get_pin(pin)?.supports_mode(mode).ok_or(
+            HardwareError::IncompatiblePin {
+                pin,
+                mode,
+                context: "try to set pin mode",
+            },
+        )?;
+        if mode == PinModeId::SERVO && !self.fits_servo_pulse(pin, self.get_frequency()) {
+            return Err(HardwareError::IncompatiblePin {
+                pin,
+                mode,
+                context: "servo pulses do not fit the PCA9685 frequency",
+            }
+            .into());
         }
+        self.data.write().get_pin_mut(pin)?.mode = _mode;
 
         // Special hack: unsupported should disable the pin, hence send no signal at all.
         if mode == PinModeId::UNSUPPORTED {
             let payload = &[(PCA9685::BASE + 4 * pin) as u16, 0, 0, 4096, 4096 >> 8];
             self.protocol.i2c_write(self.address, payload)?;
-            return Ok(());
         }
 
-        // Arbitrary selection of frequencies depending on pin mode.
-        let frequency: u16 = match mode {
-            PinModeId::OUTPUT => Ok(300), // Typical frequency to control a dimmable led.
-            PinModeId::ANALOG => Ok(30),  // Typical frequency to control a fan.
-            PinModeId::PWM => Ok(300),    // Typical frequency to control a dimmable led.
-            PinModeId::SERVO => Ok(50),
-            _ => Err(Error::from(HardwareError::IncompatiblePin {
-                mode,
-                pin,
-                context: "update digital output",
-            })),
-        }?;
-        self.set_frequency(frequency)?;
-
         Ok(())
     }
 
     fn digital_write(&mut self, pin: u8, level: bool) -> Result<(), Error> {
-        let value = if level { 255 } else { 0 };
+        let value = if level { PCA9685::STEPS - 1 } else { 0 };
         self.analog_write(pin, value)
     }
 
+    /// Writes the `level` of the pin: a pulse width (in µs) for servos, a 12-bit duty cycle (0-4095) otherwise.
     fn analog_write(&mut self, pin: u8, level: u16) -> Result<(), Error> {
-        {
+        let mode = {
             let mut lock = self.data.write();
             // Check if pin exists
             let pin_instance = lock.get_pin_mut(pin)?;
             // Store the value we will write to the current pin.
             pin_instance.value = level;
+            pin_instance.mode.id
         };
 
         // 7.3.3 LED output and PWM control
         // Creates a square signal on pin output.
-        let servo_range = self.servo_configs.get(&pin);
-
-        let (on, off): (u16, u16) = match servo_range {
-            Some(_) => (0, (level as f32 / 4.88) as u16),
-            None => {
-                let level = level.clamp(0, 255);
-                match level {
-                    0 => (0, 4096),
-                    255 => (4096, 0),
-                    level => (0, level.scale(0, 255, 0, 4095)),
-                }
-            }
+        let (on, off): (u16, u16) = match mode {
+            PinModeId::SERVO => (0, self.servo_steps(pin, level)),
+            _ => match level.min(PCA9685::STEPS - 1) {
+                0 => (0, 4096),
+                4095 => (4096, 0),
+                level => (0, level),
+            },
         };
 
         // The register corresponding to the pin (0-16) starts at BASE
@@ -312,27 +353,67 @@ impl IO for PCA9685 {
         self.protocol.i2c_write(self.address, payload)
     }
 
-    #[cfg(not(tarpaulin_include))]
-    fn digital_read(&mut self, _: u8) -> Result<bool, Error> {
-        unimplemented!()
+    /// Returns the stored value of the pin (the PCA9685 is an output-only device).
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        Ok(self.data.read().get_pin(pin)?.value != 0)
     }
 
-    #[cfg(not(tarpaulin_include))]
-    fn analog_read(&mut self, _: u8) -> Result<u16, Error> {
-        unimplemented!()
+    /// Returns the stored value of the pin (the PCA9685 is an output-only device).
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        Ok(self.data.read().get_pin(pin)?.value)
     }
 
+    /// Configures the pulse range (in µs) of the servo on the pin.
+    ///
+    /// # Errors
+    /// * `IncompatiblePin`: the pulses do not fit in a period at the current frequency.
     fn servo_config(&mut self, pin: u8, pwm_range: Range<u16>) -> Result<(), Error> {
-        self.servo_configs.insert(pin, pwm_range);
+        self.data.read().get_pin(pin)?;
+        let previous = self.servo_configs.write().insert(pin, pwm_range);
+        if !self.fits_servo_pulse(pin, self.get_frequency()) {
+            match previous {
+                Some(previous) => self.servo_configs.write().insert(pin, previous),
+                None => self.servo_configs.write().remove(&pin),
+            };
+            return Err(HardwareError::IncompatiblePin {
+                pin,
+                mode: PinModeId::SERVO,
+                context: "servo pulses do not fit the PCA9685 frequency",
+            }
+            .into());
+        }
         Ok(())
     }
 
+    /// The PCA9685 cannot shift out data.
+    fn shift_out(&mut self, data_pin: u8, _: u8, _: u8, _: &[u8]) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin: data_pin,
+            mode: PinModeId::SHIFT,
+            context: "shift out data",
+        }
+        .into())
+    }
+
     fn i2c_config(&mut self, delay: u16) -> Result<(), Error> {
         self.protocol.i2c_config(delay)
     }
 
-    fn i2c_read(&mut self, address: u8, size: u16) -> Result<(), Error> {
-        self.protocol.i2c_read(address, size)
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error> {
+        self.protocol.i2c_read(address, register, size)
+    }
+
+    fn i2c_read_continuously(
+        &mut self,
+        address: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error> {
+        self.protocol.i2c_read_continuously(address, register, size)
+    }
+
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error> {
+        self.protocol.i2c_stop_reading(address)
     }
 
     fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error> {
@@ -356,7 +437,7 @@ impl Display for PCA9685 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::io::RemoteIo;
+    use crate::io::{DhtModel, EncoderCommand, RemoteIo, StepperCommand};
     use crate::mocks::create_test_plugin_io_data;
     use crate::mocks::plugin_io::MockIoProtocol;
     use crate::mocks::transport_layer::MockTransportLayer;
@@ -376,7 +457,7 @@ mod tests {
         let pca9685 = PCA9685::default(&board).unwrap();
 
         assert_eq!(pca9685.address, 0x40);
-        assert_eq!(pca9685.frequency, 50);
+        assert_eq!(pca9685.get_frequency(), 50);
     }
 
     #[test]
@@ -385,7 +466,7 @@ mod tests {
         let pca9685 = PCA9685::new(&board, 0x41).unwrap();
 
         assert_eq!(pca9685.address, 0x41);
-        assert_eq!(pca9685.frequency, 50);
+        assert_eq!(pca9685.get_frequency(), 50);
     }
 
     #[test]
@@ -394,7 +475,7 @@ mod tests {
         let mut pca9685 = PCA9685::default(&board).unwrap();
 
         assert!(pca9685.set_frequency(100).is_ok());
-        assert_eq!(pca9685.frequency, 100);
+        assert_eq!(pca9685.get_frequency(), 100);
     }
 
     #[test]
@@ -465,21 +546,42 @@ mod tests {
         let board = Board::new(MockIoProtocol::default());
         let mut pca9685 = PCA9685::default(&board).unwrap();
 
-        // Test setting pin mode to OUTPUT
+        // Pin modes do not change the frequency of the chip.
         assert!(pca9685.set_pin_mode(0, PinModeId::OUTPUT).is_ok());
-        assert_eq!(pca9685.get_frequency(), 300);
-
-        // Test setting pin mode to SERVO
         assert!(pca9685.set_pin_mode(1, PinModeId::SERVO).is_ok());
+        assert!(pca9685.set_pin_mode(2, PinModeId::ANALOG).is_ok());
+        assert!(pca9685.set_pin_mode(3, PinModeId::PWM).is_ok());
         assert_eq!(pca9685.get_frequency(), 50);
+        assert_eq!(
+            pca9685.data.read().get_pin(1).unwrap().mode.id,
+            PinModeId::SERVO
+        );
 
-        // Test setting pin mode to ANALOG
-        assert!(pca9685.set_pin_mode(1, PinModeId::ANALOG).is_ok());
-        assert_eq!(pca9685.get_frequency(), 30);
+        // The frequency cannot be too high for an attached servo...
+        let result = pca9685.set_frequency(1000);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Hardware error: Pin (1) not compatible with mode (SERVO) - servo pulses do not fit the PCA9685 frequency."
+        );
+        assert_eq!(pca9685.get_frequency(), 50);
 
-        // Test setting pin mode to PWM
+        // ... and a servo cannot be attached when the frequency is too high.
         assert!(pca9685.set_pin_mode(1, PinModeId::PWM).is_ok());
-        assert_eq!(pca9685.get_frequency(), 300);
+        assert!(pca9685.set_frequency(1000).is_ok());
+        let result = pca9685.set_pin_mode(1, PinModeId::SERVO);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Pin (1) not compatible with mode (SERVO) - servo pulses do not fit the PCA9685 frequency."
+        );
+        assert_eq!(
+            pca9685.data.read().get_pin(1).unwrap().mode.id,
+            PinModeId::PWM
+        );
+
+        // The frequency is shared by all clones.
+        let clone = pca9685.clone();
+        assert!(pca9685.set_frequency(300).is_ok());
+        assert_eq!(clone.get_frequency(), 300);
 
         // Test setting an invalid mode
         let result = pca9685.set_pin_mode(2, PinModeId::DHT);
@@ -497,7 +599,7 @@ mod tests {
 
         assert!(pca9685.digital_write(1, true).is_ok());
         let value = pca9685.data.read().get_pin(1).unwrap().value;
-        assert_eq!(value, 255);
+        assert_eq!(value, 4095);
 
         assert!(pca9685.digital_write(1, false).is_ok());
         let value = pca9685.data.read().get_pin(1).unwrap().value;
@@ -517,9 +619,9 @@ mod tests {
         let value = pca9685.data.read().get_pin(0).unwrap().value;
         assert_eq!(value, 0);
 
-        assert!(pca9685.analog_write(0, 255).is_ok());
+        assert!(pca9685.analog_write(0, 4095).is_ok());
         let value = pca9685.data.read().get_pin(0).unwrap().value;
-        assert_eq!(value, 255);
+        assert_eq!(value, 4095);
 
         pca9685.data.write().get_pin_mut(1).unwrap().mode.id = PinModeId::SERVO;
         assert!(pca9685.servo_config(1, Range::from([300, 600])).is_ok());
@@ -528,6 +630,45 @@ mod tests {
         assert_eq!(value, 128);
     }
 
+    #[test]
+    fn test_servo_steps() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut pca9685 = PCA9685::default(&board).unwrap();
+
+        // The actual frequency is rounded by the prescale: 50.03Hz for 50Hz.
+        assert_eq!(PCA9685::prescale(50), 121);
+        assert!((PCA9685::actual_frequency(50) - 50.03).abs() < 0.01);
+
+        // Pulse widths are computed from the actual frequency (~19988µs period at 50Hz).
+        assert_eq!(pca9685.servo_steps(0, 1500), 307);
+        assert_eq!(pca9685.servo_steps(0, 30000), 4095);
+
+        // ... and limited to the configured pulse range.
+        assert!(pca9685.servo_config(0, Range::from([1000, 2000])).is_ok());
+        assert_eq!(pca9685.servo_steps(0, 500), 205);
+        assert_eq!(pca9685.servo_steps(0, 2500), 410);
+
+        assert!(pca9685.set_frequency(100).is_ok());
+        assert_eq!(pca9685.servo_steps(0, 1500), 615);
+    }
+
+    #[test]
+    fn test_read() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut pca9685 = PCA9685::default(&board).unwrap();
+
+        assert!(pca9685.analog_write(2, 128).is_ok());
+        assert_eq!(pca9685.analog_read(2).unwrap(), 128);
+        assert!(pca9685.digital_read(2).unwrap());
+
+        assert!(pca9685.digital_write(2, false).is_ok());
+        assert_eq!(pca9685.analog_read(2).unwrap(), 0);
+        assert!(!pca9685.digital_read(2).unwrap());
+
+        assert!(pca9685.analog_read(42).is_err());
+        assert!(pca9685.digital_read(42).is_err());
+    }
+
     #[test]
     fn test_servo_config() {
         let board = Board::new(MockIoProtocol::default());
@@ -538,9 +679,69 @@ mod tests {
         assert!(pca9685.servo_config(0, pwm_range).is_ok());
 
         // Verify servo config
-        assert!(pca9685.servo_configs.contains_key(&0));
-        assert_eq!(pca9685.servo_configs.get(&0).unwrap().start, 1000);
-        assert_eq!(pca9685.servo_configs.get(&0).unwrap().end, 2000);
+        assert!(pca9685.servo_configs.read().contains_key(&0));
+        assert_eq!(pca9685.servo_configs.read().get(&0).unwrap().start, 1000);
+        assert_eq!(pca9685.servo_configs.read().get(&0).unwrap().end, 2000);
+
+        // Pulses longer than a period are rejected (1000Hz = 1000µs).
+        assert!(pca9685.set_frequency(1000).is_ok());
+        let result = pca9685.servo_config(0, Range::from([500, 2400]));
+        assert!(result.is_err());
+        assert_eq!(pca9685.servo_configs.read().get(&0).unwrap().end, 2000);
+        let result = pca9685.servo_config(1, Range::from([500, 2400]));
+        assert!(result.is_err());
+        assert!(!pca9685.servo_configs.read().contains_key(&1));
+
+        assert!(pca9685.servo_config(42, pwm_range).is_err());
+    }
+
+    #[test]
+    fn test_tone() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut pca9685 = PCA9685::default(&board).unwrap();
+        assert!(pca9685.tone(0, 440, 100).is_err());
+        assert!(pca9685.shift_out(0, 1, 2, &[0xFF]).is_err());
+    }
+
+    #[test]
+    fn test_stepper() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut pca9685 = PCA9685::default(&board).unwrap();
+        assert!(pca9685.stepper(0, StepperCommand::Zero).is_err());
+    }
+
+    #[test]
+    fn test_encoder() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut pca9685 = PCA9685::default(&board).unwrap();
+        assert!(pca9685.encoder(0, EncoderCommand::Attach([0, 1])).is_err());
+    }
+
+    #[test]
+    fn test_ping_read() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut pca9685 = PCA9685::default(&board).unwrap();
+        assert!(pca9685.ping_read(0, 5, 1000).is_err());
+    }
+
+    #[test]
+    fn test_dht_read() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut pca9685 = PCA9685::default(&board).unwrap();
+        assert!(pca9685.dht_read(0, DhtModel::Dht22).is_err());
+    }
+
+    #[test]
+    fn test_onewire() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut pca9685 = PCA9685::default(&board).unwrap();
+        assert!(pca9685.onewire_config(0, true).is_err());
+        assert!(pca9685.onewire_search(0).is_err());
+        assert!(pca9685.onewire_reset(0).is_err());
+        assert!(pca9685.onewire_select(0, [0; 8]).is_err());
+        assert!(pca9685.onewire_read(0, 9, 0).is_err());
+        assert!(pca9685.onewire_write(0, [0; 8], &[0x44]).is_err());
+        assert!(pca9685.onewire_delay(0, 1000).is_err());
     }
 
     #[test]
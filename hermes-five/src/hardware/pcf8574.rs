@@ -0,0 +1,579 @@
+// ***********
+// All information are relative to PCF8574 datasheet:
+// https://www.ti.com/lit/ds/symlink/pcf8574.pdf
+
+use crate::errors::{Error, HardwareError};
+use crate::hardware::{Board, Expander, Hardware, I2cDevice};
+use crate::io::{IoData, IoProtocol, Pin, PinMode, PinModeId, IO};
+use crate::utils::{task, Range, TaskHandler};
+use parking_lot::RwLock;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Represents a PCF8574 8 pins GPIO expander (P0-P7).
+///
+/// The pins are quasi-bidirectional: a pin written HIGH is only weakly pulled-up, hence can be read
+/// as an input (INPUT and PULLUP modes are therefore equivalent), while a pin written LOW is a
+/// strong output. The inputs are reported by a continuous read of the chip (see [`IoProtocol::report_digital`])
+/// hence devices can be created on the expander pins exactly as on a [`Board`].
+///
+/// # Example
+/// ```no_run
+/// use hermes_five::devices::{DigitalInput, DigitalOutput, InputEvent};
+/// use hermes_five::hardware::{Board, BoardEvent, PCF8574};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let board = Board::run();
+///     board.on(BoardEvent::OnReady, |board: Board| async move {
+///         let expander = PCF8574::default(&board)?;
+///         let output = DigitalOutput::new(&expander, 0, false)?;
+///         let input = DigitalInput::new(&expander, "P7")?;
+///         input.on(InputEvent::OnChange, move |_: bool| {
+///             let mut output = output.clone();
+///             async move {
+///                 output.toggle()?;
+///                 Ok(())
+///             }
+///         });
+///         Ok(())
+///     });
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct PCF8574 {
+    // Address (default 0x20).
+    address: u8,
+
+    // ########################################
+    // # Volatile utility data.
+    // Levels written to the pins (one bit per pin: HIGH for inputs).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    latch: Arc<RwLock<u8>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data: Arc<RwLock<IoData>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    // Inner handler to the task reporting the inputs values.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    handler: Arc<RwLock<Option<TaskHandler>>>,
+}
+
+impl PCF8574 {
+    fn _build_pcf8574_data() -> IoData {
+        let mut data = IoData {
+            protocol_version: "PCF8574".to_string(),
+            firmware_name: "PCF8574".to_string(),
+            firmware_version: "n/a".to_string(),
+            connected: false,
+            ..Default::default()
+        };
+
+        for id in 0..8 {
+            // All pins are (weakly pulled-up) inputs at power-up.
+            let input = PinMode {
+                id: PinModeId::INPUT,
+                resolution: 1,
+            };
+            data.pins.insert(
+                id,
+                Pin {
+                    id,
+                    name: format!("P{}", id),
+                    mode: input,
+                    supported_modes: vec![
+                        PinMode {
+                            id: PinModeId::OUTPUT,
+                            resolution: 1,
+                        },
+                        input,
+                        PinMode {
+                            id: PinModeId::PULLUP,
+                            resolution: 1,
+                        },
+                    ],
+                    channel: None,
+                    value: 0,
+                },
+            );
+        }
+
+        data
+    }
+
+    pub fn default(board: &Board) -> Result<Self, Error> {
+        PCF8574::new(board, 0x20)
+    }
+
+    pub fn new(board: &dyn Hardware, address: u8) -> Result<Self, Error> {
+        let protocol = board.get_protocol();
+        let mut expander = Self {
+            address,
+            latch: Arc::new(RwLock::new(0xFF)),
+            data: Arc::new(RwLock::new(PCF8574::_build_pcf8574_data())),
+            protocol,
+            handler: Arc::new(RwLock::new(None)),
+        };
+        IoProtocol::open(&mut expander)?;
+        Ok(expander)
+    }
+
+    pub fn get_address(&self) -> u8 {
+        self.address
+    }
+
+    // Sets the level of the pin in the latch, and writes it to the chip.
+    fn write_latch(&mut self, pin: u8, level: bool) -> Result<(), Error> {
+        let latch = {
+            let mut latch = self.latch.write();
+            match level {
+                true => *latch |= 1 << pin,
+                false => *latch &= !(1 << pin),
+            }
+            *latch
+        };
+        self.i2c_device().write(&[latch])
+    }
+
+    // Stores the values read from the chip in the input pins (the subscribers are notified of the changes).
+    fn update_inputs(data: &Arc<RwLock<IoData>>, values: u8) -> Result<(), Error> {
+        let mut data = data.write();
+        for pin in 0..8 {
+            if matches!(
+                data.get_pin(pin)?.mode.id,
+                PinModeId::INPUT | PinModeId::PULLUP
+            ) {
+                data.set_pin_value(pin, ((values >> pin) & 1) as u16)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Starts the continuous read of the chip, and the task reporting the inputs values.
+    fn start_reporting(&mut self) -> Result<(), Error> {
+        let mut replies = self.protocol.get_io().read().subscribe_i2c_replies();
+        self.protocol.i2c_read_continuously(self.address, None, 1)?;
+
+        let address = self.address;
+        let data = self.data.clone();
+        *self.handler.write() = Some(task::run(async move {
+            loop {
+                match replies.recv().await {
+                    // The chip has no register: any reply from its address is a read of its pins.
+                    Ok(reply) if reply.address == address && !reply.data.is_empty() => {
+                        PCF8574::update_inputs(&data, reply.data[0])?;
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        })?);
+        Ok(())
+    }
+
+    // Stops the continuous read of the chip.
+    fn stop_reporting(&mut self) -> Result<(), Error> {
+        if let Some(handler) = self.handler.write().take() {
+            handler.abort();
+        }
+        self.protocol.i2c_stop_reading(self.address)
+    }
+
+    // Byte-level access to the chip, on the bus of the parent board.
+    fn i2c_device(&self) -> I2cDevice {
+        I2cDevice::from_protocol(self.protocol.clone(), self.address)
+    }
+}
+
+impl Expander for PCF8574 {}
+
+impl Hardware for PCF8574 {
+    fn get_protocol(&self) -> Box<dyn IoProtocol> {
+        Box::new(self.clone())
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    fn set_protocol(&mut self, protocol: Box<dyn IoProtocol>) {
+        self.protocol = protocol;
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl IoProtocol for PCF8574 {
+    fn open(&mut self) -> Result<(), Error> {
+        self.i2c_config(0)?;
+        let latch = *self.latch.read();
+        self.i2c_device().write(&[latch])?;
+        self.data.write().connected = true;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        self.data.write().digital_reported_pins.clear();
+        if self.handler.read().is_some() {
+            self.stop_reporting()?;
+        }
+        self.data.write().connected = false;
+        Ok(())
+    }
+
+    /// The PCF8574 has no analog input.
+    fn report_analog(&mut self, _: u8, _: bool) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "report analog value",
+        }
+        .into())
+    }
+
+    /// Sets the digital reporting `state` of the pin: the inputs are reported by a continuous read of the
+    /// chip while at least one pin is reported.
+    fn report_digital(&mut self, pin: u8, state: bool) -> Result<(), Error> {
+        let reporting = {
+            let mut lock = self.data.write();
+            lock.get_pin(pin)?;
+            lock.digital_reported_pins
+                .retain(|&reported| reported != pin);
+            if state {
+                lock.digital_reported_pins.push(pin);
+            }
+            !lock.digital_reported_pins.is_empty()
+        };
+        let running = self.handler.read().is_some();
+        match (reporting, running) {
+            (true, false) => self.start_reporting(),
+            (false, true) => self.stop_reporting(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets the sampling interval of the parent board (hence of the inputs reporting).
+    fn sampling_interval(&mut self, interval: u16) -> Result<(), Error> {
+        self.protocol.sampling_interval(interval)
+    }
+}
+
+impl IO for PCF8574 {
+    fn get_io(&self) -> &Arc<RwLock<IoData>> {
+        &self.data
+    }
+
+    fn is_connected(&self) -> bool {
+        self.data.read().connected
+    }
+
+    /// Sets the mode of the pin: inputs are written HIGH (weak pull-up), outputs keep their stored level.
+    ///
+    /// # Errors
+    /// * `IncompatiblePin`: the mode is not supported.
+    fn set_pin_mode(&mut self, pin: u8, mode: PinModeId) -> Result<(), Error> {
+        let (_mode, value) = {
+            let lock = self.data.read();
+            let pin_instance = lock.get_pin(pin)?;
+            let _mode = pin_instance
+                .supports_mode(mode)
+                .ok_or(HardwareError::IncompatiblePin {
+                    pin,
+                    mode,
+                    context: "try to set pin mode",
+                })?;
+            (_mode, pin_instance.value)
+        };
+
+        let level = mode != PinModeId::OUTPUT || value != 0;
+        self.write_latch(pin, level)?;
+
+        self.data.write().get_pin_mut(pin)?.mode = _mode;
+        Ok(())
+    }
+
+    fn digital_write(&mut self, pin: u8, level: bool) -> Result<(), Error> {
+        self.data.write().get_pin_mut(pin)?.value = level as u16;
+        self.write_latch(pin, level)
+    }
+
+    /// The PCF8574 has no PWM output.
+    fn analog_write(&mut self, pin: u8, _: u16) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::PWM,
+            context: "write analog value",
+        }
+        .into())
+    }
+
+    /// Reads the level of the pin from the chip: the values of all the input pins are updated at once.
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        self.data.read().get_pin(pin)?;
+        let values = self.i2c_device().read(1)?[0];
+        PCF8574::update_inputs(&self.data, values)?;
+        Ok((values >> pin) & 1 != 0)
+    }
+
+    /// The PCF8574 has no analog input.
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::ANALOG,
+            context: "read analog value",
+        }
+        .into())
+    }
+
+    /// The PCF8574 cannot control servos.
+    fn servo_config(&mut self, pin: u8, _: Range<u16>) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::SERVO,
+            context: "configure servo",
+        }
+        .into())
+    }
+
+    /// The PCF8574 cannot shift out data.
+    fn shift_out(&mut self, data_pin: u8, _: u8, _: u8, _: &[u8]) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin: data_pin,
+            mode: PinModeId::SHIFT,
+            context: "shift out data",
+        }
+        .into())
+    }
+
+    fn i2c_config(&mut self, delay: u16) -> Result<(), Error> {
+        self.protocol.i2c_config(delay)
+    }
+
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error> {
+        self.protocol.i2c_read(address, register, size)
+    }
+
+    fn i2c_read_continuously(
+        &mut self,
+        address: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error> {
+        self.protocol.i2c_read_continuously(address, register, size)
+    }
+
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error> {
+        self.protocol.i2c_stop_reading(address)
+    }
+
+    fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error> {
+        self.protocol.i2c_write(address, data)
+    }
+}
+
+impl Display for PCF8574 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let data = self.data.read();
+        write!(
+            f,
+            "{} [firmware={}, version={}, protocol={}, transport=I2C]",
+            self.get_name(),
+            data.firmware_name,
+            data.firmware_version,
+            data.protocol_version,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{Button, DigitalInput, DigitalOutput, Input};
+    use crate::io::{DhtModel, EncoderCommand, I2CReply, StepperCommand};
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+    use crate::utils::State;
+
+    // Simulates a read of the pins of the chip, as reported by the board.
+    fn _reply(board: &Board, values: u8) {
+        board.get_io().write().set_i2c_reply(I2CReply {
+            address: 0x20,
+            register: 0,
+            data: vec![values],
+        });
+    }
+
+    #[test]
+    fn test_helper() {
+        let data = PCF8574::_build_pcf8574_data();
+        assert_eq!(data.firmware_name, "PCF8574");
+        assert_eq!(data.protocol_version, "PCF8574");
+        assert_eq!(data.pins.len(), 8);
+        assert_eq!(data.get_pin("P7").unwrap().id, 7);
+        assert_eq!(data.get_pin(3).unwrap().mode.id, PinModeId::INPUT);
+    }
+
+    #[test]
+    fn test_initialization() {
+        let board = Board::new(MockIoProtocol::default());
+        let expander = PCF8574::default(&board).unwrap();
+        assert_eq!(expander.get_address(), 0x20);
+        assert_eq!(*expander.latch.read(), 0xFF);
+        assert!(expander.is_connected());
+
+        let expander = PCF8574::new(&board, 0x38).unwrap();
+        assert_eq!(expander.get_address(), 0x38);
+    }
+
+    #[test]
+    fn test_set_pin_mode() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = PCF8574::default(&board).unwrap();
+
+        // Outputs keep their stored level, inputs are written HIGH.
+        assert!(expander.set_pin_mode(0, PinModeId::OUTPUT).is_ok());
+        assert_eq!(*expander.latch.read(), 0b1111_1110);
+        assert!(expander.digital_write(0, true).is_ok());
+        assert!(expander.set_pin_mode(0, PinModeId::OUTPUT).is_ok());
+        assert_eq!(*expander.latch.read(), 0b1111_1111);
+        assert!(expander.digital_write(0, false).is_ok());
+        assert!(expander.set_pin_mode(0, PinModeId::PULLUP).is_ok());
+        assert_eq!(*expander.latch.read(), 0b1111_1111);
+        assert_eq!(
+            expander.get_io().read().get_pin(0).unwrap().mode.id,
+            PinModeId::PULLUP
+        );
+
+        let result = expander.set_pin_mode(2, PinModeId::PWM);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Pin (2) not compatible with mode (PWM) - try to set pin mode."
+        );
+        assert!(expander.set_pin_mode(8, PinModeId::OUTPUT).is_err());
+    }
+
+    #[test]
+    fn test_digital_write() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = PCF8574::default(&board).unwrap();
+
+        assert!(expander.digital_write(1, false).is_ok());
+        assert!(expander.digital_write(6, false).is_ok());
+        assert_eq!(*expander.latch.read(), 0b1011_1101);
+        assert_eq!(expander.get_io().read().get_pin(1).unwrap().value, 0);
+
+        // The latch is shared by all clones.
+        let mut clone = expander.clone();
+        assert!(clone.digital_write(1, true).is_ok());
+        assert_eq!(*expander.latch.read(), 0b1011_1111);
+        assert!(expander.digital_write(8, true).is_err());
+    }
+
+    #[test]
+    fn test_digital_read() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = PCF8574::default(&board).unwrap();
+        assert!(expander.set_pin_mode(0, PinModeId::OUTPUT).is_ok());
+
+        _reply(&board, 0b1000_0101);
+        assert!(expander.digital_read(2).unwrap());
+        _reply(&board, 0b1000_0101);
+        assert!(!expander.digital_read(3).unwrap());
+
+        // The values of the inputs are updated at once, not the ones of the outputs.
+        let lock = expander.get_io().read();
+        assert_eq!(lock.get_pin(2).unwrap().value, 1);
+        assert_eq!(lock.get_pin(7).unwrap().value, 1);
+        assert_eq!(lock.get_pin(0).unwrap().value, 0);
+        drop(lock);
+
+        // No reply from the chip.
+        assert!(expander.digital_read(2).is_err());
+        assert!(expander.digital_read(8).is_err());
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_devices() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = PCF8574::default(&board).unwrap();
+
+        // Outputs.
+        let mut output = DigitalOutput::new(&expander, 4, true).unwrap();
+        assert_eq!(*expander.latch.read(), 0b1111_1111);
+        assert!(output.turn_off().is_ok());
+        assert_eq!(*expander.latch.read(), 0b1110_1111);
+
+        // Inputs are reported when their value changes.
+        let input = DigitalInput::new(&expander, 1).unwrap();
+        let button = Button::new(&expander, "P2").unwrap();
+        assert_eq!(expander.get_io().read().digital_reported_pins, vec![1, 2]);
+
+        _reply(&board, 0b0000_0110);
+        pause!(100);
+        assert_eq!(input.get_state(), State::Boolean(true));
+        assert_eq!(button.get_state(), State::Boolean(true));
+
+        // Replies from other devices are ignored.
+        board.get_io().write().set_i2c_reply(I2CReply {
+            address: 0x21,
+            register: 0,
+            data: vec![0],
+        });
+        pause!(100);
+        assert_eq!(input.get_state(), State::Boolean(true));
+
+        _reply(&board, 0b0000_0000);
+        pause!(100);
+        assert_eq!(input.get_state(), State::Boolean(false));
+        assert_eq!(button.get_state(), State::Boolean(false));
+
+        // Reporting stops with the last reported pin.
+        assert!(expander.report_digital(1, false).is_ok());
+        assert!(expander.handler.read().is_some());
+        assert!(expander.report_digital(2, false).is_ok());
+        assert!(expander.handler.read().is_none());
+
+        input.detach();
+        button.detach();
+        assert!(expander.report_digital(1, true).is_ok());
+        assert!(expander.close().is_ok());
+        assert!(expander.handler.read().is_none());
+        assert!(!expander.is_connected());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let board = Board::new(MockIoProtocol::default());
+        let mut expander = PCF8574::default(&board).unwrap();
+
+        assert!(expander.report_analog(0, true).is_err());
+        assert!(expander.analog_write(0, 128).is_err());
+        assert!(expander.analog_read(0).is_err());
+        assert!(expander.servo_config(0, Range::from([500, 2500])).is_err());
+        assert!(expander.tone(0, 440, 100).is_err());
+        assert!(expander.shift_out(0, 1, 2, &[0xFF]).is_err());
+        assert!(expander.stepper(0, StepperCommand::Zero).is_err());
+        assert!(expander.encoder(0, EncoderCommand::Attach([0, 1])).is_err());
+        assert!(expander.ping_read(0, 5, 1000).is_err());
+        assert!(expander.dht_read(0, DhtModel::Dht22).is_err());
+        assert!(expander.onewire_config(0, true).is_err());
+        assert!(expander.onewire_search(0).is_err());
+        assert!(expander.onewire_reset(0).is_err());
+        assert!(expander.onewire_select(0, [0; 8]).is_err());
+        assert!(expander.onewire_read(0, 9, 0).is_err());
+        assert!(expander.onewire_write(0, [0; 8], &[0x44]).is_err());
+        assert!(expander.onewire_delay(0, 1000).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let board = Board::new(MockIoProtocol::default());
+        let expander = PCF8574::default(&board).unwrap();
+
+        assert_eq!(
+            format!("{}", expander),
+            "PCF8574 [firmware=PCF8574, version=n/a, protocol=PCF8574, transport=I2C]"
+        );
+        assert_eq!(
+            expander.get_protocol().to_string(),
+            "PCF8574 [firmware=PCF8574, version=n/a, protocol=PCF8574, transport=I2C]"
+        );
+    }
+}
@@ -0,0 +1,576 @@
+// ***********
+// All information are relative to 74HC595 datasheet:
+// https://www.ti.com/lit/ds/symlink/sn74hc595.pdf
+
+use crate::errors::{Error, HardwareError, UnknownError};
+use crate::hardware::{Expander, Hardware};
+use crate::io::{IoData, IoProtocol, Pin, PinClaim, PinMode, PinModeId, IO};
+use crate::utils::Range;
+use parking_lot::RwLock;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// Represents a chain of daisy-chained 74HC595 shift registers (8 outputs each), driven by 3 pins of
+/// a board: data (SER), clock (SRCLK) and latch (RCLK).
+///
+/// The outputs are pins 0-7 of the first register (the one wired to the board), pins 8-15 of the
+/// second one, and so on (named Q0, Q1, etc.): output devices can be created on them exactly as on a
+/// [`Board`](crate::hardware::Board). Each write shifts out the whole chain at once, and several
+/// outputs can be updated with a single shift-out (see [`ShiftRegister::write`]).
+///
+/// The chain is shifted out by the firmware (see [`IO::shift_out`]) when it supports the SHIFT mode on
+/// the data and clock pins. Otherwise (StandardFirmata for instance), each bit is sent with
+/// [`IO::digital_write`]: slower, but supported by all firmwares.
+///
+/// # Example
+/// ```no_run
+/// use hermes_five::devices::Led;
+/// use hermes_five::hardware::{Board, BoardEvent, ShiftRegister};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let board = Board::run();
+///     board.on(BoardEvent::OnReady, |board: Board| async move {
+///         // Two chained registers: data on pin 2, clock on pin 3, latch on pin 4.
+///         let register = ShiftRegister::new(&board, 2, 3, 4, 2)?;
+///         let mut led = Led::new(&register, 12, false)?;
+///         led.blink(500);
+///         Ok(())
+///     });
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct ShiftRegister {
+    // Board pins driving the chain.
+    data_pin: u8,
+    clock_pin: u8,
+    latch_pin: u8,
+    // Number of chained registers.
+    size: u8,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data: Arc<RwLock<IoData>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    // The ownership of the board pins (shared by all clones).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    claim: PinClaim,
+    // Indicates the firmware shifts out the data (see `ShiftRegister::open`).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    shift_mode: bool,
+}
+
+impl ShiftRegister {
+    // Maximum number of chained registers (the outputs ids fit in a u8).
+    const MAX_SIZE: u8 = 31;
+
+    fn _build_shift_register_data(size: u8) -> IoData {
+        let mut data = IoData {
+            protocol_version: "74HC595".to_string(),
+            firmware_name: "74HC595".to_string(),
+            firmware_version: "n/a".to_string(),
+            connected: false,
+            ..Default::default()
+        };
+
+        for id in 0..size * 8 {
+            let output = PinMode {
+                id: PinModeId::OUTPUT,
+                resolution: 1,
+            };
+            data.pins.insert(
+                id,
+                Pin {
+                    id,
+                    name: format!("Q{}", id),
+                    mode: output,
+                    supported_modes: vec![output],
+                    channel: None,
+                    value: 0,
+                },
+            );
+        }
+
+        data
+    }
+
+    /// Creates a chain of `size` shift registers driven by the given board pins: all outputs are LOW.
+    ///
+    /// The data and clock pins are set in SHIFT mode when the firmware supports it on both pins (the bits
+    /// are then sent one by one in OUTPUT mode otherwise), the latch pin in OUTPUT mode.
+    ///
+    /// # Errors
+    /// * `UnknownError`: the chain has no register, or more than 31.
+    /// * `UnknownPin`: a pin does not exist for this board.
+    /// * `PinInUse`: a pin is already used by another device.
+    pub fn new(
+        board: &dyn Hardware,
+        data_pin: u8,
+        clock_pin: u8,
+        latch_pin: u8,
+        size: u8,
+    ) -> Result<Self, Error> {
+        if size == 0 || size > ShiftRegister::MAX_SIZE {
+            return Err(UnknownError {
+                info: format!(
+                    "A shift register chain has 1 to {} registers ({} requested)",
+                    ShiftRegister::MAX_SIZE,
+                    size
+                ),
+            });
+        }
+
+        let protocol = board.get_protocol();
+        let claim = PinClaim::new(
+            protocol.get_io(),
+            vec![data_pin, clock_pin, latch_pin],
+            "ShiftRegister",
+        )?;
+        let mut register = Self {
+            data_pin,
+            clock_pin,
+            latch_pin,
+            size,
+            data: Arc::new(RwLock::new(ShiftRegister::_build_shift_register_data(size))),
+            protocol,
+            claim,
+            shift_mode: false,
+        };
+        IoProtocol::open(&mut register)?;
+        Ok(register)
+    }
+
+    pub fn get_data_pin(&self) -> u8 {
+        self.data_pin
+    }
+
+    pub fn get_clock_pin(&self) -> u8 {
+        self.clock_pin
+    }
+
+    pub fn get_latch_pin(&self) -> u8 {
+        self.latch_pin
+    }
+
+    /// Returns the number of chained registers.
+    pub fn get_size(&self) -> u8 {
+        self.size
+    }
+
+    /// Sets all the outputs with a single shift-out: one byte per register (the first register first),
+    /// one bit per output (Q0 in the least significant bit). Missing bytes leave their outputs LOW.
+    ///
+    /// _Note: the outputs owned by devices are updated too, but the devices state are not._
+    pub fn write(&mut self, levels: &[u8]) -> Result<(), Error> {
+        let data = self.data.clone();
+        let mut data = data.write();
+        for (id, pin) in data.pins.iter_mut() {
+            let level = levels.get((id / 8) as usize).copied().unwrap_or_default();
+            pin.value = ((level >> (id % 8)) & 1) as u16;
+        }
+        self.shift(&data)
+    }
+
+    // Shifts out the outputs values of the whole chain: the last register bits are shifted first, so
+    // that they travel through the whole chain.
+    fn shift(&mut self, data: &IoData) -> Result<(), Error> {
+        let mut bytes = vec![0u8; self.size as usize];
+        for pin in data.pins.values().filter(|pin| pin.value != 0) {
+            bytes[(pin.id / 8) as usize] |= 1 << (pin.id % 8);
+        }
+        bytes.reverse();
+        if self.shift_mode {
+            return self
+                .protocol
+                .shift_out(self.data_pin, self.clock_pin, self.latch_pin, &bytes);
+        }
+
+        // Bit-bang: each bit (MSB first) is shifted in on the clock rising edge, then all outputs are
+        // updated at once on the latch rising edge.
+        for byte in bytes {
+            for bit in (0..8).rev() {
+                self.protocol
+                    .digital_write(self.data_pin, (byte >> bit) & 1 == 1)?;
+                self.protocol.digital_write(self.clock_pin, true)?;
+                self.protocol.digital_write(self.clock_pin, false)?;
+            }
+        }
+        self.protocol.digital_write(self.latch_pin, true)?;
+        self.protocol.digital_write(self.latch_pin, false)
+    }
+}
+
+impl Expander for ShiftRegister {}
+
+impl Hardware for ShiftRegister {
+    fn get_protocol(&self) -> Box<dyn IoProtocol> {
+        Box::new(self.clone())
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    fn set_protocol(&mut self, protocol: Box<dyn IoProtocol>) {
+        self.protocol = protocol;
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl IoProtocol for ShiftRegister {
+    /// Claims and configures the board pins, then shifts out the outputs values.
+    fn open(&mut self) -> Result<(), Error> {
+        self.claim.renew()?;
+        self.shift_mode = {
+            let io = self.protocol.get_io().read();
+            io.get_pin(self.data_pin)?
+                .supports_mode(PinModeId::SHIFT)
+                .and(io.get_pin(self.clock_pin)?.supports_mode(PinModeId::SHIFT))
+                .is_some()
+        };
+        let mode = match self.shift_mode {
+            true => PinModeId::SHIFT,
+            false => PinModeId::OUTPUT,
+        };
+        for pin in [self.data_pin, self.clock_pin] {
+            self.protocol.set_pin_mode(pin, mode)?;
+        }
+        self.protocol
+            .set_pin_mode(self.latch_pin, PinModeId::OUTPUT)?;
+
+        let data = self.data.clone();
+        let mut data = data.write();
+        self.shift(&data)?;
+        data.connected = true;
+        Ok(())
+    }
+
+    /// Releases the board pins: the outputs keep their values.
+    fn close(&mut self) -> Result<(), Error> {
+        self.claim.release();
+        self.data.write().connected = false;
+        Ok(())
+    }
+
+    /// The shift register has no analog input.
+    fn report_analog(&mut self, _: u8, _: bool) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "report analog value",
+        }
+        .into())
+    }
+
+    /// The shift register has no input.
+    fn report_digital(&mut self, pin: u8, _: bool) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::INPUT,
+            context: "report digital value",
+        }
+        .into())
+    }
+
+    fn sampling_interval(&mut self, interval: u16) -> Result<(), Error> {
+        self.protocol.sampling_interval(interval)
+    }
+}
+
+impl IO for ShiftRegister {
+    fn get_io(&self) -> &Arc<RwLock<IoData>> {
+        &self.data
+    }
+
+    fn is_connected(&self) -> bool {
+        self.data.read().connected
+    }
+
+    /// The outputs are always in OUTPUT mode.
+    ///
+    /// # Errors
+    /// * `IncompatiblePin`: the mode is not supported.
+    fn set_pin_mode(&mut self, pin: u8, mode: PinModeId) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?.supports_mode(mode).ok_or(
+            HardwareError::IncompatiblePin {
+                pin,
+                mode,
+                context: "try to set pin mode",
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Sets the output value, and shifts out the whole chain.
+    fn digital_write(&mut self, pin: u8, level: bool) -> Result<(), Error> {
+        let data = self.data.clone();
+        let mut data = data.write();
+        data.get_pin_mut(pin)?.value = u16::from(level);
+        self.shift(&data)
+    }
+
+    /// The shift register has no PWM output.
+    fn analog_write(&mut self, pin: u8, _: u16) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::PWM,
+            context: "write analog value",
+        }
+        .into())
+    }
+
+    /// Returns the value of the output.
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        Ok(self.data.read().get_pin(pin)?.value != 0)
+    }
+
+    /// The shift register has no analog input.
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::ANALOG,
+            context: "read analog value",
+        }
+        .into())
+    }
+
+    /// The shift register cannot drive servos.
+    fn servo_config(&mut self, pin: u8, _: Range<u16>) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin,
+            mode: PinModeId::SERVO,
+            context: "configure servo",
+        }
+        .into())
+    }
+
+    /// The shift register outputs cannot shift out data.
+    fn shift_out(&mut self, data_pin: u8, _: u8, _: u8, _: &[u8]) -> Result<(), Error> {
+        Err(HardwareError::IncompatiblePin {
+            pin: data_pin,
+            mode: PinModeId::SHIFT,
+            context: "shift out data",
+        }
+        .into())
+    }
+
+    fn i2c_config(&mut self, delay: u16) -> Result<(), Error> {
+        self.protocol.i2c_config(delay)
+    }
+
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error> {
+        self.protocol.i2c_read(address, register, size)
+    }
+
+    fn i2c_read_continuously(
+        &mut self,
+        address: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error> {
+        self.protocol.i2c_read_continuously(address, register, size)
+    }
+
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error> {
+        self.protocol.i2c_stop_reading(address)
+    }
+
+    fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error> {
+        self.protocol.i2c_write(address, data)
+    }
+}
+
+impl Display for ShiftRegister {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let data = self.data.read();
+        write!(
+            f,
+            "{} [firmware={}, version={}, protocol={}, transport=SHIFT]",
+            self.get_name(),
+            data.firmware_name,
+            data.firmware_version,
+            data.protocol_version,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{DigitalOutput, Led};
+    use crate::hardware::Board;
+    use crate::io::{BoardProfile, DhtModel, EncoderCommand, StepperCommand, VirtualBoard};
+    use crate::mocks::plugin_io::MockIoProtocol;
+
+    // Creates a register on pins 2, 3 and 4: the data and clock pins support the SHIFT mode.
+    fn _create_register(size: u8) -> (MockIoProtocol, Board, ShiftRegister) {
+        let protocol = MockIoProtocol::with_mode([2, 3], PinModeId::SHIFT);
+        let board = Board::new(protocol.clone());
+        let register = ShiftRegister::new(&board, 2, 3, 4, size).unwrap();
+        (protocol, board, register)
+    }
+
+    #[test]
+    fn test_helper() {
+        let data = ShiftRegister::_build_shift_register_data(2);
+        assert_eq!(data.firmware_name, "74HC595");
+        assert_eq!(data.protocol_version, "74HC595");
+        assert_eq!(data.pins.len(), 16);
+        assert_eq!(data.get_pin("Q15").unwrap().id, 15);
+        assert_eq!(data.get_pin(3).unwrap().mode.id, PinModeId::OUTPUT);
+    }
+
+    #[test]
+    fn test_new() {
+        let (protocol, board, register) = _create_register(2);
+        assert_eq!(register.get_data_pin(), 2);
+        assert_eq!(register.get_clock_pin(), 3);
+        assert_eq!(register.get_latch_pin(), 4);
+        assert_eq!(register.get_size(), 2);
+        assert!(register.is_connected());
+        assert_eq!(
+            register.to_string(),
+            "ShiftRegister [firmware=74HC595, version=n/a, protocol=74HC595, transport=SHIFT]"
+        );
+
+        // The board pins are claimed and set in SHIFT mode, and the outputs are shifted out LOW.
+        assert_eq!(
+            board.get_io().read().get_pin_owner(3).unwrap(),
+            "ShiftRegister"
+        );
+        assert_eq!(
+            board.get_io().read().get_pin(2).unwrap().mode.id,
+            PinModeId::SHIFT
+        );
+        assert_eq!(
+            board.get_io().read().get_pin(4).unwrap().mode.id,
+            PinModeId::OUTPUT
+        );
+        assert_eq!(*protocol.shift_outs.read(), vec![vec![0, 0]]);
+        assert!(ShiftRegister::new(&board, 4, 5, 6, 1).is_err());
+
+        // The pins are set in OUTPUT mode when the clock pin does not support the SHIFT mode.
+        let register = ShiftRegister::new(&board, 9, 5, 6, 1).unwrap();
+        assert_eq!(
+            board.get_io().read().get_pin(9).unwrap().mode.id,
+            PinModeId::OUTPUT
+        );
+        assert_eq!(register.get_size(), 1);
+        assert_eq!(protocol.shift_outs.read().len(), 1);
+
+        let result = ShiftRegister::new(&board, 7, 8, 13, 0);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unknown error: A shift register chain has 1 to 31 registers (0 requested)."
+        );
+        assert!(ShiftRegister::new(&board, 7, 8, 13, 32).is_err());
+        assert!(ShiftRegister::new(&board, 7, 8, 66, 1).is_err());
+    }
+
+    #[test]
+    fn test_close() {
+        let (_, board, mut register) = _create_register(1);
+        assert!(register.close().is_ok());
+        assert!(!register.is_connected());
+        assert!(board.get_io().read().get_pin_owner(2).is_none());
+
+        assert!(register.open().is_ok());
+        assert!(register.is_connected());
+        assert!(board.get_io().read().get_pin_owner(2).is_some());
+    }
+
+    #[test]
+    fn test_digital_write() {
+        let (protocol, _, mut register) = _create_register(2);
+
+        // The whole chain is shifted out: the last register first.
+        assert!(register.digital_write(9, true).is_ok());
+        assert!(register.digital_write(0, true).is_ok());
+        assert!(register.digital_read(9).unwrap());
+        assert!(!register.digital_read(8).unwrap());
+        assert_eq!(
+            *protocol.shift_outs.read(),
+            vec![vec![0, 0], vec![0b0000_0010, 0], vec![0b0000_0010, 1]]
+        );
+
+        assert!(register.digital_write(16, true).is_err());
+        assert_eq!(protocol.shift_outs.read().len(), 3);
+    }
+
+    #[test]
+    fn test_write() {
+        let (protocol, _, mut register) = _create_register(2);
+
+        // All outputs are updated with a single shift-out.
+        assert!(register.write(&[0b1000_0001, 0b0100_0000]).is_ok());
+        assert!(register.digital_read(0).unwrap());
+        assert!(register.digital_read(7).unwrap());
+        assert!(register.digital_read(14).unwrap());
+        assert!(!register.digital_read(15).unwrap());
+        assert_eq!(
+            protocol.shift_outs.read().last().unwrap(),
+            &vec![0b0100_0000, 0b1000_0001]
+        );
+
+        assert!(register.write(&[0xFF]).is_ok());
+        assert_eq!(protocol.shift_outs.read().last().unwrap(), &vec![0, 0xFF]);
+        assert_eq!(protocol.shift_outs.read().len(), 3);
+    }
+
+    #[test]
+    fn test_devices() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let board = Board::from(simulator.clone()).blocking_open().unwrap();
+
+        // The simulator has no SHIFT mode: the bits are sent one by one.
+        simulator.wire_shift_register(2, 3, 4);
+        let register = ShiftRegister::new(&board, 2, 3, 4, 2).unwrap();
+        assert_eq!(simulator.get_pin_mode(3).unwrap(), PinModeId::OUTPUT);
+        assert_eq!(simulator.get_shift_data(4), vec![0, 0]);
+
+        let mut led = Led::new(&register, 3, false).unwrap();
+        let mut output = DigitalOutput::new(&register, "Q12", false).unwrap();
+        assert!(led.turn_on().is_ok());
+        assert_eq!(simulator.get_shift_data(4), vec![0, 0b0000_1000]);
+        assert!(output.turn_on().is_ok());
+        assert_eq!(simulator.get_shift_data(4), vec![0b0001_0000, 0b0000_1000]);
+        assert!(Led::new(&register, 3, false).is_err());
+
+        // The outputs have no PWM.
+        assert!(led.clone().set_brightness(50).is_err());
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_set_pin_mode() {
+        let (_, _, mut register) = _create_register(1);
+        assert!(register.set_pin_mode(0, PinModeId::OUTPUT).is_ok());
+        let result = register.set_pin_mode(0, PinModeId::INPUT);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Pin (0) not compatible with mode (INPUT) - try to set pin mode."
+        );
+        assert!(register.set_pin_mode(8, PinModeId::OUTPUT).is_err());
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let (_, _, mut register) = _create_register(1);
+        assert!(register.report_analog(0, true).is_err());
+        assert!(register.report_digital(0, true).is_err());
+        assert!(register.analog_write(0, 128).is_err());
+        assert!(register.analog_read(0).is_err());
+        assert!(register.servo_config(0, Range::from([500, 2500])).is_err());
+        assert!(register.stepper(0, StepperCommand::Zero).is_err());
+        assert!(register.encoder(0, EncoderCommand::Attach([0, 1])).is_err());
+        assert!(register.dht_read(0, DhtModel::Dht22).is_err());
+        assert!(register.onewire_config(0, true).is_err());
+        assert!(register.onewire_search(0).is_err());
+        assert!(register.onewire_reset(0).is_err());
+        assert!(register.onewire_select(0, [0; 8]).is_err());
+        assert!(register.onewire_read(0, 9, 0).is_err());
+        assert!(register.onewire_write(0, [0; 8], &[0x44]).is_err());
+        assert!(register.onewire_delay(0, 1000).is_err());
+        assert!(register.ping_read(0, 5, 1000).is_err());
+        assert!(register.tone(0, 440, 100).is_err());
+        assert!(register.shift_out(0, 1, 2, &[0xFF]).is_err());
+    }
+}
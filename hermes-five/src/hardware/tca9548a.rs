@@ -0,0 +1,472 @@
+// ***********
+// All information are relative to TCA9548A datasheet:
+// https://www.ti.com/lit/ds/symlink/tca9548a.pdf
+
+use crate::errors::{Error, HardwareError, UnknownError};
+use crate::hardware::{Board, Hardware};
+use crate::io::{IoData, IoProtocol, PinIdOrName, IO};
+use crate::utils::Range;
+use parking_lot::{Mutex, RwLock};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// Represents a TCA9548A I2C multiplexer: it connects the I2C bus of a board to one of its 8 downstream
+/// channels at a time, so that devices sharing the same address can be plugged on different channels.
+///
+/// Each channel is a [`Hardware`] of its own (see [`TCA9548A::channel`]): the channel is selected before
+/// each I2C write or read on it, and the switching is serialized between all the channels of the
+/// multiplexer (and all their clones), hence devices on different channels can be used from different tasks.
+///
+/// _Note: create the multiplexer once and clone it: two instances of the same multiplexer would not
+/// serialize their channel switching._
+///
+/// # Example
+/// ```no_run
+/// use hermes_five::hardware::{Board, BoardEvent, PCA9685, TCA9548A};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let board = Board::run();
+///     board.on(BoardEvent::OnReady, |board: Board| async move {
+///         let mux = TCA9548A::default(&board)?;
+///         // Two PCA9685 at the same address on channels 3 and 4.
+///         let left = PCA9685::new(&mux.channel(3)?, 0x40)?;
+///         let right = PCA9685::new(&mux.channel(4)?, 0x40)?;
+///         Ok(())
+///     });
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct TCA9548A {
+    // Address (default 0x70).
+    address: u8,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    // Serializes the channel switching (shared by all clones and channels).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lock: Arc<Mutex<()>>,
+}
+
+impl TCA9548A {
+    // Number of downstream channels.
+    const CHANNELS: u8 = 8;
+
+    pub fn default(board: &Board) -> Result<Self, Error> {
+        TCA9548A::new(board, 0x70)
+    }
+
+    pub fn new(board: &dyn Hardware, address: u8) -> Result<Self, Error> {
+        let mut protocol = board.get_protocol();
+        protocol.i2c_config(0)?;
+        Ok(Self {
+            address,
+            protocol,
+            lock: Default::default(),
+        })
+    }
+
+    pub fn get_address(&self) -> u8 {
+        self.address
+    }
+
+    /// Returns the downstream `channel` (0-7) of the multiplexer, to create devices on.
+    ///
+    /// # Errors
+    /// * `UnknownError`: the multiplexer has no such channel.
+    pub fn channel(&self, channel: u8) -> Result<TCA9548AChannel, Error> {
+        if channel >= TCA9548A::CHANNELS {
+            return Err(UnknownError {
+                info: format!("TCA9548A has no channel {} (0-7)", channel),
+            });
+        }
+        Ok(TCA9548AChannel {
+            channel,
+            address: self.address,
+            data: Arc::new(RwLock::new(TCA9548AChannel::_build_channel_data())),
+            protocol: self.protocol.clone(),
+            lock: self.lock.clone(),
+        })
+    }
+
+    /// Disconnects all the downstream channels from the bus.
+    pub fn disable(&mut self) -> Result<(), Error> {
+        let _guard = self.lock.lock();
+        self.protocol.i2c_write(self.address, &[0])
+    }
+}
+
+impl Display for TCA9548A {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TCA9548A (address=0x{:02X})", self.address)
+    }
+}
+
+/// Represents a downstream channel of a [`TCA9548A`] I2C multiplexer: a [`Hardware`] exposing the I2C
+/// bus behind the channel (it has no pin).
+///
+/// The replies of the devices behind the channel are kept in the channel [`IoData`], so that devices at
+/// the same address on other channels do not receive them.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone)]
+pub struct TCA9548AChannel {
+    // Channel of the multiplexer (0-7).
+    channel: u8,
+    // Address of the multiplexer.
+    address: u8,
+
+    // ########################################
+    // # Volatile utility data.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data: Arc<RwLock<IoData>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    protocol: Box<dyn IoProtocol>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lock: Arc<Mutex<()>>,
+}
+
+impl TCA9548AChannel {
+    fn _build_channel_data() -> IoData {
+        IoData {
+            protocol_version: "TCA9548A".to_string(),
+            firmware_name: "TCA9548A".to_string(),
+            firmware_version: "n/a".to_string(),
+            connected: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_channel(&self) -> u8 {
+        self.channel
+    }
+
+    pub fn get_address(&self) -> u8 {
+        self.address
+    }
+
+    // Selects the channel on the multiplexer, and runs the I2C operation before any other channel can be selected.
+    fn with_channel<T>(
+        &mut self,
+        operation: impl FnOnce(&mut Box<dyn IoProtocol>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let lock = self.lock.clone();
+        let _guard = lock.lock();
+        self.protocol
+            .i2c_write(self.address, &[1 << self.channel])?;
+        operation(&mut self.protocol)
+    }
+
+    fn no_pin(pin: u8) -> Error {
+        HardwareError::UnknownPin {
+            pin: PinIdOrName::Id(pin),
+        }
+        .into()
+    }
+}
+
+impl Hardware for TCA9548AChannel {
+    fn get_protocol(&self) -> Box<dyn IoProtocol> {
+        Box::new(self.clone())
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    fn set_protocol(&mut self, protocol: Box<dyn IoProtocol>) {
+        self.protocol = protocol;
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl IoProtocol for TCA9548AChannel {
+    fn open(&mut self) -> Result<(), Error> {
+        self.i2c_config(0)?;
+        self.data.write().connected = true;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        self.data.write().connected = false;
+        Ok(())
+    }
+
+    /// A multiplexer channel has no pin.
+    fn report_analog(&mut self, channel: u8, _: bool) -> Result<(), Error> {
+        Err(TCA9548AChannel::no_pin(channel))
+    }
+
+    /// A multiplexer channel has no pin.
+    fn report_digital(&mut self, pin: u8, _: bool) -> Result<(), Error> {
+        Err(TCA9548AChannel::no_pin(pin))
+    }
+
+    fn sampling_interval(&mut self, interval: u16) -> Result<(), Error> {
+        self.protocol.sampling_interval(interval)
+    }
+}
+
+impl IO for TCA9548AChannel {
+    fn get_io(&self) -> &Arc<RwLock<IoData>> {
+        &self.data
+    }
+
+    fn is_connected(&self) -> bool {
+        self.data.read().connected
+    }
+
+    /// A multiplexer channel has no pin.
+    fn set_pin_mode(&mut self, pin: u8, _: crate::io::PinModeId) -> Result<(), Error> {
+        Err(TCA9548AChannel::no_pin(pin))
+    }
+
+    /// A multiplexer channel has no pin.
+    fn digital_write(&mut self, pin: u8, _: bool) -> Result<(), Error> {
+        Err(TCA9548AChannel::no_pin(pin))
+    }
+
+    /// A multiplexer channel has no pin.
+    fn analog_write(&mut self, pin: u8, _: u16) -> Result<(), Error> {
+        Err(TCA9548AChannel::no_pin(pin))
+    }
+
+    /// A multiplexer channel has no pin.
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        Err(TCA9548AChannel::no_pin(pin))
+    }
+
+    /// A multiplexer channel has no pin.
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        Err(TCA9548AChannel::no_pin(pin))
+    }
+
+    /// A multiplexer channel has no pin.
+    fn servo_config(&mut self, pin: u8, _: Range<u16>) -> Result<(), Error> {
+        Err(TCA9548AChannel::no_pin(pin))
+    }
+
+    /// A multiplexer channel has no pin.
+    fn shift_out(&mut self, data_pin: u8, _: u8, _: u8, _: &[u8]) -> Result<(), Error> {
+        Err(TCA9548AChannel::no_pin(data_pin))
+    }
+
+    fn i2c_config(&mut self, delay: u16) -> Result<(), Error> {
+        self.protocol.i2c_config(delay)
+    }
+
+    /// Selects the channel and reads the device: the reply is moved to the channel [`IoData`].
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error> {
+        let data = self.data.clone();
+        self.with_channel(|protocol| {
+            protocol.i2c_read(address, register, size)?;
+            let reply = protocol.get_io().write().take_i2c_reply(address, register);
+            if let Some(reply) = reply {
+                data.write().set_i2c_reply(reply);
+            }
+            Ok(())
+        })
+    }
+
+    /// The board reads continuously whatever channel is selected at the time: continuous reads are not
+    /// supported behind a multiplexer.
+    fn i2c_read_continuously(&mut self, _: u8, _: Option<u8>, _: u16) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "read I2C continuously behind a multiplexer",
+        }
+        .into())
+    }
+
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error> {
+        self.protocol.i2c_stop_reading(address)
+    }
+
+    /// Selects the channel and writes to the device.
+    fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error> {
+        self.with_channel(|protocol| protocol.i2c_write(address, data))
+    }
+}
+
+impl Display for TCA9548AChannel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let data = self.data.read();
+        write!(
+            f,
+            "{} (channel={}) [firmware={}, version={}, protocol={}, transport=I2C]",
+            self.get_name(),
+            self.channel,
+            data.firmware_name,
+            data.firmware_version,
+            data.protocol_version,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::{I2cDevice, PCA9685};
+    use crate::io::{
+        BoardProfile, DhtModel, EncoderCommand, I2CReply, PinModeId, StepperCommand, VirtualBoard,
+    };
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use std::thread;
+
+    fn _create_mux() -> (MockIoProtocol, TCA9548A) {
+        let protocol = MockIoProtocol::default();
+        let board = Board::new(protocol.clone());
+        let mux = TCA9548A::default(&board).unwrap();
+        (protocol, mux)
+    }
+
+    #[test]
+    fn test_new() {
+        let (_, mux) = _create_mux();
+        assert_eq!(mux.get_address(), 0x70);
+        assert_eq!(mux.to_string(), "TCA9548A (address=0x70)");
+
+        let channel = mux.channel(3).unwrap();
+        assert_eq!(channel.get_channel(), 3);
+        assert_eq!(channel.get_address(), 0x70);
+        assert!(channel.is_connected());
+        assert!(channel.get_io().read().pins.is_empty());
+        assert_eq!(
+            channel.get_protocol().to_string(),
+            "TCA9548AChannel (channel=3) [firmware=TCA9548A, version=n/a, protocol=TCA9548A, transport=I2C]"
+        );
+    }
+
+    #[test]
+    fn test_write() {
+        let (protocol, mut mux) = _create_mux();
+
+        // The channel is selected before each write.
+        let mut channel = mux.channel(3).unwrap();
+        assert!(channel.i2c_write(0x40, &[0x10, 0xAA]).is_ok());
+        assert!(mux
+            .channel(0)
+            .unwrap()
+            .i2c_write(0x40, &[0x10, 0xBB])
+            .is_ok());
+        assert!(mux.disable().is_ok());
+        assert_eq!(
+            *protocol.i2c_writes.read(),
+            vec![
+                (0x70, vec![0b0000_1000]),
+                (0x40, vec![0x10, 0xAA]),
+                (0x70, vec![0b0000_0001]),
+                (0x40, vec![0x10, 0xBB]),
+                (0x70, vec![0]),
+            ]
+        );
+
+        let result = mux.channel(8);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unknown error: TCA9548A has no channel 8 (0-7)."
+        );
+    }
+
+    #[test]
+    fn test_serialized_switching() {
+        let (protocol, mux) = _create_mux();
+
+        // Writes from concurrent threads on different channels are never interleaved with a switch.
+        let handles: Vec<_> = (0..4)
+            .map(|index| {
+                let mut channel = mux.channel(index).unwrap();
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        channel.i2c_write(0x40, &[index as u16]).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let writes = protocol.i2c_writes.read();
+        assert_eq!(writes.len(), 400);
+        for pair in writes.chunks(2) {
+            let (mux_address, selection) = &pair[0];
+            let (address, data) = &pair[1];
+            assert_eq!(*mux_address, 0x70);
+            assert_eq!(*address, 0x40);
+            assert_eq!(selection[0], 1 << data[0]);
+        }
+    }
+
+    #[test]
+    fn test_read() {
+        let (protocol, mux) = _create_mux();
+        let channel = mux.channel(2).unwrap();
+        let mut device = I2cDevice::new(&channel, 0x40).unwrap();
+
+        // The reply is moved to the channel, where the device takes it.
+        protocol.data.write().set_i2c_reply(I2CReply {
+            address: 0x40,
+            register: 0x10,
+            data: vec![0x42],
+        });
+        assert_eq!(device.read_u8(0x10).unwrap(), 0x42);
+        assert!(protocol.data.read().i2c_data.is_empty());
+        assert!(channel.get_io().read().i2c_data.is_empty());
+        assert_eq!(protocol.i2c_writes.read()[0], (0x70, vec![0b0000_0100]));
+
+        let result = mux
+            .channel(2)
+            .unwrap()
+            .i2c_read_continuously(0x40, Some(0x10), 1);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_devices() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let board = Board::from(simulator.clone()).blocking_open().unwrap();
+        let mux = TCA9548A::default(&board).unwrap();
+
+        let mut pca9685 = PCA9685::new(&mux.channel(3).unwrap(), 0x40).unwrap();
+        assert!(pca9685.set_pin_mode(0, PinModeId::PWM).is_ok());
+        assert!(pca9685.analog_write(0, 2048).is_ok());
+        // LED0_OFF_L/H registers of the channel 0.
+        assert_eq!(simulator.get_i2c_data(0x40, 0x08, 2), vec![0x00, 0x08]);
+
+        simulator.set_i2c_data(0x40, 0x69, &[0x42]);
+        assert_eq!(pca9685.read_from_reg(0x69).unwrap(), 0x42);
+
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_no_pin() {
+        let (_, mux) = _create_mux();
+        let mut channel = mux.channel(0).unwrap();
+
+        let result = channel.set_pin_mode(0, PinModeId::OUTPUT);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Unknown pin 0."
+        );
+        assert!(channel.report_analog(0, true).is_err());
+        assert!(channel.report_digital(0, true).is_err());
+        assert!(channel.digital_write(0, true).is_err());
+        assert!(channel.analog_write(0, 128).is_err());
+        assert!(channel.digital_read(0).is_err());
+        assert!(channel.analog_read(0).is_err());
+        assert!(channel.servo_config(0, Range::from([500, 2500])).is_err());
+        assert!(channel.tone(0, 440, 100).is_err());
+        assert!(channel.shift_out(0, 1, 2, &[0xFF]).is_err());
+        assert!(channel.stepper(0, StepperCommand::Zero).is_err());
+        assert!(channel.encoder(0, EncoderCommand::Attach([0, 1])).is_err());
+        assert!(channel.ping_read(0, 5, 1000).is_err());
+        assert!(channel.dht_read(0, DhtModel::Dht22).is_err());
+        assert!(channel.onewire_config(0, true).is_err());
+        assert!(channel.onewire_search(0).is_err());
+        assert!(channel.onewire_reset(0).is_err());
+        assert!(channel.onewire_select(0, [0; 8]).is_err());
+        assert!(channel.onewire_read(0, 9, 0).is_err());
+        assert!(channel.onewire_write(0, [0; 8], &[0x44]).is_err());
+        assert!(channel.onewire_delay(0, 1000).is_err());
+    }
+}
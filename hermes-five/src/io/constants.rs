@@ -14,6 +14,11 @@ pub enum Message {
     ReportFirmwareVersion,
     ReportProtocolVersion,
     I2CReply,
+    StepperReply,
+    EncoderReply,
+    PingReply,
+    DhtReply,
+    OneWireReply,
 }
 
 // ########################################
@@ -58,10 +63,14 @@ pub const END_SYSEX: u8 = 0xF7;
 
 // Extended command set using sysex (0-127/0x00-0x7F)
 
+/// Play a tone (or stop it) on a pin
+pub const TONE_DATA: u8 = 0x5F;
 /// Communicate with serial devices
 pub const SERIAL_DATA: u8 = 0x60;
 /// Reply with encoders current positions
 pub const ENCODER_DATA: u8 = 0x61;
+/// Control a stepper motor using AccelStepper
+pub const ACCELSTEPPER_DATA: u8 = 0x62;
 /// Set max angle, minPulse, maxPulse, freq
 pub const SERVO_CONFIG: u8 = 0x70;
 /// String message with 14-bits per char
@@ -70,8 +79,12 @@ pub const STRING_DATA: u8 = 0x71;
 pub const STEPPER_DATA: u8 = 0x72;
 /// Send an OneWire read/write/reset/select/skip/search request
 pub const ONEWIRE_DATA: u8 = 0x73;
+/// Read a DHT sensor / reply with its temperature and humidity (ConfigurableFirmata DhtFirmata)
+pub const DHTSENSOR_DATA: u8 = 0x74;
 /// Bitstream to/from a shift register
 pub const SHIFT_DATA: u8 = 0x75;
+/// Send a pulse and read the echo duration (PingFirmata: shares its code with SHIFT_DATA)
+pub const PING_READ: u8 = 0x75;
 /// Send an I2C read/write request
 pub const I2C_REQUEST: u8 = 0x76;
 /// Reply to an I2C read request
@@ -111,3 +124,48 @@ pub const I2C_STOP_READING: u8 = 0x18;
 pub const I2C_READ_WRITE_MODE_MASK: u8 = 0x18;
 pub const I2C_10BIT_ADDRESS_MODE_MASK: u8 = 0x20;
 pub const I2C_END_TX_MASK: u8 = 0x40;
+
+// AccelStepper additions.
+pub const ACCELSTEPPER_CONFIG: u8 = 0x00;
+pub const ACCELSTEPPER_ZERO: u8 = 0x01;
+pub const ACCELSTEPPER_STEP: u8 = 0x02;
+pub const ACCELSTEPPER_TO: u8 = 0x03;
+pub const ACCELSTEPPER_ENABLE: u8 = 0x04;
+pub const ACCELSTEPPER_STOP: u8 = 0x05;
+pub const ACCELSTEPPER_REPORT_POSITION: u8 = 0x06;
+pub const ACCELSTEPPER_SET_ACCELERATION: u8 = 0x08;
+pub const ACCELSTEPPER_SET_SPEED: u8 = 0x09;
+pub const ACCELSTEPPER_MOVE_COMPLETE: u8 = 0x0A;
+
+// Encoder additions.
+pub const ENCODER_ATTACH: u8 = 0x00;
+pub const ENCODER_REPORT_POSITION: u8 = 0x01;
+pub const ENCODER_REPORT_POSITIONS: u8 = 0x02;
+pub const ENCODER_RESET_POSITION: u8 = 0x03;
+pub const ENCODER_REPORT_AUTO: u8 = 0x04;
+pub const ENCODER_DETACH: u8 = 0x05;
+
+// OneWire additions.
+pub const ONEWIRE_SEARCH_REQUEST: u8 = 0x40;
+pub const ONEWIRE_CONFIG_REQUEST: u8 = 0x41;
+pub const ONEWIRE_SEARCH_REPLY: u8 = 0x42;
+pub const ONEWIRE_READ_REPLY: u8 = 0x43;
+pub const ONEWIRE_SEARCH_ALARMS_REQUEST: u8 = 0x44;
+pub const ONEWIRE_SEARCH_ALARMS_REPLY: u8 = 0x45;
+pub const ONEWIRE_RESET_REQUEST_BIT: u8 = 0x01;
+pub const ONEWIRE_SKIP_REQUEST_BIT: u8 = 0x02;
+pub const ONEWIRE_SELECT_REQUEST_BIT: u8 = 0x04;
+pub const ONEWIRE_READ_REQUEST_BIT: u8 = 0x08;
+pub const ONEWIRE_DELAY_REQUEST_BIT: u8 = 0x10;
+pub const ONEWIRE_WRITE_REQUEST_BIT: u8 = 0x20;
+
+// DHT additions.
+pub const DHTSENSOR_READ: u8 = 0x00;
+
+// Tone additions.
+pub const TONE_TONE: u8 = 0x00;
+pub const TONE_NO_TONE: u8 = 0x01;
+
+// Shift additions.
+pub const SHIFT_OUT: u8 = 0x01;
+pub const SHIFT_MSB_FIRST: u8 = 0x01;
@@ -1,7 +1,12 @@
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use crate::errors::HardwareError::{IncompatiblePin, UnknownPin};
+use parking_lot::RwLock;
+use tokio::sync::broadcast;
+
+use crate::errors::HardwareError::{IncompatiblePin, PinInUse, UnknownPin};
 use crate::errors::*;
 
 /// Represents the internal data that a [`IoProtocol`](crate::io::IoProtocol) handles.
@@ -15,13 +20,16 @@ pub struct IoData {
     /// All `Pin` instances, representing the hardware's pins.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub pins: HashMap<u8, Pin>,
-    /// A vector of `I2CReply` instances, representing I2C communication data.
+    /// A vector of `I2CReply` instances, representing I2C communication data: only the last
+    /// [`IoData::I2C_DATA_CAPACITY`] replies are kept.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub i2c_data: Vec<I2CReply>,
     /// List pins with digital reporting activated.
     pub digital_reported_pins: Vec<u8>,
     /// List pins with analog reporting activated.
     pub analog_reported_channels: Vec<u8>,
+    /// List I2C continuous reads activated (address, register, size).
+    pub i2c_reported_reads: Vec<(u8, Option<u8>, u16)>,
     /// A string indicating the version of the protocol.
     pub protocol_version: String,
     /// A string representing the name of the firmware.
@@ -30,9 +38,45 @@ pub struct IoData {
     pub firmware_version: String,
     /// A boolean indicating whether the IoProtocol is connected.
     pub connected: bool,
+    /// The channel used to notify about input pin value changes.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) changes: Notifier<PinChange>,
+    /// The channel used to notify about the input pin values reported by the board (changed or not).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) reports: Notifier<PinChange>,
+    /// The channel used to notify about the pin states reported on query.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) pin_states: Notifier<PinChange>,
+    /// The pins claimed by a device, with the name of their owner (see [`PinClaim`]).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) claims: HashMap<u8, String>,
+    /// The configured steppers with their last reported position, by stepper id.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub steppers: HashMap<u8, i32>,
+    /// The channel used to notify about stepper moves completion.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) stepper_moves: Notifier<StepperMove>,
+    /// The attached encoders with their last reported position, by encoder id.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub encoders: HashMap<u8, i32>,
+    /// The channel used to notify about encoders position changes.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) encoder_changes: Notifier<EncoderChange>,
+    /// The channel used to notify about DHT sensors readings.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) dht_readings: Notifier<DhtReading>,
+    /// The channel used to notify about OneWire replies.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) onewire_replies: Notifier<OneWireReply>,
+    /// The channel used to notify about I2C replies.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) i2c_replies: Notifier<I2CReply>,
 }
 
 impl IoData {
+    /// The maximum number of I2C replies kept in [`IoData::i2c_data`].
+    pub const I2C_DATA_CAPACITY: usize = 64;
+
     /// Returns  a reference to a pin by its id or name.
     ///
     /// # Errors
@@ -66,6 +110,411 @@ impl IoData {
                 .1),
         }
     }
+
+    /// Sets the value of an input pin (as reported by the board) and notifies the subscribers if it changed.
+    ///
+    /// # Errors
+    /// * `UnknownPin` - An `Error` returned if the pin index is out of bounds.
+    pub fn set_pin_value(&mut self, pin: u8, value: u16) -> Result<(), Error> {
+        let instance = self.get_pin_mut(pin)?;
+        let changed = instance.value != value;
+        instance.value = value;
+        // Sending fails only if there is no subscriber, which is fine.
+        let _ = self.reports.0.send(PinChange { pin, value });
+        if changed {
+            let _ = self.changes.0.send(PinChange { pin, value });
+        }
+        Ok(())
+    }
+
+    /// Subscribes to the input pin value changes: a [`PinChange`] is received each time the board reports
+    /// a new value for an input pin.
+    pub fn subscribe(&self) -> broadcast::Receiver<PinChange> {
+        self.changes.0.subscribe()
+    }
+
+    /// Subscribes to the input pin values reports: a [`PinChange`] is received each time the board reports
+    /// the value of an input pin (whether it changed or not).
+    pub fn subscribe_reports(&self) -> broadcast::Receiver<PinChange> {
+        self.reports.0.subscribe()
+    }
+
+    /// Notifies the subscribers about the state of a pin (as reported by the board on query).
+    pub fn notify_pin_state(&self, pin: u8, value: u16) {
+        // Sending fails only if there is no subscriber, which is fine.
+        let _ = self.pin_states.0.send(PinChange { pin, value });
+    }
+
+    /// Subscribes to the pin states: a [`PinChange`] is received each time the board reports the state of a
+    /// pin on query (whether its value changed or not).
+    pub fn subscribe_pin_states(&self) -> broadcast::Receiver<PinChange> {
+        self.pin_states.0.subscribe()
+    }
+
+    /// Sets the position of a stepper (as reported by the board): the subscribers are notified if
+    /// the stepper move is `complete`.
+    pub fn set_stepper_position(&mut self, id: u8, position: i32, complete: bool) {
+        self.steppers.insert(id, position);
+        if complete {
+            // Sending fails only if there is no subscriber, which is fine.
+            let _ = self.stepper_moves.0.send(StepperMove { id, position });
+        }
+    }
+
+    /// Subscribes to the stepper moves completion: a [`StepperMove`] is received each time the board
+    /// reports a stepper has reached its target position.
+    pub fn subscribe_stepper_moves(&self) -> broadcast::Receiver<StepperMove> {
+        self.stepper_moves.0.subscribe()
+    }
+
+    /// Sets the position of an encoder (as reported by the board) and notifies the subscribers if it changed.
+    pub fn set_encoder_position(&mut self, id: u8, position: i32) {
+        if self.encoders.insert(id, position) != Some(position) {
+            // Sending fails only if there is no subscriber, which is fine.
+            let _ = self.encoder_changes.0.send(EncoderChange { id, position });
+        }
+    }
+
+    /// Subscribes to the encoders position changes: an [`EncoderChange`] is received each time the board
+    /// reports a new encoder position.
+    pub fn subscribe_encoder_changes(&self) -> broadcast::Receiver<EncoderChange> {
+        self.encoder_changes.0.subscribe()
+    }
+
+    /// Notifies the subscribers about a DHT sensor reading (as reported by the board).
+    pub fn set_dht_reading(&mut self, reading: DhtReading) {
+        // Sending fails only if there is no subscriber, which is fine.
+        let _ = self.dht_readings.0.send(reading);
+    }
+
+    /// Subscribes to the DHT sensors readings: a [`DhtReading`] is received each time the board reports
+    /// a DHT sensor reading.
+    pub fn subscribe_dht_readings(&self) -> broadcast::Receiver<DhtReading> {
+        self.dht_readings.0.subscribe()
+    }
+
+    /// Notifies the subscribers about a OneWire reply (as reported by the board).
+    pub fn set_onewire_reply(&mut self, reply: OneWireReply) {
+        // Sending fails only if there is no subscriber, which is fine.
+        let _ = self.onewire_replies.0.send(reply);
+    }
+
+    /// Subscribes to the OneWire replies: a [`OneWireReply`] is received each time the board reports
+    /// the result of a OneWire search or read request.
+    pub fn subscribe_onewire_replies(&self) -> broadcast::Receiver<OneWireReply> {
+        self.onewire_replies.0.subscribe()
+    }
+
+    /// Stores an I2C reply (as reported by the board) and notifies the subscribers: the oldest replies are
+    /// dropped beyond [`IoData::I2C_DATA_CAPACITY`].
+    pub fn set_i2c_reply(&mut self, reply: I2CReply) {
+        if self.i2c_data.len() >= Self::I2C_DATA_CAPACITY {
+            let overflow = self.i2c_data.len() + 1 - Self::I2C_DATA_CAPACITY;
+            self.i2c_data.drain(..overflow);
+        }
+        // Sending fails only if there is no subscriber, which is fine.
+        let _ = self.i2c_replies.0.send(reply.clone());
+        self.i2c_data.push(reply);
+    }
+
+    /// Subscribes to the I2C replies: an [`I2CReply`] is received each time the board reports the result
+    /// of an I2C read (one-shot or continuous).
+    pub fn subscribe_i2c_replies(&self) -> broadcast::Receiver<I2CReply> {
+        self.i2c_replies.0.subscribe()
+    }
+
+    /// Removes and returns the last reply received from the I2C device at `address` for the given `register`
+    /// (or for any register if `None`).
+    pub fn take_i2c_reply(&mut self, address: u8, register: Option<u8>) -> Option<I2CReply> {
+        let index = self.i2c_data.iter().rposition(|reply| {
+            reply.address == address && register.is_none_or(|register| reply.register == register)
+        })?;
+        Some(self.i2c_data.remove(index))
+    }
+
+    /// Returns the name of the device owning the given pin, if any.
+    ///
+    /// Pins configured in I2C mode are considered owned by the I2C bus.
+    pub fn get_pin_owner(&self, pin: u8) -> Option<String> {
+        match self.claims.get(&pin) {
+            Some(owner) => Some(owner.clone()),
+            None => self
+                .pins
+                .get(&pin)
+                .filter(|instance| instance.mode.id == PinModeId::I2C)
+                .map(|_| String::from("I2C")),
+        }
+    }
+
+    /// Claims the ownership of the given pins for the given `owner` (all or none of them).
+    ///
+    /// You most likely want to use a [`PinClaim`] instead, which releases the pins automatically.
+    ///
+    /// # Errors
+    /// * `UnknownPin` - An `Error` returned if a pin does not exist.
+    /// * `PinInUse` - An `Error` returned if a pin is already owned.
+    pub fn claim_pins(&mut self, pins: &[u8], owner: &str) -> Result<(), Error> {
+        for &pin in pins {
+            self.get_pin(pin)?;
+            if let Some(owner) = self.get_pin_owner(pin) {
+                return Err(PinInUse { pin, owner }.into());
+            }
+        }
+        for &pin in pins {
+            self.claims.insert(pin, owner.to_string());
+        }
+        Ok(())
+    }
+
+    /// Releases the ownership of the given pins.
+    pub fn release_pins(&mut self, pins: &[u8]) {
+        for pin in pins {
+            self.claims.remove(pin);
+        }
+    }
+}
+
+/// Represents the ownership of some pins by a device (see [`IoData::claim_pins`]).
+///
+/// The claim is shared by all clones of the device: the pins are released either explicitly
+/// (see [`PinClaim::release`]) or when the last clone is dropped.
+#[derive(Clone, Debug, Default)]
+pub struct PinClaim(Arc<PinClaimInner>);
+
+#[derive(Debug, Default)]
+struct PinClaimInner {
+    io: Arc<RwLock<IoData>>,
+    pins: Vec<u8>,
+    owner: String,
+    claimed: AtomicBool,
+}
+
+impl PinClaim {
+    /// Claims the given pins of the given [`IoData`] for the given `owner`.
+    ///
+    /// # Errors
+    /// * `UnknownPin` - An `Error` returned if a pin does not exist.
+    /// * `PinInUse` - An `Error` returned if a pin is already owned.
+    pub fn new<S: Into<String>>(
+        io: &Arc<RwLock<IoData>>,
+        pins: Vec<u8>,
+        owner: S,
+    ) -> Result<Self, Error> {
+        let claim = Self(Arc::new(PinClaimInner {
+            io: io.clone(),
+            pins,
+            owner: owner.into(),
+            claimed: AtomicBool::new(false),
+        }));
+        claim.renew()?;
+        Ok(claim)
+    }
+
+    /// Claims the pins back after they have been released (does nothing if they are still claimed).
+    ///
+    /// # Errors
+    /// * `PinInUse` - An `Error` returned if a pin has been claimed by someone else in the meantime.
+    pub fn renew(&self) -> Result<(), Error> {
+        let mut io = self.0.io.write();
+        if !self.0.claimed.load(Ordering::SeqCst) {
+            io.claim_pins(&self.0.pins, &self.0.owner)?;
+            self.0.claimed.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    /// Releases the pins: they can then be claimed by another device.
+    pub fn release(&self) {
+        self.0.release();
+    }
+
+    /// Indicates if the pins are currently claimed.
+    pub fn is_claimed(&self) -> bool {
+        self.0.claimed.load(Ordering::SeqCst)
+    }
+}
+
+impl PinClaimInner {
+    fn release(&self) {
+        let mut io = self.io.write();
+        if self.claimed.swap(false, Ordering::SeqCst) {
+            io.release_pins(&self.pins);
+        }
+    }
+}
+
+impl Drop for PinClaimInner {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Represents a change of an input pin value (see [`IoData::subscribe`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PinChange {
+    /// The pin id.
+    pub pin: u8,
+    /// The new pin value.
+    pub value: u16,
+}
+
+/// Represents the completion of a stepper move (see [`IoData::subscribe_stepper_moves`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepperMove {
+    /// The stepper id.
+    pub id: u8,
+    /// The position reached by the stepper.
+    pub position: i32,
+}
+
+/// Represents a change of an encoder position (see [`IoData::subscribe_encoder_changes`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncoderChange {
+    /// The encoder id.
+    pub id: u8,
+    /// The new encoder position.
+    pub position: i32,
+}
+
+/// Defines the model of a DHT sensor.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DhtModel {
+    /// DHT11: 0-50°C (±2°C), 20-80% humidity (±5%), one reading per second at most.
+    Dht11 = 11,
+    /// DHT22 (AM2302): -40-80°C (±0.5°C), 0-100% humidity (±2%), one reading every 2 seconds at most.
+    #[default]
+    Dht22 = 22,
+}
+
+/// Represents a DHT sensor reading (see [`IoData::subscribe_dht_readings`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DhtReading {
+    /// The pin the sensor is attached to.
+    pub pin: u8,
+    /// The read status reported by the board (0 means success).
+    pub status: u8,
+    /// The temperature (in °C).
+    pub temperature: f32,
+    /// The relative humidity (in %).
+    pub humidity: f32,
+}
+
+/// Represents a OneWire reply (see [`IoData::subscribe_onewire_replies`]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OneWireReply {
+    /// The addresses of the devices found on the bus by a search request.
+    Search { pin: u8, addresses: Vec<[u8; 8]> },
+    /// The bytes read from the bus, along with the correlation id of the read request.
+    Read {
+        pin: u8,
+        correlation_id: u16,
+        data: Vec<u8>,
+    },
+}
+
+/// Broadcasts notifications ([`PinChange`], [`StepperMove`], [`EncoderChange`], [`DhtReading`], [`OneWireReply`],
+/// [`I2CReply`]) to all subscribers.
+#[derive(Clone, Debug)]
+pub(crate) struct Notifier<T>(broadcast::Sender<T>);
+
+impl<T: Clone> Default for Notifier<T> {
+    fn default() -> Self {
+        Self(broadcast::channel(256).0)
+    }
+}
+
+/// Defines how a stepper motor is wired to the board.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepperInterface {
+    /// A step/direction driver board (ex: A4988, DRV8825).
+    Driver { step: u8, dir: u8 },
+    /// A 2-wire stepper.
+    TwoWire([u8; 2]),
+    /// A 3-wire stepper.
+    ThreeWire([u8; 3]),
+    /// A 4-wire stepper (ex: 28BYJ-48 with a ULN2003 driver).
+    FourWire([u8; 4]),
+}
+
+impl StepperInterface {
+    /// Returns the pins used by the interface.
+    pub fn get_pins(&self) -> Vec<u8> {
+        match *self {
+            StepperInterface::Driver { step, dir } => vec![step, dir],
+            StepperInterface::TwoWire(pins) => pins.to_vec(),
+            StepperInterface::ThreeWire(pins) => pins.to_vec(),
+            StepperInterface::FourWire(pins) => pins.to_vec(),
+        }
+    }
+}
+
+/// Defines the step size of a stepper motor.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepSize {
+    #[default]
+    Whole,
+    /// Half-step mode (3-wire and 4-wire steppers only).
+    Half,
+}
+
+/// Defines the configuration of a stepper motor.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepperConfig {
+    /// How the stepper is wired to the board.
+    pub interface: StepperInterface,
+    /// The step size.
+    pub step_size: StepSize,
+    /// The pin used to enable/disable the stepper outputs, if any.
+    pub enable_pin: Option<u8>,
+}
+
+impl StepperConfig {
+    /// Returns the pins used by the stepper.
+    pub fn get_pins(&self) -> Vec<u8> {
+        let mut pins = self.interface.get_pins();
+        pins.extend(self.enable_pin);
+        pins
+    }
+}
+
+/// Defines the commands that can be sent to a stepper (see [`IO::stepper`](crate::io::IO::stepper)).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StepperCommand {
+    /// Configures the stepper.
+    Config(StepperConfig),
+    /// Sets the current position as the zero position.
+    Zero,
+    /// Moves the stepper by the given number of steps (relative move).
+    Step(i32),
+    /// Moves the stepper to the given position (absolute move).
+    To(i32),
+    /// Enables or disables the stepper outputs (requires an enable pin).
+    Enable(bool),
+    /// Stops the stepper (decelerating if an acceleration is set).
+    Stop,
+    /// Requests the board to report the stepper current position.
+    Report,
+    /// Sets the stepper acceleration (in steps per second²: 0 disables the acceleration).
+    Acceleration(f32),
+    /// Sets the stepper max speed (in steps per second).
+    Speed(f32),
+}
+
+/// Defines the commands that can be sent to an encoder (see [`IO::encoder`](crate::io::IO::encoder)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EncoderCommand {
+    /// Attaches the encoder to the given pins (A and B) and enables the automatic position reporting.
+    Attach([u8; 2]),
+    /// Requests the board to report the encoder current position.
+    Report,
+    /// Resets the encoder position to 0.
+    Reset,
+    /// Detaches the encoder.
+    Detach,
 }
 
 /// Defines an I2C reply.
@@ -308,9 +757,38 @@ impl Display for PinModeId {
 
 #[cfg(test)]
 mod tests {
-    use crate::io::{Pin, PinIdOrName, PinMode, PinModeId};
+    use std::sync::Arc;
+
+    use parking_lot::RwLock;
+
+    use crate::io::{I2CReply, IoData, Pin, PinChange, PinClaim, PinIdOrName, PinMode, PinModeId};
     use crate::mocks::create_test_plugin_io_data;
 
+    #[test]
+    fn test_i2c_replies() {
+        let mut data = create_test_plugin_io_data();
+        let mut replies = data.subscribe_i2c_replies();
+
+        for register in 0..100 {
+            data.set_i2c_reply(I2CReply {
+                address: 0x40,
+                register,
+                data: vec![register],
+            });
+        }
+        assert_eq!(replies.try_recv().unwrap().register, 0);
+        assert_eq!(data.i2c_data.len(), IoData::I2C_DATA_CAPACITY);
+        assert_eq!(data.i2c_data[0].register, 36);
+
+        assert_eq!(data.take_i2c_reply(0x40, Some(50)).unwrap().data, vec![50]);
+        assert!(data.take_i2c_reply(0x40, Some(50)).is_none());
+        assert!(data.take_i2c_reply(0x40, Some(10)).is_none());
+        assert!(data.take_i2c_reply(0x41, Some(99)).is_none());
+        assert_eq!(data.take_i2c_reply(0x40, None).unwrap().data, vec![99]);
+        assert!(data.take_i2c_reply(0x41, None).is_none());
+        assert_eq!(data.i2c_data.len(), IoData::I2C_DATA_CAPACITY - 2);
+    }
+
     #[test]
     fn test_get_pin_success() {
         assert_eq!(create_test_plugin_io_data().get_pin(3).unwrap().value, 3);
@@ -339,6 +817,78 @@ mod tests {
         assert_eq!(hardware.get_pin_mut(11).unwrap().value, 255);
     }
 
+    #[test]
+    fn test_set_pin_value() {
+        let mut hardware = create_test_plugin_io_data();
+        let mut changes = hardware.subscribe();
+
+        hardware.set_pin_value(11, 255).unwrap();
+        assert_eq!(hardware.get_pin(11).unwrap().value, 255);
+        assert_eq!(
+            changes.try_recv().unwrap(),
+            PinChange {
+                pin: 11,
+                value: 255
+            }
+        );
+
+        // No notification if the value did not change.
+        hardware.set_pin_value(11, 255).unwrap();
+        assert!(changes.try_recv().is_err());
+
+        assert!(hardware.set_pin_value(66, 1).is_err());
+    }
+
+    #[test]
+    fn test_claim_pins() {
+        let mut hardware = create_test_plugin_io_data();
+        assert!(hardware.get_pin_owner(11).is_none());
+
+        hardware.claim_pins(&[11, 13], "Led").unwrap();
+        assert_eq!(hardware.get_pin_owner(13).unwrap(), "Led");
+
+        // Claims are all or nothing.
+        let error = hardware.claim_pins(&[12, 13], "Servo").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Hardware error: Pin (13) already in use by Led."
+        );
+        assert!(hardware.get_pin_owner(12).is_none());
+        assert!(hardware.claim_pins(&[66], "Servo").is_err());
+
+        hardware.release_pins(&[11, 13]);
+        assert!(hardware.get_pin_owner(13).is_none());
+
+        // Pins in I2C mode belong to the I2C bus.
+        hardware.get_pin_mut(2).unwrap().mode.id = PinModeId::I2C;
+        assert_eq!(hardware.get_pin_owner(2).unwrap(), "I2C");
+        assert!(hardware.claim_pins(&[2], "Button").is_err());
+    }
+
+    #[test]
+    fn test_pin_claim() {
+        let io = Arc::new(RwLock::new(create_test_plugin_io_data()));
+
+        let claim = PinClaim::new(&io, vec![13], "Led").unwrap();
+        assert!(claim.is_claimed());
+        assert!(PinClaim::new(&io, vec![13], "Servo").is_err());
+
+        // Explicit release.
+        claim.release();
+        assert!(!claim.is_claimed());
+        let other = PinClaim::new(&io, vec![13], "Servo").unwrap();
+        assert!(claim.renew().is_err());
+
+        // Release when the last clone is dropped.
+        let clone = other.clone();
+        drop(other);
+        assert_eq!(io.read().get_pin_owner(13).unwrap(), "Servo");
+        drop(clone);
+        assert!(io.read().get_pin_owner(13).is_none());
+        assert!(claim.renew().is_ok());
+        assert!(claim.is_claimed());
+    }
+
     #[test]
     fn test_pin_supports_mode() {
         let pin = Pin {
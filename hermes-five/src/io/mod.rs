@@ -1,7 +1,7 @@
 //! Defines various protocols to control devices associated to boards.
 
-use crate::errors::Error;
-use crate::utils::Range;
+use crate::errors::{Error, HardwareError};
+use crate::utils::{EventManager, Range};
 use dyn_clone::DynClone;
 use parking_lot::RwLock;
 use std::any::type_name;
@@ -60,14 +60,198 @@ pub trait IO {
     /// <https://github.com/firmata/protocol/blob/master/servos.md>
     fn servo_config(&mut self, pin: u8, pwm_range: Range<u16>) -> Result<(), Error>;
 
+    // ########################################
+    // STEPPER
+
+    /// Sends a `command` to the stepper `id` (0-9).
+    ///
+    /// Sends an ACCELSTEPPER_DATA command (0x62 - the firmware must include AccelStepperFirmata).
+    /// <https://github.com/firmata/protocol/blob/master/accelStepperFirmata.md>
+    ///
+    /// # Notes
+    /// This function is optional: it returns a `NotSupported` error by default.
+    fn stepper(&mut self, _id: u8, _command: StepperCommand) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "control stepper",
+        }
+        .into())
+    }
+
+    // ########################################
+    // ENCODER
+
+    /// Sends a `command` to the encoder `id` (0-4).
+    ///
+    /// Sends an ENCODER_DATA command (0x61 - the firmware must include EncoderFirmata).
+    /// <https://github.com/firmata/protocol/blob/master/encoder.md>
+    ///
+    /// # Notes
+    /// This function is optional: it returns a `NotSupported` error by default.
+    fn encoder(&mut self, _id: u8, _command: EncoderCommand) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "attach encoder",
+        }
+        .into())
+    }
+
+    // ########################################
+    // DHT
+
+    /// Requests a reading of the DHT sensor attached to the given `pin`: the reading is reported
+    /// asynchronously (see [`IoData::subscribe_dht_readings`]).
+    ///
+    /// Sends a DHTSENSOR_DATA command (0x74 - the firmware must include DhtFirmata).
+    ///
+    /// # Notes
+    /// This function is optional: it returns a `NotSupported` error by default.
+    fn dht_read(&mut self, _pin: u8, _model: DhtModel) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "read DHT sensor",
+        }
+        .into())
+    }
+
+    // ########################################
+    // ONEWIRE
+
+    /// Configures the OneWire bus on the given `pin`: `parasitic_power` keeps the bus powered after a write
+    /// for devices powered through the data line.
+    ///
+    /// Sends an ONEWIRE_DATA config request (0x73 - the firmware must include OneWireFirmata).
+    /// <https://github.com/firmata/protocol/blob/master/onewire.md>
+    ///
+    /// # Notes
+    /// This function (as all the OneWire functions) is optional: it returns a `NotSupported` error by default.
+    fn onewire_config(&mut self, _pin: u8, _parasitic_power: bool) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "use OneWire bus",
+        }
+        .into())
+    }
+
+    /// Searches the devices on the OneWire bus of the given `pin`: their addresses are reported
+    /// asynchronously (see [`IoData::subscribe_onewire_replies`]).
+    fn onewire_search(&mut self, _pin: u8) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "use OneWire bus",
+        }
+        .into())
+    }
+
+    /// Resets the OneWire bus of the given `pin`.
+    fn onewire_reset(&mut self, _pin: u8) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "use OneWire bus",
+        }
+        .into())
+    }
+
+    /// Selects the device at the given `address` on the OneWire bus of the given `pin`.
+    fn onewire_select(&mut self, _pin: u8, _address: [u8; 8]) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "use OneWire bus",
+        }
+        .into())
+    }
+
+    /// Reads `size` bytes from the (previously selected) device on the OneWire bus of the given `pin`:
+    /// the bytes are reported asynchronously along with the given `correlation_id`
+    /// (see [`IoData::subscribe_onewire_replies`]).
+    fn onewire_read(&mut self, _pin: u8, _size: u16, _correlation_id: u16) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "use OneWire bus",
+        }
+        .into())
+    }
+
+    /// Selects the device at the given `address`, then writes `data` to it on the OneWire bus of the given `pin`.
+    fn onewire_write(&mut self, _pin: u8, _address: [u8; 8], _data: &[u8]) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "use OneWire bus",
+        }
+        .into())
+    }
+
+    /// Makes the OneWire bus of the given `pin` wait for `delay` (in ms) before processing the next requests.
+    fn onewire_delay(&mut self, _pin: u8, _delay: u32) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "use OneWire bus",
+        }
+        .into())
+    }
+
+    // ########################################
+    // PING
+
+    /// Sends a `pulse` (in µs) on the given `pin`, then measures the duration (in µs) of the echo pulse
+    /// the board receives on that same pin within `timeout` (in µs): used by ultrasonic sensors.
+    /// The echo duration is reported asynchronously as the pin value (0 if no echo has been received).
+    ///
+    /// Sends a PING_READ command (0x75 - the firmware must include PingFirmata).
+    ///
+    /// # Notes
+    /// This function is optional: it returns a `NotSupported` error by default.
+    fn ping_read(&mut self, _pin: u8, _pulse: u32, _timeout: u32) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "read ping",
+        }
+        .into())
+    }
+
+    // ########################################
+    // TONE
+
+    /// Plays a tone of `frequency` (in Hz) on the `pin` for `duration` (in ms, 0 meaning until stopped).
+    /// A `frequency` of 0 stops the tone.
+    ///
+    /// Sends a TONE_DATA command (0x5F - the firmware must support the TONE pin mode).
+    ///
+    /// # Notes
+    /// This function is optional: it returns a `NotSupported` error by default.
+    fn tone(&mut self, _pin: u8, _frequency: u16, _duration: u16) -> Result<(), Error> {
+        Err(HardwareError::NotSupported {
+            context: "play tone",
+        }
+        .into())
+    }
+
+    // ########################################
+    // SHIFT
+
+    /// Shifts out the `data` bytes (most significant bit first) on the `data_pin`, pulsing the
+    /// `clock_pin` for each bit, then pulses the `latch_pin`: the shift registers update all their
+    /// outputs at once.
+    ///
+    /// Sends a SHIFT_DATA command (0x75 - the firmware must support the SHIFT pin mode).
+    fn shift_out(
+        &mut self,
+        data_pin: u8,
+        clock_pin: u8,
+        latch_pin: u8,
+        data: &[u8],
+    ) -> Result<(), Error>;
+
     // ########################################
     // I2C
 
     /// Configures the `delay` in microseconds for I2C devices that require a delay between when the
     /// register is written to and the data in that register can be read.
     fn i2c_config(&mut self, delay: u16) -> Result<(), Error>;
-    /// Reads `size` bytes from I2C device at the specified `address`.
-    fn i2c_read(&mut self, address: u8, size: u16) -> Result<(), Error>;
+    /// Reads `size` bytes from I2C device at the specified `address`, starting from `register` if any
+    /// (from the current register pointer of the device otherwise).
+    /// The reply is stored in [`IoData::i2c_data`] (see [`IoData::take_i2c_reply`]).
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error>;
+    /// Asks the I2C device at the specified `address` to be read continuously (at the sampling interval):
+    /// `size` bytes starting from `register` if any (from the current register pointer of the device otherwise).
+    /// The replies are broadcast to the subscribers (see [`IoData::subscribe_i2c_replies`]).
+    fn i2c_read_continuously(
+        &mut self,
+        address: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error>;
+    /// Stops all continuous reads of the I2C device at the specified `address`.
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error>;
     /// Writes `data` to the I2C device at the specified `address`.
     fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error>;
 }
@@ -115,6 +299,15 @@ pub trait IoProtocol: IO + DynClone + Send + Sync + Debug + Display {
     /// will be read.
     /// <https://github.com/firmata/protocol/blob/master/protocol.md#sampling-interval>
     fn sampling_interval(&mut self, interval: u16) -> Result<(), Error>;
+
+    /// Returns the [`EventManager`] used by the protocol to notify about its connection status changes.
+    ///
+    /// # Notes
+    /// This function is optional: only protocols able to recover from a connection loss emit such events
+    /// (see [`BoardEvent`](crate::hardware::BoardEvent)).
+    fn get_events(&self) -> Option<&EventManager> {
+        None
+    }
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -5,16 +5,20 @@
 //! Helper unofficial documentation: https://github.com/martin-eden/firmata_protocol/blob/main/protocol.md
 
 use crate::errors::{Error, HardwareError, ProtocolError};
+use crate::hardware::BoardEvent;
 use crate::io::constants::*;
 use crate::io::*;
 use crate::pause;
 use crate::utils::task::TaskHandler;
-use crate::utils::{task, Range};
+use crate::utils::{task, EventManager, Range};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::TryRecvError;
 
 /// Implements the [Firmata protocol](https://github.com/firmata/protocol) within an [`IoProtocol`].
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -30,6 +34,12 @@ pub struct RemoteIo {
     /// Inner handler to the polling task.
     #[cfg_attr(feature = "serde", serde(skip))]
     handler: Arc<RwLock<Option<TaskHandler>>>,
+    /// The event manager used to notify about connection status changes.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: EventManager,
+    /// Indicates a connection recovery is running.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    recovering: Arc<AtomicBool>,
 }
 
 impl Default for RemoteIo {
@@ -38,6 +48,8 @@ impl Default for RemoteIo {
             transport: Box::new(Serial::default()),
             data: Arc::new(Default::default()),
             handler: Arc::new(RwLock::new(None)),
+            events: Default::default(),
+            recovering: Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -47,6 +59,8 @@ impl RemoteIo {
             transport: Box::new(Serial::new(port)),
             data: Arc::new(Default::default()),
             handler: Arc::new(RwLock::new(None)),
+            events: Default::default(),
+            recovering: Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -57,6 +71,8 @@ impl<T: IoTransport + 'static> From<T> for RemoteIo {
             transport: Box::new(transport),
             data: Arc::new(Default::default()),
             handler: Arc::new(RwLock::new(None)),
+            events: Default::default(),
+            recovering: Arc::new(AtomicBool::new(false)),
         }
     }
 }
@@ -87,25 +103,18 @@ impl IoProtocol for RemoteIo {
 
     fn report_analog(&mut self, channel: u8, state: bool) -> Result<(), Error> {
         // trace!"Report analog: {}", state);
-        self.transport
-            .write(&[REPORT_ANALOG | channel, u8::from(state)])?;
+        self.write(&[REPORT_ANALOG | channel, u8::from(state)])?;
         match state {
             true => {
                 self.data.write().analog_reported_channels.push(channel);
                 self.start_polling();
             }
             false => {
-                let mut lock = self.data.write();
-                if let Some(pos) = lock
+                self.data
+                    .write()
                     .analog_reported_channels
-                    .iter()
-                    .position(|&chan| chan == channel)
-                {
-                    lock.analog_reported_channels.remove(pos);
-                    if lock.analog_reported_channels.is_empty() {
-                        self.stop_polling();
-                    }
-                }
+                    .retain(|&chan| chan != channel);
+                self.stop_polling_when_idle();
             }
         };
         Ok(())
@@ -115,7 +124,7 @@ impl IoProtocol for RemoteIo {
         let port = pin / 8;
         let payload = &[REPORT_DIGITAL | port, u8::from(state)];
         // trace!"Report digital: {:02X?}", payload);
-        self.transport.write(payload)?;
+        self.write(payload)?;
         match state {
             true => {
                 self.data.write().digital_reported_pins.push(pin);
@@ -125,17 +134,16 @@ impl IoProtocol for RemoteIo {
                 let mut lock = self.data.write();
                 if let Some(pos) = lock.digital_reported_pins.iter().position(|&id| id == pin) {
                     lock.digital_reported_pins.remove(pos);
-                    if lock.digital_reported_pins.is_empty() {
-                        self.stop_polling();
-                    }
                 }
+                drop(lock);
+                self.stop_polling_when_idle();
             }
         };
         Ok(())
     }
 
     fn sampling_interval(&mut self, interval: u16) -> Result<(), Error> {
-        self.transport.write(&[
+        self.write(&[
             START_SYSEX,
             SAMPLING_INTERVAL,
             interval as u8 & SYSEX_REALTIME,
@@ -143,6 +151,10 @@ impl IoProtocol for RemoteIo {
             END_SYSEX,
         ])
     }
+
+    fn get_events(&self) -> Option<&EventManager> {
+        Some(&self.events)
+    }
 }
 
 impl IO for RemoteIo {
@@ -168,7 +180,7 @@ impl IO for RemoteIo {
             pin_instance.mode = _mode;
         }
 
-        self.transport.write(&[SET_PIN_MODE, pin, mode as u8])
+        self.write(&[SET_PIN_MODE, pin, mode as u8])
     }
 
     fn digital_write(&mut self, pin: u8, level: bool) -> Result<(), Error> {
@@ -190,8 +202,9 @@ impl IO for RemoteIo {
 
             // Loop through all 8 pins of the current "port" to concatenate their value.
             // For instance 01100000 will set to 1 the pin 1 and 2 or current port.
+            // The last port may be incomplete: missing pins are ignored.
             while i < 8 {
-                if lock.get_pin_mut(8 * port + i)?.value != 0 {
+                if lock.get_pin(8 * port + i).is_ok_and(|pin| pin.value != 0) {
                     value |= 1 << i
                 }
                 i += 1;
@@ -204,7 +217,7 @@ impl IO for RemoteIo {
             (value >> 7) as u8 & SYSEX_REALTIME,
         ];
         // trace!"Digital write: {:02X?}", payload);
-        self.transport.write(payload)
+        self.write(payload)
     }
 
     fn analog_write(&mut self, pin: u8, level: u16) -> Result<(), Error> {
@@ -235,22 +248,28 @@ impl IO for RemoteIo {
         };
 
         // trace!"Analog write: {:02X?}", payload);
-        self.transport.write(&payload)?;
+        self.write(&payload)?;
         Ok(())
     }
 
-    #[cfg(not(tarpaulin_include))]
-    fn digital_read(&mut self, _: u8) -> Result<bool, Error> {
-        unimplemented!()
+    /// Reads the digital `pin` value.
+    ///
+    /// See [`RemoteIo::refresh_pin_value`] for how the value is obtained.
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        self.refresh_pin_value(pin)?;
+        Ok(self.data.read().get_pin(pin)?.value != 0)
     }
 
-    #[cfg(not(tarpaulin_include))]
-    fn analog_read(&mut self, _: u8) -> Result<u16, Error> {
-        unimplemented!()
+    /// Reads the analog `pin` value.
+    ///
+    /// See [`RemoteIo::refresh_pin_value`] for how the value is obtained.
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        self.refresh_pin_value(pin)?;
+        Ok(self.data.read().get_pin(pin)?.value)
     }
 
     fn servo_config(&mut self, pin: u8, pwm_range: Range<u16>) -> Result<(), Error> {
-        self.transport.write(&[
+        self.write(&[
             START_SYSEX,
             SERVO_CONFIG,
             pin,
@@ -262,8 +281,283 @@ impl IO for RemoteIo {
         ])
     }
 
+    fn stepper(&mut self, id: u8, command: StepperCommand) -> Result<(), Error> {
+        let mut buf = vec![START_SYSEX, ACCELSTEPPER_DATA];
+        match command {
+            StepperCommand::Config(config) => {
+                {
+                    let mut lock = self.data.write();
+                    for pin in config.get_pins() {
+                        let pin_instance = lock.get_pin_mut(pin)?;
+                        pin_instance.mode = pin_instance.supports_mode(PinModeId::STEPPER).ok_or(
+                            HardwareError::IncompatiblePin {
+                                pin,
+                                mode: PinModeId::STEPPER,
+                                context: "configure stepper",
+                            },
+                        )?;
+                    }
+                    lock.steppers.insert(id, 0);
+                }
+                let interface = match config.interface {
+                    StepperInterface::Driver { .. } => 0x01,
+                    StepperInterface::TwoWire(_) => 0x02,
+                    StepperInterface::ThreeWire(_) => 0x03,
+                    StepperInterface::FourWire(_) => 0x04,
+                };
+                let step_size = match config.step_size {
+                    StepSize::Whole => 0x00,
+                    StepSize::Half => 0x01,
+                };
+                buf.extend([
+                    ACCELSTEPPER_CONFIG,
+                    id,
+                    (interface << 4) | (step_size << 1) | config.enable_pin.is_some() as u8,
+                ]);
+                buf.extend(config.get_pins());
+            }
+            StepperCommand::Zero => buf.extend([ACCELSTEPPER_ZERO, id]),
+            StepperCommand::Step(steps) => {
+                buf.extend([ACCELSTEPPER_STEP, id]);
+                buf.extend(encode_i32(steps));
+            }
+            StepperCommand::To(position) => {
+                buf.extend([ACCELSTEPPER_TO, id]);
+                buf.extend(encode_i32(position));
+            }
+            StepperCommand::Enable(state) => buf.extend([ACCELSTEPPER_ENABLE, id, state as u8]),
+            StepperCommand::Stop => buf.extend([ACCELSTEPPER_STOP, id]),
+            StepperCommand::Report => buf.extend([ACCELSTEPPER_REPORT_POSITION, id]),
+            StepperCommand::Acceleration(acceleration) => {
+                buf.extend([ACCELSTEPPER_SET_ACCELERATION, id]);
+                buf.extend(encode_custom_float(acceleration));
+            }
+            StepperCommand::Speed(speed) => {
+                buf.extend([ACCELSTEPPER_SET_SPEED, id]);
+                buf.extend(encode_custom_float(speed));
+            }
+        }
+        buf.push(END_SYSEX);
+        self.write(&buf)
+    }
+
+    fn encoder(&mut self, id: u8, command: EncoderCommand) -> Result<(), Error> {
+        match command {
+            EncoderCommand::Attach(pins) => {
+                {
+                    let mut lock = self.data.write();
+                    for pin in pins {
+                        let pin_instance = lock.get_pin_mut(pin)?;
+                        pin_instance.mode = pin_instance.supports_mode(PinModeId::ENCODER).ok_or(
+                            HardwareError::IncompatiblePin {
+                                pin,
+                                mode: PinModeId::ENCODER,
+                                context: "attach encoder",
+                            },
+                        )?;
+                    }
+                    lock.encoders.insert(id, 0);
+                }
+                self.write(&[
+                    START_SYSEX,
+                    ENCODER_DATA,
+                    ENCODER_ATTACH,
+                    id,
+                    pins[0],
+                    pins[1],
+                    END_SYSEX,
+                ])?;
+                self.write(&[START_SYSEX, ENCODER_DATA, ENCODER_REPORT_AUTO, 1, END_SYSEX])
+            }
+            EncoderCommand::Report => self.write(&[
+                START_SYSEX,
+                ENCODER_DATA,
+                ENCODER_REPORT_POSITION,
+                id,
+                END_SYSEX,
+            ]),
+            EncoderCommand::Reset => {
+                self.data.write().set_encoder_position(id, 0);
+                self.write(&[
+                    START_SYSEX,
+                    ENCODER_DATA,
+                    ENCODER_RESET_POSITION,
+                    id,
+                    END_SYSEX,
+                ])
+            }
+            EncoderCommand::Detach => {
+                self.data.write().encoders.remove(&id);
+                self.write(&[START_SYSEX, ENCODER_DATA, ENCODER_DETACH, id, END_SYSEX])
+            }
+        }
+    }
+
+    fn dht_read(&mut self, pin: u8, model: DhtModel) -> Result<(), Error> {
+        self.write(&[
+            START_SYSEX,
+            DHTSENSOR_DATA,
+            DHTSENSOR_READ,
+            model as u8,
+            pin,
+            END_SYSEX,
+        ])
+    }
+
+    fn onewire_config(&mut self, pin: u8, parasitic_power: bool) -> Result<(), Error> {
+        {
+            let mut lock = self.data.write();
+            let pin_instance = lock.get_pin_mut(pin)?;
+            pin_instance.mode = pin_instance.supports_mode(PinModeId::ONEWIRE).ok_or(
+                HardwareError::IncompatiblePin {
+                    pin,
+                    mode: PinModeId::ONEWIRE,
+                    context: "configure OneWire bus",
+                },
+            )?;
+        }
+        self.write(&[
+            START_SYSEX,
+            ONEWIRE_DATA,
+            ONEWIRE_CONFIG_REQUEST,
+            pin,
+            parasitic_power as u8,
+            END_SYSEX,
+        ])
+    }
+
+    fn onewire_search(&mut self, pin: u8) -> Result<(), Error> {
+        self.write(&[
+            START_SYSEX,
+            ONEWIRE_DATA,
+            ONEWIRE_SEARCH_REQUEST,
+            pin,
+            END_SYSEX,
+        ])
+    }
+
+    fn onewire_reset(&mut self, pin: u8) -> Result<(), Error> {
+        self.onewire_request(pin, ONEWIRE_RESET_REQUEST_BIT, &[])
+    }
+
+    fn onewire_select(&mut self, pin: u8, address: [u8; 8]) -> Result<(), Error> {
+        let mut args = [0; 16];
+        args[..8].copy_from_slice(&address);
+        self.onewire_request(pin, ONEWIRE_SELECT_REQUEST_BIT, &args)
+    }
+
+    fn onewire_read(&mut self, pin: u8, size: u16, correlation_id: u16) -> Result<(), Error> {
+        let mut args = [0; 16];
+        args[8..10].copy_from_slice(&size.to_le_bytes());
+        args[10..12].copy_from_slice(&correlation_id.to_le_bytes());
+        self.onewire_request(pin, ONEWIRE_READ_REQUEST_BIT, &args)
+    }
+
+    fn onewire_write(&mut self, pin: u8, address: [u8; 8], data: &[u8]) -> Result<(), Error> {
+        let mut args = vec![0; 16];
+        args[..8].copy_from_slice(&address);
+        args.extend(data);
+        self.onewire_request(
+            pin,
+            ONEWIRE_SELECT_REQUEST_BIT | ONEWIRE_WRITE_REQUEST_BIT,
+            &args,
+        )
+    }
+
+    fn onewire_delay(&mut self, pin: u8, delay: u32) -> Result<(), Error> {
+        let mut args = [0; 16];
+        args[12..16].copy_from_slice(&delay.to_le_bytes());
+        self.onewire_request(pin, ONEWIRE_DELAY_REQUEST_BIT, &args)
+    }
+
+    fn ping_read(&mut self, pin: u8, pulse: u32, timeout: u32) -> Result<(), Error> {
+        let mut buf = vec![START_SYSEX, PING_READ, pin, 1];
+        // Each 32-bit value is sent as 4 bytes (MSB first), each one encoded into two 7-bit bytes.
+        for byte in pulse.to_be_bytes().into_iter().chain(timeout.to_be_bytes()) {
+            buf.extend([byte & SYSEX_REALTIME, byte >> 7]);
+        }
+        buf.push(END_SYSEX);
+        self.write(&buf)
+    }
+
+    fn tone(&mut self, pin: u8, frequency: u16, duration: u16) -> Result<(), Error> {
+        match frequency {
+            0 => self.write(&[START_SYSEX, TONE_DATA, TONE_NO_TONE, pin, END_SYSEX]),
+            _ => self.write(&[
+                START_SYSEX,
+                TONE_DATA,
+                TONE_TONE,
+                pin,
+                frequency as u8 & SYSEX_REALTIME,
+                (frequency >> 7) as u8 & SYSEX_REALTIME,
+                duration as u8 & SYSEX_REALTIME,
+                (duration >> 7) as u8 & SYSEX_REALTIME,
+                END_SYSEX,
+            ]),
+        }
+    }
+
+    fn shift_out(
+        &mut self,
+        data_pin: u8,
+        clock_pin: u8,
+        latch_pin: u8,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        // SHIFT_DATA is not part of StandardFirmata (and 0x75 is PING_READ for some firmwares): it is
+        // only sent when the firmware advertises the SHIFT mode on the data and clock pins.
+        {
+            let lock = self.data.read();
+            for pin in [data_pin, clock_pin] {
+                lock.get_pin(pin)?.supports_mode(PinModeId::SHIFT).ok_or(
+                    HardwareError::IncompatiblePin {
+                        pin,
+                        mode: PinModeId::SHIFT,
+                        context: "shift out data",
+                    },
+                )?;
+            }
+        }
+
+        let mut buf = vec![
+            START_SYSEX,
+            SHIFT_DATA,
+            SHIFT_OUT,
+            data_pin,
+            clock_pin,
+            latch_pin,
+            SHIFT_MSB_FIRST,
+        ];
+        for &byte in data {
+            buf.extend([byte & SYSEX_REALTIME, byte >> 7]);
+        }
+        buf.push(END_SYSEX);
+        self.write(&buf)
+    }
+
     fn i2c_config(&mut self, delay: u16) -> Result<(), Error> {
-        self.transport.write(&[
+        // The firmware switches all I2C compatible pins to I2C mode: they now belong to the I2C bus.
+        {
+            let mut lock = self.data.write();
+            if let Some((&pin, owner)) = lock.claims.iter().find(|(pin, _)| {
+                lock.pins
+                    .get(pin)
+                    .is_some_and(|instance| instance.supports_mode(PinModeId::I2C).is_some())
+            }) {
+                return Err(HardwareError::PinInUse {
+                    pin,
+                    owner: owner.clone(),
+                }
+                .into());
+            }
+            for instance in lock.pins.values_mut() {
+                if let Some(mode) = instance.supports_mode(PinModeId::I2C) {
+                    instance.mode = mode;
+                }
+            }
+        }
+
+        self.write(&[
             START_SYSEX,
             I2C_CONFIG,
             (delay as u8) & SYSEX_REALTIME,
@@ -272,17 +566,53 @@ impl IO for RemoteIo {
         ])
     }
 
-    fn i2c_read(&mut self, address: u8, size: u16) -> Result<(), Error> {
-        self.transport.write(&[
+    fn i2c_read(&mut self, address: u8, register: Option<u8>, size: u16) -> Result<(), Error> {
+        // Subscribes before the request, so that the reply cannot be missed.
+        let mut replies = self.data.read().subscribe_i2c_replies();
+        self.i2c_read_request(address, I2C_READ << 3, register, size)?;
+
+        // A continuous read of the same device may reply meanwhile: its replies are told apart by their size.
+        let continuous = self
+            .data
+            .read()
+            .i2c_reported_reads
+            .iter()
+            .any(|(read, _, _)| *read == address);
+        self.wait_for(&mut replies, |reply| {
+            reply.address == address
+                && register.is_none_or(|register| reply.register == register)
+                && (!continuous || reply.data.len() == size as usize)
+        })
+    }
+
+    fn i2c_read_continuously(
+        &mut self,
+        address: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error> {
+        self.i2c_read_request(address, I2C_READ_CONTINUOUSLY, register, size)?;
+        self.data
+            .write()
+            .i2c_reported_reads
+            .push((address, register, size));
+        self.start_polling();
+        Ok(())
+    }
+
+    fn i2c_stop_reading(&mut self, address: u8) -> Result<(), Error> {
+        self.write(&[
             START_SYSEX,
             I2C_REQUEST,
             address,
-            I2C_READ << 3,
-            (size as u8) & SYSEX_REALTIME,
-            (size >> 7) as u8 & SYSEX_REALTIME,
+            I2C_STOP_READING,
             END_SYSEX,
         ])?;
-        while self.read_and_decode()? != Message::I2CReply {}
+        self.data
+            .write()
+            .i2c_reported_reads
+            .retain(|(read, _, _)| *read != address);
+        self.stop_polling_when_idle();
         Ok(())
     }
 
@@ -296,17 +626,111 @@ impl IO for RemoteIo {
 
         buf.push(END_SYSEX);
 
-        self.transport.write(&buf)
+        self.write(&buf)
     }
 }
 
 impl RemoteIo {
+    /// Sends an I2C read request (`mode` being a one-shot or continuous read) of `size` bytes, starting from
+    /// `register` if any.
+    /// <https://github.com/firmata/protocol/blob/master/i2c.md>
+    fn i2c_read_request(
+        &mut self,
+        address: u8,
+        mode: u8,
+        register: Option<u8>,
+        size: u16,
+    ) -> Result<(), Error> {
+        let mut buf = vec![START_SYSEX, I2C_REQUEST, address, mode];
+        if let Some(register) = register {
+            buf.extend([register & SYSEX_REALTIME, register >> 7]);
+        }
+        buf.extend([
+            (size as u8) & SYSEX_REALTIME,
+            (size >> 7) as u8 & SYSEX_REALTIME,
+            END_SYSEX,
+        ]);
+        self.write(&buf)
+    }
+
+    /// Sends a OneWire request: the `args` are laid out as [address (8 bytes), number of bytes to read (2 bytes),
+    /// correlation id (2 bytes), delay (4 bytes), data to write] and encoded using the 7-bit packing.
+    /// <https://github.com/firmata/protocol/blob/master/onewire.md>
+    fn onewire_request(&mut self, pin: u8, subcommand: u8, args: &[u8]) -> Result<(), Error> {
+        let mut buf = vec![START_SYSEX, ONEWIRE_DATA, subcommand, pin];
+        buf.extend(encode_7bit(args));
+        buf.push(END_SYSEX);
+        self.write(&buf)
+    }
+
+    /// Waits (up to 1s) for a message accepted by the `filter` to be notified on the `receiver`.
+    ///
+    /// The polling task, when running, is the only reader of the transport layer: the messages it decodes
+    /// are awaited. Otherwise, the messages are read and decoded here.
+    ///
+    /// # Errors
+    /// * `Timeout`: no such message has been received in time.
+    fn wait_for<T: Clone>(
+        &mut self,
+        receiver: &mut broadcast::Receiver<T>,
+        filter: impl Fn(&T) -> bool,
+    ) -> Result<(), Error> {
+        let deadline = Instant::now() + Duration::from_millis(1000);
+        loop {
+            match receiver.try_recv() {
+                Ok(message) if filter(&message) => return Ok(()),
+                Ok(_) | Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => {}
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::from(ProtocolError::Timeout));
+            }
+
+            let polling = self.handler.read().is_some();
+            match polling {
+                true => std::thread::sleep(Duration::from_millis(1)),
+                false => match self.read_and_decode() {
+                    Ok(_)
+                    | Err(Error::ProtocolError {
+                        source: ProtocolError::Timeout,
+                    }) => {}
+                    Err(error) => return Err(error),
+                },
+            }
+        }
+    }
+
+    /// Stops the polling task once nothing is reported by the board anymore (digital, analog or I2C).
+    fn stop_polling_when_idle(&self) {
+        let idle = {
+            let lock = self.data.read();
+            lock.digital_reported_pins.is_empty()
+                && lock.analog_reported_channels.is_empty()
+                && lock.i2c_reported_reads.is_empty()
+        };
+        if idle {
+            self.stop_polling();
+        }
+    }
+
+    /// Writes to the transport layer: starts the recovery procedure if the connection is lost.
+    fn write(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let result = self.transport.write(buf);
+        if let Err(Error::ProtocolError {
+            source: ProtocolError::ConnectionLost,
+        }) = result
+        {
+            self.recover();
+        }
+        result
+    }
+
     /// Sends a software reset request.
     /// <https://github.com/firmata/protocol/blob/master/protocol.md>
     fn software_reset(&mut self) -> Result<(), Error> {
         let payload = &[SYSTEM_RESET];
         // trace!"Software reset: {:02X?}", payload);
-        self.transport.write(payload)
+        self.write(payload)
     }
 
     /// Starts a conversation with the board: validate the firmware version and...
@@ -339,24 +763,90 @@ impl RemoteIo {
     fn query_firmware(&mut self) -> Result<(), Error> {
         let payload = &[START_SYSEX, REPORT_FIRMWARE, END_SYSEX];
         // trace!"Query firmware: {:02X?}", payload);
-        self.transport.write(payload)
+        self.write(payload)
     }
 
     /// Query the board for all available capabilities.
     fn query_capabilities(&mut self) -> Result<(), Error> {
         let payload = &[START_SYSEX, CAPABILITY_QUERY, END_SYSEX];
         // trace!"Query capabilities: {:02X?}", payload);
-        self.transport.write(payload)
+        self.write(payload)
     }
 
     // ########################################
     // Read/Write on pins
 
+    /// Query the board for the current mode and state of a pin and waits for the response.
+    /// <https://github.com/firmata/protocol/blob/master/protocol.md#pin-state-query>
+    ///
+    /// # Errors
+    /// * `UnknownPin`: the pin does not exist for this board.
+    /// * `Timeout`: no response has been received in time.
+    fn query_pin_state(&mut self, pin: u8) -> Result<(), Error> {
+        // Subscribes before the query, so that the response cannot be missed.
+        let mut states = {
+            let lock = self.data.read();
+            lock.get_pin(pin)?;
+            lock.subscribe_pin_states()
+        };
+
+        let payload = &[START_SYSEX, PIN_STATE_QUERY, pin, END_SYSEX];
+        // trace!"Query pin state: {:02X?}", payload);
+        self.write(payload)?;
+
+        self.wait_for(&mut states, |state| state.pin == pin)
+    }
+
+    /// Refreshes the value of a pin from the board.
+    ///
+    /// The state of a pin is not its value for inputs (the firmware answers a PIN_STATE_QUERY with the pull-up
+    /// status of a digital input and 0 for an analog input):
+    /// - input pins (INPUT, PULLUP and ANALOG modes) keep their last reported value if their reporting is
+    ///   activated, otherwise their reporting is activated until the board reports them once,
+    /// - output pins are queried with a PIN_STATE_QUERY.
+    fn refresh_pin_value(&mut self, pin: u8) -> Result<(), Error> {
+        let (mode, port_reported, channel) = {
+            let lock = self.data.read();
+            let instance = lock.get_pin(pin)?;
+            // The board reports the digital pins by port.
+            let port_reported = lock
+                .digital_reported_pins
+                .iter()
+                .any(|id| id / 8 == pin / 8);
+            let channel = instance
+                .channel
+                .map(|channel| (channel, lock.analog_reported_channels.contains(&channel)));
+            (instance.mode.id, port_reported, channel)
+        };
+        match (mode, channel) {
+            (PinModeId::INPUT | PinModeId::PULLUP, _) if port_reported => Ok(()),
+            (PinModeId::INPUT | PinModeId::PULLUP, _) => {
+                self.read_reported(pin, REPORT_DIGITAL | (pin / 8))
+            }
+            (PinModeId::ANALOG, Some((_, true))) => Ok(()),
+            (PinModeId::ANALOG, Some((channel, false))) => {
+                self.read_reported(pin, REPORT_ANALOG | channel)
+            }
+            _ => self.query_pin_state(pin),
+        }
+    }
+
+    /// Activates a reporting (REPORT_DIGITAL or REPORT_ANALOG `command`) until the board reports the value of
+    /// `pin` once, then deactivates it.
+    fn read_reported(&mut self, pin: u8, command: u8) -> Result<(), Error> {
+        // Subscribes before activating the reporting, so that the first report cannot be missed.
+        let mut reports = self.data.read().subscribe_reports();
+        self.write(&[command, 1])?;
+        let result = self.wait_for(&mut reports, |report| report.pin == pin);
+        let restored = self.write(&[command, 0]);
+        result.and(restored)
+    }
+
     /// Query the board for available analog pins.
     fn query_analog_mapping(&mut self) -> Result<(), Error> {
         let payload = &[START_SYSEX, ANALOG_MAPPING_QUERY, END_SYSEX];
         // trace!"Query analog mapping: {:02X?}", payload);
-        self.transport.write(payload)
+        self.write(payload)
     }
 
     // ########################################
@@ -393,10 +883,17 @@ impl RemoteIo {
     /// Handle an ANALOG_MESSAGE message (0xE0 - report state of an analog pin)
     /// <https://github.com/firmata/protocol/blob/master/protocol.md#data-message-expansion>
     fn handle_analog_message(&mut self, buf: &[u8]) -> Result<Message, Error> {
-        let pin = (buf[0] & 0x0F) + 14;
+        let channel = buf[0] & 0x0F;
         let value = (buf[1] as u16) | ((buf[2] as u16) << 7);
+        let mut lock = self.get_io().write();
+        // Find the pin mapped to the channel (defaults to the UNO layout where A0 is pin 14).
+        let pin = lock
+            .pins
+            .values()
+            .find(|pin| pin.channel == Some(channel))
+            .map_or(channel + 14, |pin| pin.id);
         // trace!"Received analog message: pin({})={}", pin, value);
-        self.get_io().write().get_pin_mut(pin)?.value = value;
+        lock.set_pin_value(pin, value)?;
         Ok(Message::Analog)
     }
 
@@ -411,7 +908,9 @@ impl RemoteIo {
             let pin = (8 * port) + i;
             let mode: PinModeId = self.get_io().read().get_pin(pin)?.mode.id;
             if mode == PinModeId::INPUT || mode == PinModeId::PULLUP {
-                self.get_io().write().get_pin_mut(pin)?.value = (value >> (i & 0x07)) & 0x01;
+                self.get_io()
+                    .write()
+                    .set_pin_value(pin, (value >> (i & 0x07)) & 0x01)?;
             }
         }
         Ok(Message::Digital)
@@ -439,6 +938,11 @@ impl RemoteIo {
             REPORT_FIRMWARE => self.handle_firmware_report(buf),
             I2C_REPLY => self.handle_i2c_reply(buf),
             PIN_STATE_RESPONSE => self.handle_pin_state_response(buf),
+            ACCELSTEPPER_DATA => self.handle_stepper_reply(buf),
+            ENCODER_DATA => self.handle_encoder_reply(buf),
+            PING_READ => self.handle_ping_reply(buf),
+            DHTSENSOR_DATA => self.handle_dht_reply(buf),
+            ONEWIRE_DATA => self.handle_onewire_reply(buf),
             _ => {
                 // trace!"Sysex: unexpected data: {:02X?}", buf.as_slice());
                 Ok(Message::EmptyResponse)
@@ -559,10 +1063,142 @@ impl RemoteIo {
             reply.data.push((buf[i]) | (buf[i + 1] << 7));
             i += 2;
         }
-        self.get_io().write().i2c_data.push(reply);
+        self.get_io().write().set_i2c_reply(reply);
         Ok(Message::I2CReply)
     }
 
+    /// Handle an ACCELSTEPPER_DATA message (0x62 - report a stepper position or its move completion)
+    /// <https://github.com/firmata/protocol/blob/master/accelStepperFirmata.md>
+    fn handle_stepper_reply(&mut self, buf: &[u8]) -> Result<Message, Error> {
+        if buf.len() < 10 {
+            return Err(Error::from(ProtocolError::MessageTooShort {
+                operation: "handle_stepper_reply",
+                expected: 10,
+                received: buf.len(),
+            }));
+        }
+        let complete = match buf[2] {
+            ACCELSTEPPER_MOVE_COMPLETE => true,
+            ACCELSTEPPER_REPORT_POSITION => false,
+            _ => return Ok(Message::EmptyResponse),
+        };
+        let position = decode_i32(&buf[4..9]);
+        self.get_io()
+            .write()
+            .set_stepper_position(buf[3], position, complete);
+        Ok(Message::StepperReply)
+    }
+
+    /// Handle an ENCODER_DATA message (0x61 - report the position of one or many encoders)
+    /// <https://github.com/firmata/protocol/blob/master/encoder.md>
+    fn handle_encoder_reply(&mut self, buf: &[u8]) -> Result<Message, Error> {
+        if buf.len() < 8 {
+            return Err(Error::from(ProtocolError::MessageTooShort {
+                operation: "handle_encoder_reply",
+                expected: 8,
+                received: buf.len(),
+            }));
+        }
+        let mut lock = self.get_io().write();
+        // Each encoder position is reported using 5 bytes: [direction | id, 4 bytes position].
+        for report in buf[2..buf.len() - 1].chunks_exact(5) {
+            let magnitude = (report[1] as i32)
+                | ((report[2] as i32) << 7)
+                | ((report[3] as i32) << 14)
+                | ((report[4] as i32) << 21);
+            let position = match report[0] & 0x40 {
+                0 => magnitude,
+                _ => -magnitude,
+            };
+            lock.set_encoder_position(report[0] & 0x3F, position);
+        }
+        Ok(Message::EncoderReply)
+    }
+
+    /// Handle a PING_READ message (0x75 - report the echo duration of a ping)
+    fn handle_ping_reply(&mut self, buf: &[u8]) -> Result<Message, Error> {
+        if buf.len() < 13 {
+            return Err(Error::from(ProtocolError::MessageTooShort {
+                operation: "handle_ping_reply",
+                expected: 13,
+                received: buf.len(),
+            }));
+        }
+        let pin = buf[2] | (buf[3] << 7);
+        // The duration is reported as 4 bytes (MSB first), each one encoded into two 7-bit bytes.
+        let duration = buf[4..12].chunks_exact(2).fold(0u32, |duration, pair| {
+            (duration << 8) | (pair[0] as u32 | ((pair[1] as u32) << 7)) & 0xFF
+        });
+        self.get_io()
+            .write()
+            .set_pin_value(pin, duration.min(u16::MAX as u32) as u16)?;
+        Ok(Message::PingReply)
+    }
+
+    /// Handle a DHTSENSOR_DATA message (0x74 - report the temperature and humidity read by a DHT sensor)
+    fn handle_dht_reply(&mut self, buf: &[u8]) -> Result<Message, Error> {
+        if buf.len() < 11 {
+            return Err(Error::from(ProtocolError::MessageTooShort {
+                operation: "handle_dht_reply",
+                expected: 11,
+                received: buf.len(),
+            }));
+        }
+        // Values are reported in tenths, as signed 14-bit integers encoded into two 7-bit bytes.
+        let decode = |lsb: u8, msb: u8| {
+            let value = (lsb as i16) | ((msb as i16) << 7);
+            ((value << 2) >> 2) as f32 / 10.0
+        };
+        // buf[3] holds the sensor model.
+        let reading = DhtReading {
+            pin: buf[4],
+            status: buf[5],
+            humidity: decode(buf[6], buf[7]),
+            temperature: decode(buf[8], buf[9]),
+        };
+        self.get_io().write().set_dht_reading(reading);
+        Ok(Message::DhtReply)
+    }
+
+    /// Handle an ONEWIRE_DATA message (0x73 - report the devices found by a search or the bytes read on a OneWire bus)
+    /// <https://github.com/firmata/protocol/blob/master/onewire.md>
+    fn handle_onewire_reply(&mut self, buf: &[u8]) -> Result<Message, Error> {
+        if buf.len() < 5 {
+            return Err(Error::from(ProtocolError::MessageTooShort {
+                operation: "handle_onewire_reply",
+                expected: 5,
+                received: buf.len(),
+            }));
+        }
+        let pin = buf[3];
+        let data = decode_7bit(&buf[4..buf.len() - 1]);
+        let reply = match buf[2] {
+            ONEWIRE_SEARCH_REPLY | ONEWIRE_SEARCH_ALARMS_REPLY => OneWireReply::Search {
+                pin,
+                addresses: data
+                    .chunks_exact(8)
+                    .map(|address| address.try_into().unwrap_or_default())
+                    .collect(),
+            },
+            // The read bytes are preceded by the correlation id of the request (2 bytes).
+            ONEWIRE_READ_REPLY if data.len() < 2 => {
+                return Err(Error::from(ProtocolError::MessageTooShort {
+                    operation: "handle_onewire_reply",
+                    expected: 8,
+                    received: buf.len(),
+                }))
+            }
+            ONEWIRE_READ_REPLY => OneWireReply::Read {
+                pin,
+                correlation_id: u16::from_le_bytes([data[0], data[1]]),
+                data: data[2..].to_vec(),
+            },
+            _ => return Ok(Message::EmptyResponse),
+        };
+        self.get_io().write().set_onewire_reply(reply);
+        Ok(Message::OneWireReply)
+    }
+
     /// Handle a PIN_STATE_RESPONSE message (0x6E - report pin current mode and state)
     /// <https://github.com/firmata/protocol/blob/master/protocol.md#pin-state-query>
     fn handle_pin_state_response(&mut self, buf: &[u8]) -> Result<Message, Error> {
@@ -579,18 +1215,24 @@ impl RemoteIo {
         let pin = lock.get_pin_mut(pin)?;
         // Check if the state announce by the protocol is plausible and fetch it.
         let mode = PinModeId::from_u8(buf[3])?;
-        let current_state = pin.supports_mode(mode).unwrap();
-        pin.mode = current_state;
+        pin.mode = pin
+            .supports_mode(mode)
+            .ok_or(HardwareError::IncompatiblePin {
+                pin: buf[2],
+                mode,
+                context: "handle pin state response",
+            })?;
 
         let mut i = 4;
         let mut value: usize = 0;
         while buf[i] != END_SYSEX {
-            // Shift value by 7 bits and combine with the next 7 bits
-            value = (value << 7) | ((buf[i] as usize) & 0x7F);
+            // The state is sent as 7 bits chunks: least significant bits first.
+            value |= ((buf[i] as usize) & 0x7F) << (7 * (i - 4));
             i += 1;
         }
         pin.value = value as u16;
         // trace!"Received pin state: {:?}", pin);
+        lock.notify_pin_state(buf[2], value as u16);
         Ok(Message::PinStateResponse)
     }
 
@@ -602,11 +1244,22 @@ impl RemoteIo {
             let mut self_clone = self.clone();
             *self.handler.write() = Some(
                 task::run(async move {
-                    // Infinite loop to listen for inputs from the board.
-                    // @todo this is constant polling. Evaluate if this is the right solution and the polling resolution.
+                    // Infinite loop to listen for inputs from the board: reading blocks until some data is
+                    // received (or the transport timeout expires), and the decoded input pin changes are
+                    // notified to the subscribers (see `IoData::subscribe`).
                     loop {
-                        let _ = self_clone.read_and_decode();
-                        pause!(1);
+                        match self_clone.read_and_decode() {
+                            Err(Error::ProtocolError {
+                                source: ProtocolError::ConnectionLost,
+                            }) => self_clone.recover(),
+                            Ok(_)
+                            | Err(Error::ProtocolError {
+                                source: ProtocolError::Timeout,
+                            }) => {}
+                            // Avoids a busy loop on a failing transport layer.
+                            Err(_) => pause!(10),
+                        }
+                        tokio::task::yield_now().await;
                     }
 
                     #[allow(unreachable_code)]
@@ -625,6 +1278,198 @@ impl RemoteIo {
         }
         *self.handler.write() = None;
     }
+
+    // ########################################
+    // Connection recovery
+
+    /// Starts the recovery procedure (unless already running) after a connection loss.
+    ///
+    /// This only happens with a transport layer able to detect and recover a connection loss (see [`AutoReconnect`]).
+    /// The following events are emitted:
+    /// - `BoardEvent::OnDisconnect` when the procedure starts,
+    /// - `BoardEvent::OnReconnect` when the connection is restored,
+    /// - `BoardEvent::OnReconnectFailed` if the transport layer gave up.
+    fn recover(&self) {
+        if self.recovering.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let self_clone = self.clone();
+        let _ = task::run(async move {
+            self_clone.stop_polling();
+            self_clone.data.write().connected = false;
+            self_clone.events.emit(BoardEvent::OnDisconnect, ());
+
+            // The reconnection blocks between attempts: keep it off the runtime workers.
+            let mut restoring = self_clone.clone();
+            let result = tokio::task::spawn_blocking(move || restoring.restore())
+                .await
+                .unwrap_or_else(|error| {
+                    Err(Error::UnknownError {
+                        info: error.to_string(),
+                    })
+                });
+            self_clone.recovering.store(false, Ordering::SeqCst);
+            match result {
+                Ok(_) => self_clone.events.emit(BoardEvent::OnReconnect, ()),
+                Err(_) => self_clone.events.emit(BoardEvent::OnReconnectFailed, ()),
+            };
+            result
+        });
+    }
+
+    /// Reconnects the transport layer, re-runs the handshake and restores the pin modes and reporting.
+    fn restore(&mut self) -> Result<(), Error> {
+        // The handshake resets the pins data: save their current modes first.
+        let modes: Vec<(u8, PinModeId)> = self
+            .data
+            .read()
+            .pins
+            .values()
+            .map(|pin| (pin.id, pin.mode.id))
+            .collect();
+
+        self.transport.reconnect()?;
+        self.handshake()?;
+        self.transport.set_timeout(Duration::from_millis(500))?;
+
+        // Restore pin modes.
+        for (pin, mode) in modes {
+            let current = self.data.read().get_pin(pin).map(|pin| pin.mode.id).ok();
+            if current.is_some_and(|current| current != mode) {
+                self.set_pin_mode(pin, mode)?;
+            }
+        }
+
+        // Restore reporting.
+        let (digital_pins, analog_channels, i2c_reads) = {
+            let lock = self.data.read();
+            (
+                lock.digital_reported_pins.clone(),
+                lock.analog_reported_channels.clone(),
+                lock.i2c_reported_reads.clone(),
+            )
+        };
+        for pin in &digital_pins {
+            self.write(&[REPORT_DIGITAL | (pin / 8), 1])?;
+        }
+        for channel in &analog_channels {
+            self.write(&[REPORT_ANALOG | channel, 1])?;
+        }
+        for &(address, register, size) in &i2c_reads {
+            self.i2c_read_request(address, I2C_READ_CONTINUOUSLY, register, size)?;
+        }
+
+        self.data.write().connected = true;
+        if !digital_pins.is_empty() || !analog_channels.is_empty() || !i2c_reads.is_empty() {
+            self.start_polling();
+        }
+        Ok(())
+    }
+}
+
+/// Encodes a 32-bit signed integer into 5 sysex bytes (the sign being held by bit 3 of the last byte).
+/// <https://github.com/firmata/protocol/blob/master/accelStepperFirmata.md#32-bit-signed-integer-encoding>
+fn encode_i32(value: i32) -> [u8; 5] {
+    let magnitude = value.unsigned_abs();
+    let mut encoded = [
+        magnitude as u8 & SYSEX_REALTIME,
+        (magnitude >> 7) as u8 & SYSEX_REALTIME,
+        (magnitude >> 14) as u8 & SYSEX_REALTIME,
+        (magnitude >> 21) as u8 & SYSEX_REALTIME,
+        (magnitude >> 28) as u8 & 0x07,
+    ];
+    if value < 0 {
+        encoded[4] |= 0x08;
+    }
+    encoded
+}
+
+/// Decodes a 32-bit signed integer from 5 sysex bytes (see [`encode_i32`]).
+fn decode_i32(buf: &[u8]) -> i32 {
+    let magnitude = (buf[0] as i64)
+        | ((buf[1] as i64) << 7)
+        | ((buf[2] as i64) << 14)
+        | ((buf[3] as i64) << 21)
+        | (((buf[4] & 0x07) as i64) << 28);
+    match buf[4] & 0x08 {
+        0 => magnitude as i32,
+        _ => -magnitude as i32,
+    }
+}
+
+/// Encodes 8-bit bytes as a stream of 7-bit sysex bytes (each 7 bytes being packed into 8 sysex bytes).
+/// <https://github.com/firmata/protocol/blob/master/onewire.md>
+fn encode_7bit(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(data.len() * 8 / 7 + 1);
+    let mut shift = 0;
+    let mut previous = 0;
+    for &byte in data {
+        if shift == 0 {
+            encoded.push(byte & SYSEX_REALTIME);
+            shift = 1;
+            previous = byte >> 7;
+        } else {
+            encoded.push(((byte << shift) & SYSEX_REALTIME) | previous);
+            if shift == 6 {
+                encoded.push(byte >> 1);
+                shift = 0;
+            } else {
+                shift += 1;
+                previous = byte >> (8 - shift);
+            }
+        }
+    }
+    if shift > 0 {
+        encoded.push(previous);
+    }
+    encoded
+}
+
+/// Decodes 8-bit bytes from a stream of 7-bit sysex bytes (see [`encode_7bit`]).
+fn decode_7bit(encoded: &[u8]) -> Vec<u8> {
+    (0..encoded.len() * 7 / 8)
+        .map(|i| {
+            let position = i * 8 / 7;
+            let shift = i * 8 % 7;
+            (encoded[position] >> shift) | (encoded[position + 1] << (7 - shift))
+        })
+        .collect()
+}
+
+/// Encodes a float into 4 sysex bytes using the AccelStepperFirmata custom format:
+/// a 23 bits significand, a 4 bits base-10 exponent (biased by 11) and a sign bit.
+/// <https://github.com/firmata/protocol/blob/master/accelStepperFirmata.md#custom-float-encoding>
+fn encode_custom_float(value: f32) -> [u8; 4] {
+    const MAX_SIGNIFICAND: f64 = (1 << 23) as f64;
+
+    let sign = (value < 0.0) as u8;
+    // Uses the shortest decimal representation of the value to avoid f32 => f64 conversion artifacts.
+    let mut significand: f64 = value.abs().to_string().parse().unwrap_or_default();
+    let mut exponent: i32 = 0;
+    if significand > 0.0 {
+        exponent = significand.log10().floor() as i32;
+        significand /= 10f64.powi(exponent);
+        // Shifts the decimal point to the right as far as possible.
+        while (significand - significand.round()).abs() > 1e-9
+            && significand * 10.0 < MAX_SIGNIFICAND
+        {
+            exponent -= 1;
+            significand *= 10.0;
+        }
+        // Reduces the precision if necessary.
+        while significand >= MAX_SIGNIFICAND {
+            exponent += 1;
+            significand /= 10.0;
+        }
+    }
+    let significand = significand.round() as u32;
+    let exponent = (exponent + 11).clamp(0, 0x0F) as u8;
+    [
+        significand as u8 & SYSEX_REALTIME,
+        (significand >> 7) as u8 & SYSEX_REALTIME,
+        (significand >> 14) as u8 & SYSEX_REALTIME,
+        (significand >> 21) as u8 & 0x03 | (exponent << 2) | (sign << 6),
+    ]
 }
 
 impl Display for RemoteIo {
@@ -644,12 +1489,22 @@ impl Display for RemoteIo {
 
 #[cfg(test)]
 mod tests {
+    use crate::hardware::BoardEvent;
     use crate::io::constants::Message;
-    use crate::io::{IoProtocol, PinModeId, RemoteIo, Serial, IO};
+    use crate::io::protocols::remote::{
+        decode_7bit, decode_i32, encode_7bit, encode_custom_float, encode_i32,
+    };
+    use crate::io::{
+        BoardProfile, DhtModel, DhtReading, EncoderChange, EncoderCommand, IoProtocol,
+        OneWireReply, PinMode, PinModeId, RemoteIo, Serial, StepSize, StepperCommand,
+        StepperConfig, StepperInterface, StepperMove, Tcp, VirtualBoard, IO,
+    };
     use crate::mocks::create_test_plugin_io_data;
+    use crate::pause;
     use crate::utils::{format_as_hex, Range};
     use hermes_five::mocks::transport_layer::MockTransportLayer;
     use parking_lot::lock_api::RwLock;
+    use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::Arc;
 
     fn _create_mock_protocol() -> RemoteIo {
@@ -743,36 +1598,145 @@ mod tests {
         assert!(transport.is_connected())
     }
 
-    #[test]
-    fn test_simple_analog_write() {
-        let mut protocol = _create_mock_protocol();
-        let result = protocol.analog_write(0, 170);
+    #[hermes_five_macros::test]
+    fn test_restore() {
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x79, 0x01, 0x0C, 0xF7, // Result for query firmware
+            0xF0, 0x6C, 0x00, 0x08, 0x7F, 0x00, 0x08, 0x01, 0x08, 0x7F,
+            0xF7, // Result for report capabilities
+            0xF0, 0x6A, 0x7F, 0x7F, 0x7F, 0xF7, // Result for report capabilities
+        ]);
+        protocol.open().unwrap();
+        protocol.set_pin_mode(1, PinModeId::OUTPUT).unwrap();
+        protocol.data.write().digital_reported_pins.push(1);
+        protocol.data.write().connected = false;
+
+        let result = protocol.restore();
         assert!(result.is_ok(), "{:?}", result);
+        assert!(protocol.is_connected());
+        assert!(protocol.handler.read().is_some());
+        // The mock transport replays its data in a loop: stop polling it.
+        protocol.stop_polling();
 
+        // Pin modes and reporting are restored.
         let transport = _get_mock_transport(&protocol);
         assert!(
-            transport.write_buf.starts_with(&[0xE0, 0x2A, 0x01]),
-            "Buffer data has been sent [{:?}]",
-            format_as_hex(&transport.write_buf[..3])
+            transport.write_buf[13..].starts_with(&[
+                0xFF, // software reset
+                0xF0, 0x79, 0xF7, // query firmware
+                0xF0, 0x6B, 0xF7, // query capacities
+                0xF0, 0x69, 0xF7, // query analog mapping
+                0xF4, 0x01, 0x01, // set pin mode
+                0xD0, 0x01, // report digital
+            ]),
+            "Sending sequence is correct [{:?}]",
+            format_as_hex(&transport.write_buf)
         );
-        {
-            let lock = protocol.get_io().read();
-            let pin = lock.get_pin(0).unwrap();
-            assert_eq!(pin.value, 170, "Pin value updated");
-        }
 
-        let result = protocol.analog_write(66, 0);
-        assert!(result.is_err(), "{:?}", result);
+        // The board resets its pins on reconnection: their modes are set again.
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let mut protocol = RemoteIo::from(simulator.clone());
+        protocol.open().unwrap();
+        protocol.set_pin_mode(13, PinModeId::OUTPUT).unwrap();
+        assert!(protocol.restore().is_ok());
+        protocol.stop_polling();
         assert_eq!(
-            result.err().unwrap().to_string(),
-            "Hardware error: Unknown pin 66."
+            protocol.data.read().get_pin(13).unwrap().mode.id,
+            PinModeId::OUTPUT
         );
+        assert_eq!(simulator.get_pin_mode(13).unwrap(), PinModeId::OUTPUT);
     }
 
-    #[test]
-    fn test_extended_analog_write() {
-        let mut protocol = _create_mock_protocol();
-        // Note1: the pin to use is over 15, so we use extended protocol.
+    #[hermes_five_macros::test]
+    fn test_recover() {
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x79, 0x01, 0x0C, 0xF7, // Result for query firmware
+            0xF0, 0x6C, 0x00, 0x08, 0x7F, 0x00, 0x08, 0x01, 0x08, 0x7F,
+            0xF7, // Result for report capabilities
+            0xF0, 0x6A, 0x7F, 0x7F, 0x7F, 0xF7, // Result for report capabilities
+        ]);
+        protocol.open().unwrap();
+
+        let disconnect_flag = Arc::new(AtomicBool::new(false));
+        let moved_flag = disconnect_flag.clone();
+        protocol.events.on(BoardEvent::OnDisconnect, move |_: ()| {
+            let captured_flag = moved_flag.clone();
+            async move {
+                captured_flag.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        let reconnect_flag = Arc::new(AtomicBool::new(false));
+        let moved_flag = reconnect_flag.clone();
+        protocol.events.on(BoardEvent::OnReconnect, move |_: ()| {
+            let captured_flag = moved_flag.clone();
+            async move {
+                captured_flag.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+
+        protocol.recover();
+        pause!(1000);
+        assert!(disconnect_flag.load(Ordering::SeqCst));
+        assert!(reconnect_flag.load(Ordering::SeqCst));
+        assert!(!protocol.recovering.load(Ordering::SeqCst));
+        assert!(protocol.is_connected());
+    }
+
+    #[hermes_five_macros::test]
+    fn test_recover_failed() {
+        let protocol = RemoteIo::from(Tcp::new("127.0.0.1:0"));
+        let failed_flag = Arc::new(AtomicBool::new(false));
+        let moved_flag = failed_flag.clone();
+        protocol
+            .events
+            .on(BoardEvent::OnReconnectFailed, move |_: ()| {
+                let captured_flag = moved_flag.clone();
+                async move {
+                    captured_flag.store(true, Ordering::SeqCst);
+                    Ok(())
+                }
+            });
+
+        // A transport layer without reconnection capability gives up right away.
+        protocol.recover();
+        pause!(500);
+        assert!(failed_flag.load(Ordering::SeqCst));
+        assert!(!protocol.is_connected());
+        assert!(protocol.get_events().is_some());
+    }
+
+    #[test]
+    fn test_simple_analog_write() {
+        let mut protocol = _create_mock_protocol();
+        let result = protocol.analog_write(0, 170);
+        assert!(result.is_ok(), "{:?}", result);
+
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&[0xE0, 0x2A, 0x01]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..3])
+        );
+        {
+            let lock = protocol.get_io().read();
+            let pin = lock.get_pin(0).unwrap();
+            assert_eq!(pin.value, 170, "Pin value updated");
+        }
+
+        let result = protocol.analog_write(66, 0);
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Hardware error: Unknown pin 66."
+        );
+    }
+
+    #[test]
+    fn test_extended_analog_write() {
+        let mut protocol = _create_mock_protocol();
+        // Note1: the pin to use is over 15, so we use extended protocol.
         // Note2: the value sent is over 16384 (0x00004000) so we use multibyte sending.
         let result = protocol.analog_write(22, 17000);
         assert!(result.is_ok(), "{:?}", result);
@@ -885,6 +1849,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tone() {
+        let mut protocol = _create_mock_protocol();
+
+        let result = protocol.tone(8, 440, 500);
+        assert!(result.is_ok(), "Tone error: {:?}", result.unwrap_err());
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport
+                .write_buf
+                .starts_with(&[0xF0, 0x5F, 0x00, 0x08, 0x38, 0x03, 0x74, 0x03, 0xF7]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..9])
+        );
+
+        let mut protocol = _create_mock_protocol();
+        let result = protocol.tone(8, 0, 0);
+        assert!(result.is_ok(), "No tone error: {:?}", result.unwrap_err());
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport
+                .write_buf
+                .starts_with(&[0xF0, 0x5F, 0x01, 0x08, 0xF7]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..5])
+        );
+    }
+
     #[test]
     fn test_query_firmware() {
         let mut protocol = _create_mock_protocol();
@@ -1244,6 +2236,125 @@ mod tests {
         assert_eq!(result.err().unwrap().to_string(), "Protocol error: Not enough bytes received - 'handle_pin_state_response' expected 5 bytes, 4 received.");
     }
 
+    #[test]
+    fn test_digital_read() {
+        // Pin 3 is in OUTPUT mode, value HIGH.
+        let mut protocol = _create_mock_protocol_with_data(&[0xF0, 0x6E, 0x03, 0x01, 0x01, 0xF7]);
+        let result = protocol.digital_read(3);
+        assert!(result.is_ok(), "{:?}", result);
+        assert!(result.unwrap());
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&[0xF0, 0x6D, 0x03, 0xF7]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..4])
+        );
+
+        // The state of another pin is skipped.
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x6E, 0x04, 0x01, 0x01, 0xF7, // state of pin 4.
+            0xF0, 0x6E, 0x03, 0x01, 0x00, 0xF7, // state of pin 3.
+        ]);
+        assert!(!protocol.digital_read(3).unwrap());
+        assert!(protocol.data.read().get_pin(4).unwrap().value == 1);
+
+        // Reported pins return the last known value without query.
+        let mut protocol = _create_mock_protocol();
+        protocol.data.write().digital_reported_pins.push(10);
+        protocol.data.write().get_pin_mut(10).unwrap().value = 1;
+        assert!(protocol.digital_read(10).unwrap());
+        assert!(_get_mock_transport(&protocol)
+            .write_buf
+            .starts_with(&[0x00]));
+
+        // Unknown pin.
+        let result = protocol.digital_read(42);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Unknown pin 42."
+        );
+
+        // Input pins are not queried (their state is their pull-up status): the port of pin 10 is
+        // reported until its value is received.
+        let mut protocol = _create_mock_protocol_with_data(&[0x91, 0x04, 0x00]);
+        assert!(protocol.digital_read(10).unwrap());
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&[0xD1, 0x01, 0xD1, 0x00]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..4])
+        );
+        assert!(protocol.data.read().digital_reported_pins.is_empty());
+        assert!(protocol.handler.read().is_none());
+
+        // A pin in a mode it does not support.
+        let mut protocol = _create_mock_protocol_with_data(&[0xF0, 0x6E, 0x03, 0x04, 0x01, 0xF7]);
+        let result = protocol.digital_read(3);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Hardware error: Pin (3) not compatible with mode (SERVO)"));
+    }
+
+    #[test]
+    fn test_analog_read() {
+        // Pin 14 is in ANALOG mode (channel 14): it is reported until its value (300) is received.
+        let mut protocol = _create_mock_protocol_with_data(&[0xEE, 0x2C, 0x02]);
+        let result = protocol.analog_read(14);
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(result.unwrap(), 300);
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&[0xCE, 0x01, 0xCE, 0x00]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..4])
+        );
+        assert!(protocol.data.read().analog_reported_channels.is_empty());
+        assert!(protocol.handler.read().is_none());
+
+        // Reported pins return the last known value without query.
+        let mut protocol = _create_mock_protocol();
+        protocol.data.write().analog_reported_channels.push(14);
+        assert_eq!(protocol.analog_read(14).unwrap(), 100);
+        assert!(_get_mock_transport(&protocol)
+            .write_buf
+            .starts_with(&[0x00]));
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_query_pin_state_while_polling() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let mut protocol = RemoteIo::from(simulator.clone());
+        protocol.open().unwrap();
+
+        // The polling task reads the reports of the input pin 2.
+        protocol.set_pin_mode(2, PinModeId::INPUT).unwrap();
+        protocol.report_digital(2, true).unwrap();
+        assert!(protocol.handler.read().is_some());
+
+        // Queries wait for the polling task to decode the state of their pin, while reports are received.
+        protocol.set_pin_mode(13, PinModeId::OUTPUT).unwrap();
+        for i in 0..500 {
+            simulator.set_pin_value(2, i % 2).unwrap();
+            simulator.set_pin_value(13, (i + 1) % 2).unwrap();
+            let result = protocol.digital_read(13);
+            assert!(result.is_ok(), "Pin state error: {:?}", result.unwrap_err());
+            assert_eq!(result.unwrap(), (i + 1) % 2 == 1);
+        }
+        protocol.close().unwrap();
+    }
+
+    #[test]
+    fn test_read_timeout() {
+        // No response is ever received.
+        let mut protocol = _create_mock_protocol();
+        let result = protocol.analog_read(15);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Protocol error: Operation timed out."
+        );
+    }
+
     #[test]
     fn test_i2c_config() {
         let mut protocol = _create_mock_protocol();
@@ -1259,6 +2370,24 @@ mod tests {
             "Buffer data has been sent [{:?}]",
             format_as_hex(&transport.write_buf[..5])
         );
+
+        // I2C compatible pins now belong to the I2C bus.
+        protocol
+            .data
+            .write()
+            .get_pin_mut(9)
+            .unwrap()
+            .supported_modes = vec![PinMode {
+            id: PinModeId::I2C,
+            resolution: 1,
+        }];
+        assert!(protocol.i2c_config(100).is_ok());
+        assert_eq!(protocol.data.read().get_pin_owner(9).unwrap(), "I2C");
+
+        // ... unless they are already used by a device.
+        protocol.data.write().get_pin_mut(9).unwrap().mode = PinMode::default();
+        protocol.data.write().claim_pins(&[9], "Button").unwrap();
+        assert!(protocol.i2c_config(100).is_err());
     }
 
     #[test]
@@ -1267,7 +2396,7 @@ mod tests {
             0xF0, 0x77, 0x40, 0x00, 0x42, 0x42, 0x42, 0x42, 0xF7, // mock 4 bytes i2c answer.
         ]);
 
-        let result = protocol.i2c_read(0x40, 4); // wait and read 4 bytes.
+        let result = protocol.i2c_read(0x40, None, 4); // wait and read 4 bytes.
         assert!(result.is_ok(), "I2C read error: {:?}", result.unwrap_err());
 
         let transport = _get_mock_transport(&protocol);
@@ -1278,6 +2407,101 @@ mod tests {
             "Buffer data has been sent [{:?}]",
             format_as_hex(&transport.write_buf[..7])
         );
+
+        // The reply of another device is skipped.
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x77, 0x41, 0x00, 0x10, 0x00, 0x01, 0x00, 0xF7, // reply from 0x41.
+            0xF0, 0x77, 0x40, 0x00, 0x10, 0x00, 0x02, 0x00, 0xF7, // reply from 0x40.
+        ]);
+        let result = protocol.i2c_read(0x40, Some(0x10), 1);
+        assert!(result.is_ok(), "I2C read error: {:?}", result.unwrap_err());
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport
+                .write_buf
+                .starts_with(&[0xF0, 0x76, 0x40, 0x08, 0x10, 0x00, 0x01, 0x00, 0xF7]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..9])
+        );
+        let mut lock = protocol.get_io().write();
+        assert_eq!(lock.i2c_data.len(), 2);
+        assert_eq!(
+            lock.take_i2c_reply(0x40, Some(0x10)).unwrap().data,
+            vec![0x02]
+        );
+        assert!(lock.take_i2c_reply(0x40, Some(0x10)).is_none());
+        assert_eq!(lock.i2c_data.len(), 1);
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_i2c_read_while_polling() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let mut protocol = RemoteIo::from(simulator.clone());
+        protocol.open().unwrap();
+        simulator.set_i2c_data(0x40, 0x10, &[0x01, 0x02, 0x03]);
+
+        // The polling task reads the replies of the continuous read (register 0x10, 2 bytes).
+        protocol.i2c_read_continuously(0x40, Some(0x10), 2).unwrap();
+        assert!(protocol.handler.read().is_some());
+
+        // One-shot reads wait for the polling task to decode their reply, even when continuous replies of
+        // the same register are received meanwhile.
+        for value in 0..20 {
+            simulator.set_i2c_data(0x40, 0x12, &[value]);
+            let result = protocol.i2c_read(0x40, Some(0x10), 3);
+            assert!(result.is_ok(), "I2C read error: {:?}", result.unwrap_err());
+            let mut lock = protocol.data.write();
+            assert!(lock
+                .i2c_data
+                .iter()
+                .any(|reply| reply.register == 0x10 && reply.data == vec![0x01, 0x02, value]));
+            lock.i2c_data.clear();
+        }
+
+        simulator.set_i2c_data(0x40, 0x20, &[0x42]);
+        assert!(protocol.i2c_read(0x40, Some(0x20), 1).is_ok());
+        assert_eq!(
+            protocol
+                .data
+                .write()
+                .take_i2c_reply(0x40, Some(0x20))
+                .unwrap()
+                .data,
+            vec![0x42]
+        );
+        protocol.close().unwrap();
+    }
+
+    #[hermes_five_macros::test]
+    fn test_i2c_read_continuously() {
+        let mut protocol = _create_mock_protocol();
+
+        let result = protocol.i2c_read_continuously(0x40, Some(0x10), 2);
+        assert!(result.is_ok(), "{:?}", result);
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport
+                .write_buf
+                .starts_with(&[0xF0, 0x76, 0x40, 0x10, 0x10, 0x00, 0x02, 0x00, 0xF7]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..9])
+        );
+
+        assert_eq!(
+            protocol.data.read().i2c_reported_reads,
+            vec![(0x40, Some(0x10), 2)]
+        );
+
+        let result = protocol.i2c_stop_reading(0x40);
+        assert!(result.is_ok(), "{:?}", result);
+        assert!(protocol.data.read().i2c_reported_reads.is_empty());
+        let transport = _get_mock_transport(&protocol);
+        assert_eq!(
+            transport.write_buf[9..14],
+            [0xF0, 0x76, 0x40, 0x18, 0xF7],
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[9..14])
+        );
     }
 
     #[test]
@@ -1311,7 +2535,9 @@ mod tests {
             0xF0, 0x77, 0x40, 0x00, 0x08, 0x00, 0x63, 0x00, 0x6F, 0x00, 0x76, 0x00, 0x65, 0x00,
             0x72, 0x00, 0x61, 0x00, 0x67, 0x00, 0x65, 0x00, 0xF7,
         ]);
+        let mut replies = protocol.get_io().read().subscribe_i2c_replies();
         let result = protocol.read_and_decode();
+        assert_eq!(replies.try_recv().unwrap().register, 8);
         assert!(result.is_ok(), "{:?}", result);
         {
             let data = protocol.get_io().read();
@@ -1324,6 +2550,534 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_stepper() {
+        let config = StepperConfig {
+            interface: StepperInterface::FourWire([2, 3, 4, 5]),
+            step_size: StepSize::Half,
+            enable_pin: Some(6),
+        };
+
+        // Pins not supporting the STEPPER mode.
+        let mut protocol = _create_mock_protocol();
+        let result = protocol.stepper(0, StepperCommand::Config(config));
+        assert!(result.is_err());
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Hardware error: Pin (2) not compatible with mode (STEPPER) - configure stepper."
+        );
+
+        let mut protocol = _create_mock_protocol();
+        for pin in 2..=6 {
+            protocol
+                .get_io()
+                .write()
+                .get_pin_mut(pin)
+                .unwrap()
+                .supported_modes
+                .push(PinMode {
+                    id: PinModeId::STEPPER,
+                    resolution: 21,
+                });
+        }
+        let result = protocol.stepper(1, StepperCommand::Config(config));
+        assert!(result.is_ok(), "Stepper error: {:?}", result.unwrap_err());
+        assert_eq!(
+            protocol.get_io().read().get_pin(2).unwrap().mode.id,
+            PinModeId::STEPPER
+        );
+        assert_eq!(protocol.get_io().read().steppers.get(&1), Some(&0));
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport
+                .write_buf
+                .starts_with(&[0xF0, 0x62, 0x00, 0x01, 0x43, 0x02, 0x03, 0x04, 0x05, 0x06, 0xF7]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..11])
+        );
+
+        for (command, expected) in [
+            (StepperCommand::Zero, vec![0xF0, 0x62, 0x01, 0x01, 0xF7]),
+            (
+                StepperCommand::Step(-1000),
+                vec![0xF0, 0x62, 0x02, 0x01, 0x68, 0x07, 0x00, 0x00, 0x08, 0xF7],
+            ),
+            (
+                StepperCommand::To(2048),
+                vec![0xF0, 0x62, 0x03, 0x01, 0x00, 0x10, 0x00, 0x00, 0x00, 0xF7],
+            ),
+            (
+                StepperCommand::Enable(true),
+                vec![0xF0, 0x62, 0x04, 0x01, 0x01, 0xF7],
+            ),
+            (StepperCommand::Stop, vec![0xF0, 0x62, 0x05, 0x01, 0xF7]),
+            (StepperCommand::Report, vec![0xF0, 0x62, 0x06, 0x01, 0xF7]),
+            (
+                StepperCommand::Acceleration(0.5),
+                vec![0xF0, 0x62, 0x08, 0x01, 0x05, 0x00, 0x00, 0x28, 0xF7],
+            ),
+            (
+                StepperCommand::Speed(100.0),
+                vec![0xF0, 0x62, 0x09, 0x01, 0x01, 0x00, 0x00, 0x34, 0xF7],
+            ),
+        ] {
+            let mut protocol = _create_mock_protocol();
+            let result = protocol.stepper(1, command);
+            assert!(result.is_ok(), "Stepper error: {:?}", result.unwrap_err());
+            let transport = _get_mock_transport(&protocol);
+            assert!(
+                transport.write_buf.starts_with(&expected),
+                "Buffer data sent for {:?} [{:?}]",
+                command,
+                format_as_hex(&transport.write_buf[..expected.len()])
+            );
+        }
+    }
+
+    #[test]
+    fn test_stepper_encoding() {
+        for value in [0, 1, -1, 127, 128, -1000, i32::MAX, i32::MIN + 1] {
+            assert_eq!(decode_i32(&encode_i32(value)), value);
+        }
+        assert_eq!(encode_custom_float(0.0), [0x00, 0x00, 0x00, 0x2C]);
+        assert_eq!(encode_custom_float(-2.5), [0x19, 0x00, 0x00, 0x68]);
+        assert_eq!(encode_custom_float(1234.5), [0x39, 0x60, 0x00, 0x28]);
+        assert_eq!(encode_custom_float(0.1), [0x01, 0x00, 0x00, 0x28]);
+    }
+
+    #[test]
+    fn test_handle_stepper_reply() {
+        // Not enough data.
+        let mut protocol = _create_mock_protocol_with_data(&[0xF0, 0x62, 0x0A, 0x02, 0xF7]);
+        let result = protocol.read_and_decode();
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(result.err().unwrap().to_string(), "Protocol error: Not enough bytes received - 'handle_stepper_reply' expected 10 bytes, 5 received.");
+
+        // Stepper 2 reports position 200, then completes a move to position -1000.
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x62, 0x06, 0x02, 0x48, 0x01, 0x00, 0x00, 0x00, 0xF7, 0xF0, 0x62, 0x0A, 0x02,
+            0x68, 0x07, 0x00, 0x00, 0x08, 0xF7,
+        ]);
+        let mut moves = protocol.get_io().read().subscribe_stepper_moves();
+
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::StepperReply);
+        assert_eq!(protocol.get_io().read().steppers.get(&2), Some(&200));
+        assert!(moves.try_recv().is_err());
+
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::StepperReply);
+        assert_eq!(protocol.get_io().read().steppers.get(&2), Some(&-1000));
+        assert_eq!(
+            moves.try_recv().unwrap(),
+            StepperMove {
+                id: 2,
+                position: -1000
+            }
+        );
+    }
+
+    #[test]
+    fn test_encoder() {
+        // Pins not supporting the ENCODER mode.
+        let mut protocol = _create_mock_protocol();
+        let result = protocol.encoder(0, EncoderCommand::Attach([2, 3]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Hardware error: Pin (2) not compatible with mode (ENCODER) - attach encoder."
+        );
+
+        let mut protocol = _create_mock_protocol();
+        for pin in [2, 3] {
+            protocol
+                .get_io()
+                .write()
+                .get_pin_mut(pin)
+                .unwrap()
+                .supported_modes
+                .push(PinMode {
+                    id: PinModeId::ENCODER,
+                    resolution: 1,
+                });
+        }
+        let result = protocol.encoder(1, EncoderCommand::Attach([2, 3]));
+        assert!(result.is_ok(), "Encoder error: {:?}", result.unwrap_err());
+        assert_eq!(
+            protocol.get_io().read().get_pin(3).unwrap().mode.id,
+            PinModeId::ENCODER
+        );
+        assert_eq!(protocol.get_io().read().encoders.get(&1), Some(&0));
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&[
+                0xF0, 0x61, 0x00, 0x01, 0x02, 0x03, 0xF7, 0xF0, 0x61, 0x04, 0x01, 0xF7
+            ]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..12])
+        );
+
+        for (command, expected) in [
+            (EncoderCommand::Report, [0xF0, 0x61, 0x01, 0x01, 0xF7]),
+            (EncoderCommand::Reset, [0xF0, 0x61, 0x03, 0x01, 0xF7]),
+            (EncoderCommand::Detach, [0xF0, 0x61, 0x05, 0x01, 0xF7]),
+        ] {
+            let mut protocol = _create_mock_protocol();
+            let result = protocol.encoder(1, command);
+            assert!(result.is_ok(), "Encoder error: {:?}", result.unwrap_err());
+            let transport = _get_mock_transport(&protocol);
+            assert!(
+                transport.write_buf.starts_with(&expected),
+                "Buffer data sent for {:?} [{:?}]",
+                command,
+                format_as_hex(&transport.write_buf[..expected.len()])
+            );
+        }
+    }
+
+    #[test]
+    fn test_handle_encoder_reply() {
+        // Not enough data.
+        let mut protocol = _create_mock_protocol_with_data(&[0xF0, 0x61, 0x02, 0x01, 0xF7]);
+        let result = protocol.read_and_decode();
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(result.err().unwrap().to_string(), "Protocol error: Not enough bytes received - 'handle_encoder_reply' expected 8 bytes, 5 received.");
+
+        // Encoder 2 reports position 200, then encoders 0 and 2 report positions -3 and 200.
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x61, 0x02, 0x48, 0x01, 0x00, 0x00, 0xF7, 0xF0, 0x61, 0x40, 0x03, 0x00, 0x00,
+            0x00, 0x02, 0x48, 0x01, 0x00, 0x00, 0xF7,
+        ]);
+        let mut changes = protocol.get_io().read().subscribe_encoder_changes();
+
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::EncoderReply);
+        assert_eq!(protocol.get_io().read().encoders.get(&2), Some(&200));
+        assert_eq!(
+            changes.try_recv().unwrap(),
+            EncoderChange {
+                id: 2,
+                position: 200
+            }
+        );
+
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::EncoderReply);
+        assert_eq!(protocol.get_io().read().encoders.get(&0), Some(&-3));
+        // Only the changed positions are notified.
+        assert_eq!(
+            changes.try_recv().unwrap(),
+            EncoderChange {
+                id: 0,
+                position: -3
+            }
+        );
+        assert!(changes.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_ping_read() {
+        let mut protocol = _create_mock_protocol();
+        let result = protocol.ping_read(7, 5, 1_000_000);
+        assert!(result.is_ok(), "Ping error: {:?}", result.unwrap_err());
+        let transport = _get_mock_transport(&protocol);
+        let expected = [
+            0xF0, 0x75, 0x07, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00,
+            0x0F, 0x00, 0x42, 0x00, 0x40, 0x00, 0xF7,
+        ];
+        assert!(
+            transport.write_buf.starts_with(&expected),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..expected.len()])
+        );
+    }
+
+    #[test]
+    fn test_shift_out() {
+        let mut protocol = _create_mock_protocol();
+
+        // The sysex is not sent to pins without SHIFT support.
+        let result = protocol.shift_out(2, 3, 4, &[0xA5, 0x01]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Hardware error: Pin (2) not compatible with mode (SHIFT) - shift out data."
+        );
+        let result = protocol.shift_out(9, 3, 4, &[0xA5, 0x01]);
+        assert!(result.is_err());
+        assert!(_get_mock_transport(&protocol)
+            .write_buf
+            .iter()
+            .all(|&byte| byte == 0));
+
+        protocol
+            .data
+            .write()
+            .get_pin_mut(3)
+            .unwrap()
+            .supported_modes
+            .push(PinMode {
+                id: PinModeId::SHIFT,
+                resolution: 1,
+            });
+        let result = protocol.shift_out(9, 3, 4, &[0xA5, 0x01]);
+        assert!(result.is_ok(), "Shift error: {:?}", result.unwrap_err());
+        let transport = _get_mock_transport(&protocol);
+        let expected = [
+            0xF0, 0x75, 0x01, 0x09, 0x03, 0x04, 0x01, 0x25, 0x01, 0x01, 0x00, 0xF7,
+        ];
+        assert!(
+            transport.write_buf.starts_with(&expected),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..expected.len()])
+        );
+    }
+
+    #[test]
+    fn test_handle_ping_reply() {
+        // Not enough data.
+        let mut protocol = _create_mock_protocol_with_data(&[0xF0, 0x75, 0x07, 0x00, 0xF7]);
+        let result = protocol.read_and_decode();
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(result.err().unwrap().to_string(), "Protocol error: Not enough bytes received - 'handle_ping_reply' expected 13 bytes, 5 received.");
+
+        // Pin 7 reports an echo lasting 711µs (0x02C7).
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x75, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x47, 0x01, 0xF7,
+        ]);
+        let mut changes = protocol.get_io().read().subscribe();
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::PingReply);
+        assert_eq!(protocol.get_io().read().get_pin(7).unwrap().value, 711);
+        assert_eq!(changes.try_recv().unwrap().value, 711);
+    }
+
+    #[test]
+    fn test_dht_read() {
+        let mut protocol = _create_mock_protocol();
+        let result = protocol.dht_read(7, DhtModel::Dht22);
+        assert!(result.is_ok(), "DHT error: {:?}", result.unwrap_err());
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport
+                .write_buf
+                .starts_with(&[0xF0, 0x74, 0x00, 0x16, 0x07, 0xF7]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..6])
+        );
+    }
+
+    #[test]
+    fn test_handle_dht_reply() {
+        // Not enough data.
+        let mut protocol = _create_mock_protocol_with_data(&[0xF0, 0x74, 0x00, 0x16, 0xF7]);
+        let result = protocol.read_and_decode();
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(result.err().unwrap().to_string(), "Protocol error: Not enough bytes received - 'handle_dht_reply' expected 11 bytes, 5 received.");
+
+        // The DHT22 on pin 7 reports 45.3% and -12.5°C, then fails with a checksum error.
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x74, 0x00, 0x16, 0x07, 0x00, 0x45, 0x03, 0x03, 0x7F, 0xF7, 0xF0, 0x74, 0x00,
+            0x16, 0x07, 0x01, 0x00, 0x00, 0x00, 0x00, 0xF7,
+        ]);
+        let mut readings = protocol.get_io().read().subscribe_dht_readings();
+
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::DhtReply);
+        assert_eq!(
+            readings.try_recv().unwrap(),
+            DhtReading {
+                pin: 7,
+                status: 0,
+                temperature: -12.5,
+                humidity: 45.3,
+            }
+        );
+
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::DhtReply);
+        assert_eq!(readings.try_recv().unwrap().status, 1);
+    }
+
+    #[test]
+    fn test_onewire_encoding() {
+        assert_eq!(encode_7bit(&[]), Vec::<u8>::new());
+        assert_eq!(encode_7bit(&[0x44]), vec![0x44, 0x00]);
+        assert_eq!(encode_7bit(&[0xFF]), vec![0x7F, 0x01]);
+        let data = [
+            0x28, 0xFF, 0x64, 0x1E, 0x0B, 0x16, 0x03, 0x0E, 0x28, 0x61, 0x64, 0x12, 0x3C, 0x7C,
+            0x2F, 0x27,
+        ];
+        let encoded = encode_7bit(&data);
+        assert_eq!(
+            encoded,
+            vec![
+                0x28, 0x7E, 0x13, 0x73, 0x31, 0x41, 0x45, 0x01, 0x0E, 0x50, 0x04, 0x23, 0x26, 0x02,
+                0x0F, 0x3E, 0x2F, 0x4E, 0x00
+            ]
+        );
+        assert_eq!(decode_7bit(&encoded), data.to_vec());
+    }
+
+    #[test]
+    fn test_onewire_config() {
+        // Pin not supporting the ONEWIRE mode.
+        let mut protocol = _create_mock_protocol();
+        let result = protocol.onewire_config(4, true);
+        assert!(result.is_err());
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Hardware error: Pin (4) not compatible with mode (ONEWIRE) - configure OneWire bus."
+        );
+
+        let mut protocol = _create_mock_protocol();
+        protocol
+            .get_io()
+            .write()
+            .get_pin_mut(4)
+            .unwrap()
+            .supported_modes
+            .push(PinMode {
+                id: PinModeId::ONEWIRE,
+                resolution: 1,
+            });
+        let result = protocol.onewire_config(4, true);
+        assert!(result.is_ok(), "OneWire error: {:?}", result.unwrap_err());
+        assert_eq!(
+            protocol.get_io().read().get_pin(4).unwrap().mode.id,
+            PinModeId::ONEWIRE
+        );
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport
+                .write_buf
+                .starts_with(&[0xF0, 0x73, 0x41, 0x04, 0x01, 0xF7]),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..6])
+        );
+    }
+
+    #[test]
+    fn test_onewire_requests() {
+        let address = [0x28, 0xFF, 0x64, 0x1E, 0x0B, 0x16, 0x03, 0x0E];
+        let encoded_address = [0x28, 0x7E, 0x13, 0x73, 0x31, 0x41, 0x45, 0x01, 0x0E];
+        let empty = [0x00; 10];
+
+        let mut protocol = _create_mock_protocol();
+        assert!(protocol.onewire_search(4).is_ok());
+        assert!(_get_mock_transport(&protocol)
+            .write_buf
+            .starts_with(&[0xF0, 0x73, 0x40, 0x04, 0xF7]));
+
+        let mut protocol = _create_mock_protocol();
+        assert!(protocol.onewire_reset(4).is_ok());
+        assert!(_get_mock_transport(&protocol)
+            .write_buf
+            .starts_with(&[0xF0, 0x73, 0x01, 0x04, 0xF7]));
+
+        let mut protocol = _create_mock_protocol();
+        assert!(protocol.onewire_select(4, address).is_ok());
+        let expected = [
+            &[0xF0, 0x73, 0x04, 0x04][..],
+            &encoded_address,
+            &empty,
+            &[0xF7],
+        ]
+        .concat();
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&expected),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..expected.len()])
+        );
+
+        let mut protocol = _create_mock_protocol();
+        assert!(protocol.onewire_read(4, 9, 3).is_ok());
+        let expected = [
+            0xF0, 0x73, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x12,
+            0x00, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xF7,
+        ];
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&expected),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..expected.len()])
+        );
+
+        let mut protocol = _create_mock_protocol();
+        assert!(protocol.onewire_write(4, address, &[0x44]).is_ok());
+        let expected = [
+            &[0xF0, 0x73, 0x24, 0x04][..],
+            &encoded_address,
+            &[0x00; 9],
+            &[0x10, 0x02, 0xF7],
+        ]
+        .concat();
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&expected),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..expected.len()])
+        );
+
+        let mut protocol = _create_mock_protocol();
+        assert!(protocol.onewire_delay(4, 1000).is_ok());
+        let expected = [
+            0xF0, 0x73, 0x10, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x7A, 0x01, 0x00, 0x00, 0x00, 0xF7,
+        ];
+        let transport = _get_mock_transport(&protocol);
+        assert!(
+            transport.write_buf.starts_with(&expected),
+            "Buffer data has been sent [{:?}]",
+            format_as_hex(&transport.write_buf[..expected.len()])
+        );
+    }
+
+    #[test]
+    fn test_handle_onewire_reply() {
+        // Not enough data.
+        let mut protocol = _create_mock_protocol_with_data(&[0xF0, 0x73, 0x42, 0xF7]);
+        let result = protocol.read_and_decode();
+        assert!(result.is_err(), "{:?}", result);
+        assert_eq!(result.err().unwrap().to_string(), "Protocol error: Not enough bytes received - 'handle_onewire_reply' expected 5 bytes, 4 received.");
+
+        // A search on pin 4 reports two devices.
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x73, 0x42, 0x04, 0x28, 0x7E, 0x13, 0x73, 0x31, 0x41, 0x45, 0x01, 0x0E, 0x50,
+            0x04, 0x23, 0x26, 0x02, 0x0F, 0x3E, 0x2F, 0x4E, 0x00, 0xF7,
+        ]);
+        let mut replies = protocol.get_io().read().subscribe_onewire_replies();
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::OneWireReply);
+        assert_eq!(
+            replies.try_recv().unwrap(),
+            OneWireReply::Search {
+                pin: 4,
+                addresses: vec![
+                    [0x28, 0xFF, 0x64, 0x1E, 0x0B, 0x16, 0x03, 0x0E],
+                    [0x28, 0x61, 0x64, 0x12, 0x3C, 0x7C, 0x2F, 0x27],
+                ],
+            }
+        );
+
+        // The device read with correlation id 1 reports its scratchpad.
+        let mut protocol = _create_mock_protocol_with_data(&[
+            0xF0, 0x73, 0x43, 0x04, 0x01, 0x00, 0x60, 0x0A, 0x30, 0x49, 0x51, 0x3F, 0x7F, 0x19,
+            0x40, 0x10, 0x0C, 0xF7,
+        ]);
+        let mut replies = protocol.get_io().read().subscribe_onewire_replies();
+        let result = protocol.read_and_decode();
+        assert_eq!(result.unwrap(), Message::OneWireReply);
+        assert_eq!(
+            replies.try_recv().unwrap(),
+            OneWireReply::Read {
+                pin: 4,
+                correlation_id: 1,
+                data: vec![0x58, 0x01, 0x4B, 0x46, 0x7F, 0xFF, 0x0C, 0x10, 0xC2],
+            }
+        );
+    }
+
     #[test]
     fn test_debug_and_display() {
         let protocol = _create_mock_protocol();
@@ -1,11 +1,18 @@
 use crate::errors::Error;
+use crate::errors::ProtocolError::ConnectionLost;
 use crate::io::private::TraitToAny;
 use dyn_clone::DynClone;
 use std::fmt::{Debug, Display};
 use std::time::Duration;
 
+mod reconnect;
 mod serial;
+mod simulator;
+mod tcp;
+pub use reconnect::AutoReconnect;
 pub use serial::Serial;
+pub use simulator::{BoardProfile, VirtualBoard};
+pub use tcp::Tcp;
 
 /// Only used for tests to downcast the transport layer.
 pub(crate) mod private {
@@ -52,4 +59,13 @@ pub trait IoTransport: Debug + Display + DynClone + Send + Sync + TraitToAny {
     /// # Notes
     /// This function blocks until the buffer is filled or an error occurs. Ensure proper error handling in calling code.
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error>;
+
+    /// Attempts to re-establish a lost connection (in a blocking way).
+    ///
+    /// # Notes
+    /// This function is optional: by default, a transport layer cannot recover from a connection loss.
+    /// Wrap the transport layer within an [`AutoReconnect`] to add this capability.
+    fn reconnect(&mut self) -> Result<(), Error> {
+        Err(Error::from(ConnectionLost))
+    }
 }
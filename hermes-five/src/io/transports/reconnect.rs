@@ -0,0 +1,293 @@
+use crate::errors::Error;
+use crate::errors::ProtocolError::{ConnectionLost, IoException};
+use crate::io::IoTransport;
+use crate::pause_sync;
+use parking_lot::RwLock;
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Represents an [`IoTransport`] wrapper able to recover from a connection loss of the transport layer it wraps.
+///
+/// When the inner transport layer fails (USB cable glitch, board reset, WiFi loss, etc.), the connection is
+/// flagged as lost: all operations then fail with a `ConnectionLost` error (rather than an endless list of
+/// `IoException`) until [`IoTransport::reconnect`] is called. The [`RemoteIo`](crate::io::RemoteIo) protocol
+/// does it automatically: the inner transport layer is re-opened with an exponential backoff until it succeeds
+/// or the maximum number of attempts is reached.
+///
+/// # Example
+/// ```
+/// use hermes_five::hardware::Board;
+/// use hermes_five::io::{AutoReconnect, Serial};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let transport = AutoReconnect::new(Serial::new("/dev/ttyUSB0")).set_max_attempts(5);
+///     let board = Board::from(transport).open();
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct AutoReconnect {
+    /// The wrapped transport layer.
+    #[cfg_attr(feature = "serde", serde(with = "crate::devices::arc_rwlock_serde"))]
+    transport: Arc<RwLock<Box<dyn IoTransport>>>,
+    /// The delay (in ms) before the first reconnection attempt (default: 500ms).
+    /// The delay doubles for each further attempt.
+    retry_delay: u64,
+    /// The maximum delay (in ms) between two reconnection attempts (default: 10s).
+    max_retry_delay: u64,
+    /// The number of reconnection attempts before giving up (default: 10 - 0 means never give up).
+    max_attempts: u32,
+
+    // ########################################
+    // # Volatile utility data.
+    /// Indicates the connection has been lost.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    lost: Arc<AtomicBool>,
+}
+
+impl AutoReconnect {
+    /// Wraps the given transport layer to make it able to recover from a connection loss.
+    pub fn new<T: IoTransport>(transport: T) -> Self {
+        Self {
+            transport: Arc::new(RwLock::new(Box::new(transport))),
+            retry_delay: 500,
+            max_retry_delay: 10000,
+            max_attempts: 10,
+            lost: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns true if the connection is currently lost.
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::SeqCst)
+    }
+
+    /// Returns the delay (in ms) before the first reconnection attempt.
+    pub fn get_retry_delay(&self) -> u64 {
+        self.retry_delay
+    }
+
+    /// Sets the delay (in ms) before the first reconnection attempt.
+    /// The delay doubles for each further attempt (up to the `max_retry_delay`).
+    pub fn set_retry_delay(mut self, retry_delay: u64) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Returns the maximum delay (in ms) between two reconnection attempts.
+    pub fn get_max_retry_delay(&self) -> u64 {
+        self.max_retry_delay
+    }
+
+    /// Sets the maximum delay (in ms) between two reconnection attempts.
+    pub fn set_max_retry_delay(mut self, max_retry_delay: u64) -> Self {
+        self.max_retry_delay = max_retry_delay;
+        self
+    }
+
+    /// Returns the number of reconnection attempts before giving up.
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Sets the number of reconnection attempts before giving up (0 means never give up).
+    pub fn set_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Flags the connection as lost if the inner transport layer failed.
+    /// Timeouts are expected (no data to read) and do not count as a connection loss.
+    fn check<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        match result {
+            Err(Error::ProtocolError {
+                source: IoException { .. },
+            }) => {
+                self.lost.store(true, Ordering::SeqCst);
+                Err(Error::from(ConnectionLost))
+            }
+            result => result,
+        }
+    }
+}
+
+impl Display for AutoReconnect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AutoReconnect({})", self.transport.read())
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl IoTransport for AutoReconnect {
+    fn open(&mut self) -> Result<(), Error> {
+        self.transport.write().open()?;
+        self.lost.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        self.lost.store(false, Ordering::SeqCst);
+        self.transport.write().close()
+    }
+
+    fn set_timeout(&mut self, duration: Duration) -> Result<(), Error> {
+        self.transport.write().set_timeout(duration)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<(), Error> {
+        if self.is_lost() {
+            return Err(Error::from(ConnectionLost));
+        }
+        let result = self.transport.write().write(buf);
+        self.check(result)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        if self.is_lost() {
+            return Err(Error::from(ConnectionLost));
+        }
+        let result = self.transport.write().read_exact(buf);
+        self.check(result)
+    }
+
+    fn reconnect(&mut self) -> Result<(), Error> {
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let _ = self.transport.write().close();
+            pause_sync!(delay);
+            if self.transport.write().open().is_ok() {
+                self.lost.store(false, Ordering::SeqCst);
+                return Ok(());
+            }
+            if self.max_attempts != 0 && attempt >= self.max_attempts {
+                return Err(Error::from(ConnectionLost));
+            }
+            delay = (delay * 2).min(self.max_retry_delay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::Tcp;
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    /// Creates a local listener and returns it with its address.
+    fn _create_listener() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        (listener, address)
+    }
+
+    #[test]
+    fn test_new_auto_reconnect() {
+        let transport = AutoReconnect::new(Tcp::new("127.0.0.1:3030"));
+        assert_eq!(transport.get_retry_delay(), 500);
+        assert_eq!(transport.get_max_retry_delay(), 10000);
+        assert_eq!(transport.get_max_attempts(), 10);
+        assert!(!transport.is_lost());
+
+        let transport = transport
+            .set_retry_delay(100)
+            .set_max_retry_delay(1000)
+            .set_max_attempts(0);
+        assert_eq!(transport.get_retry_delay(), 100);
+        assert_eq!(transport.get_max_retry_delay(), 1000);
+        assert_eq!(transport.get_max_attempts(), 0);
+    }
+
+    #[test]
+    fn test_connection_loss_and_reconnect() {
+        let (listener, address) = _create_listener();
+        let mut transport = AutoReconnect::new(Tcp::new(address)).set_retry_delay(10);
+        transport.open().unwrap();
+
+        let (mut server, _) = listener.accept().unwrap();
+        server.write_all(&[0xF9]).unwrap();
+        let mut buf = [0; 1];
+        assert!(transport.read_exact(&mut buf).is_ok());
+        assert_eq!(buf, [0xF9]);
+
+        // The remote end closes the connexion.
+        drop(server);
+        let error = transport.read_exact(&mut buf).unwrap_err();
+        assert_eq!(error.to_string(), "Protocol error: Connection lost.");
+        assert!(transport.is_lost());
+
+        // All operations fail until reconnected.
+        assert!(transport.write(&[0xFF]).is_err());
+        assert!(transport.clone().read_exact(&mut buf).is_err());
+
+        assert!(transport.reconnect().is_ok());
+        assert!(!transport.is_lost());
+        assert!(transport.write(&[0xFF]).is_ok());
+    }
+
+    #[test]
+    fn test_reconnect_give_up() {
+        let (listener, address) = _create_listener();
+        let mut transport = AutoReconnect::new(Tcp::new(address))
+            .set_retry_delay(10)
+            .set_max_attempts(3);
+        transport.open().unwrap();
+        drop(listener);
+
+        let error = transport.reconnect().unwrap_err();
+        assert_eq!(error.to_string(), "Protocol error: Connection lost.");
+    }
+
+    #[test]
+    fn test_timeout_is_not_a_loss() {
+        let (_listener, address) = _create_listener();
+        let mut transport = AutoReconnect::new(Tcp::new(address));
+        transport.open().unwrap();
+        transport.set_timeout(Duration::from_millis(50)).unwrap();
+
+        let error = transport.read_exact(&mut [0; 1]).unwrap_err();
+        assert_eq!(error.to_string(), "Protocol error: Operation timed out.");
+        assert!(!transport.is_lost());
+    }
+
+    #[test]
+    fn test_default_reconnect() {
+        let mut transport = Tcp::new("127.0.0.1:3030");
+        let error = transport.reconnect().unwrap_err();
+        assert_eq!(error.to_string(), "Protocol error: Connection lost.");
+    }
+
+    #[test]
+    fn test_display_auto_reconnect() {
+        let transport = AutoReconnect::new(Tcp::new("127.0.0.1:3030"));
+        assert_eq!(
+            format!("{}", transport),
+            "AutoReconnect(Tcp(127.0.0.1:3030))"
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg(test)]
+mod serde_tests {
+    use crate::io::{AutoReconnect, IoTransport, Tcp};
+
+    #[test]
+    fn test_auto_reconnect_serialize() {
+        let transport: Box<dyn IoTransport> =
+            Box::new(AutoReconnect::new(Tcp::new("127.0.0.1:3030")).set_max_attempts(3));
+        let json = serde_json::to_string(&transport).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"AutoReconnect","transport":{"type":"Tcp","address":"127.0.0.1:3030"},"retry_delay":500,"max_retry_delay":10000,"max_attempts":3}"#
+        );
+
+        let transport: Box<dyn IoTransport> = serde_json::from_str(&json).unwrap();
+        assert_eq!(transport.to_string(), "AutoReconnect(Tcp(127.0.0.1:3030))");
+    }
+}
@@ -0,0 +1,989 @@
+use crate::errors::Error;
+use crate::errors::HardwareError::UnknownPin;
+use crate::errors::ProtocolError::{NotInitialized, Timeout};
+use crate::io::constants::*;
+use crate::io::{IoTransport, PinIdOrName, PinModeId};
+use parking_lot::{Condvar, Mutex};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Lists the board profiles a [`VirtualBoard`] can simulate.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoardProfile {
+    /// Arduino UNO: 20 pins (D0-D13 and A0-A5).
+    #[default]
+    Uno,
+    /// Arduino MEGA 2560: 70 pins (D0-D53 and A0-A15).
+    Mega,
+    /// Arduino NANO: 22 pins (D0-D13 and A0-A7 - A6 and A7 are analog only).
+    Nano,
+}
+
+impl BoardProfile {
+    /// Builds the pins of the board (in their power-on state).
+    fn build_pins(&self) -> Vec<VirtualPin> {
+        let (total, analog_start, pwm, i2c): (u8, u8, Vec<u8>, [u8; 2]) = match self {
+            BoardProfile::Uno => (20, 14, vec![3, 5, 6, 9, 10, 11], [18, 19]),
+            BoardProfile::Nano => (22, 14, vec![3, 5, 6, 9, 10, 11], [18, 19]),
+            BoardProfile::Mega => (70, 54, (2..=13).chain(44..=46).collect(), [20, 21]),
+        };
+
+        (0..total)
+            .map(|id| {
+                // Pins 0 and 1 are used by the serial communication, Nano A6 and A7 are analog only.
+                let digital = id >= 2 && !(*self == BoardProfile::Nano && id >= 20);
+                let mut modes = vec![];
+                if digital {
+                    modes.push((PinModeId::INPUT, 1));
+                    modes.push((PinModeId::OUTPUT, 1));
+                }
+                if id >= analog_start {
+                    modes.push((PinModeId::ANALOG, 10));
+                }
+                if pwm.contains(&id) {
+                    modes.push((PinModeId::PWM, 8));
+                }
+                if digital {
+                    modes.push((PinModeId::SERVO, 14));
+                }
+                if i2c.contains(&id) {
+                    modes.push((PinModeId::I2C, 1));
+                }
+                if digital {
+                    modes.push((PinModeId::PULLUP, 1));
+                }
+                VirtualPin {
+                    modes,
+                    channel: (id >= analog_start).then(|| id - analog_start),
+                    mode: PinModeId::UNSUPPORTED,
+                    value: 0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Represents a simulated pin of a [`VirtualBoard`].
+#[derive(Clone, Debug)]
+struct VirtualPin {
+    /// The supported modes (and their resolution).
+    modes: Vec<(PinModeId, u8)>,
+    /// The analog channel (if any).
+    channel: Option<u8>,
+    /// The current mode.
+    mode: PinModeId,
+    /// The current value.
+    value: u16,
+}
+
+/// Represents a simulated chain of 74HC595 shift registers wired to three pins of a [`VirtualBoard`].
+#[derive(Clone, Debug, Default)]
+struct VirtualShiftRegister {
+    data_pin: u8,
+    clock_pin: u8,
+    latch_pin: u8,
+    /// The bits shifted in since the last latch (first shifted first).
+    bits: Vec<bool>,
+    /// The bytes on the outputs (first shifted first).
+    latched: Vec<u8>,
+}
+
+impl VirtualPin {
+    /// Returns true if the pin is read as a digital input.
+    fn is_input(&self) -> bool {
+        self.mode == PinModeId::INPUT || self.mode == PinModeId::PULLUP
+    }
+}
+
+/// Represents the (shared) internal state of a [`VirtualBoard`].
+#[derive(Debug)]
+struct VirtualState {
+    /// Indicates the connection is opened.
+    connected: bool,
+    /// The timeout for read operations.
+    timeout: Duration,
+    /// The board pins.
+    pins: Vec<VirtualPin>,
+    /// The digital ports for which reporting is enabled.
+    reported_ports: HashSet<u8>,
+    /// The analog channels for which reporting is enabled.
+    reported_channels: HashSet<u8>,
+    /// The sampling interval (in ms) requested by the host.
+    sampling_interval: u16,
+    /// The I2C devices memory (256 registers per address).
+    i2c_memory: HashMap<u8, Vec<u8>>,
+    /// The I2C devices register pointer (per address).
+    i2c_pointers: HashMap<u8, u8>,
+    /// The continuous I2C reads requested by the host (address, register, size).
+    i2c_reads: Vec<(u8, u8, u16)>,
+    /// The shift registers wired to the board pins.
+    shift_registers: Vec<VirtualShiftRegister>,
+    /// Bytes received from the host and not yet processed.
+    incoming: Vec<u8>,
+    /// Bytes sent by the board and not yet read by the host.
+    outgoing: VecDeque<u8>,
+}
+
+impl Default for VirtualState {
+    fn default() -> Self {
+        Self {
+            connected: false,
+            timeout: Duration::from_millis(1000),
+            pins: vec![],
+            reported_ports: HashSet::new(),
+            reported_channels: HashSet::new(),
+            sampling_interval: 19,
+            i2c_memory: HashMap::new(),
+            i2c_pointers: HashMap::new(),
+            i2c_reads: vec![],
+            shift_registers: vec![],
+            incoming: vec![],
+            outgoing: VecDeque::new(),
+        }
+    }
+}
+
+impl VirtualState {
+    /// Simulates a board reset: pins are back to their default mode, and reporting is stopped.
+    fn reset(&mut self) {
+        for pin in self.pins.iter_mut() {
+            let supports = |mode: PinModeId| pin.modes.iter().any(|(id, _)| *id == mode);
+            pin.mode = if pin.channel.is_some() && supports(PinModeId::ANALOG) {
+                PinModeId::ANALOG
+            } else if supports(PinModeId::OUTPUT) {
+                pin.value = 0;
+                PinModeId::OUTPUT
+            } else {
+                PinModeId::UNSUPPORTED
+            };
+        }
+        self.reported_ports.clear();
+        self.reported_channels.clear();
+        self.i2c_pointers.clear();
+        self.i2c_reads.clear();
+    }
+
+    /// Returns the 256 registers memory of the I2C device at the given address.
+    fn i2c_memory(&mut self, address: u8) -> &mut Vec<u8> {
+        self.i2c_memory
+            .entry(address)
+            .or_insert_with(|| vec![0; 256])
+    }
+
+    /// Processes all complete Firmata messages received from the host.
+    fn process(&mut self) {
+        while let Some(&command) = self.incoming.first() {
+            let size = match command {
+                START_SYSEX => match self.incoming.iter().position(|&byte| byte == END_SYSEX) {
+                    Some(end) => end + 1,
+                    None => break,
+                },
+                REPORT_ANALOG..=0xCF | REPORT_DIGITAL..=0xDF => 2,
+                DIGITAL_MESSAGE..=DIGITAL_MESSAGE_BOUND
+                | ANALOG_MESSAGE..=ANALOG_MESSAGE_BOUND
+                | SET_PIN_MODE
+                | SET_DIGITAL_PIN_VALUE => 3,
+                _ => 1,
+            };
+            if self.incoming.len() < size {
+                break;
+            }
+            let message: Vec<u8> = self.incoming.drain(..size).collect();
+            self.handle_message(&message);
+        }
+    }
+
+    /// Handles a single (complete) Firmata message received from the host.
+    fn handle_message(&mut self, buf: &[u8]) {
+        match buf[0] {
+            SYSTEM_RESET => self.reset(),
+            REPORT_PROTOCOL_VERSION => self.outgoing.extend([
+                REPORT_PROTOCOL_VERSION,
+                PROTOCOL_MAJOR_VERSION,
+                PROTOCOL_MINOR_VERSION,
+            ]),
+            SET_PIN_MODE => {
+                let mode = PinModeId::from_u8(buf[2]).unwrap_or_default();
+                if let Some(pin) = self.pins.get_mut(buf[1] as usize) {
+                    if mode == PinModeId::UNSUPPORTED || pin.modes.iter().any(|(id, _)| *id == mode)
+                    {
+                        pin.mode = mode;
+                    }
+                }
+            }
+            SET_DIGITAL_PIN_VALUE => self.write_digital(buf[1], u16::from(buf[2] != 0)),
+            DIGITAL_MESSAGE..=DIGITAL_MESSAGE_BOUND => {
+                let port = buf[0] & 0x0F;
+                let value = (buf[1] as u16) | ((buf[2] as u16) << 7);
+                for i in 0..8 {
+                    let pin = 8 * port + i;
+                    if self
+                        .pins
+                        .get(pin as usize)
+                        .is_some_and(|pin| pin.mode == PinModeId::OUTPUT)
+                    {
+                        self.write_digital(pin, (value >> i) & 0x01);
+                    }
+                }
+            }
+            ANALOG_MESSAGE..=ANALOG_MESSAGE_BOUND => {
+                if let Some(pin) = self.pins.get_mut((buf[0] & 0x0F) as usize) {
+                    pin.value = (buf[1] as u16) | ((buf[2] as u16) << 7);
+                }
+            }
+            REPORT_ANALOG..=0xCF => {
+                let channel = buf[0] & 0x0F;
+                match buf[1] != 0 {
+                    true => {
+                        self.reported_channels.insert(channel);
+                        self.send_analog(channel);
+                    }
+                    false => {
+                        self.reported_channels.remove(&channel);
+                    }
+                }
+            }
+            REPORT_DIGITAL..=0xDF => {
+                let port = buf[0] & 0x0F;
+                match buf[1] != 0 {
+                    true => {
+                        self.reported_ports.insert(port);
+                        self.send_port(port);
+                    }
+                    false => {
+                        self.reported_ports.remove(&port);
+                    }
+                }
+            }
+            START_SYSEX if buf.len() > 2 => self.handle_sysex(&buf[1..buf.len() - 1]),
+            _ => {}
+        }
+    }
+
+    /// Writes a digital value on the given pin: the rising edges drive the wired shift registers.
+    fn write_digital(&mut self, pin: u8, value: u16) {
+        let Some(instance) = self.pins.get_mut(pin as usize) else {
+            return;
+        };
+        let rising = instance.value == 0 && value != 0;
+        instance.value = value;
+        if !rising {
+            return;
+        }
+
+        let pins = &self.pins;
+        for register in self.shift_registers.iter_mut() {
+            if register.clock_pin == pin {
+                let bit = pins
+                    .get(register.data_pin as usize)
+                    .is_some_and(|data| data.value != 0);
+                register.bits.push(bit);
+            }
+            if register.latch_pin == pin {
+                register.latched = register
+                    .bits
+                    .chunks(8)
+                    .map(|bits| bits.iter().fold(0, |byte, &bit| byte << 1 | u8::from(bit)))
+                    .collect();
+                register.bits.clear();
+            }
+        }
+    }
+
+    /// Handles a sysex message received from the host (without the START_SYSEX/END_SYSEX bytes).
+    fn handle_sysex(&mut self, buf: &[u8]) {
+        match buf[0] {
+            REPORT_FIRMWARE => {
+                self.outgoing.extend([
+                    START_SYSEX,
+                    REPORT_FIRMWARE,
+                    PROTOCOL_MAJOR_VERSION,
+                    PROTOCOL_MINOR_VERSION,
+                ]);
+                for char in "VirtualBoard".bytes() {
+                    self.outgoing.extend([char & SYSEX_REALTIME, char >> 7]);
+                }
+                self.outgoing.push_back(END_SYSEX);
+            }
+            CAPABILITY_QUERY => {
+                self.outgoing.extend([START_SYSEX, CAPABILITY_RESPONSE]);
+                for pin in self.pins.iter() {
+                    for (mode, resolution) in pin.modes.iter() {
+                        self.outgoing.extend([*mode as u8, *resolution]);
+                    }
+                    self.outgoing.push_back(SYSEX_REALTIME);
+                }
+                self.outgoing.push_back(END_SYSEX);
+            }
+            ANALOG_MAPPING_QUERY => {
+                self.outgoing.extend([START_SYSEX, ANALOG_MAPPING_RESPONSE]);
+                for pin in self.pins.iter() {
+                    self.outgoing
+                        .push_back(pin.channel.unwrap_or(SYSEX_REALTIME));
+                }
+                self.outgoing.push_back(END_SYSEX);
+            }
+            PIN_STATE_QUERY if buf.len() > 1 => {
+                if let Some(pin) = self.pins.get(buf[1] as usize) {
+                    self.outgoing
+                        .extend([START_SYSEX, PIN_STATE_RESPONSE, buf[1], pin.mode as u8]);
+                    // As the firmware does, the state of an input is its pull-up flag (not its value), and
+                    // the state of an output is its written value.
+                    let mut value = match pin.mode {
+                        PinModeId::INPUT | PinModeId::ANALOG => 0,
+                        PinModeId::PULLUP => 1,
+                        _ => pin.value,
+                    };
+                    // The state is sent as 7 bits chunks: least significant bits first.
+                    loop {
+                        self.outgoing.push_back(value as u8 & SYSEX_REALTIME);
+                        value >>= 7;
+                        if value == 0 {
+                            break;
+                        }
+                    }
+                    self.outgoing.push_back(END_SYSEX);
+                }
+            }
+            EXTENDED_ANALOG if buf.len() > 2 => {
+                if let Some(pin) = self.pins.get_mut(buf[1] as usize) {
+                    pin.value = buf[2..]
+                        .iter()
+                        .enumerate()
+                        .fold(0, |value, (i, &byte)| value | ((byte as u16) << (7 * i)));
+                }
+            }
+            SAMPLING_INTERVAL if buf.len() > 2 => {
+                self.sampling_interval = (buf[1] as u16) | ((buf[2] as u16) << 7);
+            }
+            I2C_REQUEST if buf.len() > 2 => self.handle_i2c_request(buf),
+            // SERVO_CONFIG, I2C_CONFIG, etc. are accepted but have no visible effect.
+            _ => {}
+        }
+    }
+
+    /// Handles an I2C_REQUEST sysex message: the I2C devices are simulated as 256 registers memories.
+    /// <https://github.com/firmata/protocol/blob/master/i2c.md>
+    fn handle_i2c_request(&mut self, buf: &[u8]) {
+        let address = buf[1];
+        let data: Vec<u16> = buf[3..]
+            .chunks(2)
+            .map(|chunk| chunk[0] as u16 | (*chunk.get(1).unwrap_or(&0) as u16) << 7)
+            .collect();
+
+        match buf[2] & I2C_READ_WRITE_MODE_MASK {
+            I2C_WRITE => {
+                // The first byte sets the register pointer, the next ones are written from there.
+                if let Some((&register, values)) = data.split_first() {
+                    let register = register as u8;
+                    self.i2c_pointers.insert(address, register);
+                    let memory = self.i2c_memory(address);
+                    for (i, &value) in values.iter().enumerate() {
+                        memory[register.wrapping_add(i as u8) as usize] = value as u8;
+                    }
+                }
+            }
+            mode if mode == I2C_READ << 3 || mode == I2C_READ_CONTINUOUSLY => {
+                let pointer = self.i2c_pointers.get(&address).copied().unwrap_or(0);
+                let (register, size) = match data.as_slice() {
+                    [register, size, ..] => (*register as u8, *size),
+                    [size] => (pointer, *size),
+                    _ => return,
+                };
+                if mode == I2C_READ_CONTINUOUSLY {
+                    self.i2c_reads.push((address, register, size));
+                }
+                self.send_i2c_reply(address, register, size);
+            }
+            I2C_STOP_READING => self.i2c_reads.retain(|(read, _, _)| *read != address),
+            _ => {}
+        }
+    }
+
+    /// Sends the content of `size` registers of the I2C device at `address`, starting from `register`.
+    fn send_i2c_reply(&mut self, address: u8, register: u8, size: u16) {
+        let memory = self.i2c_memory(address).clone();
+        self.outgoing.extend([
+            START_SYSEX,
+            I2C_REPLY,
+            address & SYSEX_REALTIME,
+            address >> 7,
+            register & SYSEX_REALTIME,
+            register >> 7,
+        ]);
+        for i in 0..size {
+            let byte = memory[register.wrapping_add(i as u8) as usize];
+            self.outgoing.extend([byte & SYSEX_REALTIME, byte >> 7]);
+        }
+        self.outgoing.push_back(END_SYSEX);
+    }
+
+    /// Queues an ANALOG_MESSAGE with the value of the given channel.
+    fn send_analog(&mut self, channel: u8) {
+        if let Some(pin) = self.pins.iter().find(|pin| pin.channel == Some(channel)) {
+            let value = pin.value;
+            self.outgoing.extend([
+                ANALOG_MESSAGE | (channel & 0x0F),
+                value as u8 & SYSEX_REALTIME,
+                (value >> 7) as u8 & SYSEX_REALTIME,
+            ]);
+        }
+    }
+
+    /// Queues a DIGITAL_MESSAGE with the value of the input pins of the given port.
+    fn send_port(&mut self, port: u8) {
+        let mut value: u16 = 0;
+        for i in 0..8 {
+            if let Some(pin) = self.pins.get((8 * port + i) as usize) {
+                if pin.is_input() && pin.value != 0 {
+                    value |= 1 << i;
+                }
+            }
+        }
+        self.outgoing.extend([
+            DIGITAL_MESSAGE | port,
+            value as u8 & SYSEX_REALTIME,
+            (value >> 7) as u8 & SYSEX_REALTIME,
+        ]);
+    }
+}
+
+/// Represents a virtual board: an in-process [`IoTransport`] simulating the device side of the Firmata protocol.
+///
+/// This transport layer requires no hardware: it answers the handshake (firmware, capabilities, analog
+/// mapping), keeps track of the pin modes and values written by the host, reports digital and analog
+/// inputs, and simulates I2C devices as 256 registers memories (and 74HC595 shift registers wired with
+/// [`VirtualBoard::wire_shift_register`]). It can be configured with various
+/// [`BoardProfile`]s (Uno, Mega, Nano).
+///
+/// All clones of a `VirtualBoard` share the same state: a clone can therefore be kept as a handle to
+/// inject input pin values and to inspect output pin state while the board is in use.
+///
+/// # Example
+/// ```
+/// use hermes_five::devices::Led;
+/// use hermes_five::hardware::Board;
+/// use hermes_five::io::{BoardProfile, VirtualBoard};
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     let simulator = VirtualBoard::new(BoardProfile::Uno);
+///     let board = Board::from(simulator.clone()).blocking_open().unwrap();
+///
+///     let mut led = Led::new(&board, 13, false).unwrap();
+///     led.turn_on().unwrap();
+///     assert_eq!(simulator.get_pin_value(13).unwrap(), 1);
+///
+///     board.close();
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default)]
+pub struct VirtualBoard {
+    /// The simulated board profile (default: Uno).
+    #[cfg_attr(feature = "serde", serde(default))]
+    profile: BoardProfile,
+
+    // ########################################
+    // # Volatile utility data.
+    /// The shared internal state.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    state: Arc<Mutex<VirtualState>>,
+    /// Notifies the readers when data is sent by the board.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    signal: Arc<Condvar>,
+}
+
+impl VirtualBoard {
+    /// Creates a new virtual board with the given profile.
+    pub fn new(profile: BoardProfile) -> Self {
+        let mut state = VirtualState {
+            pins: profile.build_pins(),
+            ..Default::default()
+        };
+        state.reset();
+        Self {
+            profile,
+            state: Arc::new(Mutex::new(state)),
+            signal: Arc::new(Condvar::new()),
+        }
+    }
+
+    /// Returns the simulated board profile.
+    pub fn get_profile(&self) -> BoardProfile {
+        self.profile
+    }
+
+    /// Returns true if the connection is opened.
+    pub fn is_connected(&self) -> bool {
+        self.state.lock().connected
+    }
+
+    /// Returns the current mode of the given pin.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: the pin does not exist for this board.
+    pub fn get_pin_mode(&self, pin: u8) -> Result<PinModeId, Error> {
+        self.with_pin(pin, |pin| pin.mode)
+    }
+
+    /// Returns the current value of the given pin: as written by the host for output pins, or as
+    /// injected with [`VirtualBoard::set_pin_value`] for input pins.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: the pin does not exist for this board.
+    pub fn get_pin_value(&self, pin: u8) -> Result<u16, Error> {
+        self.with_pin(pin, |pin| pin.value)
+    }
+
+    /// Injects a value on the given pin, as if an external signal was applied to it.
+    ///
+    /// If the pin is an input (INPUT/PULLUP or ANALOG mode) with reporting enabled, the new value is
+    /// reported to the host.
+    ///
+    /// # Errors
+    /// * `UnknownPin`: the pin does not exist for this board.
+    pub fn set_pin_value(&self, pin: u8, value: u16) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        let instance = state.pins.get_mut(pin as usize).ok_or(UnknownPin {
+            pin: PinIdOrName::Id(pin),
+        })?;
+        instance.value = value;
+
+        let port = pin / 8;
+        let (input, channel) = (instance.is_input(), instance.channel);
+        let analog = instance.mode == PinModeId::ANALOG;
+        if input && state.reported_ports.contains(&port) {
+            state.send_port(port);
+        }
+        if let Some(channel) = channel {
+            if analog && state.reported_channels.contains(&channel) {
+                state.send_analog(channel);
+            }
+        }
+        self.signal.notify_all();
+        Ok(())
+    }
+
+    /// Returns `size` bytes of the memory of the I2C device at `address`, starting from `register`.
+    pub fn get_i2c_data(&self, address: u8, register: u8, size: usize) -> Vec<u8> {
+        let mut state = self.state.lock();
+        let memory = state.i2c_memory(address);
+        (0..size)
+            .map(|i| memory[register.wrapping_add(i as u8) as usize])
+            .collect()
+    }
+
+    /// Writes the given bytes to the memory of the I2C device at `address`, starting from `register`.
+    ///
+    /// If the device is read continuously, its new content is reported to the host.
+    pub fn set_i2c_data(&self, address: u8, register: u8, data: &[u8]) {
+        let mut state = self.state.lock();
+        let memory = state.i2c_memory(address);
+        for (i, &byte) in data.iter().enumerate() {
+            memory[register.wrapping_add(i as u8) as usize] = byte;
+        }
+        let reads: Vec<(u8, u8, u16)> = state
+            .i2c_reads
+            .iter()
+            .filter(|(read, _, _)| *read == address)
+            .copied()
+            .collect();
+        for (address, register, size) in reads {
+            state.send_i2c_reply(address, register, size);
+        }
+        self.signal.notify_all();
+    }
+
+    /// Wires a chain of 74HC595 shift registers to the given pins: the bits on the data pin are
+    /// shifted in on the rising edges of the clock pin (MSB first), and latched on the rising edges of
+    /// the latch pin.
+    pub fn wire_shift_register(&self, data_pin: u8, clock_pin: u8, latch_pin: u8) {
+        self.state
+            .lock()
+            .shift_registers
+            .push(VirtualShiftRegister {
+                data_pin,
+                clock_pin,
+                latch_pin,
+                ..Default::default()
+            });
+    }
+
+    /// Returns the bytes last latched by the shift registers wired to the given latch pin (the first
+    /// shifted byte first).
+    pub fn get_shift_data(&self, latch_pin: u8) -> Vec<u8> {
+        let state = self.state.lock();
+        state
+            .shift_registers
+            .iter()
+            .find(|register| register.latch_pin == latch_pin)
+            .map(|register| register.latched.clone())
+            .unwrap_or_default()
+    }
+
+    /// Runs the given closure on the given pin.
+    fn with_pin<T, F: FnOnce(&VirtualPin) -> T>(&self, pin: u8, f: F) -> Result<T, Error> {
+        let state = self.state.lock();
+        let instance = state.pins.get(pin as usize).ok_or(UnknownPin {
+            pin: PinIdOrName::Id(pin),
+        })?;
+        Ok(f(instance))
+    }
+}
+
+impl Display for VirtualBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VirtualBoard({:?})", self.profile)?;
+        if self.is_connected() {
+            write!(f, " [*]")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl IoTransport for VirtualBoard {
+    fn open(&mut self) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        // A deserialized board has no pins yet.
+        if state.pins.is_empty() {
+            state.pins = self.profile.build_pins();
+            state.reset();
+        }
+        state.incoming.clear();
+        state.outgoing.clear();
+        state.connected = true;
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        state.connected = false;
+        state.incoming.clear();
+        state.outgoing.clear();
+        self.signal.notify_all();
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, duration: Duration) -> Result<(), Error> {
+        self.state.lock().timeout = duration;
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        if !state.connected {
+            return Err(Error::from(NotInitialized));
+        }
+        state.incoming.extend_from_slice(buf);
+        state.process();
+        self.signal.notify_all();
+        Ok(())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        let mut state = self.state.lock();
+        let deadline = Instant::now() + state.timeout;
+        loop {
+            if !state.connected {
+                return Err(Error::from(NotInitialized));
+            }
+            if state.outgoing.len() >= buf.len() {
+                break;
+            }
+            if self.signal.wait_until(&mut state, deadline).timed_out() {
+                return Err(Error::from(Timeout));
+            }
+        }
+        for byte in buf.iter_mut() {
+            *byte = state.outgoing.pop_front().unwrap();
+        }
+        Ok(())
+    }
+
+    fn reconnect(&mut self) -> Result<(), Error> {
+        self.close()?;
+        self.open()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::{AnalogInput, DigitalInput, Input, InputEvent, Led};
+    use crate::hardware::Board;
+    use crate::io::IO;
+    use crate::pause;
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    #[test]
+    fn test_profiles() {
+        let uno = BoardProfile::Uno.build_pins();
+        assert_eq!(uno.len(), 20);
+        assert!(uno[0].modes.is_empty());
+        assert_eq!(uno[14].channel, Some(0));
+        assert!(uno[3].modes.contains(&(PinModeId::PWM, 8)));
+        assert!(uno[18].modes.contains(&(PinModeId::I2C, 1)));
+
+        let mega = BoardProfile::Mega.build_pins();
+        assert_eq!(mega.len(), 70);
+        assert_eq!(mega[69].channel, Some(15));
+        assert!(mega[45].modes.contains(&(PinModeId::PWM, 8)));
+        assert!(mega[20].modes.contains(&(PinModeId::I2C, 1)));
+
+        let nano = BoardProfile::Nano.build_pins();
+        assert_eq!(nano.len(), 22);
+        assert_eq!(nano[21].channel, Some(7));
+        assert_eq!(nano[21].modes, vec![(PinModeId::ANALOG, 10)]);
+    }
+
+    #[test]
+    fn test_handshake() {
+        let board = Board::from(VirtualBoard::new(BoardProfile::Mega))
+            .blocking_open()
+            .unwrap();
+        {
+            let io = board.get_io().read();
+            assert_eq!(io.firmware_name, "VirtualBoard");
+            assert_eq!(io.firmware_version, "2.5");
+            assert_eq!(io.pins.len(), 70);
+            assert_eq!(io.get_pin("A15").unwrap().id, 69);
+            assert_eq!(io.get_pin(13).unwrap().mode.id, PinModeId::INPUT);
+        }
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_outputs() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let mut board = Board::from(simulator.clone()).blocking_open().unwrap();
+
+        let mut led = Led::new(&board, 13, false).unwrap();
+        assert_eq!(simulator.get_pin_mode(13).unwrap(), PinModeId::OUTPUT);
+        led.turn_on().unwrap();
+        assert_eq!(simulator.get_pin_value(13).unwrap(), 1);
+        led.turn_off().unwrap();
+        assert_eq!(simulator.get_pin_value(13).unwrap(), 0);
+
+        board.set_pin_mode(9, PinModeId::PWM).unwrap();
+        board.analog_write(9, 128).unwrap();
+        assert_eq!(simulator.get_pin_mode(9).unwrap(), PinModeId::PWM);
+        assert_eq!(simulator.get_pin_value(9).unwrap(), 128);
+
+        board.set_pin_mode(16, PinModeId::OUTPUT).unwrap();
+        board.digital_write(16, true).unwrap();
+        assert_eq!(simulator.get_pin_value(16).unwrap(), 1);
+
+        assert!(simulator.get_pin_value(42).is_err());
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_reads() {
+        let simulator = VirtualBoard::new(BoardProfile::Nano);
+        let mut board = Board::from(simulator.clone()).blocking_open().unwrap();
+
+        board.set_pin_mode(4, PinModeId::INPUT).unwrap();
+        simulator.set_pin_value(4, 1).unwrap();
+        assert!(board.digital_read(4).unwrap());
+
+        simulator.set_pin_value(21, 300).unwrap();
+        assert_eq!(board.analog_read(21).unwrap(), 300);
+        board.blocking_close().unwrap();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_input_reporting() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let board = Board::from(simulator.clone()).blocking_open().unwrap();
+
+        let button = DigitalInput::new(&board, 2).unwrap();
+        let sensor = AnalogInput::new(&board, "A0").unwrap();
+        pause!(200);
+        assert!(!button.get_state().as_bool());
+        assert_eq!(sensor.get_state().as_integer(), 0);
+
+        simulator.set_pin_value(2, 1).unwrap();
+        simulator.set_pin_value(14, 512).unwrap();
+        pause!(100);
+        assert!(button.get_state().as_bool());
+        assert_eq!(sensor.get_state().as_integer(), 512);
+
+        // Short pulses are not missed.
+        let pulses = Arc::new(AtomicU8::new(0));
+        let moved_pulses = pulses.clone();
+        button.on(InputEvent::OnHigh, move |_: ()| {
+            let captured_pulses = moved_pulses.clone();
+            async move {
+                captured_pulses.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        });
+        for value in [0, 1, 0, 1, 0] {
+            simulator.set_pin_value(2, value).unwrap();
+        }
+        pause!(200);
+        assert_eq!(pulses.load(Ordering::SeqCst), 2);
+
+        button.detach();
+        sensor.detach();
+        board.close();
+    }
+
+    #[test]
+    fn test_i2c() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let mut board = Board::from(simulator.clone()).blocking_open().unwrap();
+
+        board.i2c_config(0).unwrap();
+        board.i2c_write(0x40, &[0x10, 0xAA, 0xBB]).unwrap();
+        assert_eq!(simulator.get_i2c_data(0x40, 0x10, 2), vec![0xAA, 0xBB]);
+
+        simulator.set_i2c_data(0x40, 0x20, &[0x01, 0xFF]);
+        board.i2c_write(0x40, &[0x20]).unwrap();
+        board.i2c_read(0x40, None, 2).unwrap();
+        let reply = board.get_io().read().i2c_data.last().unwrap().clone();
+        assert_eq!(reply.address, 0x40);
+        assert_eq!(reply.register, 0x20);
+        assert_eq!(reply.data, vec![0x01, 0xFF]);
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_shift_out() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let mut board = Board::from(simulator.clone()).blocking_open().unwrap();
+
+        simulator.wire_shift_register(2, 3, 4);
+        for pin in [2, 3, 4] {
+            board.set_pin_mode(pin, PinModeId::OUTPUT).unwrap();
+        }
+
+        // No pin supports the SHIFT mode (as with StandardFirmata).
+        assert!(board.shift_out(2, 3, 4, &[0xA5]).is_err());
+
+        // The bits are shifted in on the clock rising edges, and latched on the latch rising edge.
+        assert!(simulator.get_shift_data(4).is_empty());
+        for byte in [0xA5u8, 0x01] {
+            for i in (0..8).rev() {
+                board.digital_write(2, byte >> i & 1 == 1).unwrap();
+                board.digital_write(3, true).unwrap();
+                board.digital_write(3, false).unwrap();
+            }
+        }
+        assert!(simulator.get_shift_data(4).is_empty());
+        board.digital_write(4, true).unwrap();
+        board.digital_write(4, false).unwrap();
+        assert_eq!(simulator.get_shift_data(4), vec![0xA5, 0x01]);
+        assert!(simulator.get_shift_data(5).is_empty());
+        board.blocking_close().unwrap();
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_i2c_read_continuously() {
+        let simulator = VirtualBoard::new(BoardProfile::Uno);
+        let mut board = Board::from(simulator.clone()).blocking_open().unwrap();
+        board.i2c_config(0).unwrap();
+
+        // Continuous reads are reported on each change, until stopped.
+        simulator.set_i2c_data(0x40, 0x20, &[0x01]);
+        let mut replies = board.get_io().read().subscribe_i2c_replies();
+        board.i2c_read_continuously(0x40, Some(0x20), 1).unwrap();
+        pause!(100);
+        assert_eq!(replies.try_recv().unwrap().data, vec![0x01]);
+        simulator.set_i2c_data(0x40, 0x20, &[0x02]);
+        pause!(100);
+        assert_eq!(replies.try_recv().unwrap().data, vec![0x02]);
+        board.i2c_stop_reading(0x40).unwrap();
+        simulator.set_i2c_data(0x40, 0x20, &[0x03]);
+        pause!(100);
+        assert!(replies.try_recv().is_err());
+        board.blocking_close().unwrap();
+    }
+
+    #[test]
+    fn test_transport() {
+        let mut simulator = VirtualBoard::default();
+        assert_eq!(simulator.get_profile(), BoardProfile::Uno);
+        assert!(simulator.write(&[SYSTEM_RESET]).is_err());
+        assert!(simulator.read_exact(&mut [0; 3]).is_err());
+
+        simulator.open().unwrap();
+        assert!(simulator.is_connected());
+        simulator.set_timeout(Duration::from_millis(50)).unwrap();
+        simulator.write(&[REPORT_PROTOCOL_VERSION]).unwrap();
+        let mut buf = [0; 3];
+        simulator.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0xF9, 0x02, 0x05]);
+
+        let error = simulator.read_exact(&mut buf).unwrap_err();
+        assert_eq!(error.to_string(), "Protocol error: Operation timed out.");
+
+        simulator.close().unwrap();
+        assert!(!simulator.is_connected());
+    }
+
+    #[test]
+    fn test_pin_state() {
+        let mut simulator = VirtualBoard::new(BoardProfile::Uno);
+        simulator.open().unwrap();
+        let mut querier = simulator.clone();
+        let mut state = move |pin: u8| {
+            querier
+                .write(&[START_SYSEX, PIN_STATE_QUERY, pin, END_SYSEX])
+                .unwrap();
+            let mut buf = [0; 6];
+            querier.read_exact(&mut buf).unwrap();
+            buf[4]
+        };
+
+        // Inputs: the pull-up flag, whatever their value.
+        simulator
+            .write(&[SET_PIN_MODE, 2, PinModeId::INPUT as u8])
+            .unwrap();
+        simulator.set_pin_value(2, 1).unwrap();
+        assert_eq!(state(2), 0);
+        simulator
+            .write(&[SET_PIN_MODE, 3, PinModeId::PULLUP as u8])
+            .unwrap();
+        assert_eq!(state(3), 1);
+        simulator.set_pin_value(14, 100).unwrap();
+        assert_eq!(state(14), 0);
+
+        // Outputs: the written value.
+        simulator
+            .write(&[SET_PIN_MODE, 13, PinModeId::OUTPUT as u8])
+            .unwrap();
+        simulator.write(&[SET_DIGITAL_PIN_VALUE, 13, 1]).unwrap();
+        assert_eq!(state(13), 1);
+    }
+
+    #[test]
+    fn test_display_virtual_board() {
+        let mut simulator = VirtualBoard::new(BoardProfile::Mega);
+        assert_eq!(format!("{}", simulator), "VirtualBoard(Mega)");
+        simulator.open().unwrap();
+        assert_eq!(format!("{}", simulator), "VirtualBoard(Mega) [*]");
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg(test)]
+mod serde_tests {
+    use crate::io::{BoardProfile, IoTransport, VirtualBoard};
+
+    #[test]
+    fn test_virtual_board_serialize() {
+        let transport: Box<dyn IoTransport> = Box::new(VirtualBoard::new(BoardProfile::Nano));
+        let json = serde_json::to_string(&transport).unwrap();
+        assert_eq!(json, r#"{"type":"VirtualBoard","profile":"Nano"}"#);
+
+        let mut transport: Box<dyn IoTransport> = serde_json::from_str(&json).unwrap();
+        assert!(transport.open().is_ok());
+        assert_eq!(transport.to_string(), "VirtualBoard(Nano) [*]");
+    }
+}
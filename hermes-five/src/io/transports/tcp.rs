@@ -0,0 +1,243 @@
+use crate::errors::Error;
+use crate::errors::ProtocolError::NotInitialized;
+use crate::io::IoTransport;
+use parking_lot::Mutex;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Represents an [`IoTransport`] layer based on a TCP socket connection.
+///
+/// This is the transport to use with boards running a network flavour of Firmata, such as
+/// [StandardFirmataWiFi](https://github.com/firmata/arduino/tree/main/examples/StandardFirmataWiFi) (ESP8266, ESP32, etc.)
+/// or [StandardFirmataEthernet](https://github.com/firmata/arduino/tree/main/examples/StandardFirmataEthernet) (Ethernet shield).
+/// Those sketches run a TCP server on the board (port 3030 by default).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug)]
+pub struct Tcp {
+    /// The remote address (`host:port`).
+    address: String,
+    /// A Read/Write io object.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    io: Arc<Mutex<Option<TcpStream>>>,
+}
+
+impl Tcp {
+    /// Constructs a new `Tcp` transport layer instance for communication with the specified address.
+    ///
+    /// # Example
+    /// ```
+    /// use hermes_five::hardware::Board;
+    /// use hermes_five::io::Tcp;
+    ///
+    /// #[hermes_five::runtime]
+    /// async fn main() {
+    ///     let board = Board::from(Tcp::new("192.168.1.100:3030")).open();
+    /// }
+    /// ```
+    pub fn new<A: Into<String>>(address: A) -> Self {
+        Self {
+            address: address.into(),
+            io: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the configured address.
+    pub fn get_address(&self) -> String {
+        self.address.clone()
+    }
+}
+
+impl Display for Tcp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Tcp({}{})",
+            self.address,
+            if self.io.lock().is_some() { " [*]" } else { "" }
+        )
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde)]
+impl IoTransport for Tcp {
+    fn open(&mut self) -> Result<(), Error> {
+        let timeout = Duration::from_secs(10);
+
+        // Try all resolved addresses until one accepts the connection.
+        let mut result = Err(std::io::Error::from(std::io::ErrorKind::NotFound));
+        for address in self.address.to_socket_addrs()? {
+            result = TcpStream::connect_timeout(&address, timeout);
+            if result.is_ok() {
+                break;
+            }
+        }
+        let connexion = result?;
+
+        // Firmata messages are very small: do not wait to send them.
+        connexion.set_nodelay(true)?;
+        connexion.set_read_timeout(Some(timeout))?;
+        connexion.set_write_timeout(Some(timeout))?;
+
+        // Save the IO (required by handshake).
+        self.io = Arc::new(Mutex::new(Some(connexion)));
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> Result<(), Error> {
+        if let Some(connexion) = self.io.lock().take() {
+            let _ = connexion.shutdown(std::net::Shutdown::Both);
+        }
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, duration: Duration) -> Result<(), Error> {
+        let lock = self.io.lock();
+        let connexion = lock.as_ref().ok_or(NotInitialized)?;
+        connexion.set_read_timeout(Some(duration))?;
+        connexion.set_write_timeout(Some(duration))?;
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let mut lock = self.io.lock();
+        lock.as_mut().ok_or(NotInitialized)?.write_all(buf)?;
+        Ok(())
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        let mut lock = self.io.lock();
+        lock.as_mut().ok_or(NotInitialized)?.read_exact(buf)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Creates a local listener and returns it with its address.
+    fn _create_listener() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        (listener, address)
+    }
+
+    #[test]
+    fn test_new_tcp_transport() {
+        let transport = Tcp::new("192.168.1.100:3030");
+        assert_eq!(transport.get_address(), "192.168.1.100:3030");
+        assert!(transport.io.lock().is_none());
+    }
+
+    #[test]
+    fn test_open_tcp_transport() {
+        let (listener, address) = _create_listener();
+        let mut transport = Tcp::new(address);
+        assert!(transport.open().is_ok());
+        assert!(transport.io.lock().is_some());
+        drop(listener);
+
+        // Nothing listens on that port anymore.
+        let mut transport = Tcp::new(transport.get_address());
+        assert!(transport.open().is_err());
+
+        // Invalid address.
+        let mut transport = Tcp::new("not an address");
+        assert!(transport.open().is_err());
+    }
+
+    #[test]
+    fn test_close_tcp_transport() {
+        let (_listener, address) = _create_listener();
+        let mut transport = Tcp::new(address);
+        transport.open().unwrap();
+        assert!(transport.close().is_ok());
+        assert!(transport.io.lock().is_none());
+        // Closing twice does not fail.
+        assert!(transport.close().is_ok());
+    }
+
+    #[test]
+    fn test_write_and_read_exact() {
+        let (listener, address) = _create_listener();
+        let mut transport = Tcp::new(address);
+        transport.open().unwrap();
+
+        // Echo server: sends back what it receives.
+        let (mut server, _) = listener.accept().unwrap();
+        let echo = std::thread::spawn(move || {
+            let mut buf = [0; 3];
+            server.read_exact(&mut buf).unwrap();
+            server.write_all(&buf).unwrap();
+        });
+
+        assert!(transport.write(&[0xF0, 0x79, 0xF7]).is_ok());
+        let mut buf = [0; 3];
+        assert!(transport.read_exact(&mut buf).is_ok());
+        assert_eq!(buf, [0xF0, 0x79, 0xF7]);
+        echo.join().unwrap();
+    }
+
+    #[test]
+    fn test_not_initialized() {
+        let mut transport = Tcp::new("127.0.0.1:3030");
+        assert!(transport.write(&[1, 2, 3]).is_err());
+        assert!(transport.read_exact(&mut [0; 3]).is_err());
+        assert_eq!(
+            transport
+                .set_timeout(Duration::from_secs(1))
+                .unwrap_err()
+                .to_string(),
+            "Protocol error: Connection has not been initialized."
+        );
+    }
+
+    #[test]
+    fn test_set_timeout() {
+        let (_listener, address) = _create_listener();
+        let mut transport = Tcp::new(address);
+        transport.open().unwrap();
+        assert!(transport.set_timeout(Duration::from_millis(50)).is_ok());
+
+        // Nothing is sent by the server: the read times out.
+        let mut buf = [0; 1];
+        assert!(transport.read_exact(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_display_tcp_transport() {
+        let (_listener, address) = _create_listener();
+        let mut transport = Tcp::new(address.clone());
+        assert_eq!(format!("{}", transport), format!("Tcp({})", address));
+        transport.open().unwrap();
+        assert_eq!(format!("{}", transport), format!("Tcp({} [*])", address));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg(test)]
+mod serde_tests {
+    use crate::hardware::{Board, Hardware};
+    use crate::io::Tcp;
+
+    #[test]
+    fn test_tcp_serialize() {
+        let board = Board::from(Tcp::new("192.168.1.100:3030"));
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(
+            json,
+            r#"{"protocol":{"type":"RemoteIo","transport":{"type":"Tcp","address":"192.168.1.100:3030"}}}"#
+        );
+
+        let board: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            board.get_protocol().to_string(),
+            "RemoteIo [firmware=, version=, protocol=, transport=Tcp(192.168.1.100:3030)]"
+        );
+    }
+}
@@ -18,10 +18,11 @@
 //! It can be compared to _[Johnny-Five](https://johnny-five.io/)_ in the javascript ecosystem.
 //!
 //! - Define remotely controllable [`Board`](hardware::Board) (Arduino currently)
-//! - Control boards though an [`IoProtocol`](io::IoProtocol) connection ([`Serial`](io::Serial) for the moment)
+//! - Control boards though an [`IoProtocol`](io::IoProtocol) connection ([`Serial`](io::Serial) or [`Tcp`](io::Tcp) for the moment - or a [`VirtualBoard`](io::VirtualBoard) simulator)
 //! - Remote control all types of [`Device`](devices::Device)s such as [`Output`](devices::Output)s (LED, servo, etc.) or [`Input`](devices::Input)s (button, switch, sensors,
 //! - etc.) individually
 //! - Create and play [`Animation`](animations::Animation) with auto-interpolate movements
+//! - Describe a whole robot in a configuration file (requires the **config** feature)
 //!
 //! **_If you wish to do the same with absolutely no code via a nice-and-shiny interface, please consult the [Hermes-Studio](https://github.com/dclause/hermes-studio) project._**
 //!
@@ -75,12 +76,15 @@
 //!
 //! - **libudev** -- (enabled by default) Activates `serialport` crate _libudev_ feature under-the-hood (required on Linux only for port listing).
 //! - **serde** -- Enables serialize/deserialize capabilities for most entities.
+//! - **config** -- Enables loading a whole robot (boards, devices, animations) from a JSON/TOML/YAML configuration file (implies **serde**).
 //! - **mock** -- Provides mocked entities of all kinds (useful for tests mostly).
 
 #[cfg(test)]
 extern crate self as hermes_five;
 
 pub mod animations;
+#[cfg(feature = "config")]
+pub mod config;
 pub mod devices;
 pub mod errors;
 pub mod hardware;
@@ -223,12 +223,10 @@ pub fn create_test_plugin_io_data() -> IoData {
             (15, create_analog_pin(15, 200)),
             (22, create_analog_pin(22, 222)),
         ]),
-        i2c_data: vec![],
-        digital_reported_pins: vec![],
-        analog_reported_channels: vec![],
         protocol_version: "fake.1.0".to_string(),
         firmware_name: "Fake protocol".to_string(),
         firmware_version: "fake.2.3".to_string(),
         connected: false,
+        ..Default::default()
     }
 }
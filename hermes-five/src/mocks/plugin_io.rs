@@ -1,6 +1,8 @@
 use crate::errors::Error;
 use crate::errors::HardwareError::IncompatiblePin;
-use crate::io::{IoData, IoProtocol, PinModeId, IO};
+use crate::io::{
+    DhtModel, EncoderCommand, IoData, IoProtocol, PinMode, PinModeId, StepperCommand, IO,
+};
 use crate::mocks::create_test_plugin_io_data;
 use crate::pause_sync;
 use crate::utils::Range;
@@ -8,6 +10,9 @@ use parking_lot::RwLock;
 use std::fmt::Display;
 use std::sync::Arc;
 
+/// An I2C write: the device address and the data written.
+pub type I2cWrite = (u8, Vec<u16>);
+
 /// Mock implement for [`IoData`].
 /// Uses [`create_test_plugin_io_data`] for the hardware:
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -17,6 +22,12 @@ pub struct MockIoProtocol {
     pub connected: bool,
     #[cfg_attr(feature = "serde", serde(skip))]
     pub data: Arc<RwLock<IoData>>,
+    /// The I2C writes received (address and data), shared by all clones.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub i2c_writes: Arc<RwLock<Vec<I2cWrite>>>,
+    /// The data shifted out, shared by all clones.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub shift_outs: Arc<RwLock<Vec<Vec<u8>>>>,
 }
 
 impl Default for MockIoProtocol {
@@ -24,7 +35,30 @@ impl Default for MockIoProtocol {
         Self {
             connected: false,
             data: Arc::new(RwLock::new(create_test_plugin_io_data())),
+            i2c_writes: Default::default(),
+            shift_outs: Default::default(),
+        }
+    }
+}
+
+impl MockIoProtocol {
+    /// Creates a mock protocol whose given pins also support the given mode (for the features the test
+    /// data does not provide: stepper, encoder, etc.).
+    pub fn with_mode<I: IntoIterator<Item = u8>>(pins: I, mode: PinModeId) -> Self {
+        let protocol = Self::default();
+        {
+            let mut data = protocol.data.write();
+            for pin in pins {
+                data.get_pin_mut(pin)
+                    .unwrap()
+                    .supported_modes
+                    .push(PinMode {
+                        id: mode,
+                        resolution: 1,
+                    });
+            }
         }
+        protocol
     }
 }
 
@@ -103,27 +137,171 @@ impl IO for MockIoProtocol {
         Ok(())
     }
 
-    fn digital_read(&mut self, _: u8) -> Result<bool, Error> {
-        unimplemented!()
+    fn digital_read(&mut self, pin: u8) -> Result<bool, Error> {
+        Ok(self.data.read().get_pin(pin)?.value != 0)
     }
 
-    fn analog_read(&mut self, _: u8) -> Result<u16, Error> {
-        unimplemented!()
+    fn analog_read(&mut self, pin: u8) -> Result<u16, Error> {
+        Ok(self.data.read().get_pin(pin)?.value)
     }
 
     fn servo_config(&mut self, _: u8, _: Range<u16>) -> Result<(), Error> {
         Ok(())
     }
 
+    /// Emulates a stepper reaching its target position instantly.
+    fn stepper(&mut self, id: u8, command: StepperCommand) -> Result<(), Error> {
+        let mut lock = self.data.write();
+        let position = lock.steppers.get(&id).copied().unwrap_or_default();
+        match command {
+            StepperCommand::Config(config) => {
+                for pin in config.interface.get_pins() {
+                    let pin_instance = lock.get_pin_mut(pin)?;
+                    pin_instance.mode =
+                        pin_instance
+                            .supports_mode(PinModeId::STEPPER)
+                            .ok_or(IncompatiblePin {
+                                pin,
+                                mode: PinModeId::STEPPER,
+                                context: "configure stepper",
+                            })?;
+                }
+                lock.set_stepper_position(id, 0, false);
+            }
+            StepperCommand::Zero => lock.set_stepper_position(id, 0, false),
+            StepperCommand::Step(steps) => lock.set_stepper_position(id, position + steps, true),
+            StepperCommand::To(target) => lock.set_stepper_position(id, target, true),
+            StepperCommand::Stop => lock.set_stepper_position(id, position, true),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Emulates an encoder: positions are meant to be set through [`IoData::set_encoder_position`].
+    fn encoder(&mut self, id: u8, command: EncoderCommand) -> Result<(), Error> {
+        let mut lock = self.data.write();
+        match command {
+            EncoderCommand::Attach(pins) => {
+                for pin in pins {
+                    let pin_instance = lock.get_pin_mut(pin)?;
+                    pin_instance.mode =
+                        pin_instance
+                            .supports_mode(PinModeId::ENCODER)
+                            .ok_or(IncompatiblePin {
+                                pin,
+                                mode: PinModeId::ENCODER,
+                                context: "attach encoder",
+                            })?;
+                }
+                lock.set_encoder_position(id, 0);
+            }
+            EncoderCommand::Reset => lock.set_encoder_position(id, 0),
+            EncoderCommand::Detach => {
+                lock.encoders.remove(&id);
+            }
+            EncoderCommand::Report => {}
+        }
+        Ok(())
+    }
+
+    /// Emulates a DHT sensor: readings are meant to be set through [`IoData::set_dht_reading`].
+    fn dht_read(&mut self, pin: u8, _: DhtModel) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?;
+        Ok(())
+    }
+
+    /// Emulates a OneWire bus: replies are meant to be set through [`IoData::set_onewire_reply`].
+    fn onewire_config(&mut self, pin: u8, _: bool) -> Result<(), Error> {
+        let mut lock = self.data.write();
+        let pin_instance = lock.get_pin_mut(pin)?;
+        pin_instance.mode =
+            pin_instance
+                .supports_mode(PinModeId::ONEWIRE)
+                .ok_or(IncompatiblePin {
+                    pin,
+                    mode: PinModeId::ONEWIRE,
+                    context: "configure OneWire bus",
+                })?;
+        Ok(())
+    }
+
+    fn onewire_search(&mut self, pin: u8) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?;
+        Ok(())
+    }
+
+    fn onewire_reset(&mut self, pin: u8) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?;
+        Ok(())
+    }
+
+    fn onewire_select(&mut self, pin: u8, _: [u8; 8]) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?;
+        Ok(())
+    }
+
+    fn onewire_read(&mut self, pin: u8, _: u16, _: u16) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?;
+        Ok(())
+    }
+
+    fn onewire_write(&mut self, pin: u8, _: [u8; 8], _: &[u8]) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?;
+        Ok(())
+    }
+
+    fn onewire_delay(&mut self, pin: u8, _: u32) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?;
+        Ok(())
+    }
+
+    /// Emulates a ping: the echo duration is meant to be set as the pin value.
+    fn ping_read(&mut self, pin: u8, _: u32, _: u32) -> Result<(), Error> {
+        self.data.read().get_pin(pin)?;
+        Ok(())
+    }
+
+    fn tone(&mut self, pin: u8, frequency: u16, _: u16) -> Result<(), Error> {
+        let mut lock = self.data.write();
+        let pin_instance = lock.get_pin_mut(pin)?;
+        pin_instance.validate_current_mode(PinModeId::TONE)?;
+        pin_instance.value = frequency;
+        Ok(())
+    }
+
+    fn shift_out(
+        &mut self,
+        data_pin: u8,
+        clock_pin: u8,
+        latch_pin: u8,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        let lock = self.data.read();
+        for pin in [data_pin, clock_pin, latch_pin] {
+            lock.get_pin(pin)?;
+        }
+        self.shift_outs.write().push(data.to_vec());
+        Ok(())
+    }
+
     fn i2c_config(&mut self, _: u16) -> Result<(), Error> {
         Ok(())
     }
 
-    fn i2c_read(&mut self, _: u8, _: u16) -> Result<(), Error> {
+    fn i2c_read(&mut self, _: u8, _: Option<u8>, _: u16) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn i2c_read_continuously(&mut self, _: u8, _: Option<u8>, _: u16) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn i2c_stop_reading(&mut self, _: u8) -> Result<(), Error> {
         Ok(())
     }
 
-    fn i2c_write(&mut self, _: u8, _: &[u16]) -> Result<(), Error> {
+    fn i2c_write(&mut self, address: u8, data: &[u16]) -> Result<(), Error> {
+        self.i2c_writes.write().push((address, data.to_vec()));
         Ok(())
     }
 }
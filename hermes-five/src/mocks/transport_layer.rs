@@ -65,4 +65,9 @@ impl IoTransport for MockTransportLayer {
         self.read_index += len;
         Ok(())
     }
+
+    fn reconnect(&mut self) -> Result<(), Error> {
+        self.close()?;
+        self.open()
+    }
 }
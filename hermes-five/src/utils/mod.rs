@@ -9,6 +9,7 @@ pub use tokio;
 mod events;
 mod range;
 mod scale;
+pub mod shutdown;
 mod state;
 pub mod task;
 
@@ -0,0 +1,283 @@
+//! Defines Hermes-Five graceful shutdown.
+//!
+//! When installed (by default through the `#[hermes_five::runtime]` macro), the shutdown hook catches
+//! SIGINT (Ctrl-C), SIGTERM and panics in order to leave the hardware in a safe state:
+//! - the user shutdown callbacks (see [`on_shutdown`]) are called,
+//! - the animations of all registered outputs are stopped and the outputs are reset to their default state,
+//! - all open boards are closed (see [`Board::blocking_close`]) and emit a [`BoardEvent::OnClose`](crate::hardware::BoardEvent::OnClose) event.
+//!
+//! The shutdown runs on a dedicated thread: the panic hook and the signal handler only request it, then wait
+//! for it (up to 3 seconds). The panics of any thread trigger a shutdown, including the ones caught by
+//! the tokio runtimes (ex: in an event handler): the hardware is left in a safe state even if the
+//! program goes on.
+//!
+//! The hook can be opted out with `#[hermes_five::runtime(graceful_shutdown = false)]`.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::runtime::Handle;
+
+use crate::hardware::Board;
+
+/// Time given to the [`BoardEvent::OnClose`](crate::hardware::BoardEvent::OnClose) handlers to run before the process exits on a signal.
+const GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// Exit code used when the process is stopped by SIGINT (128 + signal number).
+const SIGINT_EXIT_CODE: i32 = 130;
+
+/// Exit code used when the process is stopped by SIGTERM (128 + signal number).
+#[cfg(unix)]
+const SIGTERM_EXIT_CODE: i32 = 143;
+
+/// The name of the shutdown thread.
+const SHUTDOWN_THREAD_NAME: &str = "hermes-five-shutdown";
+
+/// Maximum time a shutdown request waits for the shutdown to be done.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Maximum time the shutdown waits for the boards and callbacks (possibly locked by a panicking thread).
+const LOCK_TIMEOUT: Duration = Duration::from_millis(500);
+
+type ShutdownCallback = Box<dyn Fn() + Send + Sync>;
+
+/// The currently open boards.
+static BOARDS: Mutex<Vec<Board>> = Mutex::new(Vec::new());
+/// The user shutdown callbacks.
+static CALLBACKS: Mutex<Vec<ShutdownCallback>> = Mutex::new(Vec::new());
+/// Indicates the shutdown is done (it happens once, until a board is opened again).
+static DONE: AtomicBool = AtomicBool::new(false);
+/// The channel to the shutdown thread.
+static REQUESTS: OnceLock<SyncSender<ShutdownRequest>> = OnceLock::new();
+
+/// A request to the shutdown thread.
+struct ShutdownRequest {
+    /// The runtime the board events are emitted on (if any).
+    runtime: Option<Handle>,
+    /// Notified once the shutdown is done.
+    done: SyncSender<()>,
+}
+
+/// Installs the shutdown hook: you most likely don't need this function since the `#[hermes_five::runtime]`
+/// macro does it for you.
+///
+/// # Notes
+/// This function must be called from within a tokio runtime.
+pub fn install() {
+    shutdown_thread();
+
+    // Shutdown on any thread panic (after the panic message has been printed). A panic of the shutdown
+    // thread itself (ex: in a user callback) must not wait for the shutdown thread.
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        if std::thread::current().name() != Some(SHUTDOWN_THREAD_NAME) {
+            request_shutdown();
+        }
+    }));
+
+    // Shutdown on SIGINT / SIGTERM, then exit.
+    // Note: tokio::spawn is used on purpose here, the runtime must not wait for this task to end.
+    tokio::spawn(async {
+        let exit_code = wait_for_signal().await;
+        let _ = tokio::task::spawn_blocking(request_shutdown).await;
+        tokio::time::sleep(GRACE_PERIOD).await;
+        std::process::exit(exit_code);
+    });
+}
+
+/// Registers a callback to be executed when the program shuts down, before the boards are closed.
+///
+/// # Example
+/// ```
+/// use hermes_five::utils::shutdown;
+///
+/// #[hermes_five::runtime]
+/// async fn main() {
+///     shutdown::on_shutdown(|| println!("Shutting down: bye!"));
+/// }
+/// ```
+pub fn on_shutdown<F: Fn() + Send + Sync + 'static>(callback: F) {
+    CALLBACKS.lock().push(Box::new(callback));
+}
+
+/// Gracefully shuts down all open boards (this is done once only: subsequent calls do nothing).
+///
+/// This is done automatically on SIGINT, SIGTERM and panics when the shutdown hook is installed (see [`install`]).
+///
+/// The boards and callbacks are waited for up to [`LOCK_TIMEOUT`] only: the ones held by a panicking thread
+/// are skipped.
+pub fn shutdown() {
+    if DONE.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    match CALLBACKS.try_lock_for(LOCK_TIMEOUT) {
+        Some(callbacks) => callbacks.iter().for_each(|callback| callback()),
+        None => log::error!("Shutdown callbacks are locked: skipped"),
+    }
+    // The lock is released before closing the boards: a closed board is untracked.
+    let boards = match BOARDS.try_lock_for(LOCK_TIMEOUT) {
+        Some(mut boards) => std::mem::take(&mut *boards),
+        None => {
+            log::error!("Open boards are locked: not closed");
+            vec![]
+        }
+    };
+    shutdown_boards(boards);
+}
+
+/// Returns the channel to the shutdown thread (the thread is started on first call).
+fn shutdown_thread() -> &'static SyncSender<ShutdownRequest> {
+    REQUESTS.get_or_init(|| {
+        let (sender, receiver) = mpsc::sync_channel::<ShutdownRequest>(8);
+        let _ = std::thread::Builder::new()
+            .name(String::from(SHUTDOWN_THREAD_NAME))
+            .spawn(move || {
+                for request in receiver {
+                    // The board events are emitted on the runtime of the requester.
+                    let _guard = request.runtime.as_ref().map(Handle::enter);
+                    shutdown();
+                    let _ = request.done.try_send(());
+                }
+            });
+        sender
+    })
+}
+
+/// Requests the shutdown thread to shut down, and waits for it (up to [`SHUTDOWN_TIMEOUT`]).
+fn request_shutdown() {
+    let (done, finished) = mpsc::sync_channel(1);
+    let request = ShutdownRequest {
+        runtime: Handle::try_current().ok(),
+        done,
+    };
+    if shutdown_thread().try_send(request).is_ok()
+        && finished.recv_timeout(SHUTDOWN_TIMEOUT).is_err()
+    {
+        log::error!("Shutdown timed out");
+    }
+}
+
+/// Internal only: starts tracking an open board.
+pub(crate) fn track(board: &Board) {
+    // A new board can be shut down again.
+    DONE.store(false, Ordering::SeqCst);
+    let mut boards = BOARDS.lock();
+    if !boards.iter().any(|tracked| tracked.is_same(board)) {
+        boards.push(board.clone());
+    }
+}
+
+/// Internal only: stops tracking a closed board.
+pub(crate) fn untrack(board: &Board) {
+    BOARDS.lock().retain(|tracked| !tracked.is_same(board));
+}
+
+/// Internal only: returns the first tracked (open) board matching the given predicate.
+pub(crate) fn find_tracked<F: Fn(&Board) -> bool>(predicate: F) -> Option<Board> {
+    BOARDS.lock().iter().find(|board| predicate(board)).cloned()
+}
+
+/// Closes the given boards: one failing does not prevent the others to be closed.
+fn shutdown_boards(boards: Vec<Board>) {
+    for board in boards {
+        if let Err(error) = board.shutdown() {
+            log::error!("Board shutdown failed: {}", error);
+        }
+    }
+}
+
+/// Waits for a SIGINT (Ctrl-C) or a SIGTERM (on unix), and returns the matching exit code.
+#[cfg(not(tarpaulin_include))]
+async fn wait_for_signal() -> i32 {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            return tokio::select! {
+                _ = tokio::signal::ctrl_c() => SIGINT_EXIT_CODE,
+                _ = terminate.recv() => SIGTERM_EXIT_CODE,
+            };
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+    SIGINT_EXIT_CODE
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use crate::devices::{Led, Output, Servo};
+    use crate::hardware::{Board, BoardEvent};
+    use crate::io::IO;
+    use crate::mocks::plugin_io::MockIoProtocol;
+    use crate::pause;
+    use crate::utils::shutdown::{shutdown_boards, track, untrack, BOARDS, DONE};
+
+    #[test]
+    fn test_track_boards() {
+        let board = Board::new(MockIoProtocol::default());
+        let other = Board::new(MockIoProtocol::default());
+
+        track(&board);
+        track(&board.clone());
+        track(&other);
+        let count = |board: &Board| {
+            BOARDS
+                .lock()
+                .iter()
+                .filter(|tracked| tracked.is_same(board))
+                .count()
+        };
+        assert_eq!(count(&board), 1);
+        assert_eq!(count(&other), 1);
+
+        untrack(&board);
+        assert_eq!(count(&board), 0);
+        assert_eq!(count(&other), 1);
+        untrack(&other);
+
+        // Opening a board after a shutdown allows a new one.
+        DONE.store(true, Ordering::SeqCst);
+        track(&board);
+        assert!(!DONE.load(Ordering::SeqCst));
+        untrack(&board);
+    }
+
+    #[hermes_five_macros::test]
+    async fn test_shutdown_boards() {
+        let board = Board::new(MockIoProtocol::default())
+            .blocking_open()
+            .unwrap();
+        let mut led = Led::new(&board, 13, false).unwrap();
+        let mut servo = Servo::new(&board, 12, 90).unwrap();
+        board.register_output("status", led.clone());
+        board.register_output("arm", servo.clone());
+        led.blink(50);
+        servo.to(180).unwrap();
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let moved_flag = flag.clone();
+        board.on(BoardEvent::OnClose, move |board: Board| {
+            let captured_flag = moved_flag.clone();
+            async move {
+                captured_flag.store(true, Ordering::SeqCst);
+                assert!(!board.is_connected());
+                Ok(())
+            }
+        });
+
+        shutdown_boards(vec![board.clone()]);
+        pause!(100);
+
+        // Animations are stopped, outputs are reset and the board is closed.
+        assert!(!led.is_busy());
+        assert!(led.is_off());
+        assert_eq!(servo.get_state().as_integer(), 90);
+        assert!(flag.load(Ordering::SeqCst));
+    }
+}